@@ -1,12 +1,40 @@
 fn main() {
-    // Set API URL based on build profile
-    // This env var is read by commands/activation.rs and commands/user_status.rs
-    let profile = std::env::var("PROFILE").unwrap_or_default();
-    if profile == "release" {
-        // Production URL for release builds
-        println!("cargo:rustc-env=GOODHANG_API_URL=https://api.goodhang.com");
+    // The API base URL is resolved at runtime in src/api.rs
+
+    // The commit being built, for the About dialog and bug reports. CI sets
+    // GOODHANG_BUILD_HASH itself; local builds ask git.
+    println!("cargo:rerun-if-env-changed=GOODHANG_BUILD_HASH");
+    println!("cargo:rerun-if-env-changed=GOODHANG_RELEASE_CHANNEL");
+    if std::env::var_os("GOODHANG_BUILD_HASH").is_none() {
+        if let Some(hash) = git(&["rev-parse", "--short=12", "HEAD"]) {
+            println!("cargo:rustc-env=GOODHANG_BUILD_HASH={}", hash);
+        }
+        rerun_on_new_commit();
     }
-    // Debug builds fall back to staging URL in the Rust code
 
     tauri_build::build()
 }
+
+/// Any rerun-if line turns off Cargo's default of rerunning on every
+/// change, so watch what moves when HEAD does: HEAD itself for a checkout,
+/// the branch it points at for a commit, and packed-refs for a ref that
+/// has been packed
+fn rerun_on_new_commit() {
+    let mut watched = vec!["HEAD".to_string(), "packed-refs".to_string()];
+    if let Some(branch) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        watched.push(branch);
+    }
+    for name in watched {
+        // A missing file would rerun the script on every build
+        let path = git(&["rev-parse", "--path-format=absolute", "--git-path", &name]);
+        if let Some(path) = path.filter(|p| std::path::Path::new(p).exists()) {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new("git").args(args).output().ok()?;
+    let out = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !out.is_empty()).then_some(out)
+}
@@ -0,0 +1,621 @@
+//! The GoodHang API as a trait held in managed state, so command logic talks
+//! to whichever client the app was started with: the live server, or
+//! recorded fixtures when `GOODHANG_API_FIXTURES` names a directory (debug
+//! builds only).
+//!
+//! Also the one place the API base URL is decided: a saved override from
+//! settings, then the `GOODHANG_API_URL` environment variable, then the
+//! default for the build profile.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+
+use crate::commands::auth::{forget_session, load_session};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::http::{self, BufferedResponse};
+use crate::logging::{app_log, redact};
+use crate::network;
+use crate::stores;
+
+const BASE_URL_KEY: &str = "apiBaseUrl";
+const PRODUCTION_URL: &str = "https://api.goodhang.com";
+const STAGING_URL: &str = "https://goodhang-staging.vercel.app";
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The client commands share, as managed state
+pub type Api = Arc<dyn ApiClient>;
+
+static BASE_URL_OVERRIDE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Where the current base URL came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum BaseUrlSource {
+    Settings,
+    Env,
+    Default,
+}
+
+/// Which server the base URL points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiEnvironment {
+    Production,
+    Staging,
+    /// Anything else, e.g. a local server
+    Custom,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ApiBaseUrl {
+    pub url: String,
+    pub source: BaseUrlSource,
+}
+
+/// Release builds talk to production, everything else to staging
+fn profile_default() -> &'static str {
+    if cfg!(debug_assertions) {
+        STAGING_URL
+    } else {
+        PRODUCTION_URL
+    }
+}
+
+pub fn resolve_base_url() -> ApiBaseUrl {
+    let saved = BASE_URL_OVERRIDE.read().ok().and_then(|url| url.clone());
+    if let Some(url) = saved {
+        return ApiBaseUrl {
+            url,
+            source: BaseUrlSource::Settings,
+        };
+    }
+    match std::env::var("GOODHANG_API_URL") {
+        Ok(url) if !url.trim().is_empty() => ApiBaseUrl {
+            url: url.trim().trim_end_matches('/').to_string(),
+            source: BaseUrlSource::Env,
+        },
+        _ => ApiBaseUrl {
+            url: profile_default().to_string(),
+            source: BaseUrlSource::Default,
+        },
+    }
+}
+
+/// The API base URL, without a trailing slash
+pub fn base_url() -> String {
+    resolve_base_url().url
+}
+
+/// Whether `url` is on the API server: same scheme, host and port as the
+/// base URL, and under its path. Decides which requests carry credentials.
+pub fn is_api_url(url: &str) -> bool {
+    let (Ok(url), Ok(base)) = (reqwest::Url::parse(url), reqwest::Url::parse(&base_url())) else {
+        return false;
+    };
+    let base_path = base.path().trim_end_matches('/');
+    url.origin() == base.origin()
+        && url
+            .path()
+            .strip_prefix(base_path)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+pub fn environment() -> ApiEnvironment {
+    match base_url().as_str() {
+        PRODUCTION_URL => ApiEnvironment::Production,
+        STAGING_URL => ApiEnvironment::Staging,
+        _ => ApiEnvironment::Custom,
+    }
+}
+
+/// Restore a saved base URL override at startup
+pub fn load_base_url(app: &tauri::AppHandle) {
+    let saved = stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(BASE_URL_KEY))
+        .and_then(|value| value.as_str().and_then(|url| normalize(url).ok()));
+    if let (Some(saved), Ok(mut url)) = (saved, BASE_URL_OVERRIDE.write()) {
+        app_log!("[API] Using saved base URL {}", saved);
+        *url = Some(saved);
+    }
+}
+
+fn is_loopback(url: &reqwest::Url) -> bool {
+    match url.host_str() {
+        Some("localhost") => true,
+        Some(host) => host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback()),
+        None => false,
+    }
+}
+
+/// An absolute https URL with a host, trimmed of any trailing slash. Plain
+/// http is only allowed for a server on this machine.
+fn normalize(url: &str) -> Result<String, AppError> {
+    let invalid = |detail: &str| {
+        AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Invalid API URL: {}", detail),
+        )
+    };
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| invalid(&e.to_string()))?;
+    if parsed.host_str().is_none() {
+        return Err(invalid("missing host"));
+    }
+    match parsed.scheme() {
+        "https" => {}
+        "http" if is_loopback(&parsed) => {}
+        "http" => return Err(invalid("expected https unless the server is on localhost")),
+        _ => return Err(invalid("expected https")),
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(invalid("query strings and fragments aren't allowed"));
+    }
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
+
+/// Override the base URL, or clear the override with `None`. A new URL is
+/// only saved once its health endpoint answers. The session belongs to the
+/// old server, so moving to another one signs the user out.
+pub async fn set_base_url(
+    app: &tauri::AppHandle,
+    url: Option<String>,
+) -> Result<ApiBaseUrl, AppError> {
+    let url = url.map(|url| normalize(&url)).transpose()?;
+    if let Some(url) = &url {
+        if !network::probe(url).await {
+            return Err(AppError::new(
+                ErrorCode::Network,
+                format!("No GoodHang API is reachable at {}", url),
+            ));
+        }
+    }
+
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    match &url {
+        Some(url) => store.set(BASE_URL_KEY, serde_json::Value::String(url.clone())),
+        None => {
+            store.delete(BASE_URL_KEY);
+        }
+    }
+    stores::save(app, stores::SETTINGS_STORE)?;
+
+    let previous = base_url();
+    *BASE_URL_OVERRIDE
+        .write()
+        .map_err(|_| "Base URL lock poisoned".to_string())? = url;
+    let resolved = resolve_base_url();
+    if resolved.url != previous {
+        forget_session(app).await?;
+        events::emit_app_event(app, AppEvent::LoggedOut);
+    }
+    app_log!(
+        "[API] Base URL is now {} ({:?})",
+        resolved.url,
+        resolved.source
+    );
+    Ok(resolved)
+}
+
+/// What a request sends
+#[derive(Debug, Clone)]
+pub enum ApiBody {
+    Json(serde_json::Value),
+    Bytes(Vec<u8>),
+    Multipart(Vec<(String, FormValue)>),
+}
+
+/// One field of a multipart form
+#[derive(Debug, Clone)]
+pub enum FormValue {
+    Text(String),
+    File {
+        bytes: Vec<u8>,
+        file_name: String,
+        mime_type: String,
+    },
+}
+
+impl ApiBody {
+    pub fn multipart_form(
+        parts: &[(String, FormValue)],
+    ) -> Result<reqwest::multipart::Form, String> {
+        let mut form = reqwest::multipart::Form::new();
+        for (name, value) in parts {
+            form = match value {
+                FormValue::Text(text) => form.text(name.clone(), text.clone()),
+                FormValue::File {
+                    bytes,
+                    file_name,
+                    mime_type,
+                } => {
+                    let part = reqwest::multipart::Part::bytes(bytes.clone())
+                        .file_name(file_name.clone())
+                        .mime_str(mime_type)
+                        .map_err(|e| format!("Invalid mime type: {}", e))?;
+                    form.part(name.clone(), part)
+                }
+            };
+        }
+        Ok(form)
+    }
+}
+
+/// A request against the API, addressed by path so the client decides which
+/// server (or fixture set) answers it
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    pub method: reqwest::Method,
+    /// e.g. `/api/devices`, without the base URL
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<ApiBody>,
+    /// Another service's base URL, for the few calls that don't go to the
+    /// GoodHang API (the Renubu planner). Fixtures ignore it.
+    pub base_url: Option<String>,
+}
+
+impl ApiRequest {
+    pub fn new(method: reqwest::Method, path: impl Into<String>) -> Self {
+        Self {
+            method,
+            path: path.into(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            base_url: None,
+        }
+    }
+
+    pub fn get(path: impl Into<String>) -> Self {
+        Self::new(reqwest::Method::GET, path)
+    }
+
+    pub fn post(path: impl Into<String>) -> Self {
+        Self::new(reqwest::Method::POST, path)
+    }
+
+    pub fn put(path: impl Into<String>) -> Self {
+        Self::new(reqwest::Method::PUT, path)
+    }
+
+    pub fn patch(path: impl Into<String>) -> Self {
+        Self::new(reqwest::Method::PATCH, path)
+    }
+
+    pub fn delete(path: impl Into<String>) -> Self {
+        Self::new(reqwest::Method::DELETE, path)
+    }
+
+    pub fn bearer(self, token: &str) -> Self {
+        self.header("Authorization", format!("Bearer {}", token))
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn query(mut self, name: &str, value: impl ToString) -> Self {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Result<Self, String> {
+        let value =
+            serde_json::to_value(body).map_err(|e| format!("Failed to encode request: {}", e))?;
+        self.body = Some(ApiBody::Json(value));
+        Ok(self)
+    }
+
+    pub fn bytes(mut self, body: Vec<u8>) -> Self {
+        self.body = Some(ApiBody::Bytes(body));
+        self
+    }
+
+    pub fn multipart(mut self, parts: Vec<(String, FormValue)>) -> Self {
+        self.body = Some(ApiBody::Multipart(parts));
+        self
+    }
+
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+}
+
+/// The signed-in session's token, for requests that need one
+pub fn require_token(app: &tauri::AppHandle) -> Result<String, AppError> {
+    load_session(app)
+        .map_err(|e| AppError::new(ErrorCode::Storage, e))?
+        .map(|s| s.token)
+        .ok_or_else(|| AppError::new(ErrorCode::NotSignedIn, "Not signed in"))
+}
+
+/// A response body read as it arrives, for server-sent event streams
+pub enum ApiStream {
+    Live(reqwest::Response),
+    /// A fixture, handed over as a single chunk
+    Buffered(Option<Vec<u8>>),
+}
+
+impl ApiStream {
+    /// The next piece of the body, or `None` once it has ended
+    pub async fn chunk(&mut self) -> Result<Option<Vec<u8>>, String> {
+        match self {
+            Self::Live(response) => response
+                .chunk()
+                .await
+                .map(|chunk| chunk.map(|bytes| bytes.to_vec()))
+                .map_err(|e| format!("Stream error: {}", e)),
+            Self::Buffered(body) => Ok(body.take()),
+        }
+    }
+}
+
+pub trait ApiClient: Send + Sync {
+    /// Send `request` and read the whole response. Fails only when no
+    /// response arrived; callers check the status.
+    fn send(&self, request: ApiRequest) -> BoxFuture<'_, Result<BufferedResponse, String>>;
+
+    /// Send `request` and hand back the body to read as it arrives. Unlike
+    /// `send`, a non-2xx status fails with the usual `Server error` message.
+    fn stream(&self, request: ApiRequest) -> BoxFuture<'_, Result<ApiStream, String>>;
+}
+
+/// The live API over the shared HTTP client. The base URL is resolved per
+/// request so a changed override applies without a restart.
+pub struct HttpApiClient;
+
+impl HttpApiClient {
+    fn url(request: &ApiRequest) -> Result<reqwest::Url, String> {
+        let base = request.base_url.clone().unwrap_or_else(base_url);
+        let mut url = reqwest::Url::parse(&format!("{}{}", base, request.path))
+            .map_err(|e| format!("Invalid URL: {}", e))?;
+        if !request.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&request.query);
+        }
+        Ok(url)
+    }
+
+    fn headers(request: &ApiRequest) -> Vec<(&str, String)> {
+        request
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.clone()))
+            .collect()
+    }
+}
+
+impl ApiClient for HttpApiClient {
+    fn send(&self, request: ApiRequest) -> BoxFuture<'_, Result<BufferedResponse, String>> {
+        Box::pin(async move {
+            let url = Self::url(&request)?;
+            let headers = Self::headers(&request);
+
+            // Identical concurrent reads share one round trip
+            match (&request.method, &request.body) {
+                (&reqwest::Method::GET, None) => {
+                    http::send_coalesced(request.method, url.as_str(), &headers).await
+                }
+                _ => {
+                    http::send(
+                        request.method,
+                        url.as_str(),
+                        &headers,
+                        request.body.as_ref(),
+                    )
+                    .await
+                }
+            }
+        })
+    }
+
+    fn stream(&self, request: ApiRequest) -> BoxFuture<'_, Result<ApiStream, String>> {
+        Box::pin(async move {
+            let url = Self::url(&request)?;
+            let headers = Self::headers(&request);
+            let response = http::open(
+                request.method.clone(),
+                url.as_str(),
+                &headers,
+                request.body.as_ref(),
+            )
+            .await?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(format!("Server error {}: {}", status, redact(&body)));
+            }
+            Ok(ApiStream::Live(response))
+        })
+    }
+}
+
+/// Canned responses read from a directory, one file per endpoint, named
+/// like `GET_api_devices.json`. Endpoints without a file answer 404, so a
+/// fixture set only needs the calls a scenario makes.
+pub struct FixtureApiClient {
+    fixtures: HashMap<String, Vec<u8>>,
+}
+
+impl FixtureApiClient {
+    #[cfg(test)]
+    pub(crate) fn from_files(files: &[(&str, &str)]) -> Self {
+        let fixtures = files
+            .iter()
+            .map(|(name, body)| (name.to_string(), body.as_bytes().to_vec()))
+            .collect();
+        Self { fixtures }
+    }
+
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read fixtures {}: {}", dir.display(), e))?;
+        let mut fixtures = HashMap::new();
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().is_some_and(|e| e == "json") {
+                let Some(name) = path.file_stem() else {
+                    continue;
+                };
+                let body = std::fs::read(&path)
+                    .map_err(|e| format!("Failed to read fixture {}: {}", path.display(), e))?;
+                fixtures.insert(name.to_string_lossy().into_owned(), body);
+            }
+        }
+        Ok(Self { fixtures })
+    }
+
+    fn fixture_name(method: &reqwest::Method, path: &str) -> String {
+        let slug: String = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("{}_{}", method.as_str(), slug.trim_matches('_'))
+    }
+}
+
+impl ApiClient for FixtureApiClient {
+    fn send(&self, request: ApiRequest) -> BoxFuture<'_, Result<BufferedResponse, String>> {
+        let name = Self::fixture_name(&request.method, &request.path);
+        let (status, body) = match self.fixtures.get(&name) {
+            Some(body) => (reqwest::StatusCode::OK, body.clone()),
+            None => {
+                app_log!("[API] No fixture {}", name);
+                (
+                    reqwest::StatusCode::NOT_FOUND,
+                    format!("{{\"error\":\"No fixture {}\"}}", name).into_bytes(),
+                )
+            }
+        };
+        Box::pin(async move {
+            Ok(BufferedResponse {
+                status,
+                headers: reqwest::header::HeaderMap::new(),
+                body: Arc::new(body),
+            })
+        })
+    }
+
+    fn stream(&self, request: ApiRequest) -> BoxFuture<'_, Result<ApiStream, String>> {
+        Box::pin(async move {
+            let response = self.send(request).await?.error_for_status()?;
+            Ok(ApiStream::Buffered(Some(response.body.to_vec())))
+        })
+    }
+}
+
+/// The live client, unless a debug build's `GOODHANG_API_FIXTURES` points
+/// at a fixture set. Release builds always talk to the server.
+pub fn from_env() -> Result<Api, String> {
+    #[cfg(debug_assertions)]
+    if let Ok(dir) = std::env::var("GOODHANG_API_FIXTURES") {
+        let dir = std::path::PathBuf::from(dir);
+        let client = FixtureApiClient::load(&dir)?;
+        app_log!(
+            "[API] Serving {} fixture(s) from {}",
+            client.fixtures.len(),
+            dir.display()
+        );
+        return Ok(Arc::new(client));
+    }
+    Ok(Arc::new(HttpApiClient))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures(files: &[(&str, &str)]) -> FixtureApiClient {
+        let dir = std::env::temp_dir().join(format!("goodhang-fixtures-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, body) in files {
+            std::fs::write(dir.join(name), body).unwrap();
+        }
+        let client = FixtureApiClient::load(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        client
+    }
+
+    #[test]
+    fn base_urls_need_https_off_this_machine() {
+        assert_eq!(
+            normalize(" https://api.example.com/ ").unwrap(),
+            "https://api.example.com"
+        );
+        assert_eq!(
+            normalize("http://localhost:3000").unwrap(),
+            "http://localhost:3000"
+        );
+        assert!(normalize("http://127.0.0.1:3000/base").is_ok());
+        assert!(normalize("http://[::1]:3000").is_ok());
+        assert!(normalize("http://api.example.com").is_err());
+        assert!(normalize("http://localhost.example.com").is_err());
+        assert!(normalize("ftp://api.example.com").is_err());
+        assert!(normalize("https://api.example.com/?x=1").is_err());
+        assert!(normalize("not a url").is_err());
+    }
+
+    #[test]
+    fn names_fixtures_by_method_and_path() {
+        assert_eq!(
+            FixtureApiClient::fixture_name(&reqwest::Method::GET, "/api/devices"),
+            "GET_api_devices"
+        );
+        assert_eq!(
+            FixtureApiClient::fixture_name(&reqwest::Method::PATCH, "/api/renewals/r-1"),
+            "PATCH_api_renewals_r_1"
+        );
+    }
+
+    #[tokio::test]
+    async fn serves_fixtures_and_404s_the_rest() {
+        let client = fixtures(&[
+            ("GET_api_devices.json", r#"[{"id":"d1"}]"#),
+            ("notes.txt", "not a fixture"),
+        ]);
+        assert_eq!(client.fixtures.len(), 1);
+
+        let response = client
+            .send(ApiRequest::get("/api/devices").bearer("token"))
+            .await
+            .unwrap();
+        let devices: serde_json::Value = response
+            .error_for_status()
+            .unwrap()
+            .json("/api/devices")
+            .unwrap();
+        assert_eq!(devices[0]["id"], "d1");
+
+        let missing = client
+            .send(ApiRequest::post("/api/devices").bytes(vec![1, 2, 3]))
+            .await
+            .unwrap();
+        assert_eq!(missing.status, reqwest::StatusCode::NOT_FOUND);
+        assert!(missing.error_for_status().is_err());
+    }
+
+    #[tokio::test]
+    async fn streams_fixtures_in_one_chunk() {
+        let client = FixtureApiClient::from_files(&[("GET_api_realtime", "data: {}\n\n")]);
+        let mut stream = client
+            .stream(ApiRequest::get("/api/realtime"))
+            .await
+            .unwrap();
+        assert_eq!(stream.chunk().await.unwrap().unwrap(), b"data: {}\n\n");
+        assert!(stream.chunk().await.unwrap().is_none());
+
+        let missing = client.stream(ApiRequest::get("/api/other")).await;
+        assert!(missing.is_err_and(|e| e.starts_with("Server error 404")));
+    }
+}
@@ -0,0 +1,191 @@
+use rusqlite::Connection;
+use serde::Serialize;
+use std::future::Future;
+use tauri::Manager;
+
+use crate::db::{now_secs, Database};
+use crate::errors::{classify, AppError, ErrorCode};
+use crate::logging::app_log;
+
+/// Entries older than this are dropped, as are any beyond `MAX_ENTRIES`
+const RETENTION_SECS: i64 = 180 * 24 * 60 * 60;
+const MAX_ENTRIES: i64 = 10_000;
+
+/// One sensitive command invocation. Arguments and results are never
+/// recorded, only that the command ran and how it ended.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub at: i64,
+    pub command: String,
+    /// `ok` or `error`
+    pub outcome: String,
+    #[serde(rename = "errorCode")]
+    pub error_code: Option<ErrorCode>,
+}
+
+/// Command errors that can be reduced to a code for the audit trail
+pub trait AuditOutcome {
+    fn error_code(&self) -> ErrorCode;
+}
+
+impl AuditOutcome for String {
+    fn error_code(&self) -> ErrorCode {
+        classify(self)
+    }
+}
+
+impl AuditOutcome for AppError {
+    fn error_code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
+/// Append an entry; failures are logged rather than failing the command
+pub fn record<T, E: AuditOutcome>(db: &Database, command: &str, result: &Result<T, E>) {
+    let (outcome, code) = match result {
+        Ok(_) => ("ok", None),
+        Err(e) => ("error", Some(e.error_code())),
+    };
+    let code = code
+        .and_then(|c| serde_json::to_value(c).ok())
+        .and_then(|v| v.as_str().map(str::to_string));
+
+    let inserted = db.with_conn(|conn| append(conn, command, outcome, code.as_deref()));
+    if let Err(e) = inserted {
+        app_log!("[Audit] Failed to record {}: {}", command, e);
+    }
+}
+
+/// Rows past retention, for `row` as the table or a trigger's `OLD`. By id
+/// rather than row count, and on SQLite's clock, so the prune and the
+/// trigger guarding it agree while rows are being deleted.
+fn prunable(row: &str) -> String {
+    format!(
+        "{0}at < CAST(strftime('%s', 'now') AS INTEGER) - {1}
+         OR {0}id <= (SELECT MAX(id) FROM audit_log) - {2}",
+        row, RETENTION_SECS, MAX_ENTRIES
+    )
+}
+
+fn append(
+    conn: &Connection,
+    command: &str,
+    outcome: &str,
+    code: Option<&str>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO audit_log (at, command, outcome, error_code) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![now_secs(), command, outcome, code],
+    )?;
+    // Pruned as it grows, so the trail stays bounded without a sweep
+    conn.execute(&format!("DELETE FROM audit_log WHERE {}", prunable("")), [])?;
+    Ok(())
+}
+
+/// Migration: the append-only guard refused the retention prune too. Rows
+/// still can't be changed, and only those past retention can be deleted.
+pub(crate) fn allow_retention_prune(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "DROP TRIGGER IF EXISTS audit_log_no_delete;
+         CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+         WHEN NOT ({})
+         BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;",
+        prunable("OLD.")
+    ))
+}
+
+/// Run a command body and record its outcome
+pub async fn audited<T, E: AuditOutcome>(
+    app: tauri::AppHandle,
+    command: &str,
+    body: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let result = body.await;
+    record(&app.state::<Database>(), command, &result);
+    result
+}
+
+pub fn entries(db: &Database, limit: u32) -> Result<Vec<AuditEntry>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, at, command, outcome, error_code FROM audit_log
+             ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit], |row| {
+            let code: Option<String> = row.get(4)?;
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                at: row.get(1)?,
+                command: row.get(2)?,
+                outcome: row.get(3)?,
+                error_code: code
+                    .and_then(|c| serde_json::from_value(serde_json::Value::String(c)).ok()),
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trail() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::create_tables(&conn).unwrap();
+        allow_retention_prune(&conn).unwrap();
+        conn
+    }
+
+    fn count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM audit_log", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn stays_bounded_past_max_entries() {
+        let conn = trail();
+        conn.execute(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1)
+             INSERT INTO audit_log (at, command, outcome) SELECT ?2, 'seed', 'ok' FROM n",
+            rusqlite::params![MAX_ENTRIES, now_secs()],
+        )
+        .unwrap();
+        for _ in 0..5 {
+            append(&conn, "set_data_directory", "ok", None).unwrap();
+        }
+        assert_eq!(count(&conn), MAX_ENTRIES);
+        let newest: String = conn
+            .query_row(
+                "SELECT command FROM audit_log ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(newest, "set_data_directory");
+    }
+
+    #[test]
+    fn drops_entries_past_retention() {
+        let conn = trail();
+        conn.execute(
+            "INSERT INTO audit_log (at, command, outcome) VALUES (?1, 'old', 'ok')",
+            [now_secs() - RETENTION_SECS - 60],
+        )
+        .unwrap();
+        append(&conn, "secure_logout", "ok", None).unwrap();
+        assert_eq!(count(&conn), 1);
+    }
+
+    #[test]
+    fn recent_entries_stay_append_only() {
+        let conn = trail();
+        append(&conn, "secure_logout", "error", Some("E_NETWORK")).unwrap();
+        assert!(conn.execute("DELETE FROM audit_log", []).is_err());
+        assert!(conn
+            .execute("UPDATE audit_log SET outcome = 'ok'", [])
+            .is_err());
+        assert_eq!(count(&conn), 1);
+    }
+}
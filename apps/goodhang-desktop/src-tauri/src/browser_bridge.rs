@@ -0,0 +1,629 @@
+//! Native-messaging bridge for the GoodHang browser extension.
+//!
+//! The browser launches this same binary as a native-messaging host
+//! (`run_host`). The host holds no credentials: it relays each message over
+//! a loopback socket to the running app, which checks consent and rate
+//! limits before answering.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use tokio::sync::oneshot;
+
+use crate::commands::login::random_string;
+use crate::commands::relationship_brief;
+use crate::commands::security::AppLock;
+use crate::errors::AppError;
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::stores;
+use crate::user_data;
+
+// Must match `identifier` in tauri.conf.json; the host runs without Tauri
+const APP_IDENTIFIER: &str = "com.goodhang.desktop";
+// Native-messaging host name; browsers look for `<name>.json` manifests
+const HOST_NAME: &str = "com.goodhang.desktop";
+const HOST_MANIFEST_FILE: &str = "com.goodhang.desktop.json";
+// The published extensions allowed to launch the host, set at build time.
+// A browser whose id isn't configured gets no manifest.
+const CHROME_EXTENSION_ID: Option<&str> = option_env!("GOODHANG_CHROME_EXTENSION_ID");
+const FIREFOX_EXTENSION_ID: Option<&str> = option_env!("GOODHANG_FIREFOX_EXTENSION_ID");
+const ENDPOINT_FILE: &str = "browser-bridge.json";
+pub(crate) const BRIDGE_KEY: &str = "browser_bridge";
+// Chrome caps messages to the extension at 1 MB
+const MAX_MESSAGE_BYTES: usize = 1024 * 1024;
+const RATE_LIMIT: usize = 20;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+const CONSENT_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+
+// Recent request times per extension origin
+static REQUESTS: Mutex<Option<HashMap<String, VecDeque<Instant>>>> = Mutex::new(None);
+
+/// Where the running app listens. Written with owner-only permissions; the
+/// secret keeps other local processes from using the socket.
+#[derive(Debug, Serialize, Deserialize)]
+struct Endpoint {
+    port: u16,
+    secret: String,
+}
+
+/// One relayed message: the extension's payload plus who sent it
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    secret: String,
+    origin: String,
+    message: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    Ping,
+    GetBrief {
+        #[serde(rename = "profileUrl")]
+        profile_url: String,
+    },
+}
+
+/// Extension origins the user has answered for
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct BridgeSettings {
+    #[serde(default, rename = "allowedOrigins")]
+    pub allowed_origins: Vec<String>,
+    #[serde(default, rename = "deniedOrigins")]
+    pub denied_origins: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ConsentRequestedEvent {
+    pub origin: String,
+}
+
+/// Requests waiting on the user's consent answer, by origin
+#[derive(Default)]
+pub struct BridgeConsents {
+    pending: Mutex<HashMap<String, Vec<oneshot::Sender<bool>>>>,
+}
+
+impl BridgeConsents {
+    /// Resolve every request waiting on `origin`; false if none were
+    pub fn resolve(&self, origin: &str, allowed: bool) -> bool {
+        let waiters = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(origin))
+            .unwrap_or_default();
+        let any = !waiters.is_empty();
+        for waiter in waiters {
+            let _ = waiter.send(allowed);
+        }
+        any
+    }
+}
+
+fn error_response(code: &str, message: &str) -> serde_json::Value {
+    serde_json::json!({ "ok": false, "code": code, "error": message })
+}
+
+/// The app data dir Tauri would resolve, for the host process which has no
+/// Tauri context
+fn host_data_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "macos")]
+    let base = std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join("Library/Application Support"));
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("APPDATA").map(PathBuf::from);
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+/// The calling extension's origin when the browser launched us as a
+/// native-messaging host. Chrome passes `chrome-extension://<id>/` (and on
+/// Windows `--parent-window=<n>` after it); Firefox passes the path of our
+/// host manifest and then the extension id. Anything else is a normal launch.
+pub fn host_origin(args: &[String]) -> Option<String> {
+    let chrome = |origin: &str| origin.starts_with("chrome-extension://");
+    match args {
+        [_, origin] if chrome(origin) => Some(origin.clone()),
+        [_, origin, parent] if chrome(origin) && parent.starts_with("--parent-window=") => {
+            Some(origin.clone())
+        }
+        [_, manifest, extension_id]
+            if std::path::Path::new(manifest)
+                .file_name()
+                .is_some_and(|name| name == HOST_MANIFEST_FILE) =>
+        {
+            Some(format!("moz-extension://{}", extension_id))
+        }
+        _ => None,
+    }
+}
+
+fn read_native_message(input: &mut impl Read) -> Option<serde_json::Value> {
+    let mut len = [0u8; 4];
+    input.read_exact(&mut len).ok()?;
+    let len = u32::from_ne_bytes(len) as usize;
+    if len > MAX_MESSAGE_BYTES {
+        return None;
+    }
+    let mut body = vec![0u8; len];
+    input.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+fn write_native_message(output: &mut impl Write, message: &serde_json::Value) -> bool {
+    let body = message.to_string();
+    let body = if body.len() > MAX_MESSAGE_BYTES {
+        error_response("too_large", "Response too large").to_string()
+    } else {
+        body
+    };
+    output
+        .write_all(&(body.len() as u32).to_ne_bytes())
+        .and_then(|_| output.write_all(body.as_bytes()))
+        .and_then(|_| output.flush())
+        .is_ok()
+}
+
+fn relay(origin: &str, message: serde_json::Value) -> serde_json::Value {
+    let endpoint: Option<Endpoint> = host_data_dir()
+        .and_then(|dir| std::fs::read(dir.join(ENDPOINT_FILE)).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+    let Some(endpoint) = endpoint else {
+        return error_response("app_not_running", "GoodHang isn't running");
+    };
+    let Ok(mut stream) = std::net::TcpStream::connect(("127.0.0.1", endpoint.port)) else {
+        return error_response("app_not_running", "GoodHang isn't running");
+    };
+
+    let envelope = Envelope {
+        secret: endpoint.secret,
+        origin: origin.to_string(),
+        message,
+    };
+    let mut line = serde_json::to_string(&envelope).unwrap_or_default();
+    line.push('\n');
+    if stream.write_all(line.as_bytes()).is_err() {
+        return error_response("bridge_error", "Failed to reach GoodHang");
+    }
+
+    let mut reply = String::new();
+    match BufReader::new(stream).read_line(&mut reply) {
+        Ok(n) if n > 0 => serde_json::from_str(&reply)
+            .unwrap_or_else(|_| error_response("bridge_error", "Invalid reply from GoodHang")),
+        _ => error_response("bridge_error", "GoodHang closed the connection"),
+    }
+}
+
+/// Native-messaging host loop: one relayed reply per message until the
+/// browser closes stdin. Nothing may be printed to stdout except replies,
+/// so this path never uses `app_log!`.
+pub fn run_host(origin: String) -> i32 {
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    while let Some(message) = read_native_message(&mut stdin) {
+        let reply = relay(&origin, message);
+        if !write_native_message(&mut stdout, &reply) {
+            return 1;
+        }
+    }
+    0
+}
+
+pub(crate) fn load_settings(app: &tauri::AppHandle) -> BridgeSettings {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(user_data::key(BRIDGE_KEY)))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save_settings(
+    app: &tauri::AppHandle,
+    settings: &BridgeSettings,
+) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    store.set(
+        user_data::key(BRIDGE_KEY),
+        serde_json::to_value(settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?,
+    );
+    stores::save(app, stores::SETTINGS_STORE)
+}
+
+/// Sliding-window limit per origin
+fn within_rate_limit(origin: &str) -> bool {
+    let Ok(mut guard) = REQUESTS.lock() else {
+        return false;
+    };
+    let window = guard
+        .get_or_insert_with(HashMap::new)
+        .entry(origin.to_string())
+        .or_default();
+    let now = Instant::now();
+    while window
+        .front()
+        .is_some_and(|at| now.duration_since(*at) > RATE_WINDOW)
+    {
+        window.pop_front();
+    }
+    if window.len() >= RATE_LIMIT {
+        return false;
+    }
+    window.push_back(now);
+    true
+}
+
+/// Ask the user whether `origin` may read briefs. Concurrent requests from
+/// the same origin share one prompt.
+async fn request_consent(app: &tauri::AppHandle, origin: &str) -> bool {
+    let (sender, receiver) = oneshot::channel();
+    let first = {
+        let consents = app.state::<BridgeConsents>();
+        let Ok(mut pending) = consents.pending.lock() else {
+            return false;
+        };
+        let waiters = pending.entry(origin.to_string()).or_default();
+        waiters.push(sender);
+        waiters.len() == 1
+    };
+    if first {
+        app_log!("[BrowserBridge] Consent requested for {}", origin);
+        events::emit_app_event(
+            app,
+            AppEvent::BrowserBridgeConsentRequested(ConsentRequestedEvent {
+                origin: origin.to_string(),
+            }),
+        );
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.set_focus();
+        }
+    }
+
+    match tokio::time::timeout(CONSENT_TIMEOUT, receiver).await {
+        Ok(Ok(allowed)) => allowed,
+        _ => {
+            app.state::<BridgeConsents>().resolve(origin, false);
+            false
+        }
+    }
+}
+
+async fn handle(
+    app: &tauri::AppHandle,
+    origin: &str,
+    message: serde_json::Value,
+) -> serde_json::Value {
+    if !within_rate_limit(origin) {
+        return error_response("rate_limited", "Too many requests; try again in a minute");
+    }
+    let request: Request = match serde_json::from_value(message) {
+        Ok(request) => request,
+        Err(_) => return error_response("invalid_request", "Unrecognized request"),
+    };
+    if matches!(request, Request::Ping) {
+        return serde_json::json!({ "ok": true });
+    }
+
+    let settings = load_settings(app);
+    if settings.denied_origins.iter().any(|o| o == origin) {
+        return error_response("consent_denied", "Access was declined in GoodHang");
+    }
+    if !settings.allowed_origins.iter().any(|o| o == origin) && !request_consent(app, origin).await
+    {
+        return error_response("consent_denied", "Access was declined in GoodHang");
+    }
+    if app.state::<AppLock>().is_locked() {
+        return error_response("locked", "Unlock GoodHang to continue");
+    }
+
+    match request {
+        Request::Ping => serde_json::json!({ "ok": true }),
+        Request::GetBrief { profile_url } => {
+            match relationship_brief::brief_for_profile_url(app, &profile_url).await {
+                Ok(Some(mut brief)) => {
+                    // The user's private notes stay in the app
+                    brief.notes.clear();
+                    serde_json::json!({ "ok": true, "brief": brief })
+                }
+                Ok(None) => error_response("not_found", "No relationship for this profile yet"),
+                Err(AppError { code, detail, .. }) => {
+                    app_log!("[BrowserBridge] Brief lookup failed: {}", detail);
+                    serde_json::json!({ "ok": false, "code": code, "error": detail })
+                }
+            }
+        }
+    }
+}
+
+async fn serve_connection(app: tauri::AppHandle, secret: String, stream: tokio::net::TcpStream) {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    let mut reader = tokio::io::BufReader::new(reader.take(MAX_MESSAGE_BYTES as u64 * 2));
+    if reader.read_line(&mut line).await.is_err() {
+        return;
+    }
+    let Ok(envelope) = serde_json::from_str::<Envelope>(&line) else {
+        return;
+    };
+    if envelope.secret != secret {
+        app_log!("[BrowserBridge] Rejected connection with a bad secret");
+        return;
+    }
+
+    let reply = handle(&app, &envelope.origin, envelope.message).await;
+    let mut out = reply.to_string();
+    out.push('\n');
+    let _ = writer.write_all(out.as_bytes()).await;
+}
+
+/// Write a file only the current user can read. It's created that way, so
+/// there's no window where another user could open it.
+fn write_private(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    // A leftover file would keep its old permissions when truncated
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+/// Listen on a loopback port for relayed extension messages and publish the
+/// port for the host process
+pub fn spawn_bridge_server(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(("127.0.0.1", 0)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                app_log!("[BrowserBridge] Failed to listen: {}", e);
+                return;
+            }
+        };
+        let secret = random_string(48);
+        let published = listener
+            .local_addr()
+            .map_err(|e| e.to_string())
+            .and_then(|addr| {
+                // Always the OS dir, even in portable mode: it's where the
+                // host looks, and it only lives as long as this process
+                let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+                let path = dir.join(ENDPOINT_FILE);
+                let endpoint = Endpoint {
+                    port: addr.port(),
+                    secret: secret.clone(),
+                };
+                write_private(&path, &serde_json::to_vec(&endpoint).unwrap_or_default())
+                    .map_err(|e| e.to_string())?;
+                Ok(addr.port())
+            });
+        match published {
+            Ok(port) => app_log!("[BrowserBridge] Listening on 127.0.0.1:{}", port),
+            Err(e) => {
+                app_log!("[BrowserBridge] Failed to publish endpoint: {}", e);
+                return;
+            }
+        }
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve_connection(
+                        app.clone(),
+                        secret.clone(),
+                        stream,
+                    ));
+                }
+                Err(e) => app_log!("[BrowserBridge] Accept failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Where each installed browser looks for per-user host manifests, and
+/// whether it's Firefox
+#[cfg(not(target_os = "windows"))]
+fn manifest_dirs() -> Vec<(PathBuf, bool)> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    #[cfg(target_os = "macos")]
+    let candidates = [
+        (
+            "Library/Application Support/Google/Chrome/NativeMessagingHosts",
+            false,
+        ),
+        (
+            "Library/Application Support/Chromium/NativeMessagingHosts",
+            false,
+        ),
+        (
+            "Library/Application Support/Mozilla/NativeMessagingHosts",
+            true,
+        ),
+    ];
+    #[cfg(not(target_os = "macos"))]
+    let candidates = [
+        (".config/google-chrome/NativeMessagingHosts", false),
+        (".config/chromium/NativeMessagingHosts", false),
+        (".mozilla/native-messaging-hosts", true),
+    ];
+    candidates
+        .into_iter()
+        .map(|(dir, firefox)| (home.join(dir), firefox))
+        // The browser's own folder exists only once it's installed
+        .filter(|(dir, _)| dir.parent().is_some_and(|root| root.is_dir()))
+        .collect()
+}
+
+/// Host manifest for one browser family, or `None` when no extension id
+/// was configured for it
+fn host_manifest(exe: &std::path::Path, firefox: bool) -> Option<serde_json::Value> {
+    let mut manifest = serde_json::json!({
+        "name": HOST_NAME,
+        "description": "GoodHang desktop bridge",
+        "path": exe,
+        "type": "stdio",
+    });
+    if firefox {
+        manifest["allowed_extensions"] = serde_json::json!([FIREFOX_EXTENSION_ID?]);
+    } else {
+        manifest["allowed_origins"] =
+            serde_json::json!([format!("chrome-extension://{}/", CHROME_EXTENSION_ID?)]);
+    }
+    Some(manifest)
+}
+
+/// Write `manifest` to `path` unless it's already there unchanged
+fn write_manifest(path: &std::path::Path, manifest: &serde_json::Value) -> Result<(), String> {
+    let contents = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    if std::fs::read(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Register this executable as the extension's native-messaging host with
+/// each installed browser, so the extension can launch it
+#[cfg(not(target_os = "windows"))]
+pub fn install_host_manifests() {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            app_log!("[BrowserBridge] Failed to locate executable: {}", e);
+            return;
+        }
+    };
+    for (dir, firefox) in manifest_dirs() {
+        let Some(manifest) = host_manifest(&exe, firefox) else {
+            continue;
+        };
+        if let Err(e) = write_manifest(&dir.join(HOST_MANIFEST_FILE), &manifest) {
+            app_log!("[BrowserBridge] Failed to install host manifest: {}", e);
+        }
+    }
+}
+
+/// Register this executable as the extension's native-messaging host. On
+/// Windows browsers find the manifest through a per-user registry key.
+#[cfg(target_os = "windows")]
+pub fn install_host_manifests() {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let (exe, dir) = match (std::env::current_exe(), host_data_dir()) {
+        (Ok(exe), Some(dir)) => (exe, dir),
+        _ => {
+            app_log!("[BrowserBridge] Failed to locate host manifest paths");
+            return;
+        }
+    };
+    let keys = [
+        (
+            r"HKCU\Software\Google\Chrome\NativeMessagingHosts",
+            false,
+            "chrome",
+        ),
+        (
+            r"HKCU\Software\Mozilla\NativeMessagingHosts",
+            true,
+            "firefox",
+        ),
+    ];
+    for (key, firefox, browser) in keys {
+        let Some(manifest) = host_manifest(&exe, firefox) else {
+            continue;
+        };
+        let path = dir.join(format!("{}-{}", browser, HOST_MANIFEST_FILE));
+        if let Err(e) = write_manifest(&path, &manifest) {
+            app_log!("[BrowserBridge] Failed to install host manifest: {}", e);
+            continue;
+        }
+        let status = std::process::Command::new("reg")
+            .args([
+                "add",
+                &format!(r"{}\{}", key, HOST_NAME),
+                "/ve",
+                "/t",
+                "REG_SZ",
+                "/f",
+                "/d",
+            ])
+            .arg(&path)
+            .creation_flags(CREATE_NO_WINDOW)
+            .status();
+        if !status.is_ok_and(|s| s.success()) {
+            app_log!("[BrowserBridge] Failed to register host for {}", browser);
+        }
+    }
+}
+
+/// Drop remembered consent answers, for secure logout on shared machines
+pub(crate) fn forget(app: &tauri::AppHandle) -> Result<(), String> {
+    save_settings(app, &BridgeSettings::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn recognizes_chrome_invocations() {
+        let origin = "chrome-extension://abcdefghijklmnop/";
+        assert_eq!(
+            host_origin(&args(&["app", origin])).as_deref(),
+            Some(origin)
+        );
+        assert_eq!(
+            host_origin(&args(&["app", origin, "--parent-window=0"])).as_deref(),
+            Some(origin)
+        );
+        assert_eq!(host_origin(&args(&["app", origin, "--other"])), None);
+    }
+
+    #[test]
+    fn recognizes_firefox_only_with_our_manifest() {
+        let manifest = format!(
+            "/home/me/.mozilla/native-messaging-hosts/{}",
+            HOST_MANIFEST_FILE
+        );
+        assert_eq!(
+            host_origin(&args(&["app", &manifest, "bridge@goodhang"])).as_deref(),
+            Some("moz-extension://bridge@goodhang")
+        );
+        assert_eq!(host_origin(&args(&["app", "/tmp/other.json", "x"])), None);
+    }
+
+    #[test]
+    fn ignores_normal_launches() {
+        assert_eq!(host_origin(&args(&["app"])), None);
+        assert_eq!(
+            host_origin(&args(&["app", "goodhang://auth/callback"])),
+            None
+        );
+    }
+}
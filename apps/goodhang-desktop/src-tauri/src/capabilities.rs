@@ -0,0 +1,87 @@
+use tauri::ipc::Invoke;
+
+use crate::commands::user_status::{cached_products, Products};
+use crate::errors::AppError;
+
+/// Products beyond GoodHang that a command can require
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Product {
+    FounderOs,
+    VoiceOs,
+}
+
+impl Product {
+    /// Key used for the product in `/api/user/status`
+    pub fn key(&self) -> &'static str {
+        match self {
+            Product::FounderOs => "founder_os",
+            Product::VoiceOs => "voice_os",
+        }
+    }
+
+    fn is_enabled(&self, products: &Products) -> bool {
+        match self {
+            Product::FounderOs => products.founder_os.enabled,
+            Product::VoiceOs => products.voice_os.enabled,
+        }
+    }
+}
+
+/// Commands that only make sense with a particular product enabled
+const GATED_COMMANDS: &[(&str, Product)] = &[
+    ("create_voice_memo", Product::VoiceOs),
+    ("list_voice_memos", Product::VoiceOs),
+    ("list_voice_memos_page", Product::VoiceOs),
+    ("update_voice_memo", Product::VoiceOs),
+    ("delete_voice_memo", Product::VoiceOs),
+    ("sync_voice_memos", Product::VoiceOs),
+    ("list_context_files", Product::VoiceOs),
+    ("delete_context_file", Product::VoiceOs),
+    // The journal and the relationship graph live in FounderOS
+    ("summarize_journal", Product::FounderOs),
+    ("delete_journal_entry", Product::FounderOs),
+    ("list_entities", Product::FounderOs),
+    ("list_relationships", Product::FounderOs),
+];
+
+fn required_product(command: &str) -> Option<Product> {
+    GATED_COMMANDS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map(|(_, product)| *product)
+}
+
+/// Reject a command whose product is disabled according to the last user
+/// status fetch. Before any status is known, commands are let through and
+/// the API remains the authority.
+pub fn check(command: &str) -> Result<(), AppError> {
+    match required_product(command) {
+        Some(product) => require(product),
+        None => Ok(()),
+    }
+}
+
+/// Same rule as `check`, for Rust-side flows that aren't a single command
+pub fn require(product: Product) -> Result<(), AppError> {
+    match cached_products() {
+        Some(products) if !product.is_enabled(&products) => {
+            Err(AppError::product_not_enabled(product.key()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Wrap the generated command handler so every invocation passes `check`
+/// first; rejected commands never reach their implementation
+pub fn guard<R: tauri::Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command();
+        if let Err(error) = crate::startup::check(command).and_then(|_| check(command)) {
+            invoke.resolver.reject(error);
+            return true;
+        }
+        handler(invoke)
+    }
+}
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use super::auth::{load_session, wipe_local_data};
+use super::context_brief::days_since;
+use crate::api::{Api, ApiRequest};
+use crate::audit;
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::stores;
+use crate::user_data;
+
+// Kept across the wipe so the app can still show the grace period after
+// the session is gone
+pub(crate) const DELETION_KEY: &str = "account_deletion";
+const CHALLENGE_TTL_SECS: i64 = 5 * 60;
+
+struct PendingChallenge {
+    user_id: String,
+    phrase: String,
+    expires_at: i64,
+}
+
+static CHALLENGE: Mutex<Option<PendingChallenge>> = Mutex::new(None);
+
+/// What the user must type back to confirm deletion
+#[derive(Debug, Serialize, specta::Type)]
+pub struct DeletionChallenge {
+    pub phrase: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct DeletionStatus {
+    /// none | scheduled
+    pub status: String,
+    #[serde(default, rename = "requestedAt")]
+    pub requested_at: Option<String>,
+    /// When the account is permanently deleted; signing back in before
+    /// then allows cancelling
+    #[serde(default, rename = "scheduledFor")]
+    pub scheduled_for: Option<String>,
+    #[serde(default, rename = "daysRemaining", skip_deserializing)]
+    pub days_remaining: Option<i64>,
+}
+
+impl DeletionStatus {
+    fn none() -> Self {
+        Self {
+            status: "none".to_string(),
+            requested_at: None,
+            scheduled_for: None,
+            days_remaining: None,
+        }
+    }
+
+    fn with_days_remaining(mut self) -> Self {
+        self.days_remaining = self
+            .scheduled_for
+            .as_deref()
+            .and_then(days_since)
+            .map(|d| (-d).max(0));
+        self
+    }
+}
+
+fn save_local_status(
+    app: &tauri::AppHandle,
+    status: Option<&DeletionStatus>,
+) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    match status {
+        Some(status) => store.set(
+            user_data::key(DELETION_KEY),
+            serde_json::to_value(status)
+                .map_err(|e| format!("Failed to serialize deletion status: {}", e))?,
+        ),
+        None => {
+            let _ = store.delete(user_data::key(DELETION_KEY));
+        }
+    }
+    stores::save(app, stores::SETTINGS_STORE)
+}
+
+fn load_local_status(app: &tauri::AppHandle) -> Option<DeletionStatus> {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()?
+        .get(user_data::key(DELETION_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// Start deleting the account: returns a phrase the user must type back
+/// into `confirm_account_deletion` within five minutes
+#[tauri::command]
+#[specta::specta]
+pub async fn request_account_deletion(
+    app: tauri::AppHandle,
+) -> Result<DeletionChallenge, AppError> {
+    let session = load_session(&app)?.ok_or_else(|| "Not signed in".to_string())?;
+    let code = uuid::Uuid::new_v4().simple().to_string()[..6].to_uppercase();
+    let challenge = PendingChallenge {
+        user_id: session.user_id,
+        phrase: format!("DELETE {}", code),
+        expires_at: now_secs() + CHALLENGE_TTL_SECS,
+    };
+    let response = DeletionChallenge {
+        phrase: challenge.phrase.clone(),
+        expires_at: challenge.expires_at,
+    };
+    *CHALLENGE
+        .lock()
+        .map_err(|_| "Challenge lock poisoned".to_string())? = Some(challenge);
+    Ok(response)
+}
+
+/// Schedule the account for deletion on the server, then securely wipe
+/// everything held locally and sign out
+#[tauri::command]
+#[specta::specta]
+pub async fn confirm_account_deletion(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    confirmation: String,
+) -> Result<DeletionStatus, AppError> {
+    audit::audited(app.clone(), "confirm_account_deletion", async {
+        let session = load_session(&app)?.ok_or_else(|| "Not signed in".to_string())?;
+        // Single use: a wrong answer means requesting a new phrase
+        let challenge = CHALLENGE
+            .lock()
+            .map_err(|_| "Challenge lock poisoned".to_string())?
+            .take();
+        let valid = challenge.is_some_and(|c| {
+            c.user_id == session.user_id
+                && c.expires_at >= now_secs()
+                && c.phrase == confirmation.trim()
+        });
+        if !valid {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid confirmation: request a new deletion phrase and type it exactly",
+            ));
+        }
+
+        let response = app
+            .state::<Api>()
+            .send(ApiRequest::post("/api/account/deletion").bearer(&session.token))
+            .await?;
+        let status: DeletionStatus = response.error_for_status()?.json("/api/account/deletion")?;
+        let status = status.with_days_remaining();
+        app_log!(
+            "[Account] Deletion scheduled for {}",
+            status.scheduled_for.as_deref().unwrap_or("unknown")
+        );
+
+        wipe_local_data(&app, &db)?;
+        save_local_status(&app, Some(&status))?;
+        events::emit_app_event(&app, AppEvent::AccountDeletionScheduled(status.clone()));
+        events::emit_app_event(&app, AppEvent::LoggedOut);
+        Ok(status)
+    })
+    .await
+}
+
+/// Whether the account is pending deletion. Asks the server when signed in;
+/// after the post-deletion wipe, reports what was recorded locally.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_account_deletion_status(
+    app: tauri::AppHandle,
+) -> Result<DeletionStatus, AppError> {
+    let Some(session) = load_session(&app)? else {
+        return Ok(load_local_status(&app)
+            .map(DeletionStatus::with_days_remaining)
+            .unwrap_or_else(DeletionStatus::none));
+    };
+
+    let response = app
+        .state::<Api>()
+        .send(ApiRequest::get("/api/account/deletion").bearer(&session.token))
+        .await?;
+    let status: DeletionStatus = response.error_for_status()?.json("/api/account/deletion")?;
+    let status = status.with_days_remaining();
+    save_local_status(&app, (status.status == "scheduled").then_some(&status))?;
+    Ok(status)
+}
+
+/// Keep the account after all; only possible during the grace period, from
+/// a fresh sign-in
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_account_deletion(app: tauri::AppHandle) -> Result<DeletionStatus, AppError> {
+    audit::audited(app.clone(), "cancel_account_deletion", async {
+        let session = load_session(&app)?.ok_or_else(|| "Not signed in".to_string())?;
+        app.state::<Api>()
+            .send(ApiRequest::delete("/api/account/deletion").bearer(&session.token))
+            .await?
+            .error_for_status()?;
+
+        save_local_status(&app, None)?;
+        app_log!("[Account] Deletion cancelled");
+        Ok(DeletionStatus::none())
+    })
+    .await
+}
@@ -1,12 +1,22 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tauri::Manager;
 
-fn get_api_base_url() -> String {
-    std::env::var("GOODHANG_API_URL")
-        .unwrap_or_else(|_| "https://goodhang-staging.vercel.app".to_string())
-}
+use super::analytics;
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::audit;
+use crate::db::Database;
+use crate::errors::{classify, AppError, ErrorCode};
+use crate::logging::app_log;
+use crate::network::{self, ApiHealth};
+use crate::outbox;
+use crate::search_index;
+
+/// Outbox task retrying an activation claim
+pub const CLAIM_TASK: &str = "activation_claim";
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
 pub struct AssessmentPreview {
     pub tier: String,
     #[serde(rename = "archetypeHint")]
@@ -15,7 +25,7 @@ pub struct AssessmentPreview {
     pub overall_score_range: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, specta::Type)]
 pub struct ValidationResult {
     pub valid: bool,
     #[serde(default)]
@@ -28,9 +38,15 @@ pub struct ValidationResult {
     pub user_id: Option<String>,
     pub preview: Option<AssessmentPreview>,
     pub error: Option<String>,
+    #[serde(default, rename = "errorCode", skip_deserializing)]
+    pub error_code: Option<ErrorCode>,
+    /// Set when validation failed for reasons other than the code itself, so
+    /// the activation screen can show what's wrong with the connection
+    #[serde(default, skip_deserializing)]
+    pub health: Option<ApiHealth>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
 pub struct ClaimResult {
     pub success: bool,
     #[serde(default)]
@@ -38,10 +54,12 @@ pub struct ClaimResult {
     #[serde(rename = "userId")]
     pub user_id: Option<String>,
     pub error: Option<String>,
+    #[serde(default, rename = "errorCode", skip_deserializing)]
+    pub error_code: Option<ErrorCode>,
 }
 
 // Personality profile from API
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, specta::Type)]
 pub struct PersonalityProfile {
     #[serde(default)]
     pub mbti: Option<String>,
@@ -52,7 +70,7 @@ pub struct PersonalityProfile {
 }
 
 // Badge from API
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
 pub struct Badge {
     pub id: String,
     pub name: String,
@@ -65,7 +83,7 @@ pub struct Badge {
 }
 
 // V3 Types (D&D Character Profile)
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, specta::Type)]
 pub struct CharacterProfile {
     #[serde(default)]
     pub tagline: Option<String>,
@@ -77,7 +95,7 @@ pub struct CharacterProfile {
     pub character_class: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, specta::Type)]
 pub struct Attributes {
     #[serde(default, rename = "INT")]
     pub int: Option<f64>,
@@ -93,7 +111,32 @@ pub struct Attributes {
     pub dex: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+/// Attribute codes in display order, with their names
+pub const ATTRIBUTES: &[(&str, &str)] = &[
+    ("STR", "Strength"),
+    ("DEX", "Dexterity"),
+    ("CON", "Constitution"),
+    ("INT", "Intelligence"),
+    ("WIS", "Wisdom"),
+    ("CHA", "Charisma"),
+];
+
+impl Attributes {
+    /// Score for an attribute code like `"INT"`
+    pub fn get(&self, code: &str) -> Option<f64> {
+        match code {
+            "STR" => self.str_attr,
+            "DEX" => self.dex,
+            "CON" => self.con,
+            "INT" => self.int,
+            "WIS" => self.wis,
+            "CHA" => self.cha,
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, specta::Type)]
 pub struct AssessmentSignals {
     #[serde(default)]
     pub enneagram_hint: Option<String>,
@@ -105,7 +148,7 @@ pub struct AssessmentSignals {
     pub relationship_style: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, specta::Type)]
 pub struct MatchingProfile {
     #[serde(default)]
     pub ideal_group_size: Option<String>,
@@ -119,8 +162,24 @@ pub struct MatchingProfile {
     pub avoid_match_with: Option<Vec<String>>,
 }
 
+/// One answer's scoring, as found in `question_scores` keyed by question id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestionScore {
+    #[serde(default)]
+    pub question_id: String,
+    /// 0-10
+    pub score: f64,
+    /// Attribute code to signal strength, e.g. `{"INT": 2, "DEX": 1}`
+    #[serde(default)]
+    pub attribute_signals: HashMap<String, f64>,
+    #[serde(default, alias = "extracted_themes")]
+    pub extracted_interests: Vec<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
 // Full assessment results from API - supports both V1 and V3 formats
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
 pub struct AssessmentResults {
     pub session_id: String,
     #[serde(default)]
@@ -162,83 +221,216 @@ pub struct AssessmentResults {
     pub question_scores: Option<serde_json::Value>,
 }
 
+/// Code for an activation failure, from the HTTP status or the server's
+/// message
+fn activation_error_code(status: Option<u16>, error: Option<&str>) -> ErrorCode {
+    let error = error.unwrap_or_default().to_lowercase();
+    if error.contains("expired") || status == Some(410) {
+        ErrorCode::ActivationExpired
+    } else if error.contains("claimed") || error.contains("already") || status == Some(409) {
+        ErrorCode::ActivationClaimed
+    } else if status.is_some_and(|s| s >= 500) {
+        ErrorCode::Server
+    } else {
+        ErrorCode::ActivationInvalid
+    }
+}
+
 #[tauri::command]
-pub async fn validate_activation_key(code: String) -> Result<ValidationResult, String> {
-    let client = reqwest::Client::new();
-
-    let response = client
-        .post(&format!("{}/api/activation/validate", get_api_base_url()))
-        .json(&serde_json::json!({ "code": code }))
-        .send()
-        .await
-        .map_err(|e| format!("Network error: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(ValidationResult {
-            valid: false,
+#[specta::specta]
+pub async fn validate_activation_key(
+    app: tauri::AppHandle,
+    code: String,
+) -> Result<ValidationResult, AppError> {
+    audit::audited(app.clone(), "validate_activation_key", async {
+        let request = ApiRequest::post("/api/activation/validate")
+            .json(&serde_json::json!({ "code": code }))?;
+        let response = match app.state::<Api>().send(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                return Ok(ValidationResult {
+                    error_code: Some(network::unreachable_code(e.contains("timed out"))),
+                    error: Some(e),
+                    health: Some(network::check_api_health().await),
+                    ..Default::default()
+                });
+            }
+        };
+
+        if !response.status.is_success() {
+            let error_code = activation_error_code(Some(response.status.as_u16()), None);
+            let health = match error_code {
+                ErrorCode::Server => Some(network::check_api_health().await),
+                _ => None,
+            };
+            return Ok(ValidationResult {
+                error: Some(format!("Server error: {}", response.status)),
+                error_code: Some(error_code),
+                health,
+                ..Default::default()
+            });
+        }
+
+        let mut result: ValidationResult = response
+            .json("/api/activation/validate")
+            .map_err(|e| AppError::new(ErrorCode::SchemaMismatch, e))?;
+        if !result.valid {
+            result.error_code = Some(activation_error_code(None, result.error.as_deref()));
+        }
+        Ok(result)
+    })
+    .await
+}
+
+/// POST a claim. Refusals (invalid, expired, already claimed) are answers
+/// and come back as an unsuccessful result; server errors are `Err` so the
+/// outbox retries them.
+async fn send_claim(
+    app: &tauri::AppHandle,
+    payload: serde_json::Value,
+) -> Result<ClaimResult, String> {
+    let response = app
+        .state::<Api>()
+        .send(ApiRequest::post("/api/activation/claim").json(&payload)?)
+        .await?;
+
+    if response.status.is_server_error() {
+        return Err(format!("Server error {}", response.status));
+    }
+    if !response.status.is_success() {
+        return Ok(ClaimResult {
+            success: false,
             product: None,
-            session_id: None,
-            has_existing_user: None,
             user_id: None,
-            preview: None,
-            error: Some(format!("Server error: {}", response.status())),
+            error: Some(format!("Server error: {}", response.status)),
+            error_code: Some(activation_error_code(Some(response.status.as_u16()), None)),
         });
     }
 
-    response
-        .json::<ValidationResult>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+    let mut result: ClaimResult = response.json("/api/activation/claim")?;
+    if !result.success {
+        result.error_code = Some(activation_error_code(None, result.error.as_deref()));
+    }
+    Ok(result)
 }
 
-#[tauri::command]
-pub async fn claim_activation_key(code: String, user_id: String) -> Result<ClaimResult, String> {
-    let client = reqwest::Client::new();
+/// Outbox handler: finish a claim whose first attempt got no answer
+pub async fn deliver_claim(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let result = send_claim(app, payload.clone()).await?;
+    app_log!(
+        "[Activation] Queued claim answered: success={}",
+        result.success
+    );
+    Ok(())
+}
 
-    let response = client
-        .post(&format!("{}/api/activation/claim", get_api_base_url()))
-        .json(&serde_json::json!({
+/// Claim an activation code for a user. The claim goes through the outbox,
+/// so one the server didn't answer is retried in the background.
+#[tauri::command]
+#[specta::specta]
+pub async fn claim_activation_key(
+    app: tauri::AppHandle,
+    code: String,
+    user_id: String,
+) -> Result<ClaimResult, AppError> {
+    audit::audited(app.clone(), "claim_activation_key", async {
+        let payload = serde_json::json!({
             "code": code,
             "userId": user_id
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Network error: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(ClaimResult {
-            success: false,
-            product: None,
-            user_id: None,
-            error: Some(format!("Server error: {}", response.status())),
         });
+        let entity = format!("activation:{}", code);
+        let sent = outbox::deliver_now(&app, CLAIM_TASK, &entity, payload, |payload| {
+            send_claim(&app, payload)
+        })
+        .await;
+
+        match sent {
+            Ok(result) => Ok(result),
+            Err(e) if matches!(classify(&e), ErrorCode::Server) => Ok(ClaimResult {
+                success: false,
+                product: None,
+                user_id: None,
+                error: Some(e),
+                error_code: Some(ErrorCode::Server),
+            }),
+            Err(e) => Err(e.into()),
+        }
+    })
+    .await
+}
+
+impl AssessmentResults {
+    /// Typed view of `question_scores`, sorted by question id. Entries that
+    /// don't match the V3 shape are skipped.
+    pub fn parsed_question_scores(&self) -> Vec<QuestionScore> {
+        let Some(serde_json::Value::Object(entries)) = &self.question_scores else {
+            return Vec::new();
+        };
+        let mut scores: Vec<QuestionScore> = entries
+            .iter()
+            .filter_map(|(id, value)| {
+                let mut score: QuestionScore = serde_json::from_value(value.clone()).ok()?;
+                score.question_id = id.clone();
+                Some(score)
+            })
+            .collect();
+        scores.sort_by(|a, b| a.question_id.cmp(&b.question_id));
+        scores
     }
 
-    response
-        .json::<ClaimResult>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+    /// Flatten the human-readable parts of the results for full-text search
+    fn search_text(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        parts.extend(self.archetype.clone());
+        parts.extend(self.tier.clone());
+        parts.extend(self.public_summary.clone());
+        parts.extend(self.detailed_summary.clone());
+        if let Some(roles) = &self.best_fit_roles {
+            parts.push(roles.join(", "));
+        }
+        if let Some(profile) = &self.character_profile {
+            parts.extend(profile.tagline.clone());
+            parts.extend(profile.race.clone());
+            parts.extend(profile.character_class.clone());
+            parts.extend(profile.alignment.clone());
+        }
+        if let Some(badges) = &self.badges {
+            parts.extend(badges.iter().map(|b| b.name.clone()));
+        }
+        parts.join("\n")
+    }
 }
 
 #[tauri::command]
-pub async fn fetch_assessment_results(session_id: String, token: String) -> Result<AssessmentResults, String> {
-    let client = reqwest::Client::new();
-
-    let response = client
-        .get(&format!("{}/api/assessment/{}/results", get_api_base_url(), session_id))
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .await
-        .map_err(|e| format!("Network error: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Server error {}: {}", status, body));
+#[specta::specta]
+pub async fn fetch_assessment_results(
+    app: tauri::AppHandle,
+    session_id: String,
+    token: String,
+) -> Result<AssessmentResults, AppError> {
+    let request = ApiRequest::get(format!("/api/assessment/{}/results", session_id)).bearer(&token);
+    let results: AssessmentResults = app
+        .state::<Api>()
+        .send(request)
+        .await?
+        .error_for_status()?
+        .json("/api/assessment/results")?;
+
+    let user_id = results
+        .user_id
+        .clone()
+        .or_else(|| load_session(&app).ok().flatten().map(|s| s.user_id));
+    if let Some(user_id) = user_id {
+        if let Err(e) = analytics::record_snapshot(&app.state::<Database>(), &user_id, &results) {
+            app_log!("[Activation] Failed to store results snapshot: {}", e);
+        }
     }
 
-    response
-        .json::<AssessmentResults>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+    let title = results.archetype.clone().unwrap_or_else(|| "Assessment results".to_string());
+    search_index::upsert_quietly(&app, "assessment", &results.session_id, &title, &results.search_text());
+
+    Ok(results)
 }
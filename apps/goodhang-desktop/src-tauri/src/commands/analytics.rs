@@ -0,0 +1,542 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tauri::Manager;
+
+use super::activation::{fetch_assessment_results, AssessmentResults, ATTRIBUTES};
+use crate::api::{require_token, Api, ApiRequest};
+use crate::consent::{self, Feature};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+use crate::outbox;
+
+// Population statistics move slowly; refetch at most daily
+const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+// Attributes are scored 1-10
+const ATTRIBUTE_MAX: f64 = 10.0;
+const MAX_EVENT_NAME_CHARS: usize = 64;
+/// Outbox task sending one usage event
+pub const EVENT_TASK: &str = "analytics_event";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PercentileResponse {
+    /// Attribute code to percentile (0-100) among all users
+    percentiles: HashMap<String, f64>,
+    #[serde(default, rename = "sampleSize")]
+    sample_size: Option<u64>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ChartAxis {
+    pub code: String,
+    pub label: String,
+}
+
+/// One line on the radar chart, aligned with `axes`
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ChartSeries {
+    pub key: String,
+    pub name: String,
+    /// 0-100; `None` where the value is unknown
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct AttributeChartData {
+    pub axes: Vec<ChartAxis>,
+    pub series: Vec<ChartSeries>,
+    #[serde(rename = "sampleSize")]
+    pub sample_size: Option<u64>,
+    /// True when percentiles came from an expired cache entry because the
+    /// API couldn't be reached
+    pub stale: bool,
+}
+
+/// Cached JSON for `key`, with whether it has outlived `CACHE_TTL_SECS`
+fn cache_get<T: serde::de::DeserializeOwned>(
+    db: &Database,
+    key: &str,
+) -> Result<Option<(T, bool)>, String> {
+    let row: Option<(String, i64)> = db.with_conn(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT payload, fetched_at FROM analytics_cache WHERE key = ?1")?;
+        let mut rows = stmt.query_map([key], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.next().transpose()
+    })?;
+    Ok(row.and_then(|(payload, fetched_at)| {
+        let value = serde_json::from_str(&payload).ok()?;
+        Some((value, now_secs() - fetched_at > CACHE_TTL_SECS))
+    }))
+}
+
+fn cache_put<T: Serialize>(db: &Database, key: &str, value: &T) -> Result<(), String> {
+    let payload =
+        serde_json::to_string(value).map_err(|e| format!("Failed to serialize cache: {}", e))?;
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO analytics_cache (key, payload, fetched_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET payload = ?2, fetched_at = ?3",
+            rusqlite::params![key, payload, now_secs()],
+        )
+    })
+    .map(|_| ())
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(
+    api: &Api,
+    token: &str,
+    path: &str,
+    endpoint: &str,
+) -> Result<T, String> {
+    let response = api
+        .send(ApiRequest::get(path).bearer(token))
+        .await?
+        .error_for_status()?;
+    response.json(endpoint)
+}
+
+/// Fresh cache if we have it, else the API, else a stale cache entry when
+/// the API fails. The flag reports whether stale data was served.
+async fn cached_fetch<T>(
+    db: &Database,
+    api: &Api,
+    key: &str,
+    token: &str,
+    path: &str,
+    endpoint: &str,
+) -> Result<(T, bool), String>
+where
+    T: Serialize + serde::de::DeserializeOwned,
+{
+    let cached = cache_get::<T>(db, key)?;
+    if let Some((value, false)) = cached {
+        return Ok((value, false));
+    }
+
+    match get_json::<T>(api, token, path, endpoint).await {
+        Ok(fresh) => {
+            cache_put(db, key, &fresh)?;
+            Ok((fresh, false))
+        }
+        Err(e) => match cached {
+            Some((value, _)) => {
+                app_log!("[Analytics] Serving stale {}: {}", key, e);
+                Ok((value, true))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Population percentiles, or `None` if they can't be had right now; the
+/// chart still renders the user's own scores without them
+async fn percentiles(
+    db: &Database,
+    api: &Api,
+    token: &str,
+    session_id: &str,
+) -> (Option<PercentileResponse>, bool) {
+    let key = format!("percentiles:{}", session_id);
+    let path = format!("/api/assessment/{}/percentiles", session_id);
+    match cached_fetch(db, api, &key, token, &path, "/api/assessment/percentiles").await {
+        Ok((value, stale)) => (Some(value), stale),
+        Err(e) => {
+            app_log!("[Analytics] Percentiles unavailable: {}", e);
+            (None, false)
+        }
+    }
+}
+
+/// Radar chart series for the six attributes: the user's scores scaled to
+/// 0-100 and, when available, their population percentiles
+#[tauri::command]
+#[specta::specta]
+pub async fn get_attribute_chart_data(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    session_id: String,
+) -> Result<AttributeChartData, AppError> {
+    let token = require_token(&app)?;
+    let results = fetch_assessment_results(app.clone(), session_id.clone(), token.clone()).await?;
+    let (percentiles, stale) = percentiles(&db, &app.state::<Api>(), &token, &session_id).await;
+
+    let scores = ATTRIBUTES
+        .iter()
+        .map(|(code, _)| {
+            let value = results.attributes.as_ref()?.get(code)?;
+            Some((value / ATTRIBUTE_MAX * 100.0).clamp(0.0, 100.0))
+        })
+        .collect();
+    let mut series = vec![ChartSeries {
+        key: "score".to_string(),
+        name: "You".to_string(),
+        values: scores,
+    }];
+    if let Some(p) = &percentiles {
+        series.push(ChartSeries {
+            key: "percentile".to_string(),
+            name: "Percentile".to_string(),
+            values: ATTRIBUTES
+                .iter()
+                .map(|(code, _)| p.percentiles.get(*code).map(|v| v.clamp(0.0, 100.0)))
+                .collect(),
+        });
+    }
+
+    Ok(AttributeChartData {
+        axes: ATTRIBUTES
+            .iter()
+            .map(|(code, label)| ChartAxis {
+                code: code.to_string(),
+                label: label.to_string(),
+            })
+            .collect(),
+        series,
+        sample_size: percentiles.and_then(|p| p.sample_size),
+        stale,
+    })
+}
+
+// How many answers to call out at each end
+const HIGHLIGHT_COUNT: usize = 3;
+const GENERAL_DIMENSION: &str = "general";
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AnswerInsight {
+    #[serde(rename = "questionId")]
+    pub question_id: String,
+    pub score: f64,
+    pub dimension: String,
+    pub notes: Option<String>,
+    pub interests: Vec<String>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct DimensionInsight {
+    /// Attribute code, or `general` for answers with no attribute signal
+    pub dimension: String,
+    pub label: String,
+    #[serde(rename = "questionCount")]
+    pub question_count: usize,
+    #[serde(rename = "averageScore")]
+    pub average_score: f64,
+    pub strongest: AnswerInsight,
+    pub weakest: AnswerInsight,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct QuestionAnalytics {
+    pub dimensions: Vec<DimensionInsight>,
+    #[serde(rename = "strongestAnswers")]
+    pub strongest_answers: Vec<AnswerInsight>,
+    #[serde(rename = "weakestAnswers")]
+    pub weakest_answers: Vec<AnswerInsight>,
+    #[serde(rename = "questionCount")]
+    pub question_count: usize,
+}
+
+/// The attribute an answer speaks to most strongly
+fn primary_dimension(signals: &HashMap<String, f64>) -> String {
+    signals
+        .iter()
+        .filter(|(_, strength)| **strength > 0.0)
+        .max_by(|a, b| a.1.total_cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(code, _)| code.clone())
+        .unwrap_or_else(|| GENERAL_DIMENSION.to_string())
+}
+
+fn by_score_desc(a: &AnswerInsight, b: &AnswerInsight) -> std::cmp::Ordering {
+    b.score
+        .total_cmp(&a.score)
+        .then_with(|| a.question_id.cmp(&b.question_id))
+}
+
+/// Question scores grouped by the attribute each answer signals most, with
+/// the strongest and weakest answers called out
+#[tauri::command]
+#[specta::specta]
+pub async fn get_question_analytics(
+    app: tauri::AppHandle,
+    session_id: String,
+) -> Result<QuestionAnalytics, AppError> {
+    let token = require_token(&app)?;
+    let results = fetch_assessment_results(app.clone(), session_id, token).await?;
+
+    let mut answers: Vec<AnswerInsight> = results
+        .parsed_question_scores()
+        .into_iter()
+        .map(|q| AnswerInsight {
+            dimension: primary_dimension(&q.attribute_signals),
+            question_id: q.question_id,
+            score: q.score,
+            notes: q.notes,
+            interests: q.extracted_interests,
+        })
+        .collect();
+    answers.sort_by(by_score_desc);
+
+    // Attribute order first, then the catch-all
+    let order = ATTRIBUTES
+        .iter()
+        .copied()
+        .chain(std::iter::once((GENERAL_DIMENSION, "General")));
+    let dimensions = order
+        .filter_map(|(code, label)| {
+            let group: Vec<&AnswerInsight> =
+                answers.iter().filter(|a| a.dimension == code).collect();
+            Some(DimensionInsight {
+                dimension: code.to_string(),
+                label: label.to_string(),
+                question_count: group.len(),
+                average_score: group.iter().map(|a| a.score).sum::<f64>() / group.len() as f64,
+                strongest: (*group.first()?).clone(),
+                weakest: (*group.last()?).clone(),
+            })
+        })
+        .collect();
+
+    Ok(QuestionAnalytics {
+        dimensions,
+        strongest_answers: answers.iter().take(HIGHLIGHT_COUNT).cloned().collect(),
+        weakest_answers: answers
+            .iter()
+            .rev()
+            .take(HIGHLIGHT_COUNT)
+            .cloned()
+            .collect(),
+        question_count: answers.len(),
+    })
+}
+
+/// One stored version of the user's results, with changes from the one before
+#[derive(Debug, Serialize, specta::Type)]
+pub struct AssessmentHistoryEntry {
+    pub version: usize,
+    #[serde(rename = "recordedAt")]
+    pub recorded_at: i64,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    pub archetype: Option<String>,
+    pub tier: Option<String>,
+    #[serde(rename = "overallScore")]
+    pub overall_score: f64,
+    #[serde(rename = "archetypeChanged")]
+    pub archetype_changed: bool,
+    /// `None` for the first version
+    #[serde(rename = "scoreDelta")]
+    pub score_delta: Option<f64>,
+    /// Attribute code to change since the previous version
+    #[serde(rename = "attributeDeltas")]
+    pub attribute_deltas: HashMap<String, f64>,
+}
+
+/// Keep a copy of fetched results, skipping ones identical to a stored
+/// version
+pub(crate) fn record_snapshot(
+    db: &Database,
+    user_id: &str,
+    results: &AssessmentResults,
+) -> Result<(), String> {
+    let payload = serde_json::to_string(results)
+        .map_err(|e| format!("Failed to serialize results: {}", e))?;
+    let content_hash = hex::encode(Sha256::digest(payload.as_bytes()));
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT OR IGNORE INTO assessment_snapshots
+                (user_id, session_id, content_hash, payload, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                user_id,
+                results.session_id,
+                content_hash,
+                payload,
+                now_secs()
+            ],
+        )
+    })
+    .map(|_| ())
+}
+
+/// Archetype changes and score deltas across every stored version, oldest
+/// first
+#[tauri::command]
+#[specta::specta]
+pub async fn get_assessment_history(
+    db: tauri::State<'_, Database>,
+    user_id: String,
+) -> Result<Vec<AssessmentHistoryEntry>, String> {
+    let rows: Vec<(String, i64)> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT payload, recorded_at FROM assessment_snapshots
+             WHERE user_id = ?1 ORDER BY recorded_at ASC, id ASC",
+        )?;
+        let rows = stmt.query_map([&user_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })?;
+
+    let snapshots = rows.into_iter().filter_map(|(payload, recorded_at)| {
+        let results: AssessmentResults = serde_json::from_str(&payload).ok()?;
+        Some((results, recorded_at))
+    });
+
+    let mut history: Vec<AssessmentHistoryEntry> = Vec::new();
+    let mut previous: Option<AssessmentResults> = None;
+    for (results, recorded_at) in snapshots {
+        let attribute_deltas = ATTRIBUTES
+            .iter()
+            .filter_map(|(code, _)| {
+                let before = previous.as_ref()?.attributes.as_ref()?.get(code)?;
+                let after = results.attributes.as_ref()?.get(code)?;
+                Some((code.to_string(), after - before))
+            })
+            .collect();
+        history.push(AssessmentHistoryEntry {
+            version: history.len() + 1,
+            recorded_at,
+            session_id: results.session_id.clone(),
+            archetype_changed: previous
+                .as_ref()
+                .is_some_and(|p| p.archetype != results.archetype),
+            score_delta: previous
+                .as_ref()
+                .map(|p| results.overall_score - p.overall_score),
+            attribute_deltas,
+            archetype: results.archetype.clone(),
+            tier: results.tier.clone(),
+            overall_score: results.overall_score,
+        });
+        previous = Some(results);
+    }
+    Ok(history)
+}
+
+const COHORTS: &[&str] = &["all_users", "same_archetype", "same_company"];
+// Below this many members, percentiles say more about noise than the user
+const MIN_COHORT_SIZE: u64 = 20;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CohortResponse {
+    #[serde(rename = "sampleSize")]
+    sample_size: u64,
+    /// Overall score and attribute code to percentile (0-100); may be
+    /// omitted when the server considers the cohort too small
+    #[serde(default)]
+    percentiles: HashMap<String, f64>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct CohortComparison {
+    pub cohort: String,
+    #[serde(rename = "sampleSize")]
+    pub sample_size: u64,
+    /// False when the cohort is too small for percentiles to mean much;
+    /// `percentiles` is then empty
+    pub meaningful: bool,
+    pub percentiles: HashMap<String, f64>,
+    pub stale: bool,
+}
+
+/// Percentile comparison against a cohort: `all_users`, `same_archetype`,
+/// or `same_company`
+#[tauri::command]
+#[specta::specta]
+pub async fn get_cohort_comparison(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    session_id: String,
+    cohort: String,
+) -> Result<CohortComparison, AppError> {
+    if !COHORTS.contains(&cohort.as_str()) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid cohort '{}': expected one of {}",
+                cohort,
+                COHORTS.join(", ")
+            ),
+        ));
+    }
+    let token = require_token(&app)?;
+
+    let key = format!("cohort:{}:{}", session_id, cohort);
+    let path = format!("/api/assessment/{}/cohorts/{}", session_id, cohort);
+    let (response, stale) = cached_fetch::<CohortResponse>(
+        &db,
+        &app.state::<Api>(),
+        &key,
+        &token,
+        &path,
+        "/api/assessment/cohorts",
+    )
+    .await?;
+
+    let meaningful = response.sample_size >= MIN_COHORT_SIZE && !response.percentiles.is_empty();
+    Ok(CohortComparison {
+        cohort,
+        sample_size: response.sample_size,
+        meaningful,
+        percentiles: if meaningful {
+            response.percentiles
+        } else {
+            HashMap::new()
+        },
+        stale,
+    })
+}
+
+/// Record a usage event. Dropped unless the user opted in to analytics;
+/// kept events are sent through the outbox.
+#[tauri::command]
+#[specta::specta]
+pub async fn track_event(
+    app: tauri::AppHandle,
+    name: String,
+    properties: Option<serde_json::Value>,
+) -> Result<(), AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_EVENT_NAME_CHARS {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid event name: use 1 to {} characters",
+                MAX_EVENT_NAME_CHARS
+            ),
+        ));
+    }
+    if !consent::is_granted(&app, Feature::Analytics) {
+        return Ok(());
+    }
+    outbox::enqueue(
+        &app,
+        EVENT_TASK,
+        None,
+        serde_json::json!({
+            "name": name,
+            "properties": properties,
+            "occurredAt": now_secs(),
+        }),
+    )?;
+    Ok(())
+}
+
+/// Outbox handler: send one usage event, unless consent was withdrawn
+/// while it waited
+pub async fn deliver_event(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    if !consent::is_granted(app, Feature::Analytics) {
+        return Ok(());
+    }
+    let token = require_token(app)?;
+    app.state::<Api>()
+        .send(
+            ApiRequest::post("/api/analytics/events")
+                .bearer(&token)
+                .json(&serde_json::json!({ "events": [payload] }))?,
+        )
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
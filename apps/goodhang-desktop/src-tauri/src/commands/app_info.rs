@@ -0,0 +1,108 @@
+use serde::Serialize;
+use tauri_plugin_updater::UpdaterExt;
+
+use crate::api::{self, ApiEnvironment};
+use crate::db::now_secs;
+use crate::errors::AppError;
+use crate::logging::app_log;
+use crate::stores;
+
+/// Device-wide, like the API URL: when updates were last checked for
+const LAST_UPDATE_CHECK_KEY: &str = "lastUpdateCheck";
+
+/// What the About dialog shows, and what goes with every feedback report
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AppInfo {
+    pub version: String,
+    /// The commit built from; absent when the build had no git checkout
+    #[serde(rename = "buildHash")]
+    pub build_hash: Option<String>,
+    /// dev | beta | stable
+    pub channel: String,
+    #[serde(rename = "apiEnvironment")]
+    pub api_environment: ApiEnvironment,
+    #[serde(rename = "apiBaseUrl")]
+    pub api_base_url: String,
+    pub os: String,
+    pub arch: String,
+    /// When `check_for_updates` last completed, in unix seconds
+    #[serde(rename = "lastUpdateCheck")]
+    pub last_update_check: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct UpdateCheck {
+    /// The newer version on offer, if there is one
+    pub available: Option<String>,
+    /// Release notes for it
+    pub notes: Option<String>,
+    #[serde(rename = "checkedAt")]
+    pub checked_at: i64,
+}
+
+/// Release channel baked in at build time; debug builds are always `dev`
+fn channel() -> &'static str {
+    match option_env!("GOODHANG_RELEASE_CHANNEL") {
+        Some(channel) if !channel.is_empty() => channel,
+        _ if cfg!(debug_assertions) => "dev",
+        _ => "stable",
+    }
+}
+
+fn last_update_check(app: &tauri::AppHandle) -> Option<i64> {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(LAST_UPDATE_CHECK_KEY))
+        .and_then(|value| value.as_i64())
+}
+
+pub(crate) fn app_info(app: &tauri::AppHandle) -> AppInfo {
+    AppInfo {
+        version: app.package_info().version.to_string(),
+        build_hash: option_env!("GOODHANG_BUILD_HASH").map(str::to_string),
+        channel: channel().to_string(),
+        api_environment: api::environment(),
+        api_base_url: api::base_url(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        last_update_check: last_update_check(app),
+    }
+}
+
+/// Version, build and environment details for the About dialog
+#[tauri::command]
+#[specta::specta]
+pub async fn get_app_info(app: tauri::AppHandle) -> Result<AppInfo, String> {
+    Ok(app_info(&app))
+}
+
+/// Ask the update server whether a newer version is out, and remember when
+/// it answered. Installing is left to the updater plugin.
+#[tauri::command]
+#[specta::specta]
+pub async fn check_for_updates(app: tauri::AppHandle) -> Result<UpdateCheck, AppError> {
+    let update = app
+        .updater()
+        .map_err(|e| format!("Failed to start update check: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Network error checking for updates: {}", e))?;
+
+    let checked_at = now_secs();
+    stores::update(&app, stores::SETTINGS_STORE, |store| {
+        store.set(LAST_UPDATE_CHECK_KEY, serde_json::json!(checked_at));
+        Ok(())
+    })
+    .await?;
+
+    let check = UpdateCheck {
+        available: update.as_ref().map(|u| u.version.clone()),
+        notes: update.and_then(|u| u.body),
+        checked_at,
+    };
+    app_log!(
+        "[Updates] Checked: {}",
+        check.available.as_deref().unwrap_or("up to date")
+    );
+    Ok(check)
+}
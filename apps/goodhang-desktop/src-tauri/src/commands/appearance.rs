@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Manager, Theme};
+
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::stores;
+
+const APPEARANCE_KEY: &str = "appearance";
+// The OS sends no event for high contrast, so it's checked on this interval
+const HIGH_CONTRAST_INTERVAL: Duration = Duration::from_secs(10);
+const PREFERENCES: &[&str] = &["system", "light", "dark"];
+
+// Last state sent to the webview, so repeated OS events don't re-emit
+static LAST_EMITTED: Mutex<Option<Appearance>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+pub struct Appearance {
+    /// light | dark: what the UI should render
+    pub theme: String,
+    /// system | light | dark: the user's override
+    pub preference: String,
+    #[serde(rename = "highContrast")]
+    pub high_contrast: bool,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct AppearanceUpdate {
+    pub preference: String,
+}
+
+fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+fn saved_preference(app: &tauri::AppHandle) -> String {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(APPEARANCE_KEY))
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|p| PREFERENCES.contains(&p.as_str()))
+        .unwrap_or_else(|| "system".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn high_contrast() -> bool {
+    std::process::Command::new("defaults")
+        .args(["read", "com.apple.universalaccess", "increaseContrast"])
+        .output()
+        .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).trim() == "1")
+}
+
+#[cfg(target_os = "windows")]
+fn high_contrast() -> bool {
+    use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETHIGHCONTRAST, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    let mut info = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        ..Default::default()
+    };
+    // SAFETY: `info` is a sized HIGHCONTRASTW that outlives the call
+    let read = unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            info.cbSize,
+            Some(&mut info as *mut HIGHCONTRASTW as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    };
+    read.is_ok() && info.dwFlags.contains(HCF_HIGHCONTRASTON)
+}
+
+#[cfg(target_os = "linux")]
+fn high_contrast() -> bool {
+    std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.a11y.interface", "high-contrast"])
+        .output()
+        .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).trim() == "true")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn high_contrast() -> bool {
+    false
+}
+
+fn current(app: &tauri::AppHandle) -> Appearance {
+    let preference = saved_preference(app);
+    let theme = match preference.as_str() {
+        "system" => app
+            .get_webview_window("main")
+            .and_then(|window| window.theme().ok())
+            .map(theme_name)
+            .unwrap_or("light")
+            .to_string(),
+        forced => forced.to_string(),
+    };
+    Appearance {
+        theme,
+        preference,
+        high_contrast: high_contrast(),
+    }
+}
+
+fn forced_theme(preference: &str) -> Option<Theme> {
+    match preference {
+        "light" => Some(Theme::Light),
+        "dark" => Some(Theme::Dark),
+        _ => None,
+    }
+}
+
+/// Force every window to the preferred theme, or hand it back to the OS
+fn apply(app: &tauri::AppHandle, preference: &str) {
+    for window in app.webview_windows().values() {
+        if let Err(e) = window.set_theme(forced_theme(preference)) {
+            app_log!("[Appearance] Failed to set theme: {}", e);
+        }
+    }
+}
+
+/// Page load hook, so windows opened after startup (quick capture, say)
+/// get the saved preference too
+pub fn apply_to_window(window: &tauri::Window) {
+    let preference = saved_preference(window.app_handle());
+    if let Err(e) = window.set_theme(forced_theme(&preference)) {
+        app_log!("[Appearance] Failed to set theme: {}", e);
+    }
+}
+
+/// Emit `appearance-changed` if anything differs from the last emit
+fn emit_if_changed(app: &tauri::AppHandle) {
+    let appearance = current(app);
+    let Ok(mut last) = LAST_EMITTED.lock() else {
+        return;
+    };
+    if last.as_ref() != Some(&appearance) {
+        *last = Some(appearance.clone());
+        events::emit_app_event(app, AppEvent::AppearanceChanged(appearance));
+    }
+}
+
+/// Window event hook for OS light/dark flips. Reading high contrast can
+/// shell out, so it's kept off the event loop.
+pub fn handle_theme_changed(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || emit_if_changed(&app));
+}
+
+/// Apply the saved preference at startup and watch for high-contrast changes
+pub fn spawn_appearance_monitor(app: tauri::AppHandle) {
+    apply(&app, &saved_preference(&app));
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let handle = app.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || emit_if_changed(&handle)).await;
+            tokio::time::sleep(HIGH_CONTRAST_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_appearance(app: tauri::AppHandle) -> Result<Appearance, String> {
+    Ok(current(&app))
+}
+
+/// Override the OS theme ("light"/"dark") or follow it again ("system")
+#[tauri::command]
+#[specta::specta]
+pub async fn set_appearance(
+    app: tauri::AppHandle,
+    appearance: AppearanceUpdate,
+) -> Result<Appearance, AppError> {
+    if !PREFERENCES.contains(&appearance.preference.as_str()) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid appearance '{}': expected system, light or dark",
+                appearance.preference
+            ),
+        ));
+    }
+    let store = stores::open(&app, stores::SETTINGS_STORE)?;
+    store.set(APPEARANCE_KEY, serde_json::json!(appearance.preference));
+    stores::save(&app, stores::SETTINGS_STORE)?;
+
+    apply(&app, &appearance.preference);
+    emit_if_changed(&app);
+    Ok(current(&app))
+}
@@ -1,8 +1,25 @@
 use serde::{Deserialize, Serialize};
-use tauri_plugin_store::StoreExt;
-use std::path::PathBuf;
+use tauri::Manager;
 
-const STORE_FILENAME: &str = "auth.json";
+use super::backup::STORES;
+use crate::audit;
+use crate::crypto;
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::http;
+use crate::logging::{self, app_log};
+use crate::paths;
+use crate::session::session_expiry;
+use crate::store_integrity::{QUARANTINE_DIR, SNAPSHOT_DIR};
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const STORE_FILENAME: &str = "auth.json";
+
+// Locally downloaded or recorded assets removed on secure logout, relative to
+// the app data dir
+pub(crate) const ASSET_DIRS: &[&str] = &["voice_memos", super::hangs::CALENDAR_DIR];
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SessionData {
@@ -11,10 +28,13 @@ pub struct SessionData {
     #[serde(rename = "sessionId")]
     pub session_id: String,
     pub token: String,
+    /// Unix seconds; server-provided TTL when known, otherwise read from the token
+    #[serde(default, rename = "expiresAt", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
 }
 
 /// Permanent device registration - stores the activation code and refresh token
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
 pub struct DeviceRegistration {
     #[serde(rename = "activationCode")]
     pub activation_code: String,
@@ -25,136 +45,355 @@ pub struct DeviceRegistration {
     pub refresh_token: String,
 }
 
+/// Read the stored session for use by other Rust-side commands
+pub(crate) fn load_session(app: &tauri::AppHandle) -> Result<Option<SessionData>, String> {
+    let store = stores::open(app, STORE_FILENAME)?;
+
+    match store.get("session") {
+        Some(value) => serde_json::from_value(value)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse session: {}", e)),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn load_device_registration(
+    app: &tauri::AppHandle,
+) -> Result<Option<DeviceRegistration>, String> {
+    let store = stores::open(app, STORE_FILENAME)?;
+
+    match store.get("device_registration") {
+        Some(value) => serde_json::from_value(value)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse registration: {}", e)),
+        None => Ok(None),
+    }
+}
+
+pub(crate) async fn save_device_registration(
+    app: &tauri::AppHandle,
+    registration: &DeviceRegistration,
+) -> Result<(), String> {
+    let value = serde_json::to_value(registration)
+        .map_err(|e| format!("Failed to serialize registration: {}", e))?;
+
+    stores::update(app, STORE_FILENAME, |store| {
+        store.set("device_registration", value);
+        Ok(())
+    })
+    .await
+}
+
+pub(crate) async fn save_session(app: &tauri::AppHandle, session: &SessionData) -> Result<(), String> {
+    let value = serde_json::to_value(session)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+
+    stores::update(app, STORE_FILENAME, |store| {
+        store.set("session", value);
+        Ok(())
+    })
+    .await?;
+
+    // A different account signing in gets its own local data
+    user_data::activate(app, Some(&session.user_id))
+}
+
+/// Re-save a store written before at-rest encryption so it's encrypted on
+/// disk immediately, rather than on the next incidental write
+pub(crate) fn encrypt_legacy_store(app: &tauri::AppHandle) -> Result<(), String> {
+    let path = paths::data_dir(app)?.join(STORE_FILENAME);
+
+    let Ok(bytes) = std::fs::read(&path) else {
+        return Ok(()); // No store yet
+    };
+    if crypto::is_encrypted(&bytes) {
+        return Ok(());
+    }
+
+    stores::save(app, STORE_FILENAME)?;
+
+    app_log!("[Auth] Migrated plaintext store to encrypted storage");
+    Ok(())
+}
+
 #[tauri::command]
+#[specta::specta]
 pub async fn store_device_registration(
     app: tauri::AppHandle,
     activation_code: String,
     user_id: String,
     product: String,
     refresh_token: String,
-) -> Result<(), String> {
-    let registration = DeviceRegistration {
-        activation_code,
-        user_id,
-        product,
-        refresh_token,
-    };
-
-    let store = app.store(PathBuf::from(STORE_FILENAME))
-        .map_err(|e| format!("Failed to open store: {}", e))?;
-
-    store.set("device_registration", serde_json::to_value(&registration)
-        .map_err(|e| format!("Failed to serialize registration: {}", e))?);
+) -> Result<(), AppError> {
+    audit::audited(app.clone(), "store_device_registration", async {
+        let registration = DeviceRegistration {
+            activation_code,
+            user_id,
+            product,
+            refresh_token,
+        };
 
-    store.save()
-        .map_err(|e| format!("Failed to save store: {}", e))?;
+        save_device_registration(&app, &registration)
+            .await
+            .map_err(storage_error)?;
 
-    println!("[Auth] Device registration stored successfully");
-    Ok(())
+        app_log!("[Auth] Device registration stored successfully");
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn get_device_registration(app: tauri::AppHandle) -> Result<Option<DeviceRegistration>, String> {
-    let store = app.store(PathBuf::from(STORE_FILENAME))
-        .map_err(|e| format!("Failed to open store: {}", e))?;
+#[specta::specta]
+pub async fn get_device_registration(app: tauri::AppHandle) -> Result<Option<DeviceRegistration>, AppError> {
+    let store = stores::open(&app, STORE_FILENAME).map_err(storage_error)?;
 
     match store.get("device_registration") {
         Some(value) => {
             let registration: DeviceRegistration = serde_json::from_value(value.clone())
-                .map_err(|e| format!("Failed to parse registration: {}", e))?;
-            println!("[Auth] Device registration found: userId={}", registration.user_id);
+                .map_err(|e| storage_error(format!("Failed to parse registration: {}", e)))?;
+            app_log!("[Auth] Device registration found: userId={}", registration.user_id);
             Ok(Some(registration))
         }
         None => {
-            println!("[Auth] No device registration found");
+            app_log!("[Auth] No device registration found");
             Ok(None)
         }
     }
 }
 
 #[tauri::command]
-pub async fn clear_device_registration(app: tauri::AppHandle) -> Result<(), String> {
-    let store = app.store(PathBuf::from(STORE_FILENAME))
-        .map_err(|e| format!("Failed to open store: {}", e))?;
-
-    let _ = store.delete("device_registration"); // Returns bool, ignore result
+#[specta::specta]
+pub async fn clear_device_registration(app: tauri::AppHandle) -> Result<(), AppError> {
+    audit::audited(app.clone(), "clear_device_registration", async {
+        stores::update(&app, STORE_FILENAME, |store| {
+            let _ = store.delete("device_registration"); // Returns bool, ignore result
+            Ok(())
+        })
+        .await
+        .map_err(storage_error)?;
 
-    store.save()
-        .map_err(|e| format!("Failed to save store: {}", e))?;
-
-    println!("[Auth] Device registration cleared");
-    Ok(())
+        app_log!("[Auth] Device registration cleared");
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
+#[specta::specta]
 pub async fn store_session(
     app: tauri::AppHandle,
     user_id: String,
     session_id: String,
     token: String,
-) -> Result<(), String> {
-    let session = SessionData {
-        user_id,
-        session_id,
-        token,
-    };
+    expires_in: Option<i64>,
+) -> Result<(), AppError> {
+    audit::audited(app.clone(), "store_session", async {
+        let session = SessionData {
+            user_id,
+            session_id,
+            token,
+            expires_at: expires_in.map(|ttl| now_secs() + ttl),
+        };
 
-    let store = app.store(PathBuf::from(STORE_FILENAME))
-        .map_err(|e| format!("Failed to open store: {}", e))?;
+        save_session(&app, &session).await.map_err(storage_error)?;
 
-    store.set("session", serde_json::to_value(&session)
-        .map_err(|e| format!("Failed to serialize session: {}", e))?);
-
-    store.save()
-        .map_err(|e| format!("Failed to save store: {}", e))?;
-
-    println!("[Auth] Session stored successfully");
-    Ok(())
+        app_log!("[Auth] Session stored successfully");
+        Ok(())
+    })
+    .await
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct SessionInfo {
     #[serde(rename = "userId")]
     pub user_id: String,
     #[serde(rename = "sessionId")]
     pub session_id: String,
     pub token: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<i64>,
 }
 
 #[tauri::command]
-pub async fn get_session(app: tauri::AppHandle) -> Result<Option<SessionInfo>, String> {
-    let store = app.store(PathBuf::from(STORE_FILENAME))
-        .map_err(|e| format!("Failed to open store: {}", e))?;
+#[specta::specta]
+pub async fn get_session(app: tauri::AppHandle) -> Result<Option<SessionInfo>, AppError> {
+    let store = stores::open(&app, STORE_FILENAME).map_err(storage_error)?;
 
     match store.get("session") {
         Some(value) => {
             let session: SessionData = serde_json::from_value(value.clone())
-                .map_err(|e| format!("Failed to parse session: {}", e))?;
+                .map_err(|e| storage_error(format!("Failed to parse session: {}", e)))?;
 
-            println!("[Auth] Session found: userId={}", session.user_id);
+            app_log!("[Auth] Session found: userId={}", session.user_id);
+            let expires_at = session_expiry(&session);
             Ok(Some(SessionInfo {
                 user_id: session.user_id,
                 session_id: session.session_id,
                 token: session.token,
+                expires_at,
             }))
         }
         None => {
-            println!("[Auth] No session found");
+            app_log!("[Auth] No session found");
             Ok(None)
         }
     }
 }
 
 #[tauri::command]
-pub async fn clear_session(app: tauri::AppHandle) -> Result<(), String> {
-    let store = app.store(PathBuf::from(STORE_FILENAME))
-        .map_err(|e| format!("Failed to open store: {}", e))?;
+#[specta::specta]
+pub async fn clear_session(app: tauri::AppHandle) -> Result<(), AppError> {
+    audit::audited(app.clone(), "clear_session", async {
+        forget_session(&app).await.map_err(storage_error)
+    })
+    .await
+}
+
+/// Drop the session token, keeping the device registration
+pub(crate) async fn forget_session(app: &tauri::AppHandle) -> Result<(), String> {
+    stores::update(app, STORE_FILENAME, |store| {
+        let _ = store.delete("session"); // Ignore error if not exists
+        Ok(())
+    })
+    .await?;
+
+    app_log!("[Auth] Session cleared");
+    Ok(())
+}
+
+/// This device was revoked remotely (or by "log out everywhere"): drop the
+/// session and registration so the app falls back to the activation screen
+pub(crate) async fn handle_device_revoked(app: &tauri::AppHandle) -> Result<(), String> {
+    let result = async {
+        let user_id = match load_device_registration(app)? {
+            Some(registration) => Some(registration.user_id),
+            None => load_session(app)?.map(|s| s.user_id),
+        };
+
+        // One change, so a concurrent save can't persist the session without
+        // the registration or the other way round
+        stores::update(app, STORE_FILENAME, |store| {
+            let _ = store.delete("session");
+            let _ = store.delete("device_registration");
+            Ok(())
+        })
+        .await?;
+
+        app_log!("[Auth] Device revoked, local credentials cleared");
+        if let Some(user_id) = user_id {
+            user_data::remove_user(app, &user_id)?;
+        }
+        Ok(())
+    }
+    .await;
+    audit::record(&app.state::<Database>(), "device_revoked", &result);
+    events::emit_app_event(app, AppEvent::DeviceRevoked);
+    result
+}
+
+/// Full local wipe for shared machines: clears the store, deletes cached data
+/// and downloaded assets, vacuums the database, and rotates the at-rest key
+/// so anything left in free space or old copies can't be decrypted
+#[tauri::command]
+#[specta::specta]
+pub async fn secure_logout(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<(), AppError> {
+    audit::audited(app.clone(), "secure_logout", async {
+        wipe_local_data(&app, &db).map_err(storage_error)?;
+        app_log!("[Auth] Secure logout complete");
+        events::emit_app_event(&app, AppEvent::LoggedOut);
+        Ok(())
+    })
+    .await
+}
 
-    let _ = store.delete("session"); // Ignore error if not exists
+/// Clear the session and every piece of local user data, rotating the
+/// database key so wiped pages can't be recovered with the old one
+pub(crate) fn wipe_local_data(app: &tauri::AppHandle, db: &Database) -> Result<(), String> {
+    let data_dir = paths::data_dir(app)?;
 
-    store.save()
-        .map_err(|e| format!("Failed to save store: {}", e))?;
+    let store = stores::open(app, STORE_FILENAME)?;
+    store.clear();
+    // Old copies of the stores, under the old key and holding the session
+    for dir in [SNAPSHOT_DIR, QUARANTINE_DIR] {
+        best_effort(dir, remove_dir(&data_dir.join(dir)));
+    }
+
+    // Re-key the database, then write every store under the new key before
+    // the key itself is persisted: the stores on disk are never left under
+    // a key that's no longer stored anywhere
+    let new_key = crypto::new_data_key();
+    db.wipe_user_data(&hex::encode(new_key))?;
+    crypto::set_data_key(new_key)?;
+    for name in STORES {
+        if let Err(e) = stores::save(app, name) {
+            app_log!("[Auth] Failed to re-encrypt {}: {}", name, e);
+        }
+    }
+    // The database is already under the new key, so persist it regardless
+    crypto::replace_data_key(&data_dir, new_key)?;
+
+    // The session and database are gone by now; everything below is cleanup
+    // that shouldn't stop the rest of it running
+    best_effort("logs", logging::clear());
+    best_effort("response quarantine", http::clear_quarantine());
+    match user_data::data_dir(app) {
+        Ok(user_dir) => {
+            for dir in ASSET_DIRS {
+                best_effort(dir, remove_dir(&user_dir.join(dir)));
+            }
+        }
+        Err(e) => best_effort("assets", Err(e)),
+    }
+    super::user_status::clear_status_cache();
+    super::billing::clear_subscription_cache();
+    crate::palette::clear(app);
+    super::market::forget_search_key();
+    best_effort("location", super::location::forget_location(app));
+    best_effort("reassessment", super::reassessment::forget(app));
+    best_effort("slack", super::slack::forget_slack(app));
+    best_effort("email signals", super::email_signals::forget_email_signals(app));
+    best_effort("screenshots", super::feedback::forget_screenshots(app));
+    best_effort("browser bridge", crate::browser_bridge::forget(app));
+    best_effort("consent", crate::consent::forget(app));
+    best_effort("image cache", crate::image_cache::clear(app));
+    // The key rotation left every other account's database unreadable
+    best_effort("other accounts", user_data::remove_all(app));
 
-    println!("[Auth] Session cleared");
     Ok(())
 }
+
+/// Session and registration reads and writes all go through the encrypted
+/// store, so their failures are storage failures
+fn storage_error(detail: String) -> AppError {
+    AppError::new(ErrorCode::Storage, detail)
+}
+
+fn remove_dir(path: &std::path::Path) -> Result<(), String> {
+    if path.exists() {
+        std::fs::remove_dir_all(path)
+            .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Log a cleanup step of the wipe that failed rather than abandon the rest:
+/// a logout stopped halfway would leave more behind than one skipped step
+fn best_effort(step: &str, result: Result<(), String>) {
+    if let Err(e) = result {
+        app_log!("[Auth] Wipe step '{}' failed: {}", step, e);
+    }
+}
+
+/// Most recent sensitive command invocations, newest first
+#[tauri::command]
+#[specta::specta]
+pub async fn get_audit_log(
+    db: tauri::State<'_, Database>,
+    limit: Option<u32>,
+) -> Result<Vec<audit::AuditEntry>, AppError> {
+    audit::entries(&db, limit.unwrap_or(200).min(1000)).map_err(storage_error)
+}
@@ -0,0 +1,526 @@
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use super::auth::{self, load_session};
+use crate::api;
+use crate::audit;
+use crate::crypto;
+use crate::db::{self, now_secs, Database};
+use crate::errors::{self, AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::http::CLIENT_VERSION;
+use crate::logging::app_log;
+use crate::migrations;
+use crate::paths;
+use crate::realtime;
+use crate::session;
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const BACKUP_FORMAT_VERSION: u32 = 1;
+pub(crate) const BACKUP_EXTENSION: &str = "ghbackup";
+/// Stores copied into a backup, decrypted so they open under a new data key
+pub(crate) const STORES: &[&str] = &[auth::STORE_FILENAME, stores::SETTINGS_STORE];
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+pub(crate) const DB_FILE: &str = "db/goodhang.db";
+/// Hex key the archived database copy is encrypted under
+const DB_KEY_FILE: &str = "db/key";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+/// Store keys tied to this device, which would clone its identity onto
+/// another machine; never backed up or restored
+const DEVICE_KEYS: &[&str] = &["device_registration"];
+
+/// Describes a backup archive; stored inside it as `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BackupManifest {
+    #[serde(rename = "formatVersion")]
+    pub format_version: u32,
+    /// Database schema the archive was taken at
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u32,
+    #[serde(rename = "appVersion")]
+    pub app_version: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+    /// Store files under `store/`
+    pub stores: Vec<String>,
+    /// Asset files under `files/<dir>/`
+    pub files: usize,
+    /// Size of the encrypted archive
+    #[serde(default, rename = "sizeBytes")]
+    pub size_bytes: u64,
+}
+
+/// What to do with data already on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreMode {
+    /// Keep local data and add what the backup has on top; local wins on
+    /// conflict
+    Merge,
+    /// Discard local data in favour of the backup
+    Replace,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct RestoreReport {
+    pub manifest: BackupManifest,
+    pub mode: RestoreMode,
+    /// Database rows copied in
+    pub rows: usize,
+    /// Asset files written
+    pub files: usize,
+    /// Whether the stored refresh token produced a fresh session
+    #[serde(rename = "sessionRestored")]
+    pub session_restored: bool,
+}
+
+pub(crate) fn check_passphrase(passphrase: &str) -> Result<(), AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid passphrase: use at least {} characters",
+                MIN_PASSPHRASE_CHARS
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Every entry of a store but the device's own, as one JSON object
+fn store_contents(app: &tauri::AppHandle, name: &str) -> Result<serde_json::Value, String> {
+    let store = stores::open(app, name)?;
+    Ok(serde_json::Value::Object(
+        store
+            .entries()
+            .into_iter()
+            .filter(|(key, _)| !DEVICE_KEYS.contains(&key.as_str()))
+            .collect(),
+    ))
+}
+
+/// Copy the database into the cache dir under a fresh random key, returning
+/// the copy and its key. Nothing unencrypted touches the disk; the key only
+/// travels inside the sealed archive.
+fn database_copy(app: &tauri::AppHandle) -> Result<(PathBuf, String), String> {
+    let dir = paths::cache_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let path = dir.join(format!("backup-{}.db", uuid::Uuid::new_v4()));
+    let key_hex = hex::encode(crypto::new_data_key());
+
+    if let Err(e) = app.state::<Database>().export_copy(&path, &key_hex) {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+    Ok((path, key_hex))
+}
+
+/// Recorded and downloaded assets, as (name in archive, path on disk)
+fn asset_files(app: &tauri::AppHandle) -> Result<Vec<(String, PathBuf)>, String> {
+    let data_dir = user_data::data_dir(app)?;
+    let mut files = Vec::new();
+    for dir in auth::ASSET_DIRS {
+        let Ok(entries) = std::fs::read_dir(data_dir.join(dir)) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_file()) {
+            if let Some(name) = path.file_name() {
+                files.push((format!("files/{}/{}", dir, name.to_string_lossy()), path));
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn add_file<W: Write + std::io::Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    name: &str,
+    contents: &mut impl Read,
+) -> Result<(), String> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(name, options)
+        .map_err(|e| e.to_string())
+        .and_then(|_| std::io::copy(contents, zip).map_err(|e| e.to_string()))
+        .map(|_| ())
+        .map_err(|e| format!("Failed to write {}: {}", name, e))
+}
+
+fn open_file(path: &Path, name: &str) -> Result<std::fs::File, String> {
+    std::fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", name, e))
+}
+
+/// Stream the archive into `sealed` a chunk at a time, so neither the
+/// database nor the assets are ever held in memory whole
+fn build_backup(
+    app: &tauri::AppHandle,
+    passphrase: &str,
+    sealed: std::fs::File,
+) -> Result<BackupManifest, String> {
+    let mut manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version: db::SCHEMA_VERSION,
+        app_version: CLIENT_VERSION.to_string(),
+        created_at: now_secs(),
+        user_id: load_session(app)?.map(|s| s.user_id),
+        stores: STORES.iter().map(|s| s.to_string()).collect(),
+        files: 0,
+        size_bytes: 0,
+    };
+
+    let mut zip = zip::ZipWriter::new(crypto::SealedWriter::new(sealed, passphrase)?);
+    for name in STORES {
+        let contents = serde_json::to_vec(&store_contents(app, name)?)
+            .map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+        add_file(
+            &mut zip,
+            &format!("store/{}", name),
+            &mut contents.as_slice(),
+        )?;
+    }
+
+    let (db_copy, db_key) = database_copy(app)?;
+    let added = open_file(&db_copy, DB_FILE)
+        .and_then(|mut file| add_file(&mut zip, DB_FILE, &mut file))
+        .and_then(|_| add_file(&mut zip, DB_KEY_FILE, &mut db_key.as_bytes()));
+    let _ = std::fs::remove_file(&db_copy);
+    added?;
+
+    let assets = asset_files(app)?;
+    for (name, path) in &assets {
+        add_file(&mut zip, name, &mut open_file(path, name)?)?;
+    }
+    manifest.files = assets.len();
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    add_file(&mut zip, MANIFEST_FILE, &mut manifest_json.as_slice())?;
+
+    let sealed = zip
+        .finish()
+        .map_err(|e| format!("Failed to finish backup: {}", e))?
+        .finish()
+        .map_err(|e| format!("Failed to finish backup: {}", e))?;
+    manifest.size_bytes = sealed
+        .metadata()
+        .map_err(|e| format!("Failed to finish backup: {}", e))?
+        .len();
+    Ok(manifest)
+}
+
+/// Build the backup next to `destination`, private from the moment it's
+/// created, and move it into place once complete
+fn write_backup(
+    app: &tauri::AppHandle,
+    destination: &Path,
+    passphrase: &str,
+) -> Result<BackupManifest, String> {
+    let partial = destination.with_extension(format!("{}.partial", BACKUP_EXTENSION));
+    let result = crypto::create_private(&partial)
+        .map_err(|e| format!("Failed to write backup: {}", e))
+        .and_then(|file| build_backup(app, passphrase, file))
+        .and_then(|manifest| {
+            std::fs::rename(&partial, destination)
+                .map_err(|e| format!("Failed to save backup: {}", e))?;
+            Ok(manifest)
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+/// Save the local store, database and recorded assets to `path` as one
+/// archive encrypted with `passphrase`, for moving to another machine
+/// without onboarding again. The passphrase is the only way back in.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_backup(
+    app: tauri::AppHandle,
+    path: String,
+    passphrase: String,
+) -> Result<BackupManifest, AppError> {
+    audit::audited(app.clone(), "create_backup", async {
+        let destination = PathBuf::from(&path);
+        if destination.extension().and_then(|e| e.to_str()) != Some(BACKUP_EXTENSION)
+            || !destination.parent().is_some_and(|p| p.is_dir())
+        {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!(
+                    "Invalid destination: expected a .{} path in an existing folder",
+                    BACKUP_EXTENSION
+                ),
+            ));
+        }
+        check_passphrase(&passphrase)?;
+
+        let handle = app.clone();
+        let manifest = tauri::async_runtime::spawn_blocking(move || {
+            write_backup(&handle, &destination, &passphrase)
+        })
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))??;
+
+        app_log!(
+            "[Backup] Backup written ({} bytes, {} files)",
+            manifest.size_bytes,
+            manifest.files
+        );
+        Ok(manifest)
+    })
+    .await
+}
+
+fn invalid_backup(detail: impl Into<String>) -> AppError {
+    AppError::new(
+        ErrorCode::InvalidInput,
+        format!("Invalid backup: {}", detail.into()),
+    )
+}
+
+type Archive = zip::ZipArchive<crypto::SealedReader>;
+
+fn read_entry(archive: &mut Archive, name: &str) -> Result<Vec<u8>, AppError> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| invalid_backup(format!("{} is missing", name)))?;
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e| invalid_backup(format!("{} is unreadable: {}", name, e)))?;
+    Ok(bytes)
+}
+
+/// Authenticate the whole archive, then check its manifest can be restored
+/// by this build. Entries are decrypted again as they're read.
+fn open_backup(path: &Path, passphrase: &str) -> Result<(Archive, BackupManifest), AppError> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to read backup: {}", e))?;
+    let size_bytes = file
+        .metadata()
+        .map_err(|e| format!("Failed to read backup: {}", e))?
+        .len();
+    let mut sealed = crypto::SealedReader::open(file, passphrase)
+        .map_err(|e| AppError::new(ErrorCode::InvalidInput, e))?;
+    sealed
+        .verify()
+        .map_err(|e| AppError::new(ErrorCode::InvalidInput, e))?;
+    let mut archive = zip::ZipArchive::new(sealed).map_err(|e| invalid_backup(e.to_string()))?;
+
+    let mut manifest: BackupManifest =
+        serde_json::from_slice(&read_entry(&mut archive, MANIFEST_FILE)?)
+            .map_err(|e| invalid_backup(format!("unreadable manifest: {}", e)))?;
+    if manifest.format_version > BACKUP_FORMAT_VERSION
+        || manifest.schema_version > db::SCHEMA_VERSION
+    {
+        return Err(invalid_backup(
+            "it was made by a newer version of GoodHang; update the app first",
+        ));
+    }
+    manifest.size_bytes = size_bytes;
+    Ok((archive, manifest))
+}
+
+/// Copy the backup's database in; older schema versions are upgraded on the
+/// way. The copy is extracted still encrypted under its own key.
+fn restore_database(
+    app: &tauri::AppHandle,
+    archive: &mut Archive,
+    mode: RestoreMode,
+) -> Result<usize, AppError> {
+    let key_hex = String::from_utf8(read_entry(archive, DB_KEY_FILE)?)
+        .ok()
+        .and_then(|key| hex::decode(key.trim()).ok())
+        .filter(|key| key.len() == 32)
+        .map(hex::encode)
+        .ok_or_else(|| invalid_backup(format!("{} is unreadable", DB_KEY_FILE)))?;
+    let dir = paths::cache_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let path = dir.join(format!("restore-{}.db", uuid::Uuid::new_v4()));
+
+    let extracted = match archive.by_name(DB_FILE) {
+        Ok(mut entry) => crypto::create_private(&path)
+            .and_then(|mut file| std::io::copy(&mut entry, &mut file))
+            .map_err(|e| AppError::from(format!("Failed to write database copy: {}", e))),
+        Err(_) => Err(invalid_backup(format!("{} is missing", DB_FILE))),
+    };
+    let result = extracted.and_then(|_| {
+        Ok(app
+            .state::<Database>()
+            .import_copy(&path, &key_hex, mode == RestoreMode::Replace)?)
+    });
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+fn restore_stores(
+    app: &tauri::AppHandle,
+    archive: &mut Archive,
+    mode: RestoreMode,
+) -> Result<(), AppError> {
+    for name in STORES {
+        let entry = format!("store/{}", name);
+        let contents: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_slice(&read_entry(archive, &entry)?)
+                .map_err(|e| invalid_backup(format!("{} is unreadable: {}", entry, e)))?;
+        let store = stores::open(app, name)?;
+        if mode == RestoreMode::Replace {
+            for (key, _) in store.entries() {
+                if !DEVICE_KEYS.contains(&key.as_str()) {
+                    let _ = store.delete(key);
+                }
+            }
+        }
+        let contents = contents
+            .into_iter()
+            .filter(|(key, _)| !DEVICE_KEYS.contains(&key.as_str()));
+        for (key, value) in contents {
+            if mode == RestoreMode::Replace || !store.has(&key) {
+                store.set(key, value);
+            }
+        }
+        stores::save(app, name)?;
+    }
+    Ok(())
+}
+
+/// Write `files/<dir>/<name>` entries back into their asset directories.
+/// Anything else under `files/` is ignored.
+fn restore_files(
+    app: &tauri::AppHandle,
+    archive: &mut Archive,
+    mode: RestoreMode,
+) -> Result<usize, AppError> {
+    let data_dir = user_data::data_dir(app)?;
+    if mode == RestoreMode::Replace {
+        for dir in auth::ASSET_DIRS {
+            let _ = std::fs::remove_dir_all(data_dir.join(dir));
+        }
+    }
+
+    let mut written = 0;
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| invalid_backup(e.to_string()))?;
+        let Some((dir, name)) = entry
+            .name()
+            .strip_prefix("files/")
+            .and_then(|rest| rest.split_once('/'))
+            .map(|(dir, name)| (dir.to_string(), name.to_string()))
+        else {
+            continue;
+        };
+        let safe_name =
+            !name.is_empty() && !name.contains(['/', '\\']) && name != ".." && name != ".";
+        if !entry.is_file() || !safe_name || !auth::ASSET_DIRS.contains(&dir.as_str()) {
+            continue;
+        }
+
+        let target_dir = data_dir.join(&dir);
+        let target = target_dir.join(&name);
+        if mode == RestoreMode::Merge && target.exists() {
+            continue;
+        }
+        std::fs::create_dir_all(&target_dir)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+        let mut file = std::fs::File::create(&target)
+            .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+        std::io::copy(&mut entry, &mut file)
+            .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Restore a backup made by `create_backup`. `merge` adds the backup to
+/// what's here (only for the same account); `replace` swaps local data for
+/// it. The session is then refreshed with the restored refresh token.
+/// Settings read at startup take full effect on the next launch.
+#[tauri::command]
+#[specta::specta]
+pub async fn restore_backup(
+    app: tauri::AppHandle,
+    path: String,
+    passphrase: String,
+    mode: RestoreMode,
+) -> Result<RestoreReport, AppError> {
+    audit::audited(app.clone(), "restore_backup", async {
+        let source = PathBuf::from(&path);
+        if !source.is_file() {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Invalid backup: {} is not a file", path),
+            ));
+        }
+
+        let handle = app.clone();
+        let (manifest, rows, files) = tauri::async_runtime::spawn_blocking(move || {
+            let (mut archive, manifest) = open_backup(&source, &passphrase)?;
+            let local_user = load_session(&handle)?.map(|s| s.user_id);
+            if mode == RestoreMode::Merge
+                && local_user.is_some()
+                && manifest.user_id.is_some()
+                && local_user != manifest.user_id
+            {
+                return Err(AppError::new(
+                    ErrorCode::InvalidInput,
+                    "Invalid restore: this backup belongs to another account; replace local data instead",
+                ));
+            }
+
+            // A replacing restore takes over as the backup's account, so
+            // its data lands in that account's namespace
+            if let (RestoreMode::Replace, Some(user_id)) = (mode, &manifest.user_id) {
+                user_data::activate(&handle, Some(user_id))?;
+            }
+            let rows = restore_database(&handle, &mut archive, mode)?;
+            restore_stores(&handle, &mut archive, mode)?;
+            migrations::migrate_stores(&handle)?;
+            let files = restore_files(&handle, &mut archive, mode)?;
+            Ok::<_, AppError>((manifest, rows, files))
+        })
+        .await
+        .map_err(|e| format!("Restore task failed: {}", e))??;
+
+        api::load_base_url(&app);
+        errors::load_locale(&app);
+        if mode == RestoreMode::Replace {
+            user_data::clear_cached(&app);
+            realtime::reconnect();
+        }
+
+        let session_restored = match load_session(&app)? {
+            Some(current) => match session::refresh_session(&app, &current).await {
+                Ok(()) => {
+                    events::emit_app_event(&app, AppEvent::SessionRefreshed);
+                    true
+                }
+                Err(e) => {
+                    app_log!("[Backup] Session refresh after restore failed: {}", e);
+                    false
+                }
+            },
+            None => false,
+        };
+
+        app_log!(
+            "[Backup] Restored {:?}: {} rows, {} files",
+            mode,
+            rows,
+            files
+        );
+        Ok(RestoreReport {
+            manifest,
+            mode,
+            rows,
+            files,
+            session_restored,
+        })
+    })
+    .await
+}
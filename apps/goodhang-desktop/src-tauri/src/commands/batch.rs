@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+
+const MAX_CONCURRENT_REQUESTS: usize = 6;
+const MAX_BATCH_SIZE: usize = 32;
+
+/// One read in a batch, answered under `key`
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct BatchRequest {
+    pub key: String,
+    /// An API path such as `/api/hangs`
+    pub path: String,
+    #[serde(default)]
+    pub query: HashMap<String, String>,
+}
+
+/// The decoded body, or why there isn't one
+#[derive(Debug, Serialize, specta::Type)]
+pub struct BatchResult {
+    pub status: Option<u16>,
+    pub data: Option<serde_json::Value>,
+    pub error: Option<AppError>,
+}
+
+/// Resolve `.`/`..` segments (including percent-encoded ones) and return the
+/// path only if it still sits under `/api/` on the same host, without a
+/// query, fragment or encoded slash of its own
+fn normalize_path(path: &str) -> Option<String> {
+    if !path.starts_with('/') {
+        return None;
+    }
+    let base = reqwest::Url::parse("http://api.invalid/").ok()?;
+    let url = base.join(path).ok()?;
+    let same_origin = url.origin() == base.origin();
+    let plain = url.query().is_none()
+        && url.fragment().is_none()
+        && !url.path().to_ascii_lowercase().contains("%2f");
+    (same_origin && plain && url.path().starts_with("/api/")).then(|| url.path().to_string())
+}
+
+async fn fetch_one(api: Api, token: String, request: BatchRequest) -> BatchResult {
+    let mut api_request = ApiRequest::get(request.path.as_str()).bearer(&token);
+    for (name, value) in &request.query {
+        api_request = api_request.query(name, value);
+    }
+
+    let response = match api.send(api_request).await {
+        Ok(response) => response,
+        Err(e) => {
+            return BatchResult {
+                status: None,
+                data: None,
+                error: Some(e.into()),
+            }
+        }
+    };
+    let status = Some(response.status.as_u16());
+    match response
+        .error_for_status()
+        .and_then(|r| r.json::<serde_json::Value>(&request.path))
+    {
+        Ok(data) => BatchResult {
+            status,
+            data: Some(data),
+            error: None,
+        },
+        Err(e) => BatchResult {
+            status,
+            data: None,
+            error: Some(e.into()),
+        },
+    }
+}
+
+/// Run several API reads concurrently, a few at a time, and return each
+/// result under its key. One request failing doesn't fail the batch, so a
+/// dashboard can load in one invoke instead of a waterfall.
+#[tauri::command]
+#[specta::specta]
+pub async fn batch_fetch(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+    mut requests: Vec<BatchRequest>,
+) -> Result<HashMap<String, BatchResult>, AppError> {
+    if requests.len() > MAX_BATCH_SIZE {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Batches are limited to {} requests", MAX_BATCH_SIZE),
+        ));
+    }
+    let mut keys = HashSet::with_capacity(requests.len());
+    for request in &mut requests {
+        if !keys.insert(request.key.clone()) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Duplicate batch key '{}'", request.key),
+            ));
+        }
+        request.path = normalize_path(&request.path).ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidInput,
+                format!(
+                    "Invalid batch path for '{}': expected /api/...",
+                    request.key
+                ),
+            )
+        })?;
+    }
+    let token = load_session(&app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+
+    let slots = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
+    let mut tasks = JoinSet::new();
+    let total = requests.len();
+    for request in requests {
+        let (api, token, slots) = (api.inner().clone(), token.clone(), slots.clone());
+        tasks.spawn(async move {
+            let _slot = slots.acquire_owned().await;
+            let key = request.key.clone();
+            (key, fetch_one(api, token, request).await)
+        });
+    }
+
+    let mut results = HashMap::with_capacity(total);
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((key, result)) => {
+                results.insert(key, result);
+            }
+            Err(e) => app_log!("[Batch] Request task failed: {}", e),
+        }
+    }
+
+    let failed = results.values().filter(|r| r.error.is_some()).count();
+    app_log!("[Batch] {} request(s), {} failed", total, failed);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_path;
+
+    #[test]
+    fn keeps_paths_under_api() {
+        assert_eq!(normalize_path("/api/hangs").as_deref(), Some("/api/hangs"));
+        assert_eq!(
+            normalize_path("/api/./hangs/../members").as_deref(),
+            Some("/api/members")
+        );
+    }
+
+    #[test]
+    fn rejects_paths_that_escape_api() {
+        assert_eq!(normalize_path("/api/../admin"), None);
+        assert_eq!(normalize_path("/api/%2e%2e/admin"), None);
+        assert_eq!(normalize_path("/api/..%2Fadmin"), None);
+        assert_eq!(normalize_path("//evil.example/api/x"), None);
+        assert_eq!(normalize_path("https://evil.example/api/x"), None);
+        assert_eq!(normalize_path("/api/hangs?limit=5"), None);
+        assert_eq!(normalize_path("api/hangs"), None);
+    }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::Manager;
+use tauri_plugin_shell::ShellExt;
+
+use crate::api::{require_token, Api, ApiRequest};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct SubscriptionStatus {
+    /// none | trialing | active | past_due | canceled
+    pub status: String,
+    #[serde(default)]
+    pub plan: Option<String>,
+    /// Unix seconds
+    #[serde(default, rename = "currentPeriodEnd")]
+    pub current_period_end: Option<i64>,
+    #[serde(default, rename = "cancelAtPeriodEnd")]
+    pub cancel_at_period_end: bool,
+    /// Feature keys the subscription unlocks, e.g. `expert_content`
+    #[serde(default)]
+    pub entitlements: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PortalResponse {
+    url: String,
+}
+
+static SUBSCRIPTION: Mutex<Option<SubscriptionStatus>> = Mutex::new(None);
+
+fn store_subscription(status: &SubscriptionStatus) {
+    if let Ok(mut cached) = SUBSCRIPTION.lock() {
+        *cached = Some(status.clone());
+    }
+}
+
+/// Subscription from the last fetch or realtime push, if any
+pub(crate) fn cached_subscription() -> Option<SubscriptionStatus> {
+    SUBSCRIPTION.lock().ok().and_then(|s| s.clone())
+}
+
+pub(crate) fn clear_subscription_cache() {
+    if let Ok(mut cached) = SUBSCRIPTION.lock() {
+        cached.take();
+    }
+}
+
+async fn fetch_subscription(api: &Api, token: &str) -> Result<SubscriptionStatus, String> {
+    let response = api
+        .send(ApiRequest::get("/api/billing/subscription").bearer(token))
+        .await?;
+
+    let status: SubscriptionStatus = response
+        .error_for_status()?
+        .json("/api/billing/subscription")?;
+    store_subscription(&status);
+    Ok(status)
+}
+
+/// Realtime `subscription_changed` push: refresh the cache and re-emit as
+/// `subscription-changed`
+pub(crate) fn handle_subscription_changed(app: &tauri::AppHandle, data: serde_json::Value) {
+    match serde_json::from_value::<SubscriptionStatus>(data) {
+        Ok(status) => {
+            app_log!("[Billing] Subscription changed: {}", status.status);
+            store_subscription(&status);
+            events::emit_app_event(app, AppEvent::SubscriptionChanged(status));
+        }
+        Err(e) => app_log!("[Billing] Skipping malformed subscription update: {}", e),
+    }
+}
+
+/// Current subscription, or the last known one while offline
+#[tauri::command]
+#[specta::specta]
+pub async fn get_subscription_status(app: tauri::AppHandle) -> Result<SubscriptionStatus, String> {
+    let token = require_token(&app)?;
+    match fetch_subscription(&app.state::<Api>(), &token).await {
+        Err(e) if e.starts_with("Network error") => match cached_subscription() {
+            Some(status) => {
+                app_log!("[Billing] Offline, serving cached subscription");
+                Ok(status)
+            }
+            None => Err(e),
+        },
+        result => result,
+    }
+}
+
+/// Open the Stripe customer portal to manage payment and plan
+#[tauri::command]
+#[specta::specta]
+pub async fn open_billing_portal(app: tauri::AppHandle) -> Result<(), String> {
+    let token = require_token(&app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(ApiRequest::post("/api/billing/portal").bearer(&token))
+        .await?;
+    let portal: PortalResponse = response.error_for_status()?.json("/api/billing/portal")?;
+
+    #[allow(deprecated)]
+    app.shell()
+        .open(portal.url.as_str(), None)
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    app_log!("[Billing] Billing portal opened");
+    Ok(())
+}
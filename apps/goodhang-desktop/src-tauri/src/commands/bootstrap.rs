@@ -0,0 +1,71 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::activation::{fetch_assessment_results, AssessmentResults};
+use super::auth::{get_device_registration, get_session, DeviceRegistration, SessionInfo};
+use super::user_status::{fetch_user_status, UserStatus};
+use crate::errors::AppError;
+use crate::logging::app_log;
+
+/// Everything the frontend needs on launch, assembled in one round trip.
+/// Each part fails independently; failures are reported in `errors` keyed by
+/// part name rather than failing the whole bootstrap.
+#[derive(Debug, Default, Serialize, specta::Type)]
+pub struct BootstrapPayload {
+    pub session: Option<SessionInfo>,
+    #[serde(rename = "deviceRegistration")]
+    pub device_registration: Option<DeviceRegistration>,
+    #[serde(rename = "userStatus")]
+    pub user_status: Option<UserStatus>,
+    #[serde(rename = "assessmentResults")]
+    pub assessment_results: Option<AssessmentResults>,
+    pub errors: HashMap<String, AppError>,
+}
+
+fn record<T, E: Into<AppError>>(
+    errors: &mut HashMap<String, AppError>,
+    part: &str,
+    result: Result<T, E>,
+) -> Option<T> {
+    result.map_err(|e| errors.insert(part.to_string(), e.into())).ok()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn bootstrap(app: tauri::AppHandle) -> Result<BootstrapPayload, String> {
+    let mut payload = BootstrapPayload::default();
+
+    let (session, registration) = tokio::join!(
+        get_session(app.clone()),
+        get_device_registration(app.clone())
+    );
+    payload.session = record(&mut payload.errors, "session", session).flatten();
+    payload.device_registration =
+        record(&mut payload.errors, "deviceRegistration", registration).flatten();
+
+    // Remote parts need a signed-in session
+    let Some(session) = payload.session.clone() else {
+        return Ok(payload);
+    };
+
+    let (user_status, assessment) = tokio::join!(
+        fetch_user_status(
+            app.clone(),
+            session.token.clone(),
+            Some(session.user_id.clone())
+        ),
+        fetch_assessment_results(
+            app.clone(),
+            session.session_id.clone(),
+            session.token.clone()
+        ),
+    );
+    payload.user_status = record(&mut payload.errors, "userStatus", user_status);
+    payload.assessment_results = record(&mut payload.errors, "assessmentResults", assessment);
+
+    app_log!(
+        "[Bootstrap] Complete with {} error(s)",
+        payload.errors.len()
+    );
+    Ok(payload)
+}
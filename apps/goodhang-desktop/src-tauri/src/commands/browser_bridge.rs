@@ -0,0 +1,58 @@
+use tauri::Manager;
+
+use crate::audit;
+use crate::browser_bridge::{load_settings, save_settings, BridgeConsents, BridgeSettings};
+use crate::logging::app_log;
+
+/// Answer a `browser-bridge-consent-requested` prompt. The answer is
+/// remembered so the extension isn't asked about again.
+#[tauri::command]
+#[specta::specta]
+pub async fn respond_to_bridge_consent(
+    app: tauri::AppHandle,
+    origin: String,
+    allow: bool,
+) -> Result<(), String> {
+    audit::audited(app.clone(), "respond_to_bridge_consent", async {
+        let mut settings = load_settings(&app);
+        settings.allowed_origins.retain(|o| o != &origin);
+        settings.denied_origins.retain(|o| o != &origin);
+        if allow {
+            settings.allowed_origins.push(origin.clone());
+        } else {
+            settings.denied_origins.push(origin.clone());
+        }
+        save_settings(&app, &settings)?;
+
+        app.state::<BridgeConsents>().resolve(&origin, allow);
+        app_log!(
+            "[BrowserBridge] {} {}",
+            if allow { "Allowed" } else { "Denied" },
+            origin
+        );
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_browser_bridge_origins(app: tauri::AppHandle) -> Result<BridgeSettings, String> {
+    Ok(load_settings(&app))
+}
+
+/// Forget the answer for an origin; its next request prompts again
+#[tauri::command]
+#[specta::specta]
+pub async fn revoke_browser_bridge_origin(
+    app: tauri::AppHandle,
+    origin: String,
+) -> Result<(), String> {
+    audit::audited(app.clone(), "revoke_browser_bridge_origin", async {
+        let mut settings = load_settings(&app);
+        settings.allowed_origins.retain(|o| o != &origin);
+        settings.denied_origins.retain(|o| o != &origin);
+        save_settings(&app, &settings)
+    })
+    .await
+}
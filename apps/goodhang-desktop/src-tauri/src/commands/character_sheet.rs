@@ -0,0 +1,216 @@
+use resvg::{tiny_skia, usvg};
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+use super::activation::{fetch_assessment_results, AssessmentResults, ATTRIBUTES};
+use super::auth::load_session;
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+
+// US Letter at 100 units per inch; PNGs render at 2x for print
+const UNITS_PER_INCH: f32 = 100.0;
+const PAGE_WIDTH: f32 = 8.5 * UNITS_PER_INCH;
+const PAGE_HEIGHT: f32 = 11.0 * UNITS_PER_INCH;
+const PNG_SCALE: f32 = 2.0;
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Greedy word wrap for SVG text, which has no layout of its own
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= max_chars => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+/// Lay out the V3 character profile as a printable sheet
+fn render_svg(results: &AssessmentResults) -> Result<String, String> {
+    let profile = results
+        .character_profile
+        .as_ref()
+        .ok_or_else(|| "These results have no character profile to export".to_string())?;
+    let text = |value: &Option<String>| xml_escape(value.as_deref().unwrap_or("Unknown"));
+
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="Georgia, 'Times New Roman', serif">
+<rect width="100%" height="100%" fill="#f6efe0"/>
+<rect x="30" y="30" width="{iw}" height="{ih}" fill="none" stroke="#5b3a1a" stroke-width="4"/>
+<rect x="40" y="40" width="{iw2}" height="{ih2}" fill="none" stroke="#5b3a1a" stroke-width="1"/>
+<text x="{cx}" y="110" text-anchor="middle" font-size="40" font-weight="bold" fill="#3b2410">Character Sheet</text>
+<text x="{cx}" y="150" text-anchor="middle" font-size="20" font-style="italic" fill="#5b3a1a">{tagline}</text>
+<g font-size="16" fill="#3b2410">
+<text x="90" y="220" font-weight="bold">RACE</text><text x="90" y="248" font-size="24">{race}</text>
+<text x="340" y="220" font-weight="bold">CLASS</text><text x="340" y="248" font-size="24">{class}</text>
+<text x="590" y="220" font-weight="bold">ALIGNMENT</text><text x="590" y="248" font-size="24">{alignment}</text>
+</g>
+"##,
+        w = PAGE_WIDTH,
+        h = PAGE_HEIGHT,
+        iw = PAGE_WIDTH - 60.0,
+        ih = PAGE_HEIGHT - 60.0,
+        iw2 = PAGE_WIDTH - 80.0,
+        ih2 = PAGE_HEIGHT - 80.0,
+        cx = PAGE_WIDTH / 2.0,
+        tagline = xml_escape(profile.tagline.as_deref().unwrap_or_default()),
+        race = text(&profile.race),
+        class = text(&profile.character_class),
+        alignment = text(&profile.alignment),
+    );
+
+    // Attribute blocks, two rows of three, scored 1-10
+    for (i, (code, label)) in ATTRIBUTES.iter().enumerate() {
+        let x = 90.0 + (i % 3) as f32 * 240.0;
+        let y = 300.0 + (i / 3) as f32 * 190.0;
+        let value = results.attributes.as_ref().and_then(|a| a.get(code));
+        let score = value
+            .map(|v| format!("{}", v.round()))
+            .unwrap_or_else(|| "-".to_string());
+        let bar = value
+            .map(|v| (v.clamp(0.0, 10.0) / 10.0) as f32 * 160.0)
+            .unwrap_or(0.0);
+        svg.push_str(&format!(
+            r##"<g fill="#3b2410">
+<rect x="{x}" y="{y}" width="200" height="160" rx="12" fill="#fffaf0" stroke="#5b3a1a" stroke-width="2"/>
+<text x="{tx}" y="{ly}" text-anchor="middle" font-size="22" font-weight="bold">{code}</text>
+<text x="{tx}" y="{ny}" text-anchor="middle" font-size="14">{label}</text>
+<text x="{tx}" y="{sy}" text-anchor="middle" font-size="48" font-weight="bold">{score}</text>
+<rect x="{bx}" y="{by}" width="160" height="8" fill="#e4d6bc"/>
+<rect x="{bx}" y="{by}" width="{bar}" height="8" fill="#8b4513"/>
+</g>
+"##,
+            tx = x + 100.0,
+            ly = y + 34.0,
+            ny = y + 54.0,
+            sy = y + 112.0,
+            bx = x + 20.0,
+            by = y + 134.0,
+        ));
+    }
+
+    let summary = results
+        .public_summary
+        .as_deref()
+        .or(results.detailed_summary.as_deref())
+        .unwrap_or_default();
+    if !summary.is_empty() {
+        svg.push_str(
+            r##"<text x="90" y="720" font-size="18" font-weight="bold" fill="#3b2410">BACKSTORY</text>
+<g font-size="16" fill="#3b2410">
+"##,
+        );
+        for (i, line) in wrap(summary, 80).iter().take(12).enumerate() {
+            svg.push_str(&format!(
+                "<text x=\"90\" y=\"{}\">{}</text>\n",
+                752.0 + i as f32 * 24.0,
+                xml_escape(line)
+            ));
+        }
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\" fill=\"#8a6a4a\">Good Hang</text>\n</svg>\n",
+        PAGE_WIDTH / 2.0,
+        PAGE_HEIGHT - 50.0
+    ));
+    Ok(svg)
+}
+
+fn parse_svg(svg: &str) -> Result<usvg::Tree, String> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    usvg::Tree::from_str(svg, &options).map_err(|e| format!("Failed to lay out sheet: {}", e))
+}
+
+fn render_png(tree: &usvg::Tree) -> Result<Vec<u8>, String> {
+    let mut pixmap = tiny_skia::Pixmap::new(
+        (PAGE_WIDTH * PNG_SCALE) as u32,
+        (PAGE_HEIGHT * PNG_SCALE) as u32,
+    )
+    .ok_or_else(|| "Failed to allocate image".to_string())?;
+    resvg::render(
+        tree,
+        tiny_skia::Transform::from_scale(PNG_SCALE, PNG_SCALE),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .encode_png()
+        .map_err(|e| format!("Failed to encode PNG: {}", e))
+}
+
+fn render_pdf(tree: &usvg::Tree) -> Result<Vec<u8>, String> {
+    // The page size comes from the SVG size over the DPI; the default 72
+    // would print an 850-unit sheet 11.8 inches wide instead of on Letter
+    svg2pdf::to_pdf(
+        tree,
+        svg2pdf::ConversionOptions::default(),
+        svg2pdf::PageOptions {
+            dpi: UNITS_PER_INCH,
+        },
+    )
+    .map_err(|e| format!("Failed to render PDF: {}", e))
+}
+
+/// Render the V3 character profile as a printable PDF or PNG and save it
+/// where the user chooses. Returns the saved path, or `None` if cancelled.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_character_sheet(
+    app: tauri::AppHandle,
+    session_id: String,
+    format: String,
+) -> Result<Option<String>, AppError> {
+    if !matches!(format.as_str(), "pdf" | "png") {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Invalid format '{}': expected pdf or png", format),
+        ));
+    }
+    let token = load_session(&app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    let results = fetch_assessment_results(app.clone(), session_id, token).await?;
+    let svg = render_svg(&results)?;
+
+    let kind = format.clone();
+    let bytes = tauri::async_runtime::spawn_blocking(move || {
+        let tree = parse_svg(&svg)?;
+        match kind.as_str() {
+            "png" => render_png(&tree),
+            _ => render_pdf(&tree),
+        }
+    })
+    .await
+    .map_err(|e| format!("Render task failed: {}", e))??;
+
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_file_name(format!("character-sheet.{}", format))
+        .add_filter(format.to_uppercase(), &[format.as_str()])
+        .save_file(move |picked| {
+            let _ = tx.send(picked);
+        });
+    let Some(picked) = rx.await.ok().flatten() else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| format!("Failed to resolve save location: {}", e))?;
+
+    std::fs::write(&path, bytes).map_err(|e| format!("Failed to write sheet: {}", e))?;
+    app_log!("[CharacterSheet] Exported {} sheet", format);
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
@@ -0,0 +1,319 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::Manager;
+use tokio::sync::oneshot;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest, ApiStream};
+use crate::consent::{self, Feature};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::http::SseParser;
+use crate::logging::app_log;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct TokensUsed {
+    #[serde(default)]
+    pub input: u64,
+    #[serde(default)]
+    pub output: u64,
+    #[serde(default)]
+    pub total: u64,
+}
+
+/// Final result of a streamed chat turn
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ChatCompletion {
+    pub content: String,
+    #[serde(default, rename = "tokensUsed")]
+    pub tokens_used: Option<TokensUsed>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default, rename = "stopReason")]
+    pub stop_reason: Option<String>,
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+// SSE events emitted by the chat stream endpoints
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum StreamEvent {
+    Token {
+        text: String,
+    },
+    Complete {
+        content: String,
+        #[serde(default, rename = "tokensUsed")]
+        tokens_used: Option<TokensUsed>,
+        #[serde(default)]
+        model: Option<String>,
+        #[serde(default, rename = "stopReason")]
+        stop_reason: Option<String>,
+    },
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ChatTokenEvent {
+    #[serde(rename = "streamId")]
+    pub stream_id: String,
+    pub text: String,
+}
+
+/// Cancellation handles for in-flight chat streams, keyed by stream id
+#[derive(Default)]
+pub struct ChatStreams {
+    active: Mutex<HashMap<String, oneshot::Sender<()>>>,
+}
+
+impl ChatStreams {
+    /// Track a new stream. An id already in use is refused: replacing its
+    /// sender would leave the first stream impossible to cancel.
+    pub(crate) fn register(&self, stream_id: &str) -> Result<oneshot::Receiver<()>, AppError> {
+        let mut active = self
+            .active
+            .lock()
+            .map_err(|_| "Chat stream lock poisoned".to_string())?;
+        if active.contains_key(stream_id) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Chat stream {} is already running", stream_id),
+            ));
+        }
+        let (tx, rx) = oneshot::channel();
+        active.insert(stream_id.to_string(), tx);
+        Ok(rx)
+    }
+
+    pub(crate) fn remove(&self, stream_id: &str) -> Option<oneshot::Sender<()>> {
+        self.active.lock().ok()?.remove(stream_id)
+    }
+}
+
+/// Proxy a Front-of-House persona chat through Rust so the auth token never
+/// reaches the webview. Tokens are re-emitted as `chat-token` events; the
+/// final completion is returned once the stream ends.
+#[tauri::command]
+#[specta::specta]
+pub async fn stream_chat(
+    app: tauri::AppHandle,
+    streams: tauri::State<'_, ChatStreams>,
+    stream_id: String,
+    persona: String,
+    messages: Vec<ChatMessage>,
+) -> Result<ChatCompletion, AppError> {
+    consent::require(&app, Feature::AiChat)?;
+    let session = load_session(&app)?.ok_or_else(|| "Not signed in".to_string())?;
+    let mut cancel_rx = streams.register(&stream_id)?;
+
+    let result = run_stream(
+        &app,
+        &stream_id,
+        &session.token,
+        &persona,
+        &messages,
+        &mut cancel_rx,
+    )
+    .await;
+    streams.remove(&stream_id);
+
+    if let Err(e) = &result {
+        app_log!("[Chat] Stream {} failed: {}", stream_id, e);
+    }
+    result
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_chat_stream(
+    streams: tauri::State<'_, ChatStreams>,
+    stream_id: String,
+) -> Result<bool, String> {
+    match streams.remove(&stream_id) {
+        Some(tx) => {
+            let _ = tx.send(());
+            app_log!("[Chat] Stream {} cancelled", stream_id);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+async fn run_stream(
+    app: &tauri::AppHandle,
+    stream_id: &str,
+    token: &str,
+    persona: &str,
+    messages: &[ChatMessage],
+    cancel_rx: &mut oneshot::Receiver<()>,
+) -> Result<ChatCompletion, AppError> {
+    let request = ApiRequest::post("/api/front-of-house/chat/stream")
+        .bearer(token)
+        .header("Accept", "text/event-stream")
+        .json(&serde_json::json!({
+            "persona": persona,
+            "messages": messages,
+        }))?;
+    let mut response = app.state::<Api>().stream(request).await?;
+
+    read_stream(&mut response, cancel_rx, |text| {
+        events::emit_app_event(
+            app,
+            AppEvent::ChatToken(ChatTokenEvent {
+                stream_id: stream_id.to_string(),
+                text,
+            }),
+        );
+    })
+    .await
+}
+
+/// Read a chat stream up to its `complete` event, handing each token to
+/// `on_token`. A stream that closes before then was cut off, so it fails
+/// rather than passing off the partial reply as finished.
+async fn read_stream(
+    response: &mut ApiStream,
+    cancel_rx: &mut oneshot::Receiver<()>,
+    mut on_token: impl FnMut(String),
+) -> Result<ChatCompletion, AppError> {
+    let mut parser = SseParser::default();
+    let mut content = String::new();
+
+    loop {
+        let chunk = tokio::select! {
+            _ = &mut *cancel_rx => {
+                return Ok(ChatCompletion {
+                    content,
+                    tokens_used: None,
+                    model: None,
+                    stop_reason: Some("cancelled".to_string()),
+                    cancelled: true,
+                });
+            }
+            chunk = response.chunk() => {
+                chunk.map_err(|e| AppError::new(ErrorCode::Network, e))?
+            }
+        };
+
+        let done = chunk.is_none();
+        let payloads = match chunk {
+            Some(bytes) => parser.push(&bytes),
+            None => parser.finish().into_iter().collect(),
+        };
+
+        for payload in payloads {
+            let event = match serde_json::from_str::<StreamEvent>(&payload) {
+                Ok(event) => event,
+                Err(e) => {
+                    app_log!("[Chat] Skipping unparseable SSE event: {}", e);
+                    continue;
+                }
+            };
+
+            match event {
+                StreamEvent::Token { text } => {
+                    content.push_str(&text);
+                    on_token(text);
+                }
+                StreamEvent::Complete {
+                    content: final_content,
+                    tokens_used,
+                    model,
+                    stop_reason,
+                } => {
+                    return Ok(ChatCompletion {
+                        content: final_content,
+                        tokens_used,
+                        model,
+                        stop_reason,
+                        cancelled: false,
+                    });
+                }
+                StreamEvent::Error { error } => {
+                    return Err(AppError::new(ErrorCode::Server, error));
+                }
+            }
+        }
+
+        if done {
+            return Err(AppError::new(
+                ErrorCode::Network,
+                format!(
+                    "Chat stream ended before completing ({} characters received)",
+                    content.chars().count()
+                ),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ApiClient, FixtureApiClient};
+
+    const PATH: &str = "/api/front-of-house/chat/stream";
+
+    async fn read_fixture(body: &str) -> (Result<ChatCompletion, AppError>, Vec<String>) {
+        let api = FixtureApiClient::from_files(&[("POST_api_front_of_house_chat_stream", body)]);
+        let mut response = api.stream(ApiRequest::post(PATH)).await.unwrap();
+        let (_cancel_tx, mut cancel_rx) = oneshot::channel();
+        let mut tokens = Vec::new();
+        let result = read_stream(&mut response, &mut cancel_rx, |text| tokens.push(text)).await;
+        (result, tokens)
+    }
+
+    #[tokio::test]
+    async fn completes_on_the_complete_event() {
+        let (result, tokens) = read_fixture(concat!(
+            "data: {\"type\":\"token\",\"text\":\"Hi \"}\n\n",
+            "data: {\"type\":\"token\",\"text\":\"there\"}\n\n",
+            "data: {\"type\":\"complete\",\"content\":\"Hi there\",\"stopReason\":\"end_turn\"}\n\n",
+        ))
+        .await;
+        let completion = result.unwrap();
+        assert_eq!(completion.content, "Hi there");
+        assert_eq!(completion.stop_reason.as_deref(), Some("end_turn"));
+        assert!(!completion.cancelled);
+        assert_eq!(tokens, ["Hi ", "there"]);
+    }
+
+    #[tokio::test]
+    async fn a_stream_cut_off_before_complete_fails() {
+        let (result, tokens) =
+            read_fixture("data: {\"type\":\"token\",\"text\":\"Hi \"}\n\n").await;
+        assert_eq!(result.unwrap_err().code, ErrorCode::Network);
+        assert_eq!(tokens, ["Hi "]);
+    }
+
+    #[tokio::test]
+    async fn error_events_fail_the_stream() {
+        let (result, _) =
+            read_fixture("data: {\"type\":\"error\",\"error\":\"overloaded\"}\n\n").await;
+        let error = result.unwrap_err();
+        assert_eq!(error.code, ErrorCode::Server);
+        assert_eq!(error.detail, "overloaded");
+    }
+
+    #[test]
+    fn refuses_a_stream_id_already_running() {
+        let streams = ChatStreams::default();
+        let _first = streams.register("s1").unwrap();
+        assert_eq!(
+            streams.register("s1").unwrap_err().code,
+            ErrorCode::InvalidInput
+        );
+        assert!(streams.remove("s1").is_some());
+        assert!(streams.register("s1").is_ok());
+    }
+}
@@ -0,0 +1,273 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::Manager;
+
+use super::chat::TokensUsed;
+use crate::audit;
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+use crate::search_index;
+
+const SEARCH_SCOPE: &str = "chat";
+const CHAT_ROLES: &[&str] = &["user", "assistant"];
+const TITLE_CHARS: usize = 80;
+
+/// A persona conversation without its turns
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Conversation {
+    pub id: String,
+    pub persona: String,
+    pub title: String,
+    #[serde(rename = "turnCount")]
+    pub turn_count: i64,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ChatTurn {
+    pub id: String,
+    pub role: String,
+    pub content: String,
+    pub model: Option<String>,
+    #[serde(rename = "tokensUsed")]
+    pub tokens_used: Option<TokensUsed>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ConversationDetail {
+    #[serde(flatten)]
+    pub conversation: Conversation,
+    pub turns: Vec<ChatTurn>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SavedTurn {
+    #[serde(rename = "conversationId")]
+    pub conversation_id: String,
+    #[serde(rename = "turnId")]
+    pub turn_id: String,
+}
+
+fn row_to_conversation(row: &rusqlite::Row) -> rusqlite::Result<Conversation> {
+    Ok(Conversation {
+        id: row.get("id")?,
+        persona: row.get("persona")?,
+        title: row.get("title")?,
+        turn_count: row.get("turn_count")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+fn row_to_turn(row: &rusqlite::Row) -> rusqlite::Result<ChatTurn> {
+    let tokens: Option<String> = row.get("tokens_used")?;
+    Ok(ChatTurn {
+        id: row.get("id")?,
+        role: row.get("role")?,
+        content: row.get("content")?,
+        model: row.get("model")?,
+        tokens_used: tokens.and_then(|t| serde_json::from_str(&t).ok()),
+        created_at: row.get("created_at")?,
+    })
+}
+
+const CONVERSATION_SELECT: &str = "SELECT c.*,
+    (SELECT COUNT(*) FROM chat_turns t WHERE t.conversation_id = c.id) AS turn_count
+    FROM chat_conversations c";
+
+fn load_conversation(db: &Database, id: &str) -> Result<Option<ConversationDetail>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!("{} WHERE c.id = ?1", CONVERSATION_SELECT))?;
+        let Some(conversation) = stmt
+            .query_map([id], row_to_conversation)?
+            .next()
+            .transpose()?
+        else {
+            return Ok(None);
+        };
+        let mut stmt = conn.prepare(
+            "SELECT * FROM chat_turns WHERE conversation_id = ?1 ORDER BY created_at, rowid",
+        )?;
+        let turns = stmt
+            .query_map([id], row_to_turn)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(Some(ConversationDetail {
+            conversation,
+            turns,
+        }))
+    })
+}
+
+/// Re-index the whole conversation under its id so search hits open it
+fn index_conversation(app: &tauri::AppHandle, id: &str) {
+    let db = app.state::<Database>();
+    match load_conversation(&db, id) {
+        Ok(Some(detail)) => {
+            let body = detail
+                .turns
+                .iter()
+                .map(|t| t.content.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            search_index::upsert_quietly(app, SEARCH_SCOPE, id, &detail.conversation.title, &body);
+        }
+        Ok(None) => {}
+        Err(e) => app_log!("[ChatHistory] Failed to index {}: {}", id, e),
+    }
+}
+
+fn title_for(content: &str) -> String {
+    content
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or("New conversation")
+        .chars()
+        .take(TITLE_CHARS)
+        .collect()
+}
+
+/// Append a turn, starting a new conversation when `conversation_id` is
+/// omitted. The first user message becomes the title.
+#[tauri::command]
+#[specta::specta]
+#[allow(clippy::too_many_arguments)]
+pub async fn save_chat_turn(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    conversation_id: Option<String>,
+    persona: String,
+    role: String,
+    content: String,
+    model: Option<String>,
+    tokens_used: Option<TokensUsed>,
+) -> Result<SavedTurn, AppError> {
+    if !CHAT_ROLES.contains(&role.as_str()) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Invalid chat role: {}", role),
+        ));
+    }
+    let tokens_used = tokens_used
+        .map(|t| serde_json::to_string(&t))
+        .transpose()
+        .map_err(|e| format!("Failed to serialize tokens: {}", e))?;
+    let now = now_secs();
+    let conversation_id = conversation_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let turn_id = uuid::Uuid::new_v4().to_string();
+
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO chat_conversations (id, persona, title, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)
+             ON CONFLICT(id) DO UPDATE SET updated_at = excluded.updated_at",
+            rusqlite::params![conversation_id, persona, title_for(&content), now],
+        )?;
+        tx.execute(
+            "INSERT INTO chat_turns (id, conversation_id, role, content, model, tokens_used, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![turn_id, conversation_id, role, content, model, tokens_used, now],
+        )?;
+        tx.commit()
+    })?;
+
+    index_conversation(&app, &conversation_id);
+    Ok(SavedTurn {
+        conversation_id,
+        turn_id,
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_conversation(
+    db: tauri::State<'_, Database>,
+    id: String,
+) -> Result<Option<ConversationDetail>, String> {
+    load_conversation(&db, &id)
+}
+
+/// Conversations, most recently active first
+#[tauri::command]
+#[specta::specta]
+pub async fn list_conversations(
+    db: tauri::State<'_, Database>,
+    persona: Option<String>,
+) -> Result<Vec<Conversation>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE (?1 IS NULL OR c.persona = ?1) ORDER BY c.updated_at DESC",
+            CONVERSATION_SELECT
+        ))?;
+        let rows = stmt.query_map([persona], row_to_conversation)?;
+        rows.collect()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_conversation(db: tauri::State<'_, Database>, id: String) -> Result<(), String> {
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM chat_turns WHERE conversation_id = ?1", [&id])?;
+        tx.execute("DELETE FROM chat_conversations WHERE id = ?1", [&id])?;
+        tx.commit()
+    })?;
+    search_index::remove(&db, SEARCH_SCOPE, &id)?;
+    app_log!("[ChatHistory] Conversation {} deleted", id);
+    Ok(())
+}
+
+/// Write a conversation to `destination` as Markdown (default) or JSON
+#[tauri::command]
+#[specta::specta]
+pub async fn export_conversation(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+    destination: String,
+    format: Option<String>,
+) -> Result<(), AppError> {
+    audit::audited(app.clone(), "export_conversation", async {
+        let detail = load_conversation(&db, &id)?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Conversation not found"))?;
+
+        let contents = match format.as_deref().unwrap_or("markdown") {
+            "json" => serde_json::to_string_pretty(&detail)
+                .map_err(|e| format!("Failed to serialize conversation: {}", e))?,
+            "markdown" => {
+                let mut out = format!(
+                    "# {}\n\n_{}_\n",
+                    detail.conversation.title, detail.conversation.persona
+                );
+                for turn in &detail.turns {
+                    let speaker = if turn.role == "user" {
+                        "You"
+                    } else {
+                        detail.conversation.persona.as_str()
+                    };
+                    out.push_str(&format!("\n**{}:**\n\n{}\n", speaker, turn.content));
+                }
+                out
+            }
+            other => {
+                return Err(AppError::new(
+                    ErrorCode::InvalidInput,
+                    format!("Invalid export format: {}", other),
+                ))
+            }
+        };
+
+        std::fs::write(PathBuf::from(&destination), contents)
+            .map_err(|e| format!("Failed to write conversation: {}", e))?;
+        app_log!("[ChatHistory] Conversation {} exported", id);
+        Ok(())
+    })
+    .await
+}
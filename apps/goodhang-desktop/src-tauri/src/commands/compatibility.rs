@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use super::activation::Attributes;
+
+// Relative weight of each component; components missing from either
+// profile are dropped and the rest renormalized
+const ATTRIBUTES_WEIGHT: f64 = 0.35;
+const INTERESTS_WEIGHT: f64 = 0.35;
+const SOCIAL_ENERGY_WEIGHT: f64 = 0.15;
+const ENERGY_PATTERN_WEIGHT: f64 = 0.15;
+// Attributes are scored 1-10
+const ATTRIBUTE_RANGE: f64 = 9.0;
+
+/// The parts of an assessment profile that feed compatibility
+#[derive(Debug, Default, Deserialize, specta::Type)]
+pub struct CompatibilityProfile {
+    #[serde(default)]
+    pub attributes: Option<Attributes>,
+    #[serde(default)]
+    pub interest_vectors: Vec<String>,
+    /// introvert | extrovert | ambivert | selective_extrovert
+    #[serde(default)]
+    pub social_energy: Option<String>,
+    /// spontaneous | planned | flexible | routine_oriented
+    #[serde(default)]
+    pub energy_pattern: Option<String>,
+}
+
+/// Per-component similarity in 0..=1, `None` when either side lacks it
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CompatibilityBreakdown {
+    pub attributes: Option<f64>,
+    pub interests: Option<f64>,
+    #[serde(rename = "socialEnergy")]
+    pub social_energy: Option<f64>,
+    #[serde(rename = "energyPattern")]
+    pub energy_pattern: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CompatibilityScore {
+    /// 0-100; `None` if the profiles have nothing comparable
+    pub score: Option<u8>,
+    pub breakdown: CompatibilityBreakdown,
+    #[serde(rename = "sharedInterests")]
+    pub shared_interests: Vec<String>,
+}
+
+fn attribute_values(a: &Attributes) -> [Option<f64>; 6] {
+    [a.int, a.wis, a.cha, a.con, a.str_attr, a.dex]
+}
+
+/// Mean closeness across attributes both profiles have
+fn attribute_similarity(mine: &Attributes, theirs: &Attributes) -> Option<f64> {
+    let closeness: Vec<f64> = attribute_values(mine)
+        .into_iter()
+        .zip(attribute_values(theirs))
+        .filter_map(|(a, b)| Some(1.0 - ((a? - b?).abs() / ATTRIBUTE_RANGE).min(1.0)))
+        .collect();
+    (!closeness.is_empty()).then(|| closeness.iter().sum::<f64>() / closeness.len() as f64)
+}
+
+fn interest_set(interests: &[String]) -> BTreeSet<String> {
+    interests
+        .iter()
+        .map(|i| i.trim().to_lowercase())
+        .filter(|i| !i.is_empty())
+        .collect()
+}
+
+/// Jaccard overlap of interest vectors, plus the shared ones for display
+fn interest_similarity(mine: &[String], theirs: &[String]) -> (Option<f64>, Vec<String>) {
+    let (mine, theirs) = (interest_set(mine), interest_set(theirs));
+    if mine.is_empty() || theirs.is_empty() {
+        return (None, Vec::new());
+    }
+    let shared: Vec<String> = mine.intersection(&theirs).cloned().collect();
+    let union = mine.union(&theirs).count();
+    (Some(shared.len() as f64 / union as f64), shared)
+}
+
+/// Introverts and extroverts can get along, but the middle of the range
+/// meets everyone halfway
+fn social_energy_similarity(mine: &str, theirs: &str) -> f64 {
+    if mine == theirs {
+        return 1.0;
+    }
+    match (mine.min(theirs), mine.max(theirs)) {
+        ("ambivert", _) | (_, "ambivert") => 0.8,
+        ("extrovert", "selective_extrovert") => 0.7,
+        ("introvert", "selective_extrovert") => 0.7,
+        ("extrovert", "introvert") => 0.4,
+        _ => 0.5,
+    }
+}
+
+fn energy_pattern_similarity(mine: &str, theirs: &str) -> f64 {
+    if mine == theirs {
+        return 1.0;
+    }
+    match (mine.min(theirs), mine.max(theirs)) {
+        ("flexible", _) | (_, "flexible") => 0.8,
+        ("planned", "routine_oriented") => 0.8,
+        ("planned", "spontaneous") => 0.4,
+        ("routine_oriented", "spontaneous") => 0.3,
+        _ => 0.5,
+    }
+}
+
+pub fn compatibility(
+    mine: &CompatibilityProfile,
+    theirs: &CompatibilityProfile,
+) -> CompatibilityScore {
+    let attributes = match (&mine.attributes, &theirs.attributes) {
+        (Some(a), Some(b)) => attribute_similarity(a, b),
+        _ => None,
+    };
+    let (interests, shared_interests) =
+        interest_similarity(&mine.interest_vectors, &theirs.interest_vectors);
+    let social_energy = mine
+        .social_energy
+        .as_deref()
+        .zip(theirs.social_energy.as_deref())
+        .map(|(a, b)| social_energy_similarity(a, b));
+    let energy_pattern = mine
+        .energy_pattern
+        .as_deref()
+        .zip(theirs.energy_pattern.as_deref())
+        .map(|(a, b)| energy_pattern_similarity(a, b));
+
+    let weighted = [
+        (attributes, ATTRIBUTES_WEIGHT),
+        (interests, INTERESTS_WEIGHT),
+        (social_energy, SOCIAL_ENERGY_WEIGHT),
+        (energy_pattern, ENERGY_PATTERN_WEIGHT),
+    ];
+    let total_weight: f64 = weighted
+        .iter()
+        .filter(|(v, _)| v.is_some())
+        .map(|(_, w)| w)
+        .sum();
+    let score = (total_weight > 0.0).then(|| {
+        let sum: f64 = weighted
+            .iter()
+            .filter_map(|(v, w)| Some(v.as_ref()? * w))
+            .sum();
+        (sum / total_weight * 100.0).round().clamp(0.0, 100.0) as u8
+    });
+
+    CompatibilityScore {
+        score,
+        breakdown: CompatibilityBreakdown {
+            attributes,
+            interests,
+            social_energy,
+            energy_pattern,
+        },
+        shared_interests,
+    }
+}
+
+/// Score two profiles locally, for instant previews while browsing people
+#[tauri::command]
+#[specta::specta]
+pub async fn compute_compatibility(
+    my_profile: CompatibilityProfile,
+    other_profile: CompatibilityProfile,
+) -> Result<CompatibilityScore, String> {
+    Ok(compatibility(&my_profile, &other_profile))
+}
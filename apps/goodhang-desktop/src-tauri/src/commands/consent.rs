@@ -0,0 +1,86 @@
+use serde::Serialize;
+
+use super::{analytics, email_signals, location};
+use crate::audit;
+use crate::consent::{self, Feature};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::outbox;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Consent {
+    pub feature: String,
+    pub granted: bool,
+    /// When the user last answered; `None` if they never have
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<i64>,
+}
+
+/// Every consent-gated feature and the user's current answer
+#[tauri::command]
+#[specta::specta]
+pub async fn get_consents(app: tauri::AppHandle) -> Result<Vec<Consent>, String> {
+    let records = consent::load_all(&app);
+    Ok(Feature::ALL
+        .iter()
+        .map(|feature| {
+            let record = records.get(feature.key());
+            Consent {
+                feature: feature.key().to_string(),
+                granted: record.is_some_and(|r| r.granted),
+                updated_at: record.map(|r| r.updated_at),
+            }
+        })
+        .collect())
+}
+
+/// Grant or withdraw consent for a feature. Withdrawing also drops the data
+/// that feature collected locally.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_consent(
+    app: tauri::AppHandle,
+    feature: String,
+    granted: bool,
+) -> Result<Consent, AppError> {
+    audit::audited(app.clone(), "set_consent", async {
+        let parsed = Feature::parse(&feature).ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Invalid consent feature: {}", feature),
+            )
+        })?;
+        let record = consent::save(&app, parsed, granted)?;
+
+        if !granted {
+            match parsed {
+                Feature::Location => location::forget_location(&app)?,
+                Feature::ContactImport => email_signals::forget_email_signals(&app)?,
+                // Events queued while consent was granted must not go out
+                Feature::Analytics => {
+                    let dropped = outbox::discard_kind(&app, analytics::EVENT_TASK)?;
+                    app_log!("[Consent] Dropped {} queued analytics events", dropped);
+                }
+                Feature::ClipboardWatching
+                | Feature::JournalAi
+                | Feature::ExternalLookups
+                | Feature::AiChat => {}
+            }
+        }
+        app_log!(
+            "[Consent] {} {}",
+            feature,
+            if granted { "granted" } else { "withdrawn" }
+        );
+
+        let consent = Consent {
+            feature,
+            granted,
+            updated_at: Some(record.updated_at),
+        };
+        events::emit_app_event(&app, AppEvent::ConsentChanged(consent.clone()));
+        Ok(consent)
+    })
+    .await
+}
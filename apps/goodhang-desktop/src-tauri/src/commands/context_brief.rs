@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+use super::auth::load_session;
+use super::email_signals::last_contacts;
+use super::market::{self, MarketEntity};
+use super::relationship_brief::assemble_brief;
+use super::relationship_notes::notes_for;
+use super::signal_weights::load_weights;
+use crate::consent::{self, Feature};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::http;
+use crate::logging::app_log;
+use crate::scoring::{self, Classification, ScoringInput, Weights};
+
+const GATHERER_TIMEOUT: Duration = Duration::from_secs(20);
+const CACHE_TTL_SECS: i64 = 30 * 60;
+
+/// Who the brief is about. `entityId` is the GFT entity, when there is one.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ContextSubject {
+    #[serde(default, rename = "entityId")]
+    pub entity_id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub industry: Option<String>,
+    /// Addresses to look up in the local email signals
+    #[serde(default)]
+    pub emails: Vec<String>,
+    /// Scoring adapter to classify with: person (default) or company
+    #[serde(default = "default_entity_type", rename = "entityType")]
+    pub entity_type: String,
+}
+
+fn default_entity_type() -> String {
+    "person".to_string()
+}
+
+impl ContextSubject {
+    fn cache_key(&self) -> String {
+        let id = match &self.entity_id {
+            Some(id) => id.clone(),
+            None => self.name.trim().to_lowercase(),
+        };
+        format!("context_brief:{}", id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum GathererStatus {
+    Ok,
+    Failed,
+    /// Not applicable to this subject, e.g. no domain for ARI
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct GathererReport {
+    pub name: String,
+    pub status: GathererStatus,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    pub data: Option<serde_json::Value>,
+}
+
+/// Everything the available gatherers found, with how each one fared
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ContextBrief {
+    pub subject: ContextSubject,
+    pub gatherers: Vec<GathererReport>,
+    /// Risk/opportunity quadrant from what the gatherers found
+    #[serde(default)]
+    pub classification: Option<Classification>,
+    #[serde(rename = "assembledAt")]
+    pub assembled_at: i64,
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// Where the ARI service is. Release builds only use it when configured;
+/// dev builds fall back to a local ARI server.
+fn ari_base_url() -> Option<String> {
+    std::env::var("GOODHANG_ARI_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .or_else(|| cfg!(debug_assertions).then(|| "http://localhost:4250".to_string()))
+}
+
+/// Run one gatherer with a deadline, timing it. `Ok(None)` means skipped.
+async fn run_gatherer<T: Serialize>(
+    name: &str,
+    gather: impl Future<Output = Result<Option<T>, String>>,
+) -> GathererReport {
+    let started = Instant::now();
+    let outcome = match tokio::time::timeout(GATHERER_TIMEOUT, gather).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(format!("Timed out after {}s", GATHERER_TIMEOUT.as_secs())),
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let (status, error, data) = match outcome {
+        Ok(Some(data)) => (GathererStatus::Ok, None, serde_json::to_value(data).ok()),
+        Ok(None) => (GathererStatus::Skipped, None, None),
+        Err(e) => {
+            app_log!("[ContextBrief] {} gatherer failed: {}", name, e);
+            (GathererStatus::Failed, Some(e), None)
+        }
+    };
+    GathererReport {
+        name: name.to_string(),
+        status,
+        duration_ms,
+        error,
+        data,
+    }
+}
+
+/// ARI (AI Recommendation Index) score from the entity's metadata
+async fn gather_ari(base_url: &str, domain: &str) -> Result<Option<serde_json::Value>, String> {
+    let response = http::client()
+        .get(format!("{}/api/v1/entities", base_url))
+        .query(&[("domain", domain)])
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("ARI error {}", response.status()));
+    }
+    let body: serde_json::Value = http::parse_response(response, "ari /entities").await?;
+    Ok(body
+        .pointer("/data/0/metadata/ari")
+        .cloned()
+        .filter(|ari| !ari.is_null()))
+}
+
+/// Whole days since a `YYYY-MM-DD...` timestamp
+pub(crate) fn days_since(timestamp: &str) -> Option<i64> {
+    let date = timestamp.get(..10)?;
+    let mut parts = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (y, m, d) = (parts.next()??, parts.next()??, parts.next()??);
+    // Days from civil date (Howard Hinnant's algorithm)
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(now_secs().div_euclid(86_400) - days)
+}
+
+/// Scoring facts pulled out of the gatherers' data
+fn scoring_input(gatherers: &[GathererReport]) -> ScoringInput {
+    let data = |name: &str| {
+        gatherers
+            .iter()
+            .find(|g| g.name == name)
+            .and_then(|g| g.data.as_ref())
+    };
+    let count =
+        |value: Option<&serde_json::Value>| value.and_then(|v| v.as_array()).map_or(0, Vec::len);
+
+    let relationship = data("relationships");
+    let interaction_days = relationship
+        .and_then(|r| r.get("lastInteractionAt"))
+        .and_then(|v| v.as_str())
+        .and_then(days_since);
+    let email_days = data("email")
+        .and_then(|e| e.get("lastContactAt"))
+        .and_then(|v| v.as_i64())
+        .map(|at| (now_secs() - at).div_euclid(86_400));
+    ScoringInput {
+        // Whichever contact was most recent
+        days_since_contact: interaction_days.into_iter().chain(email_days).min(),
+        note_count: count(data("history")),
+        shared_context_count: count(relationship.and_then(|r| r.get("sharedContext"))),
+        talking_point_count: count(relationship.and_then(|r| r.get("talkingPoints"))),
+        compatibility: relationship
+            .and_then(|r| r.get("compatibility"))
+            .and_then(|v| v.as_f64()),
+        ari_score: data("ari")
+            .and_then(|a| a.get("overall_score"))
+            .and_then(|v| v.as_f64()),
+        recent_news_count: data("market")
+            .and_then(|m| m.get("results"))
+            .and_then(|r| r.as_array())
+            .map_or(0, |results| {
+                results
+                    .iter()
+                    .filter(|r| r.get("kind").and_then(|k| k.as_str()) == Some("news"))
+                    .count()
+            }),
+    }
+}
+
+fn cached(db: &Database, key: &str) -> Option<ContextBrief> {
+    let (payload, fetched_at): (String, i64) = db
+        .with_conn(|conn| {
+            conn.query_row(
+                "SELECT payload, fetched_at FROM analytics_cache WHERE key = ?1",
+                [key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+        })
+        .ok()?;
+    if now_secs() - fetched_at > CACHE_TTL_SECS {
+        return None;
+    }
+    serde_json::from_str(&payload).ok()
+}
+
+fn cache_put(db: &Database, key: &str, brief: &ContextBrief) -> Result<(), String> {
+    let payload =
+        serde_json::to_string(brief).map_err(|e| format!("Failed to serialize cache: {}", e))?;
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO analytics_cache (key, payload, fetched_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET payload = ?2, fetched_at = ?3",
+            rusqlite::params![key, payload, brief.assembled_at],
+        )
+    })
+    .map(|_| ())
+}
+
+/// Re-classify every cached brief of `entity_type` with new weights, from
+/// the gatherer data already in the cache. Returns how many were updated.
+pub(crate) fn rescore_cached(
+    db: &Database,
+    entity_type: &str,
+    overrides: &Weights,
+) -> Result<usize, String> {
+    let rows: Vec<(String, String)> = db.with_conn(|conn| {
+        let mut stmt = conn
+            .prepare("SELECT key, payload FROM analytics_cache WHERE key LIKE 'context_brief:%'")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    })?;
+
+    let mut count = 0;
+    for (key, payload) in rows {
+        let Ok(mut brief) = serde_json::from_str::<ContextBrief>(&payload) else {
+            continue;
+        };
+        if brief.subject.entity_type != entity_type {
+            continue;
+        }
+        brief.classification =
+            scoring::classify(entity_type, &scoring_input(&brief.gatherers), overrides);
+        let payload = serde_json::to_string(&brief)
+            .map_err(|e| format!("Failed to serialize cache: {}", e))?;
+        // Keeps fetched_at, so re-scoring doesn't extend the brief's freshness
+        db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE analytics_cache SET payload = ?1 WHERE key = ?2",
+                [&payload, &key],
+            )
+        })?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Fan out to every gatherer that applies, concurrently. One failing (or the
+/// backend being unreachable) never fails the brief; it's reported instead.
+async fn assemble(app: &tauri::AppHandle, subject: &ContextSubject) -> ContextBrief {
+    let token = load_session(app).ok().flatten().map(|s| s.token);
+    let entity_id = subject.entity_id.as_deref();
+    let market_entity = MarketEntity {
+        name: subject.name.clone(),
+        domain: subject.domain.clone(),
+        industry: subject.industry.clone(),
+    };
+
+    let relationships = run_gatherer("relationships", async {
+        match (entity_id, &token) {
+            (Some(id), Some(token)) => assemble_brief(app, token, id).await.map(Some),
+            (Some(_), None) => Err("Not signed in".to_string()),
+            (None, _) => Ok(None),
+        }
+    });
+    let history = run_gatherer("history", async {
+        match entity_id {
+            Some(id) => notes_for(&app.state::<Database>(), id).map(Some),
+            None => Ok(None),
+        }
+    });
+    // Third-party lookups are skipped without consent rather than failed
+    let lookups = consent::is_granted(app, Feature::ExternalLookups);
+    let market = run_gatherer("market", async {
+        if !lookups {
+            return Ok(None);
+        }
+        market::gather(app, &market_entity)
+            .await
+            .map(Some)
+            .map_err(|e| e.detail)
+    });
+    let ari = run_gatherer("ari", async {
+        match (subject.domain.as_deref(), ari_base_url()) {
+            (Some(domain), Some(base_url)) if lookups => gather_ari(&base_url, domain).await,
+            _ => Ok(None),
+        }
+    });
+    let email = run_gatherer("email", async {
+        if subject.emails.is_empty() {
+            return Ok(None);
+        }
+        let contacts = last_contacts(&app.state::<Database>(), &subject.emails)?;
+        Ok(contacts.into_iter().max_by_key(|c| c.last_contact_at))
+    });
+    let (relationships, history, market, ari, email) =
+        tokio::join!(relationships, history, market, ari, email);
+
+    let gatherers = vec![relationships, history, market, ari, email];
+    ContextBrief {
+        subject: subject.clone(),
+        classification: scoring::classify(
+            &subject.entity_type,
+            &scoring_input(&gatherers),
+            &load_weights(app, &subject.entity_type),
+        ),
+        gatherers,
+        assembled_at: now_secs(),
+        cached: false,
+    }
+}
+
+/// The cached brief for `subject` unless `refresh` is set, else a freshly
+/// assembled one
+pub(crate) async fn brief_for(
+    app: &tauri::AppHandle,
+    subject: &ContextSubject,
+    refresh: bool,
+) -> ContextBrief {
+    let db = app.state::<Database>();
+    let key = subject.cache_key();
+    if !refresh {
+        if let Some(mut brief) = cached(&db, &key) {
+            brief.cached = true;
+            return brief;
+        }
+    }
+
+    let brief = assemble(app, subject).await;
+    // Don't pin a brief where nothing worked; the next open should retry
+    if brief
+        .gatherers
+        .iter()
+        .any(|g| g.status == GathererStatus::Ok)
+    {
+        if let Err(e) = cache_put(&db, &key, &brief) {
+            app_log!("[ContextBrief] Failed to cache brief: {}", e);
+        }
+    }
+    brief
+}
+
+/// Locally assembled context for a person or company, the desktop
+/// counterpart of the backend's `gatherIntelligence()`. Cached per subject
+/// for half an hour unless `refresh` is set.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_context_brief(
+    app: tauri::AppHandle,
+    subject: ContextSubject,
+    refresh: Option<bool>,
+) -> Result<ContextBrief, AppError> {
+    if subject.name.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid subject: name is required",
+        ));
+    }
+    Ok(brief_for(&app, &subject, refresh.unwrap_or(false)).await)
+}
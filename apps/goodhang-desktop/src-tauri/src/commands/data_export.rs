@@ -0,0 +1,427 @@
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::activation::fetch_assessment_results;
+use super::auth::load_session;
+use super::journal;
+use super::voice_memos::memo_dir;
+use crate::api::{Api, ApiRequest};
+use crate::audit;
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::http::{self, CLIENT_VERSION, MAX_PAGE_SIZE};
+use crate::logging::app_log;
+
+const MANIFEST_VERSION: u32 = 1;
+
+/// Local tables exported as-is, by section name
+const LOCAL_SECTIONS: &[(&str, &str)] = &[
+    (
+        "voice_memos",
+        "SELECT id, title, duration_secs, transcript, tags, file_name, mime_type,
+                created_at, updated_at, sync_status
+         FROM voice_memos ORDER BY created_at",
+    ),
+    (
+        "assessment_snapshots",
+        "SELECT session_id, payload, recorded_at FROM assessment_snapshots ORDER BY recorded_at",
+    ),
+    (
+        "relationship_notes",
+        "SELECT * FROM relationship_notes ORDER BY created_at",
+    ),
+    ("reminders", "SELECT * FROM reminders ORDER BY due_at"),
+    (
+        "chat_conversations",
+        "SELECT * FROM chat_conversations ORDER BY created_at",
+    ),
+    ("chat_turns", "SELECT * FROM chat_turns ORDER BY created_at"),
+    (
+        "bookmarks",
+        "SELECT item_id, payload, bookmarked_at FROM bookmarks WHERE removed = 0",
+    ),
+    (
+        "context_files",
+        "SELECT id, remote_id, file_name, mime_type, size_bytes, status, result_id, created_at
+         FROM uploads WHERE kind = 'context_file' ORDER BY created_at",
+    ),
+    (
+        "prompt_templates",
+        "SELECT * FROM prompt_templates WHERE source = 'user'",
+    ),
+];
+
+/// Paginated API listings, walked to the end
+const SERVER_LISTINGS: &[(&str, &str)] = &[
+    ("relationships", "/api/relationships"),
+    ("context_files", "/api/voice/context-files"),
+    ("entities", "/api/entities"),
+    ("badges", "/api/badges"),
+];
+
+/// Single-response API resources
+const SERVER_RESOURCES: &[(&str, &str)] = &[
+    ("profile", "/api/profile"),
+    ("hangs", "/api/hangs"),
+    ("bookmarks", "/api/library/bookmarks"),
+];
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ExportProgress {
+    pub section: String,
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ManifestSection {
+    pub name: String,
+    /// local | server
+    pub source: String,
+    /// Path of the section inside the archive; absent when it failed
+    pub file: Option<String>,
+    pub items: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ExportManifest {
+    #[serde(rename = "formatVersion")]
+    pub format_version: u32,
+    #[serde(rename = "appVersion")]
+    pub app_version: String,
+    #[serde(rename = "exportedAt")]
+    pub exported_at: i64,
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+    pub sections: Vec<ManifestSection>,
+    /// Voice memo recordings under `local/voice_memos/`
+    #[serde(rename = "audioFiles")]
+    pub audio_files: usize,
+}
+
+struct Section {
+    name: String,
+    source: &'static str,
+    result: Result<serde_json::Value, String>,
+}
+
+impl Section {
+    fn path(&self) -> String {
+        format!("{}/{}.json", self.source, self.name)
+    }
+}
+
+fn column_value(value: rusqlite::types::ValueRef) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null | ValueRef::Blob(_) => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into(),
+    }
+}
+
+/// Every row of a query as JSON objects keyed by column name
+fn dump_rows(db: &Database, sql: &str, params: &[&str]) -> Result<serde_json::Value, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(sql)?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+            let mut object = serde_json::Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                object.insert(column.clone(), column_value(row.get_ref(i)?));
+            }
+            Ok(serde_json::Value::Object(object))
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })
+    .map(serde_json::Value::Array)
+}
+
+async fn get_json(api: &Api, token: &str, path: &str) -> Result<serde_json::Value, String> {
+    api.send(ApiRequest::get(path).bearer(token))
+        .await?
+        .error_for_status()?
+        .json(path)
+}
+
+async fn fetch_all(api: &Api, token: &str, path: &str) -> Result<serde_json::Value, String> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let request = ApiRequest::get(path).bearer(token);
+        let page: http::Page<serde_json::Value> =
+            http::fetch_page(api, request, cursor.as_deref(), Some(MAX_PAGE_SIZE)).await?;
+        items.extend(page.items);
+        match page.next_cursor.filter(|_| page.has_more) {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    Ok(serde_json::Value::Array(items))
+}
+
+fn item_count(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Array(items) => items.len(),
+        serde_json::Value::Null => 0,
+        _ => 1,
+    }
+}
+
+fn emit_progress(app: &tauri::AppHandle, section: &str, completed: usize, total: usize) {
+    events::emit_app_event(
+        app,
+        AppEvent::DataExportProgress(ExportProgress {
+            section: section.to_string(),
+            completed,
+            total,
+        }),
+    );
+}
+
+fn add_json(
+    zip: &mut zip::ZipWriter<std::fs::File>,
+    options: zip::write::SimpleFileOptions,
+    name: &str,
+    value: &serde_json::Value,
+) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+    zip.start_file(name, options)
+        .and_then(|_| zip.write_all(&bytes).map_err(Into::into))
+        .map_err(|e| format!("Failed to write {}: {}", name, e))
+}
+
+/// Write the archive next to `destination` and move it into place once
+/// complete, so a failed export never leaves a partial zip behind
+fn write_archive(
+    destination: &Path,
+    manifest: &serde_json::Value,
+    sections: &[Section],
+    audio: &[(String, PathBuf)],
+) -> Result<(), String> {
+    let partial = destination.with_extension("zip.partial");
+    let file = std::fs::File::create(&partial)
+        .map_err(|e| format!("Failed to create export file: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o600));
+    }
+
+    let write = || -> Result<(), String> {
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        add_json(&mut zip, options, "manifest.json", manifest)?;
+        for section in sections {
+            if let Ok(value) = &section.result {
+                add_json(&mut zip, options, &section.path(), value)?;
+            }
+        }
+        for (file_name, path) in audio {
+            let name = format!("local/voice_memos/{}", file_name);
+            let mut source = std::fs::File::open(path)
+                .map_err(|e| format!("Failed to read {}: {}", file_name, e))?;
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+            std::io::copy(&mut source, &mut zip)
+                .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+        }
+        zip.finish()
+            .map_err(|e| format!("Failed to finish export: {}", e))?;
+        Ok(())
+    };
+
+    match write() {
+        Ok(()) => std::fs::rename(&partial, destination)
+            .map_err(|e| format!("Failed to save export: {}", e)),
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(e)
+        }
+    }
+}
+
+/// Everything the app holds locally plus the user's server-side data, as a
+/// zip with a manifest. Sections that fail are listed in the manifest with
+/// their error rather than failing the export. Progress is reported through
+/// `data-export-progress` events.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_my_data(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+    db: tauri::State<'_, Database>,
+    destination: String,
+) -> Result<ExportManifest, AppError> {
+    audit::audited(app.clone(), "export_my_data", async {
+        let destination = PathBuf::from(&destination);
+        if destination.extension().and_then(|e| e.to_str()) != Some("zip")
+            || !destination.parent().is_some_and(|p| p.is_dir())
+        {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid destination: expected a .zip path in an existing folder",
+            ));
+        }
+
+        let session = load_session(&app)?;
+        let token = session.as_ref().map(|s| s.token.clone());
+        let session_ids: Vec<String> = db.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT DISTINCT session_id FROM assessment_snapshots")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect()
+        })?;
+
+        // Local tables, journal, session, then each server resource
+        let total = LOCAL_SECTIONS.len() + 2 + SERVER_LISTINGS.len() + SERVER_RESOURCES.len() + 1;
+        let mut sections = Vec::with_capacity(total);
+        let mut push = |section: Section| {
+            emit_progress(&app, &section.name, sections.len() + 1, total);
+            sections.push(section);
+        };
+
+        for (name, sql) in LOCAL_SECTIONS {
+            push(Section {
+                name: name.to_string(),
+                source: "local",
+                result: dump_rows(&db, sql, &[]),
+            });
+        }
+        push(Section {
+            name: "journal_summaries".to_string(),
+            source: "local",
+            result: dump_rows(
+                &db,
+                "SELECT subject_id AS period, output AS summary, created_at
+                 FROM summary_cache WHERE kind = ?1",
+                &[journal::SUMMARY_CACHE_KIND],
+            ),
+        });
+        push(Section {
+            name: "session".to_string(),
+            source: "local",
+            // The token is a credential, not the user's data
+            result: Ok(match &session {
+                Some(s) => serde_json::json!({
+                    "userId": s.user_id,
+                    "sessionId": s.session_id,
+                    "expiresAt": s.expires_at,
+                }),
+                None => serde_json::Value::Null,
+            }),
+        });
+
+        for (name, path) in SERVER_LISTINGS {
+            let result = match &token {
+                Some(token) => fetch_all(&api, token, path).await,
+                None => Err("Not signed in".to_string()),
+            };
+            push(Section {
+                name: name.to_string(),
+                source: "server",
+                result,
+            });
+        }
+        for (name, path) in SERVER_RESOURCES {
+            let result = match &token {
+                Some(token) => get_json(&api, token, path).await,
+                None => Err("Not signed in".to_string()),
+            };
+            push(Section {
+                name: name.to_string(),
+                source: "server",
+                result,
+            });
+        }
+        let assessments = match &token {
+            Some(token) => {
+                let mut results = Vec::new();
+                for session_id in &session_ids {
+                    match fetch_assessment_results(app.clone(), session_id.clone(), token.clone())
+                        .await
+                    {
+                        Ok(r) => results.push(r),
+                        Err(e) => {
+                            app_log!(
+                                "[DataExport] Assessment {} skipped: {}",
+                                session_id,
+                                e.detail
+                            )
+                        }
+                    }
+                }
+                serde_json::to_value(results).map_err(|e| e.to_string())
+            }
+            None => Err("Not signed in".to_string()),
+        };
+        push(Section {
+            name: "assessments".to_string(),
+            source: "server",
+            result: assessments,
+        });
+        drop(push);
+
+        let memo_dir = memo_dir(&app)?;
+        let audio: Vec<(String, PathBuf)> = db
+            .with_conn(|conn| {
+                let mut stmt = conn.prepare("SELECT file_name FROM voice_memos")?;
+                let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+                rows.collect::<rusqlite::Result<Vec<_>>>()
+            })?
+            .into_iter()
+            .map(|name| {
+                let path = memo_dir.join(&name);
+                (name, path)
+            })
+            .filter(|(_, path)| path.is_file())
+            .collect();
+
+        let manifest = ExportManifest {
+            format_version: MANIFEST_VERSION,
+            app_version: CLIENT_VERSION.to_string(),
+            exported_at: now_secs(),
+            user_id: session.map(|s| s.user_id),
+            sections: sections
+                .iter()
+                .map(|s| ManifestSection {
+                    name: s.name.clone(),
+                    source: s.source.to_string(),
+                    file: s.result.as_ref().ok().map(|_| s.path()),
+                    items: s.result.as_ref().map_or(0, item_count),
+                    error: s.result.as_ref().err().cloned(),
+                })
+                .collect(),
+            audio_files: audio.len(),
+        };
+
+        emit_progress(&app, "archive", total, total);
+        let archive_manifest = serde_json::to_value(&manifest)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        tauri::async_runtime::spawn_blocking(move || {
+            write_archive(&destination, &archive_manifest, &sections, &audio)
+        })
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))??;
+
+        let failed = manifest
+            .sections
+            .iter()
+            .filter(|s| s.error.is_some())
+            .count();
+        app_log!(
+            "[DataExport] Exported {} section(s), {} failed, {} audio file(s)",
+            manifest.sections.len() - failed,
+            failed,
+            manifest.audio_files
+        );
+        Ok(manifest)
+    })
+    .await
+}
@@ -0,0 +1,406 @@
+//! Moving local data to another folder, e.g. off a small system drive. A
+//! move copies the data and caches, checks the copy byte for byte, points
+//! the location file at it and restarts; the old copy is removed on the next
+//! launch, once the app has started from the new one.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use super::backup::{check_passphrase, STORES};
+use super::uploads::file_sha256;
+use crate::audit;
+use crate::crypto::{self, FALLBACK_KEY_FILE};
+use crate::db::Database;
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::paths::{self, Location};
+use crate::startup;
+use crate::store_integrity::{QUARANTINE_DIR, SNAPSHOT_DIR};
+use crate::user_data::USERS_DIR;
+
+/// What moves from the data directory besides the stores. Only what the app
+/// writes there: on some platforms the webview keeps its files alongside.
+const DATA_ENTRIES: &[&str] = &[
+    SNAPSHOT_DIR,
+    QUARANTINE_DIR,
+    USERS_DIR,
+    FALLBACK_KEY_FILE,
+    "models",
+    "question_banks",
+];
+/// What moves from the cache directory
+const CACHE_ENTRIES: &[&str] = &[USERS_DIR, "models", "badges"];
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct DataDirectory {
+    /// The chosen folder, or null for the OS default location
+    pub path: Option<String>,
+    #[serde(rename = "dataDir")]
+    pub data_dir: String,
+    #[serde(rename = "cacheDir")]
+    pub cache_dir: String,
+    /// Data lives beside the executable and can't be moved
+    pub portable: bool,
+    /// Size of everything a move would copy
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct DataMoveProgress {
+    /// copying | verifying
+    pub phase: String,
+    #[serde(rename = "bytesDone")]
+    pub bytes_done: u64,
+    #[serde(rename = "bytesTotal")]
+    pub bytes_total: u64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PortableStatus {
+    /// Running from a portable folder whose data isn't open yet
+    pub locked: bool,
+    /// A passphrase has been set; otherwise the first unlock sets it
+    #[serde(rename = "hasPassphrase")]
+    pub has_passphrase: bool,
+}
+
+/// A top-level entry to move, and every file under it as
+/// (source, destination, size)
+struct Transfer {
+    from: PathBuf,
+    to: PathBuf,
+    files: Vec<(PathBuf, PathBuf, u64)>,
+}
+
+fn collect_files(
+    from: &Path,
+    to: &Path,
+    out: &mut Vec<(PathBuf, PathBuf, u64)>,
+) -> Result<(), String> {
+    let meta = std::fs::symlink_metadata(from)
+        .map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+    if meta.is_dir() {
+        let entries = std::fs::read_dir(from)
+            .map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+        // An entry skipped here would still be removed with the old copy
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+            collect_files(&entry.path(), &to.join(entry.file_name()), out)?;
+        }
+    } else if meta.is_file() {
+        out.push((from.to_path_buf(), to.to_path_buf(), meta.len()));
+    }
+    Ok(())
+}
+
+/// Everything that moves from the current location to `target`
+fn transfers(app: &tauri::AppHandle, target: Option<&Path>) -> Result<Vec<Transfer>, String> {
+    let (data_from, cache_from) = (paths::data_dir(app)?, paths::cache_dir(app)?);
+    let (data_to, cache_to) = paths::dirs_at(app, target)?;
+    let entries = STORES
+        .iter()
+        .chain(DATA_ENTRIES)
+        .map(|name| (data_from.join(name), data_to.join(name)))
+        .chain(
+            CACHE_ENTRIES
+                .iter()
+                .map(|name| (cache_from.join(name), cache_to.join(name))),
+        );
+
+    let mut transfers = Vec::new();
+    for (from, to) in entries.filter(|(from, _)| from.exists()) {
+        let mut files = Vec::new();
+        collect_files(&from, &to, &mut files)?;
+        transfers.push(Transfer { from, to, files });
+    }
+    Ok(transfers)
+}
+
+struct Progress<'a> {
+    app: &'a tauri::AppHandle,
+    phase: &'static str,
+    done: u64,
+    total: u64,
+    reported: u64,
+}
+
+impl Progress<'_> {
+    /// Emit at most once per percent
+    fn advance(&mut self, bytes: u64) {
+        self.done += bytes;
+        if self.done < self.total && self.done - self.reported < self.total / 100 {
+            return;
+        }
+        self.reported = self.done;
+        events::emit_app_event(
+            self.app,
+            AppEvent::DataMoveProgress(DataMoveProgress {
+                phase: self.phase.to_string(),
+                bytes_done: self.done,
+                bytes_total: self.total,
+            }),
+        );
+    }
+}
+
+fn copy_and_verify(app: &tauri::AppHandle, transfers: &[Transfer]) -> Result<(), String> {
+    let files: Vec<&(PathBuf, PathBuf, u64)> = transfers.iter().flat_map(|t| &t.files).collect();
+    let total = files.iter().map(|(_, _, size)| size).sum();
+
+    let mut progress = Progress {
+        app,
+        phase: "copying",
+        done: 0,
+        total,
+        reported: 0,
+    };
+    for (from, to, size) in &files {
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::copy(from, to).map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+        progress.advance(*size);
+    }
+
+    let mut progress = Progress {
+        app,
+        phase: "verifying",
+        done: 0,
+        total,
+        reported: 0,
+    };
+    for (from, to, size) in &files {
+        if file_sha256(from)? != file_sha256(to)? {
+            return Err(format!(
+                "Copy of {} doesn't match the original",
+                from.display()
+            ));
+        }
+        progress.advance(*size);
+    }
+    Ok(())
+}
+
+/// Copy everything to `target` and check the copy, returning what was
+/// copied from. A failed move removes whatever it had copied.
+fn move_data(app: &tauri::AppHandle, target: Option<&Path>) -> Result<Vec<PathBuf>, String> {
+    let transfers = transfers(app, target)?;
+    if let Some(taken) = transfers.iter().find(|t| t.to.exists()) {
+        return Err(format!(
+            "Invalid location: {} already exists",
+            taken.to.display()
+        ));
+    }
+
+    if let Err(e) =
+        copy_and_verify(app, &transfers).and_then(|_| unchanged(app, target, &transfers))
+    {
+        for transfer in &transfers {
+            let _ = std::fs::remove_dir_all(&transfer.to);
+            let _ = std::fs::remove_file(&transfer.to);
+        }
+        return Err(e);
+    }
+    Ok(transfers.into_iter().map(|t| t.from).collect())
+}
+
+/// Check nothing was written to the old location while it was copied: the
+/// same files as before, at the same sizes. Writers that hold the write
+/// pause can't, but anything else would be lost with the old copy.
+fn unchanged(
+    app: &tauri::AppHandle,
+    target: Option<&Path>,
+    copied: &[Transfer],
+) -> Result<(), String> {
+    let files = |transfers: &[Transfer]| {
+        let mut files: Vec<(PathBuf, u64)> = transfers
+            .iter()
+            .flat_map(|t| &t.files)
+            .map(|(from, _, size)| (from.clone(), *size))
+            .collect();
+        files.sort();
+        files
+    };
+    if files(&transfers(app, target)?) != files(copied) {
+        return Err("Data changed while it was being copied; try the move again".to_string());
+    }
+    Ok(())
+}
+
+fn is_empty_or_missing(dir: &Path) -> bool {
+    std::fs::read_dir(dir).map_or(true, |mut entries| entries.next().is_none())
+}
+
+/// A folder the data can move into: absolute, writable, outside the current
+/// and default locations, and not already holding data
+fn validate_target(app: &tauri::AppHandle, path: &str) -> Result<PathBuf, AppError> {
+    let invalid = |message: String| AppError::new(ErrorCode::InvalidInput, message);
+    let target = PathBuf::from(path);
+    if !target.is_absolute() {
+        return Err(invalid(format!(
+            "Invalid location: {} is not a full path",
+            path
+        )));
+    }
+
+    let (os_data, os_cache) = paths::dirs_at(app, None)?;
+    let current = [paths::data_dir(app)?, paths::cache_dir(app)?];
+    if [os_data, os_cache]
+        .iter()
+        .chain(&current)
+        .any(|dir| target.starts_with(dir))
+    {
+        return Err(invalid(format!(
+            "Invalid location: {} is inside the app's own folders",
+            path
+        )));
+    }
+    let (data, cache) = paths::dirs_at(app, Some(&target))?;
+    if !is_empty_or_missing(&data) || !is_empty_or_missing(&cache) {
+        return Err(invalid(format!(
+            "Invalid location: {} already holds app data",
+            path
+        )));
+    }
+
+    std::fs::create_dir_all(&target)
+        .map_err(|e| invalid(format!("Invalid location: can't create {}: {}", path, e)))?;
+    let probe = target.join(".write-test");
+    std::fs::write(&probe, b"")
+        .map_err(|e| invalid(format!("Invalid location: {} is not writable: {}", path, e)))?;
+    let _ = std::fs::remove_file(probe);
+    Ok(target)
+}
+
+/// Where local data lives now, and how much a move would copy
+#[tauri::command]
+#[specta::specta]
+pub async fn get_data_directory(app: tauri::AppHandle) -> Result<DataDirectory, String> {
+    let handle = app.clone();
+    let size_bytes = tauri::async_runtime::spawn_blocking(move || {
+        let current = paths::chosen_root(&handle);
+        let transfers = transfers(&handle, current)?;
+        Ok::<_, String>(
+            transfers
+                .iter()
+                .flat_map(|t| &t.files)
+                .map(|(_, _, size)| size)
+                .sum(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Size task failed: {}", e))??;
+
+    Ok(DataDirectory {
+        path: paths::chosen_root(&app).map(|root| root.to_string_lossy().into_owned()),
+        data_dir: paths::data_dir(&app)?.to_string_lossy().into_owned(),
+        cache_dir: paths::cache_dir(&app)?.to_string_lossy().into_owned(),
+        portable: paths::is_portable(),
+        size_bytes,
+    })
+}
+
+/// Whether portable data still waits for its passphrase
+#[tauri::command]
+#[specta::specta]
+pub async fn get_portable_status(app: tauri::AppHandle) -> Result<PortableStatus, String> {
+    Ok(PortableStatus {
+        locked: paths::is_portable() && !startup::is_open(),
+        has_passphrase: paths::is_portable() && crypto::has_portable_key(&paths::data_dir(&app)?),
+    })
+}
+
+/// Open portable data with `passphrase`, then start everything that reads
+/// it. The first unlock sets the passphrase.
+#[tauri::command]
+#[specta::specta]
+pub async fn unlock_portable_data(
+    app: tauri::AppHandle,
+    passphrase: String,
+) -> Result<(), AppError> {
+    if !paths::is_portable() || startup::is_open() {
+        return Ok(());
+    }
+    let data_dir = paths::data_dir(&app)?;
+    if !crypto::has_portable_key(&data_dir) {
+        check_passphrase(&passphrase)?;
+    }
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        crypto::unlock_portable_key(&data_dir, &passphrase)?;
+        startup::open_local_data(&handle)
+    })
+    .await
+    .map_err(|e| format!("Unlock task failed: {}", e))??;
+    app_log!("[DataLocation] Portable data unlocked");
+    Ok(())
+}
+
+/// Move local data and caches to the folder at `path`, or back to the OS
+/// default when it's null. The database and other writers are held while
+/// it's copied; once the copy checks out the app switches over and
+/// restarts, and the old copy is removed on the next launch. Progress
+/// arrives as `data-move-progress`.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_data_directory(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<(), AppError> {
+    let moved = audit::audited(app.clone(), "set_data_directory", async {
+        if paths::is_portable() {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid location: data stays beside the app in portable mode",
+            ));
+        }
+        if path.as_deref().map(Path::new) == paths::chosen_root(&app) {
+            return Ok(false);
+        }
+        let target = match &path {
+            Some(path) => Some(validate_target(&app, path)?),
+            None => None,
+        };
+
+        let handle = app.clone();
+        let destination = target.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            // Stores, recordings and caches wait while the copy is made,
+            // and once it's committed stay shut until the restart
+            let mut moved = paths::pause_writes();
+            let leftover = handle
+                .state::<Database>()
+                .with_checkpoint(|| move_data(&handle, destination.as_deref()))?;
+            paths::write_location(
+                &handle,
+                &Location {
+                    root: destination,
+                    leftover,
+                },
+            )?;
+            *moved = true;
+            Ok::<_, String>(())
+        })
+        .await
+        .map_err(|e| format!("Move task failed: {}", e))??;
+
+        app_log!(
+            "[DataLocation] Data moved to {}, restarting",
+            target.map_or_else(
+                || "the default location".to_string(),
+                |root| root.display().to_string()
+            )
+        );
+        Ok(true)
+    })
+    .await?;
+
+    if moved {
+        app.restart();
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use super::auth::handle_device_revoked;
+use crate::api::{require_token, Api, ApiRequest};
+use crate::audit;
+use crate::errors::AppError;
+use crate::http;
+use crate::logging::app_log;
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct RegisteredDevice {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default, rename = "lastSeenAt")]
+    pub last_seen_at: Option<String>,
+    #[serde(default, rename = "createdAt")]
+    pub created_at: Option<String>,
+    /// True for the device making the request
+    #[serde(default)]
+    pub current: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceListResponse {
+    devices: Vec<RegisteredDevice>,
+}
+
+async fn fetch_devices(api: &Api, token: &str) -> Result<Vec<RegisteredDevice>, String> {
+    let response = api
+        .send(ApiRequest::get("/api/devices").bearer(token))
+        .await?;
+    Ok(response
+        .error_for_status()?
+        .json::<DeviceListResponse>("/api/devices")?
+        .devices)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_registered_devices(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+) -> Result<Vec<RegisteredDevice>, AppError> {
+    let token = require_token(&app)?;
+    Ok(fetch_devices(&api, &token).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_registered_devices_page(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<http::Page<RegisteredDevice>, AppError> {
+    let token = require_token(&app)?;
+    Ok(http::fetch_page(
+        &api,
+        ApiRequest::get("/api/devices").bearer(&token),
+        cursor.as_deref(),
+        limit,
+    )
+    .await?)
+}
+
+/// Revoke one device. Revoking this device also clears local credentials.
+#[tauri::command]
+#[specta::specta]
+pub async fn revoke_device(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+    device_id: String,
+) -> Result<(), AppError> {
+    audit::audited(app.clone(), "revoke_device", async {
+        let token = require_token(&app)?;
+
+        // Check whether we're revoking ourselves before the token stops working
+        let is_current = fetch_devices(&api, &token)
+            .await?
+            .iter()
+            .any(|d| d.id == device_id && d.current);
+
+        api.send(ApiRequest::delete(format!("/api/devices/{}", device_id)).bearer(&token))
+            .await?
+            .error_for_status()?;
+
+        app_log!("[Devices] Device revoked: id={}", device_id);
+        if is_current {
+            handle_device_revoked(&app).await?;
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Revoke every device, including this one
+#[tauri::command]
+#[specta::specta]
+pub async fn logout_everywhere(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+) -> Result<(), AppError> {
+    audit::audited(app.clone(), "logout_everywhere", async {
+        let token = require_token(&app)?;
+
+        api.send(ApiRequest::post("/api/devices/revoke-all").bearer(&token))
+            .await?
+            .error_for_status()?;
+
+        app_log!("[Devices] Logged out everywhere");
+        Ok(handle_device_revoked(&app).await?)
+    })
+    .await
+}
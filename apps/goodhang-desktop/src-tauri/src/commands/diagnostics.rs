@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::sync::{broadcast, oneshot};
+
+use crate::audit;
+use crate::events::{self, AppEvent};
+use crate::logging::{self, app_log};
+
+const DEFAULT_RECENT_LINES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiagnosticsBundle {
+    #[serde(rename = "appVersion")]
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub logs: String,
+}
+
+pub(crate) fn build_bundle(app: &tauri::AppHandle) -> DiagnosticsBundle {
+    DiagnosticsBundle {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        logs: logging::redacted_log_contents(),
+    }
+}
+
+/// Write a redacted diagnostics bundle (app metadata + logs) to `destination`
+#[tauri::command]
+#[specta::specta]
+pub async fn export_diagnostics(app: tauri::AppHandle, destination: String) -> Result<(), String> {
+    audit::audited(app.clone(), "export_diagnostics", async {
+        let bundle = build_bundle(&app);
+        let json = serde_json::to_string_pretty(&bundle)
+            .map_err(|e| format!("Failed to serialize diagnostics: {}", e))?;
+
+        // Redact the whole document once more so nothing unscrubbed reaches disk
+        std::fs::write(PathBuf::from(&destination), logging::redact(&json))
+            .map_err(|e| format!("Failed to write diagnostics: {}", e))?;
+
+        app_log!("[Diagnostics] Bundle exported");
+        Ok(())
+    })
+    .await
+}
+
+/// Which log lines a viewer wants
+#[derive(Debug, Clone, Default, Deserialize, specta::Type)]
+pub struct LogFilter {
+    /// Text a line must contain, ignoring case
+    pub contains: Option<String>,
+    /// Only lines from one component, e.g. `Sync` for `[Sync] ...`
+    pub tag: Option<String>,
+    /// How many recent lines to send before live ones; defaults to 200
+    pub recent: Option<u32>,
+}
+
+impl LogFilter {
+    fn matches(&self, line: &str) -> bool {
+        let tagged = self
+            .tag
+            .as_ref()
+            .is_none_or(|tag| line.contains(&format!("[{}]", tag)));
+        let contains = self
+            .contains
+            .as_ref()
+            .is_none_or(|text| line.to_lowercase().contains(&text.to_lowercase()));
+        tagged && contains
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct LogLinesEvent {
+    pub lines: Vec<String>,
+    /// False for the recent lines sent when tailing starts
+    pub live: bool,
+}
+
+/// The running `tail_logs` stream, if any
+#[derive(Default)]
+pub struct LogTail {
+    stop: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl LogTail {
+    /// Swap in a new stream's stop handle, stopping the previous stream
+    fn replace(&self, next: Option<oneshot::Sender<()>>) -> bool {
+        let previous = match self.stop.lock() {
+            Ok(mut stop) => std::mem::replace(&mut *stop, next),
+            Err(_) => None,
+        };
+        previous.map(|tx| tx.send(())).is_some()
+    }
+}
+
+fn emit_lines(app: &tauri::AppHandle, lines: Vec<String>, live: bool) {
+    events::emit_app_event(app, AppEvent::LogLines(LogLinesEvent { lines, live }));
+}
+
+/// Stream log lines matching `filter` as `log-lines` events: the most recent
+/// first, in one batch, then each new line as it's written. Lines are
+/// redacted as in the log file. Replaces any stream already running; stop
+/// with `stop_tail_logs`.
+#[tauri::command]
+#[specta::specta]
+pub async fn tail_logs(
+    app: tauri::AppHandle,
+    tail: tauri::State<'_, LogTail>,
+    filter: LogFilter,
+) -> Result<(), String> {
+    let (contents, mut live) = logging::subscribe();
+    let (stop_tx, mut stop_rx) = oneshot::channel();
+    tail.replace(Some(stop_tx));
+
+    let mut recent: Vec<String> = contents
+        .lines()
+        .filter(|line| filter.matches(line))
+        .map(str::to_string)
+        .collect();
+    let keep = filter
+        .recent
+        .map_or(DEFAULT_RECENT_LINES, |recent| recent as usize);
+    recent.drain(..recent.len().saturating_sub(keep));
+    emit_lines(&app, recent, false);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let line = tokio::select! {
+                _ = &mut stop_rx => break,
+                line = live.recv() => line,
+            };
+            match line {
+                Ok(line) if filter.matches(&line) => emit_lines(&app, vec![line], true),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    emit_lines(&app, vec![format!("[{} lines skipped]", missed)], true)
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Stop the `tail_logs` stream. Returns whether one was running.
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_tail_logs(tail: tauri::State<'_, LogTail>) -> Result<bool, String> {
+    Ok(tail.replace(None))
+}
+
+/// Paths of the current and rotated log files, oldest first
+#[tauri::command]
+#[specta::specta]
+pub async fn get_log_files() -> Result<Vec<String>, String> {
+    Ok(logging::log_files()
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
@@ -0,0 +1,215 @@
+use discord_rich_presence::{activity, DiscordIpc, DiscordIpcClient};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::Manager;
+
+use super::focus::Focus;
+use super::security::AppLock;
+use super::user_status::cached_products;
+use crate::logging::app_log;
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const DISCORD_KEY: &str = "discord";
+const UPDATE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Discord application id; baked in at build time, overridable at runtime
+fn client_id() -> Option<String> {
+    std::env::var("GOODHANG_DISCORD_CLIENT_ID")
+        .ok()
+        .or_else(|| option_env!("GOODHANG_DISCORD_CLIENT_ID").map(str::to_string))
+        .filter(|id| !id.is_empty())
+}
+
+/// What, if anything, to show on the user's Discord profile
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct DiscordPresenceSettings {
+    pub enabled: bool,
+    #[serde(default = "default_true", rename = "showArchetype")]
+    pub show_archetype: bool,
+    #[serde(default = "default_true", rename = "showFocus")]
+    pub show_focus: bool,
+    #[serde(default, rename = "openToHangs")]
+    pub open_to_hangs: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DiscordPresenceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            show_archetype: true,
+            show_focus: true,
+            open_to_hangs: false,
+        }
+    }
+}
+
+/// The activity we want Discord to show, compared between ticks so the
+/// socket is only written when something changed
+#[derive(Debug, Clone, PartialEq)]
+struct Presence {
+    details: String,
+    state: Option<String>,
+    ends_at: Option<i64>,
+}
+
+fn load_settings(app: &tauri::AppHandle) -> DiscordPresenceSettings {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(user_data::key(DISCORD_KEY)))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn desired_presence(app: &tauri::AppHandle) -> Option<Presence> {
+    let settings = load_settings(app);
+    if !settings.enabled || app.state::<AppLock>().is_locked() {
+        return None;
+    }
+
+    let archetype = settings
+        .show_archetype
+        .then(|| {
+            cached_products()?
+                .goodhang
+                .assessment?
+                .archetype
+                .filter(|a| !a.is_empty())
+        })
+        .flatten();
+    let focus = settings
+        .show_focus
+        .then(|| app.state::<Focus>().current_session())
+        .flatten();
+
+    let (details, ends_at) = match focus {
+        Some(session) => (
+            match session.label {
+                Some(label) => format!("Focusing: {}", label),
+                None => "In a focus session".to_string(),
+            },
+            Some(session.ends_at),
+        ),
+        None if settings.open_to_hangs => ("Open to hangs".to_string(), None),
+        None => ("Good Hang".to_string(), None),
+    };
+    if archetype.is_none() && ends_at.is_none() && !settings.open_to_hangs {
+        return None; // Nothing the user chose to share
+    }
+
+    Some(Presence {
+        details,
+        state: archetype,
+        ends_at,
+    })
+}
+
+/// Keeps the IPC connection across ticks and reconnects when Discord
+/// restarts. Runs on its own thread since the client is blocking.
+struct Publisher {
+    client: Option<DiscordIpcClient>,
+    shown: Option<Presence>,
+}
+
+impl Publisher {
+    fn connect(&mut self) -> Result<&mut DiscordIpcClient, String> {
+        if self.client.is_none() {
+            let id = client_id().ok_or_else(|| "No Discord client id configured".to_string())?;
+            let mut client = DiscordIpcClient::new(&id).map_err(|e| e.to_string())?;
+            client.connect().map_err(|e| e.to_string())?;
+            app_log!("[Discord] Connected to local RPC");
+            self.client = Some(client);
+        }
+        self.client
+            .as_mut()
+            .ok_or_else(|| "Discord client unavailable".to_string())
+    }
+
+    fn disconnect(&mut self) {
+        if let Some(mut client) = self.client.take() {
+            let _ = client.clear_activity();
+            let _ = client.close();
+        }
+        self.shown = None;
+    }
+
+    fn publish(&mut self, presence: &Presence) -> Result<(), String> {
+        let client = self.connect()?;
+        let mut activity = activity::Activity::new()
+            .details(&presence.details)
+            .assets(activity::Assets::new().large_image("goodhang"));
+        if let Some(state) = &presence.state {
+            activity = activity.state(state);
+        }
+        if let Some(ends_at) = presence.ends_at {
+            activity = activity.timestamps(activity::Timestamps::new().end(ends_at));
+        }
+        client.set_activity(activity).map_err(|e| e.to_string())?;
+        self.shown = Some(presence.clone());
+        Ok(())
+    }
+
+    fn tick(&mut self, app: &tauri::AppHandle) {
+        match desired_presence(app) {
+            Some(presence) if self.shown.as_ref() != Some(&presence) => {
+                if let Err(e) = self.publish(&presence) {
+                    // Usually Discord isn't running; try again next tick
+                    if self.client.is_some() {
+                        app_log!("[Discord] Failed to update presence: {}", e);
+                    }
+                    self.client = None;
+                    self.shown = None;
+                }
+            }
+            Some(_) => {}
+            None if self.client.is_some() => self.disconnect(),
+            None => {}
+        }
+    }
+}
+
+pub fn spawn_discord_presence(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut publisher = Publisher {
+            client: None,
+            shown: None,
+        };
+        loop {
+            publisher.tick(&app);
+            std::thread::sleep(UPDATE_INTERVAL);
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_discord_presence_settings(
+    app: tauri::AppHandle,
+) -> Result<DiscordPresenceSettings, String> {
+    Ok(load_settings(&app))
+}
+
+/// Takes effect on the next publisher tick
+#[tauri::command]
+#[specta::specta]
+pub async fn update_discord_presence_settings(
+    app: tauri::AppHandle,
+    settings: DiscordPresenceSettings,
+) -> Result<(), String> {
+    let store = stores::open(&app, stores::SETTINGS_STORE)?;
+    store.set(
+        user_data::key(DISCORD_KEY),
+        serde_json::to_value(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?,
+    );
+    stores::save(&app, stores::SETTINGS_STORE)?;
+    app_log!(
+        "[Discord] Presence settings updated: enabled={}",
+        settings.enabled
+    );
+    Ok(())
+}
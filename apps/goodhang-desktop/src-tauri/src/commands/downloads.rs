@@ -0,0 +1,381 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+
+use super::auth::load_session;
+use super::uploads::file_sha256;
+use crate::api;
+use crate::db::now_secs;
+use crate::events::{self, AppEvent};
+use crate::http;
+use crate::logging::{app_log, redact};
+use crate::notifications;
+use crate::paths;
+
+const MAX_CONCURRENT_DOWNLOADS: usize = 2;
+// Emit progress at most once per this many bytes
+const PROGRESS_STEP_BYTES: u64 = 512 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    Queued,
+    Downloading,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// What a download is for, which decides where it lands on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadKind {
+    WhisperModel,
+    BadgeIcons,
+    QuestionBank,
+    Report,
+}
+
+impl DownloadKind {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "whisper_model" => Ok(DownloadKind::WhisperModel),
+            "badge_icons" => Ok(DownloadKind::BadgeIcons),
+            "question_bank" => Ok(DownloadKind::QuestionBank),
+            "report" => Ok(DownloadKind::Report),
+            _ => Err(format!("Unknown download kind: {}", value)),
+        }
+    }
+
+    fn directory(&self, app: &tauri::AppHandle) -> Result<PathBuf, String> {
+        match self {
+            DownloadKind::WhisperModel => paths::data_dir(app).map(|d| d.join("models/whisper")),
+            DownloadKind::BadgeIcons => paths::cache_dir(app).map(|d| d.join("badges")),
+            DownloadKind::QuestionBank => paths::data_dir(app).map(|d| d.join("question_banks")),
+            DownloadKind::Report => app
+                .path()
+                .download_dir()
+                .map_err(|e| format!("Failed to resolve download directory: {}", e)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Download {
+    pub id: String,
+    pub kind: DownloadKind,
+    pub url: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    /// Final location once completed
+    pub path: String,
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: Option<u64>,
+    #[serde(rename = "bytesDownloaded")]
+    pub bytes_downloaded: u64,
+    pub status: DownloadStatus,
+    pub error: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(skip)]
+    sha256: Option<String>,
+}
+
+struct DownloadEntry {
+    download: Download,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Download queue shared across commands. At most
+/// `MAX_CONCURRENT_DOWNLOADS` transfer at once; the rest wait their turn.
+pub struct Downloads {
+    entries: Mutex<HashMap<String, DownloadEntry>>,
+    slots: Arc<Semaphore>,
+}
+
+impl Default for Downloads {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS)),
+        }
+    }
+}
+
+impl Downloads {
+    fn update(&self, id: &str, f: impl FnOnce(&mut Download)) -> Option<Download> {
+        let mut entries = self.entries.lock().ok()?;
+        let entry = entries.get_mut(id)?;
+        f(&mut entry.download);
+        Some(entry.download.clone())
+    }
+}
+
+fn emit_progress(app: &tauri::AppHandle, download: &Download) {
+    events::emit_app_event(app, AppEvent::DownloadProgress(download.clone()));
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Stream `download.url` into a `.part` file, resuming from whatever a
+/// previous attempt left behind. Returns `Ok(false)` if cancelled.
+async fn transfer(
+    app: &tauri::AppHandle,
+    download: &Download,
+    cancelled: &AtomicBool,
+) -> Result<bool, String> {
+    let downloads = app.state::<Downloads>();
+    let final_path = PathBuf::from(&download.path);
+    let part = part_path(&final_path);
+    if let Some(dir) = final_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let existing = std::fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+    // Byte ranges must address the file itself, not a compressed encoding of it
+    let mut request = http::client()
+        .get(&download.url)
+        .header("Accept-Encoding", "identity");
+    if existing > 0 {
+        request = request.header("Range", format!("bytes={}-", existing));
+    }
+    // Only our own API gets credentials
+    if api::is_api_url(&download.url) {
+        if let Some(session) = load_session(app)? {
+            request = request.header("Authorization", format!("Bearer {}", session.token));
+        }
+    }
+
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Server error {}: {}", status, redact(&body)));
+    }
+
+    // 206 continues the partial file; a plain 200 means the server ignored the range
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { existing } else { 0 };
+    let total = response.content_length().map(|len| len + downloaded);
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part)
+        .await
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+
+    let mut last_emit = downloaded;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?
+    {
+        if cancelled.load(Ordering::SeqCst) {
+            return Ok(false);
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        downloaded += chunk.len() as u64;
+
+        if downloaded - last_emit >= PROGRESS_STEP_BYTES {
+            last_emit = downloaded;
+            if let Some(download) = downloads.update(&download.id, |d| {
+                d.bytes_downloaded = downloaded;
+                d.total_bytes = total;
+            }) {
+                emit_progress(app, &download);
+            }
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    drop(file);
+
+    if let Some(expected) = &download.sha256 {
+        let hash_path = part.clone();
+        let actual = tauri::async_runtime::spawn_blocking(move || file_sha256(&hash_path))
+            .await
+            .map_err(|e| format!("Hashing task failed: {}", e))??;
+        if !actual.eq_ignore_ascii_case(expected) {
+            // A corrupt partial file would poison every later resume
+            let _ = std::fs::remove_file(&part);
+            return Err("Checksum mismatch; the download was discarded".to_string());
+        }
+    }
+
+    std::fs::rename(&part, &final_path)
+        .map_err(|e| format!("Failed to move download into place: {}", e))?;
+    downloads.update(&download.id, |d| {
+        d.bytes_downloaded = downloaded;
+        d.total_bytes = Some(downloaded);
+    });
+    Ok(true)
+}
+
+/// Queue a download. Re-queuing the same URL and file name after a failure
+/// resumes from the partial file.
+pub(crate) fn enqueue(
+    app: &tauri::AppHandle,
+    url: String,
+    kind: DownloadKind,
+    file_name: String,
+    sha256: Option<String>,
+) -> Result<Download, String> {
+    if file_name.contains(['/', '\\']) || file_name.starts_with('.') {
+        return Err("Invalid file name".to_string());
+    }
+    let path = kind.directory(app)?.join(&file_name);
+
+    let download = Download {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind,
+        url,
+        file_name,
+        path: path.display().to_string(),
+        total_bytes: None,
+        bytes_downloaded: 0,
+        status: DownloadStatus::Queued,
+        error: None,
+        created_at: now_secs(),
+        sha256,
+    };
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let downloads = app.state::<Downloads>();
+    downloads
+        .entries
+        .lock()
+        .map_err(|_| "Download lock poisoned".to_string())?
+        .insert(
+            download.id.clone(),
+            DownloadEntry {
+                download: download.clone(),
+                cancelled: cancelled.clone(),
+            },
+        );
+    emit_progress(app, &download);
+
+    let app = app.clone();
+    let slots = downloads.slots.clone();
+    let id = download.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let Ok(_permit) = slots.acquire_owned().await else {
+            return;
+        };
+        let downloads = app.state::<Downloads>();
+        if cancelled.load(Ordering::SeqCst) {
+            return;
+        }
+        let Some(download) = downloads.update(&id, |d| d.status = DownloadStatus::Downloading)
+        else {
+            return;
+        };
+        emit_progress(&app, &download);
+
+        let outcome = transfer(&app, &download, &cancelled).await;
+        let updated = downloads.update(&id, |d| match &outcome {
+            Ok(true) => d.status = DownloadStatus::Completed,
+            Ok(false) => d.status = DownloadStatus::Cancelled,
+            Err(e) => {
+                d.status = DownloadStatus::Failed;
+                d.error = Some(e.clone());
+            }
+        });
+        match &outcome {
+            Ok(true) => {
+                app_log!("[Downloads] Download complete: {}", download.file_name);
+                if download.kind == DownloadKind::Report {
+                    notifications::notify(&app, "Report downloaded", &download.file_name);
+                }
+            }
+            Ok(false) => {
+                let _ = std::fs::remove_file(part_path(Path::new(&download.path)));
+                app_log!("[Downloads] Download cancelled: {}", download.file_name);
+            }
+            Err(e) => app_log!(
+                "[Downloads] Download failed: {} error={}",
+                download.file_name,
+                e
+            ),
+        }
+        if let Some(updated) = updated {
+            emit_progress(&app, &updated);
+        }
+    });
+
+    Ok(download)
+}
+
+/// Queue a file download; progress arrives as `download-progress` events
+#[tauri::command]
+#[specta::specta]
+pub async fn start_download(
+    app: tauri::AppHandle,
+    url: String,
+    kind: String,
+    file_name: String,
+    sha256: Option<String>,
+) -> Result<Download, String> {
+    enqueue(&app, url, DownloadKind::parse(&kind)?, file_name, sha256)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_downloads(
+    downloads: tauri::State<'_, Downloads>,
+) -> Result<Vec<Download>, String> {
+    let entries = downloads
+        .entries
+        .lock()
+        .map_err(|_| "Download lock poisoned".to_string())?;
+    let mut list: Vec<Download> = entries.values().map(|e| e.download.clone()).collect();
+    list.sort_by_key(|d| std::cmp::Reverse(d.created_at));
+    Ok(list)
+}
+
+/// Cancel a queued or running download and discard its partial file
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_download(
+    app: tauri::AppHandle,
+    downloads: tauri::State<'_, Downloads>,
+    id: String,
+) -> Result<(), String> {
+    let was_queued = {
+        let entries = downloads
+            .entries
+            .lock()
+            .map_err(|_| "Download lock poisoned".to_string())?;
+        let entry = entries
+            .get(&id)
+            .ok_or_else(|| "Download not found".to_string())?;
+        entry.cancelled.store(true, Ordering::SeqCst);
+        entry.download.status == DownloadStatus::Queued
+    };
+
+    // Running downloads notice the flag on their next chunk; queued ones
+    // never start, so settle them here
+    if was_queued {
+        if let Some(download) = downloads.update(&id, |d| d.status = DownloadStatus::Cancelled) {
+            emit_progress(&app, &download);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,475 @@
+use serde::{Deserialize, Serialize};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::consent::{self, Feature};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const EMAIL_SIGNALS_KEY: &str = "email_signals";
+const KEYCHAIN_SERVICE: &str = "com.goodhang.desktop";
+const KEYCHAIN_ACCOUNT: &str = "imap-password";
+const SCAN_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const DEFAULT_LOOKBACK_DAYS: u32 = 365;
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+// Later scans re-read a day of overlap so nothing near the boundary is missed
+const RESCAN_OVERLAP_SECS: i64 = SECS_PER_DAY;
+// A server that stops answering fails the scan instead of hanging it
+const IMAP_TIMEOUT: Duration = Duration::from_secs(60);
+// UIDs per FETCH, keeping command lines within what servers accept
+const FETCH_BATCH: usize = 500;
+
+// Keeps the periodic loop and on-demand scans from running at once
+static SCAN_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Opt-in IMAP connection. Only message headers are read and nothing leaves
+/// the device; the password lives in the OS keychain, not here.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct EmailSignalSettings {
+    pub enabled: bool,
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub username: String,
+    /// Received mail; the sender counts as a contact
+    #[serde(default = "default_inbox", rename = "inboxMailbox")]
+    pub inbox_mailbox: String,
+    /// Sent mail; every recipient counts as a contact
+    #[serde(default, rename = "sentMailbox")]
+    pub sent_mailbox: Option<String>,
+    #[serde(default = "default_lookback", rename = "lookbackDays")]
+    pub lookback_days: u32,
+    #[serde(default, rename = "lastScannedAt")]
+    pub last_scanned_at: Option<i64>,
+}
+
+fn default_port() -> u16 {
+    993
+}
+
+fn default_inbox() -> String {
+    "INBOX".to_string()
+}
+
+fn default_lookback() -> u32 {
+    DEFAULT_LOOKBACK_DAYS
+}
+
+/// Most recent mail exchanged with one address
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct LastContact {
+    pub address: String,
+    #[serde(rename = "lastContactAt")]
+    pub last_contact_at: i64,
+    /// inbound | outbound
+    #[serde(rename = "lastDirection")]
+    pub last_direction: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct EmailScanSummary {
+    #[serde(rename = "messagesScanned")]
+    pub messages_scanned: usize,
+    #[serde(rename = "contactsUpdated")]
+    pub contacts_updated: usize,
+}
+
+fn load_settings(app: &tauri::AppHandle) -> Option<EmailSignalSettings> {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(user_data::key(EMAIL_SIGNALS_KEY)))
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+fn save_settings(
+    app: &tauri::AppHandle,
+    settings: Option<&EmailSignalSettings>,
+) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    match settings {
+        Some(settings) => store.set(
+            user_data::key(EMAIL_SIGNALS_KEY),
+            serde_json::to_value(settings)
+                .map_err(|e| format!("Failed to serialize settings: {}", e))?,
+        ),
+        None => {
+            let _ = store.delete(user_data::key(EMAIL_SIGNALS_KEY));
+        }
+    }
+    stores::save(app, stores::SETTINGS_STORE)
+}
+
+fn keychain_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))
+}
+
+/// `15-Oct-2026`, the date format IMAP SEARCH expects
+fn imap_date(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .format("%d-%b-%Y")
+        .to_string()
+}
+
+type ImapSession = imap::Session<native_tls::TlsStream<TcpStream>>;
+
+fn open_session(settings: &EmailSignalSettings, password: &str) -> Result<ImapSession, String> {
+    let tls = native_tls::TlsConnector::builder()
+        .build()
+        .map_err(|e| format!("TLS setup failed: {}", e))?;
+    let address = (settings.host.as_str(), settings.port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Network error: {}", e))?
+        .next()
+        .ok_or_else(|| format!("Network error: could not resolve {}", settings.host))?;
+    let stream = TcpStream::connect_timeout(&address, IMAP_TIMEOUT)
+        .map_err(|e| format!("Network error: {}", e))?;
+    stream
+        .set_read_timeout(Some(IMAP_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(IMAP_TIMEOUT)))
+        .map_err(|e| format!("Network error: {}", e))?;
+    let stream = tls
+        .connect(settings.host.as_str(), stream)
+        .map_err(|e| format!("Network error: {}", e))?;
+    let mut client = imap::Client::new(stream);
+    client
+        .read_greeting()
+        .map_err(|e| format!("Network error: {}", e))?;
+    client
+        .login(&settings.username, password)
+        .map_err(|(e, _)| format!("Mail login failed: {}", e))
+}
+
+/// Latest contact per address in one mailbox, read from envelopes only
+fn scan_mailbox(
+    session: &mut ImapSession,
+    mailbox: &str,
+    since: i64,
+    outbound: bool,
+    own_address: &str,
+    contacts: &mut std::collections::HashMap<String, (i64, &'static str)>,
+) -> Result<usize, String> {
+    // EXAMINE opens read-only, so scanning never marks anything as seen
+    session
+        .examine(mailbox)
+        .map_err(|e| format!("Failed to open {}: {}", mailbox, e))?;
+    let uids = session
+        .uid_search(format!("SINCE {}", imap_date(since)))
+        .map_err(|e| format!("Mail search failed: {}", e))?;
+    let mut uids = uids.into_iter().collect::<Vec<_>>();
+    uids.sort_unstable();
+
+    let direction = if outbound { "outbound" } else { "inbound" };
+    let mut scanned = 0;
+    for batch in uids.chunks(FETCH_BATCH) {
+        let set = batch
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let messages = session
+            .uid_fetch(set, "(ENVELOPE INTERNALDATE)")
+            .map_err(|e| format!("Mail fetch failed: {}", e))?;
+        scanned += messages.len();
+        record_envelopes(&messages, direction, outbound, own_address, contacts);
+    }
+    Ok(scanned)
+}
+
+/// Fold one FETCH response's senders or recipients into `contacts`
+fn record_envelopes(
+    messages: &[imap::types::Fetch],
+    direction: &'static str,
+    outbound: bool,
+    own_address: &str,
+    contacts: &mut std::collections::HashMap<String, (i64, &'static str)>,
+) {
+    for message in messages.iter() {
+        let (Some(envelope), Some(date)) = (message.envelope(), message.internal_date()) else {
+            continue;
+        };
+        let at = date.timestamp();
+        let addresses = if outbound {
+            envelope
+                .to
+                .iter()
+                .chain(envelope.cc.iter())
+                .flatten()
+                .collect::<Vec<_>>()
+        } else {
+            envelope.from.iter().flatten().collect()
+        };
+        for address in addresses {
+            let (Some(mailbox), Some(host)) = (address.mailbox.as_deref(), address.host.as_deref())
+            else {
+                continue;
+            };
+            let email = format!(
+                "{}@{}",
+                String::from_utf8_lossy(mailbox),
+                String::from_utf8_lossy(host)
+            )
+            .to_lowercase();
+            if email == own_address {
+                continue;
+            }
+            let entry = contacts.entry(email).or_insert((at, direction));
+            if at > entry.0 {
+                *entry = (at, direction);
+            }
+        }
+    }
+}
+
+/// Blocking IMAP pass over the configured mailboxes
+fn scan(
+    settings: &EmailSignalSettings,
+    password: &str,
+    since: i64,
+) -> Result<(usize, Vec<LastContact>), String> {
+    let mut session = open_session(settings, password)?;
+    let own_address = settings.username.to_lowercase();
+    let mut contacts = std::collections::HashMap::new();
+
+    let mut scanned = scan_mailbox(
+        &mut session,
+        &settings.inbox_mailbox,
+        since,
+        false,
+        &own_address,
+        &mut contacts,
+    )?;
+    if let Some(sent) = &settings.sent_mailbox {
+        scanned += scan_mailbox(&mut session, sent, since, true, &own_address, &mut contacts)?;
+    }
+    let _ = session.logout();
+
+    let contacts = contacts
+        .into_iter()
+        .map(|(address, (at, direction))| LastContact {
+            address,
+            last_contact_at: at,
+            last_direction: direction.to_string(),
+        })
+        .collect();
+    Ok((scanned, contacts))
+}
+
+fn record_contacts(db: &Database, contacts: &[LastContact]) -> Result<(), String> {
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        for contact in contacts {
+            // Only ever moves forward, so overlapping scans are harmless
+            tx.execute(
+                "INSERT INTO email_contacts (address, last_contact_at, last_direction)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(address) DO UPDATE SET
+                    last_contact_at = excluded.last_contact_at,
+                    last_direction = excluded.last_direction
+                 WHERE excluded.last_contact_at > email_contacts.last_contact_at",
+                rusqlite::params![
+                    contact.address,
+                    contact.last_contact_at,
+                    contact.last_direction
+                ],
+            )?;
+        }
+        tx.commit()
+    })
+}
+
+async fn run_scan(app: &tauri::AppHandle) -> Result<Option<EmailScanSummary>, String> {
+    let Some(mut settings) = load_settings(app)
+        .filter(|s| s.enabled && consent::is_granted(app, Feature::ContactImport))
+    else {
+        return Ok(None);
+    };
+    if SCAN_RUNNING.swap(true, Ordering::SeqCst) {
+        return Ok(None);
+    }
+
+    let started_at = now_secs();
+    let since = settings
+        .last_scanned_at
+        .map(|t| t - RESCAN_OVERLAP_SECS)
+        .unwrap_or(started_at - settings.lookback_days as i64 * SECS_PER_DAY);
+    let job = settings.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let password = keychain_entry()?
+            .get_password()
+            .map_err(|e| format!("Failed to read mail password: {}", e))?;
+        scan(&job, &password, since)
+    })
+    .await
+    .map_err(|e| format!("Mail scan task failed: {}", e))
+    .and_then(|r| r);
+    SCAN_RUNNING.store(false, Ordering::SeqCst);
+
+    let (messages_scanned, contacts) = result?;
+    record_contacts(&app.state::<Database>(), &contacts)?;
+    settings.last_scanned_at = Some(started_at);
+    save_settings(app, Some(&settings))?;
+
+    let summary = EmailScanSummary {
+        messages_scanned,
+        contacts_updated: contacts.len(),
+    };
+    app_log!(
+        "[EmailSignals] Scanned {} messages, {} contacts",
+        summary.messages_scanned,
+        summary.contacts_updated
+    );
+    events::emit_app_event(app, AppEvent::EmailSignalsUpdated(summary.clone()));
+    Ok(Some(summary))
+}
+
+/// Last mail contact for each address we have a signal for
+pub(crate) fn last_contacts(
+    db: &Database,
+    addresses: &[String],
+) -> Result<Vec<LastContact>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT address, last_contact_at, last_direction FROM email_contacts
+             WHERE address = ?1",
+        )?;
+        let mut found = Vec::new();
+        for address in addresses {
+            let mut rows = stmt.query_map([address.trim().to_lowercase()], |row| {
+                Ok(LastContact {
+                    address: row.get(0)?,
+                    last_contact_at: row.get(1)?,
+                    last_direction: row.get(2)?,
+                })
+            })?;
+            if let Some(contact) = rows.next() {
+                found.push(contact?);
+            }
+        }
+        Ok(found)
+    })
+}
+
+/// Drop the connection, stored password, and every derived signal, e.g. on
+/// secure logout
+pub(crate) fn forget_email_signals(app: &tauri::AppHandle) -> Result<(), String> {
+    if let Ok(entry) = keychain_entry() {
+        let _ = entry.delete_credential();
+    }
+    app.state::<Database>()
+        .with_conn(|conn| conn.execute("DELETE FROM email_contacts", []))?;
+    save_settings(app, None)
+}
+
+/// Periodically rescan while the connector is enabled
+pub fn spawn_email_signal_scan(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if super::sync_policy::conserving() {
+                app_log!("[EmailSignals] Scan deferred by sync policy");
+            } else if let Err(e) = run_scan(&app).await {
+                app_log!("[EmailSignals] Scan failed: {}", e);
+            }
+            tokio::time::sleep(SCAN_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_email_signal_settings(
+    app: tauri::AppHandle,
+) -> Result<Option<EmailSignalSettings>, String> {
+    Ok(load_settings(&app))
+}
+
+/// Opt in: verify the login, keep the password in the keychain, and start
+/// the first scan
+#[tauri::command]
+#[specta::specta]
+pub async fn connect_email_signals(
+    app: tauri::AppHandle,
+    settings: EmailSignalSettings,
+    password: String,
+) -> Result<EmailSignalSettings, AppError> {
+    consent::require(&app, Feature::ContactImport)?;
+    if settings.host.trim().is_empty() || settings.username.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid mail settings: host and username are required",
+        ));
+    }
+    let settings = EmailSignalSettings {
+        enabled: true,
+        last_scanned_at: None,
+        ..settings
+    };
+
+    let probe = settings.clone();
+    let secret = password.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        open_session(&probe, &secret).map(|mut session| {
+            let _ = session.logout();
+        })
+    })
+    .await
+    .map_err(|e| format!("Mail login task failed: {}", e))??;
+
+    keychain_entry()?
+        .set_password(&password)
+        .map_err(|e| format!("Failed to store mail password: {}", e))?;
+    save_settings(&app, Some(&settings))?;
+    app_log!("[EmailSignals] Connected to {}", settings.host);
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run_scan(&handle).await {
+            app_log!("[EmailSignals] Initial scan failed: {}", e);
+        }
+    });
+    Ok(settings)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn disconnect_email_signals(app: tauri::AppHandle) -> Result<(), String> {
+    forget_email_signals(&app)?;
+    app_log!("[EmailSignals] Disconnected");
+    Ok(())
+}
+
+/// Scan now rather than waiting for the next interval. `None` when the
+/// connector is off or a scan is already running.
+#[tauri::command]
+#[specta::specta]
+pub async fn scan_email_signals(app: tauri::AppHandle) -> Result<Option<EmailScanSummary>, String> {
+    run_scan(&app).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_last_contacts(
+    db: tauri::State<'_, Database>,
+    addresses: Vec<String>,
+) -> Result<Vec<LastContact>, String> {
+    last_contacts(&db, &addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imap_date_uses_search_format() {
+        // 2026-10-15T12:00:00Z
+        assert_eq!(imap_date(1_792_065_600), "15-Oct-2026");
+        assert_eq!(imap_date(0), "01-Jan-1970");
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use tauri_plugin_shell::ShellExt;
+
+use crate::api::{require_token, Api, ApiRequest};
+use crate::errors::AppError;
+use crate::logging::app_log;
+
+#[derive(Debug, Deserialize)]
+struct ExpertContentResponse {
+    id: String,
+    title: String,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default, rename = "publishedAt")]
+    published_at: Option<String>,
+    entitled: bool,
+    #[serde(default)]
+    body: Option<String>,
+    /// Display price, e.g. "$19"
+    #[serde(default)]
+    price: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckoutResponse {
+    #[serde(rename = "checkoutUrl")]
+    checkout_url: String,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ExpertContent {
+    pub id: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub summary: Option<String>,
+    #[serde(rename = "publishedAt")]
+    pub published_at: Option<String>,
+    pub body: String,
+}
+
+/// What the UI gets when the user isn't entitled: enough to render a teaser
+/// and a buy button, never the body. The button calls
+/// `purchase_expert_content`; viewing alone doesn't start a checkout.
+#[derive(Debug, Serialize, specta::Type)]
+pub struct PaywallRequired {
+    pub id: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub summary: Option<String>,
+    pub price: Option<String>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+#[serde(tag = "access", rename_all = "snake_case")]
+pub enum ExpertContentAccess {
+    Granted(ExpertContent),
+    PaywallRequired(PaywallRequired),
+}
+
+/// A published expert assessment if the user has access, otherwise a
+/// paywall teaser
+#[tauri::command]
+#[specta::specta]
+pub async fn get_expert_content(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+    assessment_id: String,
+) -> Result<ExpertContentAccess, AppError> {
+    let token = require_token(&app)?;
+
+    let content: ExpertContentResponse = api
+        .send(ApiRequest::get(format!("/api/expert-content/{}", assessment_id)).bearer(&token))
+        .await?
+        .error_for_status()?
+        .json("/api/expert-content/{id}")?;
+
+    // Trust the entitlement flag, not the mere presence of a body
+    if let (true, Some(body)) = (content.entitled, content.body) {
+        return Ok(ExpertContentAccess::Granted(ExpertContent {
+            id: content.id,
+            title: content.title,
+            author: content.author,
+            summary: content.summary,
+            published_at: content.published_at,
+            body,
+        }));
+    }
+
+    app_log!("[ExpertContent] Paywall for {}", assessment_id);
+    Ok(ExpertContentAccess::PaywallRequired(PaywallRequired {
+        id: content.id,
+        title: content.title,
+        author: content.author,
+        summary: content.summary,
+        price: content.price,
+    }))
+}
+
+/// Start a Stripe checkout for an expert assessment and open it in the
+/// browser. Only called from the paywall's buy button.
+#[tauri::command]
+#[specta::specta]
+pub async fn purchase_expert_content(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+    assessment_id: String,
+) -> Result<(), AppError> {
+    let token = require_token(&app)?;
+
+    let checkout: CheckoutResponse = api
+        .send(
+            ApiRequest::post(format!("/api/expert-content/{}/checkout", assessment_id))
+                .bearer(&token),
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/expert-content/{id}/checkout")?;
+
+    #[allow(deprecated)]
+    app.shell()
+        .open(checkout.checkout_url.as_str(), None)
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    app_log!("[ExpertContent] Checkout opened for {}", assessment_id);
+    Ok(())
+}
@@ -0,0 +1,348 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::Manager;
+
+use super::app_info::{app_info, AppInfo};
+use super::auth::load_session;
+use super::diagnostics::build_bundle;
+use crate::api::{Api, ApiRequest};
+use crate::db::now_secs;
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::{self, app_log};
+use crate::outbox;
+use crate::user_data;
+
+/// Outbox task posting one piece of feedback
+pub const FEEDBACK_TASK: &str = "feedback";
+const MAX_TEXT_CHARS: usize = 5000;
+// The end of the log is what explains a bug; the whole file can run to MBs
+const MAX_REPORT_LOG_BYTES: usize = 256 * 1024;
+const SCREENSHOT_DIR: &str = "feedback_screenshots";
+// Solid, so nothing underneath can be recovered the way blurred text can
+const REDACTION_FILL: image::Rgba<u8> = image::Rgba([0, 0, 0, 255]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackCategory {
+    Bug,
+    Idea,
+    Question,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Feedback {
+    /// Client-generated so retries from the outbox are idempotent
+    pub id: String,
+    pub category: FeedbackCategory,
+    pub text: String,
+    /// Version, build and platform details, as `get_app_info` reports them
+    pub app: AppInfo,
+    /// Whether a redacted diagnostics bundle goes with it
+    #[serde(rename = "hasDiagnostics")]
+    pub has_diagnostics: bool,
+    /// From `capture_window_screenshot`, attached when it's sent
+    #[serde(rename = "screenshotId")]
+    pub screenshot_id: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+/// A rectangle in the window's CSS pixels, as `getBoundingClientRect`
+/// reports it
+#[derive(Debug, Clone, Copy, Deserialize, specta::Type)]
+pub struct ScreenRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Screenshot {
+    /// Pass to `submit_feedback` to attach it
+    pub id: String,
+    pub width: u32,
+    pub height: u32,
+    /// How many regions were blacked out
+    pub redacted: usize,
+    /// The PNG as a `data:` URL, for a preview
+    #[serde(rename = "dataUrl")]
+    pub data_url: String,
+}
+
+/// The last `max` bytes of `text`, starting at a line boundary
+fn tail(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut start = text.len() - max;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let rest = &text[start..];
+    rest.find('\n').map_or(rest, |newline| &rest[newline + 1..])
+}
+
+fn screenshot_path(app: &tauri::AppHandle, id: &str) -> Result<PathBuf, String> {
+    // Ids come back from the webview, so only accept ones we could have made
+    uuid::Uuid::parse_str(id).map_err(|_| format!("Invalid screenshot id: {}", id))?;
+    Ok(user_data::cache_dir(app)?
+        .join(SCREENSHOT_DIR)
+        .join(format!("{}.png", id)))
+}
+
+/// Grab the OS window behind `window`: ours by process and title. Fails
+/// rather than guess, since another of our windows could be showing
+/// something else.
+fn capture(window: &tauri::WebviewWindow) -> Result<image::RgbaImage, String> {
+    let title = window
+        .title()
+        .map_err(|e| format!("Failed to read window title: {}", e))?;
+    let pid = std::process::id();
+    let windows = xcap::Window::all().map_err(|e| format!("Failed to list windows: {}", e))?;
+    let ours: Vec<&xcap::Window> = windows.iter().filter(|w| w.pid() == pid).collect();
+    let target = ours
+        .iter()
+        .find(|w| w.title() == title)
+        .ok_or_else(|| "Failed to find the app window".to_string())?;
+    target
+        .capture_image()
+        .map_err(|e| format!("Failed to capture window: {}", e))
+}
+
+/// Black out each region in place, mapping it from the webview's CSS pixels to
+/// the captured image's. Returns how many were inside the image.
+fn redact_regions(
+    window: &tauri::WebviewWindow,
+    image: &mut image::RgbaImage,
+    regions: &[ScreenRegion],
+) -> Result<usize, String> {
+    let scale = window
+        .scale_factor()
+        .map_err(|e| format!("Failed to read window scale: {}", e))?;
+    let inner = window
+        .inner_size()
+        .map_err(|e| format!("Failed to read window size: {}", e))?;
+    // The capture can include the title bar and borders around the webview
+    let (offset_x, offset_y) = if image.dimensions() == (inner.width, inner.height) {
+        (0, 0)
+    } else {
+        let inner_pos = window
+            .inner_position()
+            .map_err(|e| format!("Failed to read window position: {}", e))?;
+        let outer_pos = window
+            .outer_position()
+            .map_err(|e| format!("Failed to read window position: {}", e))?;
+        (
+            (inner_pos.x - outer_pos.x).max(0) as u32,
+            (inner_pos.y - outer_pos.y).max(0) as u32,
+        )
+    };
+
+    let mut redacted = 0;
+    for region in regions {
+        let x = (region.x * scale).max(0.0) as u32 + offset_x;
+        let y = (region.y * scale).max(0.0) as u32 + offset_y;
+        if x >= image.width() || y >= image.height() {
+            continue;
+        }
+        let width = ((region.width * scale).ceil().max(0.0) as u32).min(image.width() - x);
+        let height = ((region.height * scale).ceil().max(0.0) as u32).min(image.height() - y);
+        if width == 0 || height == 0 {
+            continue;
+        }
+        for py in y..y + height {
+            for px in x..x + width {
+                image.put_pixel(px, py, REDACTION_FILL);
+            }
+        }
+        redacted += 1;
+    }
+    Ok(redacted)
+}
+
+/// Screenshot the calling window for a bug report. With `redact_sensitive`,
+/// `regions` (whatever the frontend marks sensitive, in CSS pixels) are
+/// blacked out before the image is kept. Attach it by passing its id to
+/// `submit_feedback`.
+#[tauri::command]
+#[specta::specta]
+pub async fn capture_window_screenshot(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    redact_sensitive: bool,
+    regions: Vec<ScreenRegion>,
+) -> Result<Screenshot, AppError> {
+    let handle = app.clone();
+    let screenshot = tauri::async_runtime::spawn_blocking(move || {
+        let mut image = capture(&window)?;
+        let redacted = if redact_sensitive {
+            redact_regions(&window, &mut image, &regions)?
+        } else {
+            0
+        };
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode screenshot: {}", e))?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let path = screenshot_path(&handle, &id)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        std::fs::write(&path, &png).map_err(|e| format!("Failed to write screenshot: {}", e))?;
+
+        Ok::<_, String>(Screenshot {
+            id,
+            width: image.width(),
+            height: image.height(),
+            redacted,
+            data_url: format!("data:image/png;base64,{}", STANDARD.encode(&png)),
+        })
+    })
+    .await
+    .map_err(|e| format!("Screenshot task failed: {}", e))??;
+
+    app_log!(
+        "[Feedback] Captured screenshot {} ({} regions redacted)",
+        screenshot.id,
+        screenshot.redacted
+    );
+    Ok(screenshot)
+}
+
+/// Delete screenshots not yet sent, e.g. on secure logout
+pub(crate) fn forget_screenshots(app: &tauri::AppHandle) -> Result<(), String> {
+    let dir = user_data::cache_dir(app)?.join(SCREENSHOT_DIR);
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)
+            .map_err(|e| format!("Failed to remove screenshots: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Send feedback or a bug report, with app info and optionally a redacted
+/// diagnostics bundle (the end of the log) and a screenshot from
+/// `capture_window_screenshot`. Queued locally, so feedback written offline
+/// is sent once the app is back online. Works signed out, since a broken
+/// sign-in is worth reporting too.
+#[tauri::command]
+#[specta::specta]
+pub async fn submit_feedback(
+    app: tauri::AppHandle,
+    category: FeedbackCategory,
+    text: String,
+    include_diagnostics: bool,
+    screenshot_id: Option<String>,
+) -> Result<Feedback, AppError> {
+    let text = text.trim().to_string();
+    if text.is_empty() || text.chars().count() > MAX_TEXT_CHARS {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid feedback: expected 1 to {} characters",
+                MAX_TEXT_CHARS
+            ),
+        ));
+    }
+    if let Some(id) = &screenshot_id {
+        if !screenshot_path(&app, id)?.is_file() {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Invalid screenshot: {} was not found", id),
+            ));
+        }
+    }
+
+    let feedback = Feedback {
+        id: uuid::Uuid::new_v4().to_string(),
+        category,
+        text,
+        app: app_info(&app),
+        has_diagnostics: include_diagnostics,
+        screenshot_id,
+        created_at: now_secs(),
+    };
+
+    let mut payload = serde_json::to_value(&feedback)
+        .map_err(|e| format!("Failed to serialize feedback: {}", e))?;
+    if include_diagnostics {
+        let mut diagnostics = build_bundle(&app);
+        diagnostics.logs = tail(&diagnostics.logs, MAX_REPORT_LOG_BYTES).to_string();
+        let diagnostics = serde_json::to_string(&diagnostics)
+            .map_err(|e| format!("Failed to serialize diagnostics: {}", e))?;
+        // Redact the whole bundle once more, as for an exported one
+        payload["diagnostics"] = serde_json::from_str(&logging::redact(&diagnostics))
+            .map_err(|e| format!("Failed to serialize diagnostics: {}", e))?;
+    }
+
+    outbox::enqueue(&app, FEEDBACK_TASK, None, payload)?;
+    app_log!("[Feedback] Queued {:?} feedback", feedback.category);
+    Ok(feedback)
+}
+
+/// Outbox handler: post one piece of feedback, with its screenshot read
+/// back in now rather than kept in the queue
+pub(crate) async fn deliver_feedback(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let mut body = payload.clone();
+    let screenshot = payload["screenshotId"]
+        .as_str()
+        .map(|id| screenshot_path(app, id))
+        .transpose()?;
+    if let Some(path) = &screenshot {
+        match std::fs::read(path) {
+            Ok(png) => {
+                body["screenshot"] = serde_json::json!({
+                    "mimeType": "image/png",
+                    "data": STANDARD.encode(png),
+                });
+            }
+            Err(e) => app_log!("[Feedback] Sending without screenshot: {}", e),
+        }
+    }
+
+    let mut request = ApiRequest::post("/api/feedback").json(&body)?;
+    if let Some(session) = load_session(app)? {
+        request = request.bearer(&session.token);
+    }
+    app.state::<Api>().send(request).await?.error_for_status()?;
+    if let Some(path) = screenshot {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_keeps_short_text_whole() {
+        assert_eq!(tail("one\ntwo\n", 100), "one\ntwo\n");
+        assert_eq!(tail("", 0), "");
+    }
+
+    #[test]
+    fn tail_starts_at_a_line_boundary() {
+        let text = "first line\nsecond line\nthird\n";
+        assert_eq!(tail(text, 12), "third\n");
+        assert_eq!(tail(text, text.len() - 1), "second line\nthird\n");
+    }
+
+    #[test]
+    fn tail_never_splits_a_character() {
+        let text = "ééééé\nlast\n";
+        // Keeping 11 of the 16 bytes starts inside the third `é`
+        assert!(!text.is_char_boundary(text.len() - 11));
+        assert_eq!(tail(text, 11), "last\n");
+        assert_eq!(tail("ééé", 3), "é");
+    }
+}
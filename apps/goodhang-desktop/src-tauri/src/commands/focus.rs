@@ -0,0 +1,205 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+use super::auth::load_session;
+use super::user_status::cached_products;
+use crate::api::{Api, ApiRequest};
+use crate::db::now_secs;
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::notifications;
+
+const MIN_FOCUS_SECS: u64 = 60;
+const MAX_FOCUS_SECS: u64 = 8 * 60 * 60;
+// macOS has no public Focus API; these Shortcuts, if the user created them, toggle it
+#[cfg(target_os = "macos")]
+const FOCUS_ON_SHORTCUT: &str = "GoodHang Focus On";
+#[cfg(target_os = "macos")]
+const FOCUS_OFF_SHORTCUT: &str = "GoodHang Focus Off";
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FocusSession {
+    pub label: Option<String>,
+    #[serde(rename = "startedAt")]
+    pub started_at: i64,
+    #[serde(rename = "endsAt")]
+    pub ends_at: i64,
+    /// Whether OS Do Not Disturb was switched on for this session
+    #[serde(rename = "osFocusApplied")]
+    pub os_focus_applied: bool,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FocusEndedEvent {
+    pub session: FocusSession,
+    /// False when ended early
+    pub completed: bool,
+    #[serde(rename = "endedAt")]
+    pub ended_at: i64,
+}
+
+/// The running focus session and the timer that ends it
+#[derive(Default)]
+pub struct Focus {
+    current: Mutex<Option<(FocusSession, tauri::async_runtime::JoinHandle<()>)>>,
+}
+
+impl Focus {
+    /// The running session, for Rust-side callers
+    pub fn current_session(&self) -> Option<FocusSession> {
+        self.current
+            .lock()
+            .ok()?
+            .as_ref()
+            .map(|(session, _)| session.clone())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_os_focus(enabled: bool) -> bool {
+    let shortcut = if enabled {
+        FOCUS_ON_SHORTCUT
+    } else {
+        FOCUS_OFF_SHORTCUT
+    };
+    match std::process::Command::new("shortcuts")
+        .args(["run", shortcut])
+        .status()
+    {
+        Ok(status) if status.success() => true,
+        Ok(_) | Err(_) => {
+            app_log!("[Focus] Shortcut \"{}\" unavailable", shortcut);
+            false
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_os_focus(_enabled: bool) -> bool {
+    // Windows Focus Assist has no supported API for third-party apps
+    false
+}
+
+/// Record a finished session as a FounderOS work block, when the user has FounderOS
+async fn log_work_block(app: &tauri::AppHandle, event: &FocusEndedEvent) -> Result<(), String> {
+    if !cached_products().is_some_and(|p| p.founder_os.enabled) {
+        return Ok(());
+    }
+    let session = load_session(app)?.ok_or_else(|| "Not signed in".to_string())?;
+
+    app.state::<Api>()
+        .send(
+            ApiRequest::post("/api/founder-os/work-blocks")
+                .bearer(&session.token)
+                .json(&serde_json::json!({
+                    "source": "focus_session",
+                    "label": event.session.label,
+                    "startedAt": event.session.started_at,
+                    "endedAt": event.ended_at,
+                    "durationSecs": event.ended_at - event.session.started_at,
+                    "completed": event.completed,
+                }))?,
+        )
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+fn finish(app: &tauri::AppHandle, completed: bool) -> Option<FocusEndedEvent> {
+    let (session, timer) = app.state::<Focus>().current.lock().ok()?.take()?;
+    if !completed {
+        timer.abort();
+    }
+
+    notifications::set_suppressed(false);
+    if session.os_focus_applied {
+        set_os_focus(false);
+    }
+
+    let event = FocusEndedEvent {
+        session,
+        completed,
+        ended_at: now_secs(),
+    };
+    app_log!("[Focus] Session ended: completed={}", completed);
+    events::emit_app_event(app, AppEvent::FocusEnded(event.clone()));
+
+    let handle = app.clone();
+    let logged = event.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = log_work_block(&handle, &logged).await {
+            app_log!("[Focus] Failed to log work block: {}", e);
+        }
+    });
+    Some(event)
+}
+
+/// Start a focus session: GoodHang notifications are held back, OS Do Not
+/// Disturb is optionally switched on, and a notification fires when time is up
+#[tauri::command]
+#[specta::specta]
+pub async fn start_focus_session(
+    app: tauri::AppHandle,
+    focus: tauri::State<'_, Focus>,
+    duration_secs: u64,
+    label: Option<String>,
+    toggle_os_focus: Option<bool>,
+) -> Result<FocusSession, String> {
+    let duration = duration_secs.clamp(MIN_FOCUS_SECS, MAX_FOCUS_SECS);
+
+    // A new session replaces any running one
+    finish(&app, false);
+
+    let os_focus_applied = toggle_os_focus.unwrap_or(false) && set_os_focus(true);
+    let started_at = now_secs();
+    let session = FocusSession {
+        label,
+        started_at,
+        ends_at: started_at + duration as i64,
+        os_focus_applied,
+    };
+
+    let handle = app.clone();
+    let timer = tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(duration)).await;
+        if finish(&handle, true).is_some() {
+            notifications::force_notify(
+                &handle,
+                "Focus session complete",
+                "Nice work. Time for a break.",
+            );
+        }
+    });
+
+    notifications::set_suppressed(true);
+    *focus
+        .current
+        .lock()
+        .map_err(|_| "Focus lock poisoned".to_string())? = Some((session.clone(), timer));
+
+    app_log!("[Focus] Session started: {}s", duration);
+    events::emit_app_event(&app, AppEvent::FocusStarted(session.clone()));
+    Ok(session)
+}
+
+/// End the running session early
+#[tauri::command]
+#[specta::specta]
+pub async fn end_focus_session(app: tauri::AppHandle) -> Result<Option<FocusEndedEvent>, String> {
+    Ok(finish(&app, false))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_focus_session(
+    focus: tauri::State<'_, Focus>,
+) -> Result<Option<FocusSession>, String> {
+    Ok(focus
+        .current
+        .lock()
+        .map_err(|_| "Focus lock poisoned".to_string())?
+        .as_ref()
+        .map(|(session, _)| session.clone()))
+}
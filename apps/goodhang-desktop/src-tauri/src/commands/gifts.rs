@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::Manager;
+use tauri_plugin_shell::ShellExt;
+
+use super::login::is_plausible_email;
+use crate::api::{require_token, Api, ApiRequest};
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+// Checkout sessions expire server-side well before this
+const POLL_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct GiftCheckout {
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(rename = "checkoutUrl")]
+    pub checkout_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct GiftOrder {
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    /// pending | paid | issued | failed | expired
+    pub status: String,
+    #[serde(rename = "recipientEmail")]
+    pub recipient_email: String,
+    /// Present once issued; the key is also emailed to the recipient
+    #[serde(default, rename = "activationCode")]
+    pub activation_code: Option<String>,
+}
+
+impl GiftOrder {
+    fn is_final(&self) -> bool {
+        matches!(self.status.as_str(), "issued" | "failed" | "expired")
+    }
+}
+
+/// Start a Stripe checkout for a gift activation key and open it in the
+/// browser
+#[tauri::command]
+#[specta::specta]
+pub async fn create_gift_checkout(
+    app: tauri::AppHandle,
+    recipient_email: String,
+    message: Option<String>,
+) -> Result<GiftCheckout, AppError> {
+    let recipient_email = recipient_email.trim().to_lowercase();
+    if !is_plausible_email(&recipient_email) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Please enter a valid email address",
+        ));
+    }
+    let token = require_token(&app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/gifts/checkout")
+                .bearer(&token)
+                .json(&serde_json::json!({
+                    "recipientEmail": recipient_email,
+                    "message": message,
+                }))?,
+        )
+        .await?;
+    let checkout: GiftCheckout = response.error_for_status()?.json("/api/gifts/checkout")?;
+
+    #[allow(deprecated)]
+    app.shell()
+        .open(checkout.checkout_url.as_str(), None)
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    app_log!("[Gifts] Checkout opened: order={}", checkout.order_id);
+    Ok(checkout)
+}
+
+/// Wait for a gift order to settle, returning once the key is issued or the
+/// order fails or expires
+#[tauri::command]
+#[specta::specta]
+pub async fn poll_gift_status(
+    app: tauri::AppHandle,
+    order_id: String,
+) -> Result<GiftOrder, AppError> {
+    let token = require_token(&app)?;
+    let api = app.state::<Api>();
+    let path = format!("/api/gifts/{}", order_id);
+
+    let poll = async {
+        loop {
+            let response = api.send(ApiRequest::get(&path).bearer(&token)).await?;
+            let order: GiftOrder = response.error_for_status()?.json("/api/gifts/{id}")?;
+            if order.is_final() {
+                return Ok::<_, String>(order);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    };
+
+    let order = tokio::time::timeout(POLL_TIMEOUT, poll)
+        .await
+        .map_err(|_| AppError::new(ErrorCode::Timeout, "Timed out waiting for gift payment"))??;
+    app_log!("[Gifts] Order {} settled: {}", order.order_id, order.status);
+    Ok(order)
+}
@@ -0,0 +1,534 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::Manager;
+use tauri_plugin_shell::ShellExt;
+
+use crate::api::{require_token, Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::user_data;
+
+const MAX_TIME_OPTIONS: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct TimeOption {
+    /// RFC 3339 timestamps
+    #[serde(rename = "startsAt")]
+    pub starts_at: String,
+    #[serde(rename = "endsAt")]
+    pub ends_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct HangParticipant {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// accepted | declined | maybe | pending
+    #[serde(default)]
+    pub response: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Hang {
+    pub id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// proposed | confirmed | cancelled
+    pub status: String,
+    pub participants: Vec<HangParticipant>,
+    #[serde(default, rename = "timeOptions")]
+    pub time_options: Vec<TimeOption>,
+    /// The agreed time once confirmed
+    #[serde(default, rename = "scheduledFor")]
+    pub scheduled_for: Option<TimeOption>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default, rename = "createdBy")]
+    pub created_by: Option<String>,
+}
+
+fn parse_time(rfc3339: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+impl Hang {
+    /// Sort key for upcoming lists: the agreed time, else the earliest option
+    fn starts_at(&self) -> Option<DateTime<Utc>> {
+        match &self.scheduled_for {
+            Some(time) => parse_time(&time.starts_at),
+            None => self
+                .time_options
+                .iter()
+                .filter_map(|t| parse_time(&t.starts_at))
+                .min(),
+        }
+    }
+
+    /// When the hang is over: the agreed end, else the latest option's end
+    fn ends_at(&self) -> Option<DateTime<Utc>> {
+        match &self.scheduled_for {
+            Some(time) => parse_time(&time.ends_at),
+            None => self
+                .time_options
+                .iter()
+                .filter_map(|t| parse_time(&t.ends_at))
+                .max(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct HangListResponse {
+    hangs: Vec<Hang>,
+}
+
+fn write_hangs(conn: &rusqlite::Connection, hangs: &[Hang]) -> rusqlite::Result<()> {
+    for hang in hangs {
+        conn.execute(
+            "INSERT INTO hangs (id, status, starts_at, payload, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(id) DO UPDATE SET status = ?2, starts_at = ?3, payload = ?4,
+                updated_at = ?5",
+            rusqlite::params![
+                hang.id,
+                hang.status,
+                // Normalised to UTC so the column sorts in time order
+                hang.starts_at()
+                    .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true)),
+                serde_json::to_string(hang).unwrap_or_default(),
+                now_secs()
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+pub(crate) fn cache_hangs(db: &Database, hangs: &[Hang]) -> Result<(), String> {
+    db.with_conn(|conn| write_hangs(conn, hangs))
+}
+
+/// Replace the cache with the server's list in one transaction, so a failed
+/// insert can't leave the cache empty
+fn replace_cached_hangs(db: &Database, hangs: &[Hang]) -> Result<(), String> {
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM hangs", [])?;
+        write_hangs(&tx, hangs)?;
+        tx.commit()
+    })
+}
+
+/// Cached hangs that aren't cancelled or already over, soonest first
+pub(crate) fn cached_upcoming(db: &Database) -> Result<Vec<Hang>, String> {
+    let payloads = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT payload FROM hangs WHERE status != 'cancelled'")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })?;
+    Ok(upcoming(
+        payloads
+            .iter()
+            .filter_map(|p| serde_json::from_str(p).ok())
+            .collect(),
+        Utc::now(),
+    ))
+}
+
+fn upcoming(hangs: Vec<Hang>, now: DateTime<Utc>) -> Vec<Hang> {
+    let mut hangs: Vec<Hang> = hangs
+        .into_iter()
+        .filter(|h| h.ends_at().is_none_or(|end| end > now))
+        .collect();
+    // Hangs without a usable time sort last
+    hangs.sort_by_key(|h| (h.starts_at().is_none(), h.starts_at()));
+    hangs
+}
+
+fn validate_proposal(participants: &[String], time_options: &[TimeOption]) -> Result<(), String> {
+    if participants.is_empty() {
+        return Err("Invalid hang: add at least one participant".to_string());
+    }
+    if time_options.is_empty() || time_options.len() > MAX_TIME_OPTIONS {
+        return Err(format!(
+            "Invalid hang: offer between 1 and {} time options",
+            MAX_TIME_OPTIONS
+        ));
+    }
+    let now = Utc::now();
+    for option in time_options {
+        let start = parse_time(&option.starts_at);
+        let end = parse_time(&option.ends_at);
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err("Invalid hang: time options must be RFC 3339 timestamps".to_string());
+        };
+        if end <= start {
+            return Err("Invalid hang: each time option must end after it starts".to_string());
+        }
+        if end <= now {
+            return Err("Invalid hang: time options must be in the future".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Propose a hang to other members with a few candidate times
+#[tauri::command]
+#[specta::specta]
+pub async fn propose_hang(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    participants: Vec<String>,
+    time_options: Vec<TimeOption>,
+    title: Option<String>,
+    location: Option<String>,
+) -> Result<Hang, String> {
+    validate_proposal(&participants, &time_options)?;
+    let token = require_token(&app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/hangs")
+                .bearer(&token)
+                .json(&serde_json::json!({
+                    "participants": participants,
+                    "timeOptions": time_options,
+                    "title": title,
+                    "location": location,
+                }))?,
+        )
+        .await?;
+
+    let hang: Hang = response.error_for_status()?.json("/api/hangs")?;
+    cache_hangs(&db, std::slice::from_ref(&hang))?;
+    app_log!("[Hangs] Hang proposed: id={}", hang.id);
+    Ok(hang)
+}
+
+/// Upcoming hangs, soonest first. Falls back to the local cache when the
+/// API can't be reached.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_upcoming_hangs(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<Vec<Hang>, String> {
+    let token = require_token(&app)?;
+
+    let fetched = async {
+        let response = app
+            .state::<Api>()
+            .send(
+                ApiRequest::get("/api/hangs")
+                    .query("status", "upcoming")
+                    .bearer(&token),
+            )
+            .await?;
+        response
+            .error_for_status()?
+            .json::<HangListResponse>("/api/hangs")
+    }
+    .await;
+
+    match fetched {
+        Ok(list) => {
+            // The server's list is authoritative; drop cached hangs it no longer has
+            replace_cached_hangs(&db, &list.hangs)?;
+            cached_upcoming(&db)
+        }
+        Err(e) if e.starts_with("Network error") => {
+            app_log!("[Hangs] Offline, serving cached hangs");
+            cached_upcoming(&db)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_hang(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+    reason: Option<String>,
+) -> Result<Hang, String> {
+    let token = require_token(&app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post(format!("/api/hangs/{}/cancel", id))
+                .bearer(&token)
+                .json(&serde_json::json!({ "reason": reason }))?,
+        )
+        .await?;
+
+    let hang: Hang = response
+        .error_for_status()?
+        .json("/api/hangs/{id}/cancel")?;
+    cache_hangs(&db, std::slice::from_ref(&hang))?;
+    app_log!("[Hangs] Hang cancelled: id={}", id);
+    Ok(hang)
+}
+
+const INVITE_RESPONSES: &[&str] = &["accept", "decline", "maybe"];
+// Under the app data dir; cleared on secure logout
+pub(crate) const CALENDAR_DIR: &str = "calendar";
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Invite {
+    pub id: String,
+    pub hang: Hang,
+    #[serde(default, rename = "invitedBy")]
+    pub invited_by: Option<HangParticipant>,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Our response so far, if any
+    #[serde(default)]
+    pub response: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct InviteRespondedEvent {
+    #[serde(rename = "inviteId")]
+    pub invite_id: String,
+    #[serde(rename = "hangId")]
+    pub hang_id: String,
+    pub response: String,
+    /// Calendar file opened for an accepted invite
+    #[serde(rename = "calendarFile")]
+    pub calendar_file: Option<String>,
+}
+
+async fn fetch_invite(app: &tauri::AppHandle, id: &str) -> Result<Invite, String> {
+    let token = require_token(app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(ApiRequest::get(format!("/api/invites/{}", id)).bearer(&token))
+        .await?;
+
+    response.error_for_status()?.json("/api/invites/{id}")
+}
+
+/// `20261015T180000Z` from a UTC RFC 3339 timestamp. The API reports times
+/// in UTC; anything else is skipped rather than written with the wrong zone.
+fn ics_time(rfc3339: &str) -> Option<String> {
+    let utc = rfc3339.strip_suffix('Z')?;
+    let whole_seconds = utc.split('.').next()?;
+    let compact: String = whole_seconds
+        .chars()
+        .filter(|c| *c != '-' && *c != ':')
+        .collect();
+    (compact.len() == 15).then(|| format!("{}Z", compact))
+}
+
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Write an .ics for the hang and hand it to the default calendar app
+fn add_to_calendar(app: &tauri::AppHandle, hang: &Hang) -> Result<Option<PathBuf>, String> {
+    let Some(time) = hang.scheduled_for.as_ref().or(hang.time_options.first()) else {
+        return Ok(None);
+    };
+    let (Some(start), Some(end)) = (ics_time(&time.starts_at), ics_time(&time.ends_at)) else {
+        app_log!(
+            "[Hangs] Skipping calendar entry, non-UTC time: {}",
+            time.starts_at
+        );
+        return Ok(None);
+    };
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//GoodHang//Desktop//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:hang-{}@goodhang", hang.id),
+        format!("DTSTAMP:{}", Utc::now().format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART:{}", start),
+        format!("DTEND:{}", end),
+        format!(
+            "SUMMARY:{}",
+            ics_escape(hang.title.as_deref().unwrap_or("GoodHang"))
+        ),
+    ];
+    if let Some(location) = &hang.location {
+        lines.push(format!("LOCATION:{}", ics_escape(location)));
+    }
+    lines.extend(["END:VEVENT".to_string(), "END:VCALENDAR".to_string()]);
+
+    let dir = user_data::data_dir(app)?.join(CALENDAR_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let path = dir.join(format!("{}.ics", hang.id));
+    std::fs::write(&path, lines.join("\r\n") + "\r\n")
+        .map_err(|e| format!("Failed to write calendar file: {}", e))?;
+
+    #[allow(deprecated)]
+    app.shell()
+        .open(path.to_string_lossy().as_ref(), None)
+        .map_err(|e| format!("Failed to open calendar file: {}", e))?;
+    Ok(Some(path))
+}
+
+/// Fetch the invite behind `goodhang://invite/{id}` and hand it to the UI
+/// as `invite-opened`
+pub(crate) fn open_from_deep_link(app: &tauri::AppHandle, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match fetch_invite(&app, &id).await {
+            Ok(invite) => {
+                events::emit_app_event(&app, AppEvent::InviteOpened(invite));
+            }
+            Err(e) => {
+                app_log!("[Hangs] Failed to open invite {}: {}", id, e);
+                events::emit_app_event(&app, AppEvent::InviteOpenFailed(AppError::from(e)));
+            }
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_invite(app: tauri::AppHandle, id: String) -> Result<Invite, AppError> {
+    Ok(fetch_invite(&app, &id).await?)
+}
+
+/// RSVP to an invite. Accepting also adds the hang to the user's calendar.
+#[tauri::command]
+#[specta::specta]
+pub async fn respond_to_invite(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+    response: String,
+) -> Result<Invite, AppError> {
+    if !INVITE_RESPONSES.contains(&response.as_str()) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid response '{}': expected one of {}",
+                response,
+                INVITE_RESPONSES.join(", ")
+            ),
+        ));
+    }
+    let token = require_token(&app)?;
+
+    let reply = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post(format!("/api/invites/{}/respond", id))
+                .bearer(&token)
+                .json(&serde_json::json!({ "response": response }))?,
+        )
+        .await?;
+    let invite: Invite = reply
+        .error_for_status()?
+        .json("/api/invites/{id}/respond")?;
+    cache_hangs(&db, std::slice::from_ref(&invite.hang))?;
+
+    // The RSVP already succeeded; a calendar hiccup shouldn't fail it
+    let calendar_file = if response == "accept" {
+        add_to_calendar(&app, &invite.hang).unwrap_or_else(|e| {
+            app_log!("[Hangs] Calendar write-through failed: {}", e);
+            None
+        })
+    } else {
+        None
+    };
+
+    app_log!("[Hangs] Invite {} answered: {}", id, response);
+    events::emit_app_event(
+        &app,
+        AppEvent::InviteResponded(InviteRespondedEvent {
+            invite_id: invite.id.clone(),
+            hang_id: invite.hang.id.clone(),
+            response,
+            calendar_file: calendar_file.map(|p| p.to_string_lossy().into_owned()),
+        }),
+    );
+    Ok(invite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hang(id: &str, starts_at: &str, ends_at: &str) -> Hang {
+        Hang {
+            id: id.to_string(),
+            title: None,
+            status: "confirmed".to_string(),
+            participants: Vec::new(),
+            time_options: Vec::new(),
+            scheduled_for: Some(TimeOption {
+                starts_at: starts_at.to_string(),
+                ends_at: ends_at.to_string(),
+            }),
+            location: None,
+            created_by: None,
+        }
+    }
+
+    #[test]
+    fn upcoming_orders_by_instant_across_offsets() {
+        let now = parse_time("2026-10-15T00:00:00Z").unwrap();
+        let hangs = vec![
+            // 18:00 UTC, later than the next one despite sorting first as text
+            hang(
+                "a",
+                "2026-10-20T11:00:00-07:00",
+                "2026-10-20T12:00:00-07:00",
+            ),
+            hang("b", "2026-10-20T17:00:00Z", "2026-10-20T18:00:00Z"),
+        ];
+        let ids: Vec<String> = upcoming(hangs, now).into_iter().map(|h| h.id).collect();
+        assert_eq!(ids, ["b", "a"]);
+    }
+
+    #[test]
+    fn upcoming_drops_finished_hangs() {
+        let now = parse_time("2026-10-15T12:00:00Z").unwrap();
+        let hangs = vec![
+            hang("past", "2026-10-14T10:00:00Z", "2026-10-14T11:00:00Z"),
+            hang("ongoing", "2026-10-15T11:00:00Z", "2026-10-15T13:00:00Z"),
+        ];
+        let ids: Vec<String> = upcoming(hangs, now).into_iter().map(|h| h.id).collect();
+        assert_eq!(ids, ["ongoing"]);
+    }
+
+    #[test]
+    fn proposal_compares_times_not_text() {
+        let option = TimeOption {
+            starts_at: "2099-01-01T10:00:00+02:00".to_string(),
+            ends_at: "2099-01-01T09:30:00Z".to_string(),
+        };
+        assert!(validate_proposal(&["u1".to_string()], &[option]).is_ok());
+    }
+
+    #[test]
+    fn proposal_rejects_past_and_malformed_times() {
+        let past = TimeOption {
+            starts_at: "2001-01-01T10:00:00Z".to_string(),
+            ends_at: "2001-01-01T11:00:00Z".to_string(),
+        };
+        let malformed = TimeOption {
+            starts_at: "tomorrow".to_string(),
+            ends_at: "2099-01-01T11:00:00Z".to_string(),
+        };
+        assert!(validate_proposal(&["u1".to_string()], &[past]).is_err());
+        assert!(validate_proposal(&["u1".to_string()], &[malformed]).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+use crate::image_cache;
+
+/// Local file path for an image URL, fetching it into the cache on a miss.
+/// The frontend loads it through the asset protocol (`convertFileSrc`).
+#[tauri::command]
+#[specta::specta]
+pub async fn get_cached_image(app: tauri::AppHandle, url: String) -> Result<String, String> {
+    image_cache::get_or_fetch(&app, &url)
+        .await
+        .map(|path| path.display().to_string())
+}
+
+/// Warm the cache for images about to be shown; returns how many are cached
+#[tauri::command]
+#[specta::specta]
+pub async fn prefetch_images(app: tauri::AppHandle, urls: Vec<String>) -> Result<usize, String> {
+    Ok(image_cache::prefetch(&app, urls).await)
+}
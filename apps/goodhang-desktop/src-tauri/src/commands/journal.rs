@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::consent::{self, Feature};
+use crate::db::{now_secs, Database};
+use crate::logging::app_log;
+use crate::outbox;
+use crate::search_index;
+use crate::summary_cache;
+
+pub(crate) const SUMMARY_CACHE_KIND: &str = "journal_summary";
+pub const ENTRY_TASK: &str = "journal_entry";
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct JournalEntry {
+    pub id: String,
+    pub body: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct JournalSummary {
+    pub summary: String,
+    /// True when served from the local cache without calling the model
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// Summarize the entries of a journal period (e.g. `2026-W41`). The summary
+/// is reused until the entries change; `refresh` forces a new one.
+#[tauri::command]
+#[specta::specta]
+pub async fn summarize_journal(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    period: String,
+    entries: Vec<JournalEntry>,
+    refresh: Option<bool>,
+) -> Result<JournalSummary, String> {
+    consent::require(&app, Feature::JournalAi)?;
+    let created: Vec<String> = entries.iter().map(|e| e.created_at.to_string()).collect();
+    let hash = summary_cache::input_hash(
+        entries
+            .iter()
+            .zip(&created)
+            .flat_map(|(e, at)| [e.id.as_str(), at.as_str(), e.body.as_str()]),
+    );
+    if !refresh.unwrap_or(false) {
+        if let Some(summary) = summary_cache::get(&db, SUMMARY_CACHE_KIND, &period, &hash) {
+            return Ok(JournalSummary {
+                summary,
+                cached: true,
+            });
+        }
+    }
+
+    let token = load_session(&app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/journal/summarize")
+                .bearer(&token)
+                .json(&serde_json::json!({ "period": period, "entries": entries }))?,
+        )
+        .await?
+        .error_for_status()?;
+    let summary: JournalSummary = response.json("/api/journal/summarize")?;
+
+    if let Err(e) = summary_cache::put(&db, SUMMARY_CACHE_KIND, &period, &hash, &summary.summary) {
+        app_log!("[Journal] Failed to cache summary: {}", e);
+    }
+    Ok(summary)
+}
+
+/// Save a journal entry captured outside the journal view. It's indexed for
+/// search right away and delivered to the API through the outbox.
+pub(crate) fn capture_entry(app: &tauri::AppHandle, body: &str) -> Result<JournalEntry, String> {
+    let entry = JournalEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        body: body.trim().to_string(),
+        created_at: now_secs(),
+    };
+    let title: String = entry
+        .body
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(80)
+        .collect();
+    search_index::upsert_quietly(app, "journal", &entry.id, &title, &entry.body);
+    outbox::enqueue(
+        app,
+        ENTRY_TASK,
+        Some(&format!("journal:{}", entry.id)),
+        serde_json::to_value(&entry).map_err(|e| format!("Failed to serialize entry: {}", e))?,
+    )?;
+    app_log!("[Journal] Entry {} captured", entry.id);
+    Ok(entry)
+}
+
+/// Outbox handler: create the entry server-side. The client id makes
+/// retries idempotent.
+pub(crate) async fn deliver_entry(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let token = load_session(app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    app.state::<Api>()
+        .send(
+            ApiRequest::post("/api/journal/entries")
+                .bearer(&token)
+                .json(payload)?,
+        )
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{Listener, Manager};
+
+use crate::api::{require_token, Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::http::{self, Page};
+use crate::logging::app_log;
+use crate::network;
+use crate::outbox;
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Outbox task pushing one bookmark add or removal
+pub const BOOKMARK_TASK: &str = "bookmark";
+
+// Keeps the periodic loop and on-demand syncs from pulling at the same time
+static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// An expert summary or article in the content library
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct LibraryItem {
+    pub id: String,
+    /// summary | article
+    pub kind: String,
+    pub title: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Full text; only present when fetching a single item
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default, rename = "publishedAt")]
+    pub published_at: Option<String>,
+}
+
+/// A reading-list entry. `item` is the locally saved copy and stays `None`
+/// until it could be fetched.
+#[derive(Debug, Serialize, specta::Type)]
+pub struct Bookmark {
+    #[serde(rename = "itemId")]
+    pub item_id: String,
+    #[serde(rename = "bookmarkedAt")]
+    pub bookmarked_at: i64,
+    pub item: Option<LibraryItem>,
+    pub synced: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BookmarkListResponse {
+    items: Vec<LibraryItem>,
+}
+
+async fn fetch_item(api: &Api, token: &str, id: &str) -> Result<LibraryItem, String> {
+    let request = ApiRequest::get(format!("/api/library/{}", id)).bearer(token);
+    api.send(request)
+        .await?
+        .error_for_status()?
+        .json("/api/library/{id}")
+}
+
+fn save_item(db: &Database, item: &LibraryItem) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE bookmarks SET payload = ?1 WHERE item_id = ?2",
+            rusqlite::params![serde_json::to_string(item).unwrap_or_default(), item.id],
+        )
+    })?;
+    Ok(())
+}
+
+fn load_bookmarks(db: &Database) -> Result<Vec<Bookmark>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT item_id, bookmarked_at, payload, sync_status FROM bookmarks
+             WHERE removed = 0 ORDER BY bookmarked_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let payload: Option<String> = row.get(2)?;
+            let status: String = row.get(3)?;
+            Ok(Bookmark {
+                item_id: row.get(0)?,
+                bookmarked_at: row.get(1)?,
+                item: payload.and_then(|p| serde_json::from_str(&p).ok()),
+                synced: status == "synced",
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })
+}
+
+/// One page of the library, optionally filtered to a category
+#[tauri::command]
+#[specta::specta]
+pub async fn browse_library(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+    category: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<LibraryItem>, String> {
+    let token = require_token(&app)?;
+    let mut request = ApiRequest::get("/api/library").bearer(&token);
+    if let Some(category) = category.as_deref().filter(|c| !c.is_empty()) {
+        request = request.query("category", category);
+    }
+    http::fetch_page(&api, request, cursor.as_deref(), limit).await
+}
+
+/// Add an item to the reading list. The full text is saved locally right
+/// away when online, otherwise on the next sync.
+#[tauri::command]
+#[specta::specta]
+pub async fn bookmark_item(
+    app: tauri::AppHandle,
+    api: tauri::State<'_, Api>,
+    db: tauri::State<'_, Database>,
+    id: String,
+) -> Result<Bookmark, AppError> {
+    if id.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid bookmark: missing item id",
+        ));
+    }
+    let token = require_token(&app)?;
+
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO bookmarks (item_id, bookmarked_at, removed, sync_status)
+             VALUES (?1, ?2, 0, 'pending')
+             ON CONFLICT(item_id) DO UPDATE SET removed = 0, sync_status = 'pending'",
+            rusqlite::params![id, now_secs()],
+        )
+    })?;
+
+    if network::is_online() {
+        match fetch_item(&api, &token, &id).await {
+            Ok(item) => save_item(&db, &item)?,
+            Err(e) => app_log!("[Library] Couldn't save {} for offline reading: {}", id, e),
+        }
+    }
+    app_log!("[Library] Bookmarked {}", id);
+    outbox::enqueue(
+        &app,
+        BOOKMARK_TASK,
+        Some(&format!("bookmark:{}", id)),
+        serde_json::json!({ "itemId": id, "removed": false }),
+    )?;
+
+    load_bookmarks(&db)?
+        .into_iter()
+        .find(|b| b.item_id == id)
+        .ok_or_else(|| AppError::from("Bookmark was not saved".to_string()))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_bookmark(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+) -> Result<(), String> {
+    // Tombstoned rather than deleted so the removal reaches the server
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE bookmarks SET removed = 1, sync_status = 'pending' WHERE item_id = ?1",
+            [&id],
+        )
+    })?;
+    app_log!("[Library] Bookmark removed: {}", id);
+    outbox::enqueue(
+        &app,
+        BOOKMARK_TASK,
+        Some(&format!("bookmark:{}", id)),
+        serde_json::json!({ "itemId": id, "removed": true }),
+    )?;
+    Ok(())
+}
+
+/// Outbox handler: tell the server about one bookmark add or removal. The
+/// local row is only marked synced if it still matches what was sent.
+pub async fn deliver_bookmark(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let item_id = payload["itemId"]
+        .as_str()
+        .ok_or_else(|| "Invalid task payload: missing itemId".to_string())?;
+    let removed = payload["removed"].as_bool().unwrap_or(false);
+    let token = require_token(app)?;
+
+    let path = format!("/api/library/bookmarks/{}", item_id);
+    let request = if removed {
+        ApiRequest::delete(path)
+    } else {
+        ApiRequest::put(path)
+    };
+    app.state::<Api>()
+        .send(request.bearer(&token))
+        .await?
+        .error_for_status()?;
+
+    app.state::<Database>().with_conn(|conn| {
+        if removed {
+            conn.execute(
+                "DELETE FROM bookmarks WHERE item_id = ?1 AND removed = 1",
+                [item_id],
+            )
+        } else {
+            conn.execute(
+                "UPDATE bookmarks SET sync_status = 'synced' WHERE item_id = ?1 AND removed = 0",
+                [item_id],
+            )
+        }
+    })?;
+    Ok(())
+}
+
+/// The offline reading list, newest first
+#[tauri::command]
+#[specta::specta]
+pub async fn list_bookmarks(db: tauri::State<'_, Database>) -> Result<Vec<Bookmark>, String> {
+    load_bookmarks(&db)
+}
+
+/// Pull the server's list so bookmarks made on other devices show up here.
+/// Local changes are pushed through the outbox.
+pub async fn sync_bookmarks(app: &tauri::AppHandle) {
+    if SYNC_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Err(e) = sync_inner(app).await {
+        app_log!("[Library] Bookmark sync failed: {}", e);
+    }
+    SYNC_RUNNING.store(false, Ordering::SeqCst);
+}
+
+async fn sync_inner(app: &tauri::AppHandle) -> Result<(), String> {
+    let Ok(token) = require_token(app) else {
+        return Ok(()); // Not signed in, nothing to sync against
+    };
+    if !network::is_online() {
+        return Ok(()); // Retried when the connectivity monitor reports back-online
+    }
+    let db = app.state::<Database>();
+    let api = app.state::<Api>();
+
+    let remote: BookmarkListResponse = api
+        .send(ApiRequest::get("/api/library/bookmarks").bearer(&token))
+        .await?
+        .error_for_status()?
+        .json("/api/library/bookmarks")?;
+
+    // Unsynced local changes win; everything else mirrors the server
+    let remote_ids: Vec<&str> = remote.items.iter().map(|i| i.id.as_str()).collect();
+    db.with_conn(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT item_id FROM bookmarks WHERE sync_status = 'synced'")?;
+        let local = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for id in local.iter().filter(|id| !remote_ids.contains(&id.as_str())) {
+            conn.execute("DELETE FROM bookmarks WHERE item_id = ?1", [id])?;
+        }
+        for item in &remote.items {
+            conn.execute(
+                "INSERT INTO bookmarks (item_id, bookmarked_at, removed, sync_status)
+                 VALUES (?1, ?2, 0, 'synced')
+                 ON CONFLICT(item_id) DO NOTHING",
+                rusqlite::params![item.id, now_secs()],
+            )?;
+        }
+        Ok(())
+    })?;
+
+    // The list endpoint omits bodies; fill in anything not yet saved offline
+    let missing = db.with_conn(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT item_id FROM bookmarks WHERE removed = 0 AND payload IS NULL")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })?;
+    for id in missing {
+        match fetch_item(&api, &token, &id).await {
+            Ok(item) => save_item(&db, &item)?,
+            Err(e) => app_log!("[Library] Failed to fetch {}: {}", id, e),
+        }
+    }
+
+    events::emit_app_event(app, AppEvent::BookmarksSynced);
+    Ok(())
+}
+
+/// Background loop that syncs the reading list periodically, and as soon as
+/// the API becomes reachable again
+pub fn spawn_bookmark_sync(app: tauri::AppHandle) {
+    let handle = app.clone();
+    app.listen("back-online", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move { sync_bookmarks(&handle).await });
+    });
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            sync_bookmarks(&app).await;
+            tokio::time::sleep(SYNC_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use super::activation::Badge;
+use crate::api::{require_token, Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::http::{self, Page, MAX_PAGE_SIZE};
+use crate::logging::app_log;
+
+const RELATIONSHIPS_CACHE_KEY: &str = "relationships:all";
+const RELATIONSHIPS_TTL_SECS: i64 = 60 * 60;
+
+/// A person, company, project, etc. extracted from the user's conversations
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Entity {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub entity_type: String,
+    pub name: String,
+    #[serde(default)]
+    pub context: Option<String>,
+    #[serde(default)]
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Relationship {
+    pub id: String,
+    pub name: String,
+    #[serde(default, rename = "relationshipType")]
+    pub relationship_type: Option<String>,
+    #[serde(default, rename = "avatarUrl")]
+    pub avatar_url: Option<String>,
+    #[serde(default, rename = "lastInteractionAt")]
+    pub last_interaction_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ContextFile {
+    pub id: String,
+    pub name: String,
+    #[serde(default, rename = "sizeBytes")]
+    pub size_bytes: Option<u64>,
+    #[serde(default, rename = "updatedAt")]
+    pub updated_at: Option<String>,
+}
+
+async fn list<T: serde::de::DeserializeOwned>(
+    app: &tauri::AppHandle,
+    path: &str,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<T>, String> {
+    let token = require_token(app)?;
+    let request = ApiRequest::get(path).bearer(&token);
+    http::fetch_page(&app.state::<Api>(), request, cursor.as_deref(), limit).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_entities(
+    app: tauri::AppHandle,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<Entity>, String> {
+    list(&app, "/api/entities", cursor, limit).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_badges(
+    app: tauri::AppHandle,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<Badge>, String> {
+    list(&app, "/api/badges", cursor, limit).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_relationships(
+    app: tauri::AppHandle,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<Relationship>, String> {
+    list(&app, "/api/relationships", cursor, limit).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_context_files(
+    app: tauri::AppHandle,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<ContextFile>, String> {
+    list(&app, "/api/voice/context-files", cursor, limit).await
+}
+
+/// Every relationship, for matching names in free text. Cached for an hour;
+/// an expired copy is served when the API can't be reached.
+pub(crate) async fn cached_relationships(
+    app: &tauri::AppHandle,
+    db: &Database,
+) -> Result<Vec<Relationship>, String> {
+    let cached: Option<(Vec<Relationship>, i64)> = db
+        .with_conn(|conn| {
+            conn.query_row(
+                "SELECT payload, fetched_at FROM analytics_cache WHERE key = ?1",
+                [RELATIONSHIPS_CACHE_KEY],
+                |row| Ok((row.get::<_, String>(0)?, row.get(1)?)),
+            )
+        })
+        .ok()
+        .and_then(|(payload, fetched_at)| Some((serde_json::from_str(&payload).ok()?, fetched_at)));
+    if let Some((relationships, fetched_at)) = &cached {
+        if now_secs() - fetched_at <= RELATIONSHIPS_TTL_SECS {
+            return Ok(relationships.clone());
+        }
+    }
+
+    let mut relationships = Vec::new();
+    let mut cursor = None;
+    let fetched = loop {
+        match list::<Relationship>(app, "/api/relationships", cursor, Some(MAX_PAGE_SIZE)).await {
+            Ok(page) => {
+                relationships.extend(page.items);
+                match page.next_cursor.filter(|_| page.has_more) {
+                    Some(next) => cursor = Some(next),
+                    None => break Ok(relationships),
+                }
+            }
+            Err(e) => break Err(e),
+        }
+    };
+
+    match (fetched, cached) {
+        (Ok(relationships), _) => {
+            let payload = serde_json::to_string(&relationships)
+                .map_err(|e| format!("Failed to serialize cache: {}", e))?;
+            db.with_conn(|conn| {
+                conn.execute(
+                    "INSERT INTO analytics_cache (key, payload, fetched_at) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(key) DO UPDATE SET payload = ?2, fetched_at = ?3",
+                    rusqlite::params![RELATIONSHIPS_CACHE_KEY, payload, now_secs()],
+                )
+            })?;
+            Ok(relationships)
+        }
+        (Err(e), Some((relationships, _))) => {
+            app_log!("[Listings] Serving stale relationships: {}", e);
+            Ok(relationships)
+        }
+        (Err(e), None) => Err(e),
+    }
+}
@@ -0,0 +1,371 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use super::chat::{ChatCompletion, ChatStreams, ChatTokenEvent, TokensUsed};
+use crate::consent::{self, Feature};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::http::{self, SseParser};
+use crate::logging::{app_log, redact};
+
+// Default ports of `ollama serve` and llama.cpp's `llama-server`
+const OLLAMA_URL: &str = "http://127.0.0.1:11434";
+const LLAMA_CPP_URL: &str = "http://127.0.0.1:8080";
+const DETECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalBackend {
+    Ollama,
+    LlamaCpp,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct LocalLlmStatus {
+    pub available: bool,
+    pub backend: Option<LocalBackend>,
+    #[serde(rename = "baseUrl")]
+    pub base_url: Option<String>,
+    pub models: Vec<String>,
+}
+
+/// Built-in prompts for work that should never leave the device
+#[derive(Debug, Clone, Copy, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalTask {
+    SummarizeJournal,
+    DraftBrief,
+}
+
+impl LocalTask {
+    fn system_prompt(&self) -> &'static str {
+        match self {
+            LocalTask::SummarizeJournal => {
+                "Summarize these journal entries in a few short paragraphs. Note recurring \
+                 themes, moods and people mentioned. Do not invent details."
+            }
+            LocalTask::DraftBrief => {
+                "Draft a short brief to prepare for meeting this person: who they are, what \
+                 you have in common, and two or three things worth asking about. Use only \
+                 the notes provided."
+            }
+        }
+    }
+}
+
+// Required, so another server's JSON at the same path doesn't match
+#[derive(Debug, Deserialize)]
+struct OllamaTags {
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiModels {
+    #[serde(default)]
+    data: Vec<OpenAiModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiModel {
+    id: String,
+}
+
+// One NDJSON line from Ollama's /api/generate
+#[derive(Debug, Deserialize)]
+struct OllamaChunk {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+// One SSE event from llama.cpp's /completion
+#[derive(Debug, Deserialize)]
+struct LlamaCppChunk {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    stop: bool,
+    #[serde(default)]
+    tokens_predicted: Option<u64>,
+    #[serde(default)]
+    tokens_evaluated: Option<u64>,
+}
+
+/// Server URL override. Only loopback addresses are accepted, so "local"
+/// can't quietly become a remote host.
+fn override_url() -> Result<Option<String>, AppError> {
+    let Ok(raw) = std::env::var("GOODHANG_LOCAL_LLM_URL") else {
+        return Ok(None);
+    };
+    let url = reqwest::Url::parse(&raw)
+        .map_err(|e| AppError::new(ErrorCode::InvalidInput, format!("Invalid URL: {}", e)))?;
+    match url.host_str() {
+        Some("localhost" | "127.0.0.1" | "[::1]") => {
+            Ok(Some(raw.trim_end_matches('/').to_string()))
+        }
+        _ => Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid URL: GOODHANG_LOCAL_LLM_URL must point at this machine",
+        )),
+    }
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(url: &str) -> Option<T> {
+    let response = http::client()
+        .get(url)
+        .timeout(DETECT_TIMEOUT)
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json().await.ok()
+}
+
+async fn probe(base_url: &str) -> Option<LocalLlmStatus> {
+    if let Some(tags) = get_json::<OllamaTags>(&format!("{}/api/tags", base_url)).await {
+        return Some(LocalLlmStatus {
+            available: true,
+            backend: Some(LocalBackend::Ollama),
+            base_url: Some(base_url.to_string()),
+            models: tags.models.into_iter().map(|m| m.name).collect(),
+        });
+    }
+    // Plenty of servers speak /v1/models; only llama-server's /props has
+    // its generation settings, and /completion is what we'd call
+    let props = get_json::<serde_json::Value>(&format!("{}/props", base_url)).await?;
+    props.get("default_generation_settings")?;
+    let models = get_json::<OpenAiModels>(&format!("{}/v1/models", base_url))
+        .await
+        .map(|models| models.data.into_iter().map(|m| m.id).collect())
+        .unwrap_or_default();
+    Some(LocalLlmStatus {
+        available: true,
+        backend: Some(LocalBackend::LlamaCpp),
+        base_url: Some(base_url.to_string()),
+        models,
+    })
+}
+
+async fn detect() -> Result<LocalLlmStatus, AppError> {
+    let candidates = match override_url()? {
+        Some(url) => vec![url],
+        None => vec![OLLAMA_URL.to_string(), LLAMA_CPP_URL.to_string()],
+    };
+    for base_url in candidates {
+        if let Some(status) = probe(&base_url).await {
+            return Ok(status);
+        }
+    }
+    Ok(LocalLlmStatus {
+        available: false,
+        backend: None,
+        base_url: None,
+        models: Vec::new(),
+    })
+}
+
+/// Look for a running Ollama or llama.cpp server on this machine
+#[tauri::command]
+#[specta::specta]
+pub async fn detect_local_llm() -> Result<LocalLlmStatus, AppError> {
+    detect().await
+}
+
+/// Generate with a local model; nothing is sent off the device. Tokens
+/// arrive as `local-llm-token` events and `cancel_chat_stream` stops the
+/// stream like a hosted chat.
+#[tauri::command]
+#[specta::specta]
+pub async fn local_generate(
+    app: tauri::AppHandle,
+    streams: tauri::State<'_, ChatStreams>,
+    stream_id: String,
+    prompt: String,
+    model: Option<String>,
+    task: Option<LocalTask>,
+) -> Result<ChatCompletion, AppError> {
+    if matches!(task, Some(LocalTask::SummarizeJournal)) {
+        consent::require(&app, Feature::JournalAi)?;
+    }
+    let status = detect().await?;
+    let (Some(backend), Some(base_url)) = (status.backend, status.base_url) else {
+        return Err(AppError::new(
+            ErrorCode::NotFound,
+            "No local model server found; start Ollama or llama.cpp",
+        ));
+    };
+    let model = match model.or_else(|| status.models.first().cloned()) {
+        Some(model) => model,
+        None if backend == LocalBackend::LlamaCpp => String::new(),
+        None => {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid model: no local models are installed",
+            ))
+        }
+    };
+    let system = task.map(|t| t.system_prompt());
+
+    let mut cancel_rx = streams.register(&stream_id)?;
+    let result = run_local_stream(
+        &app,
+        &stream_id,
+        backend,
+        &base_url,
+        &model,
+        system,
+        &prompt,
+        &mut cancel_rx,
+    )
+    .await;
+    streams.remove(&stream_id);
+
+    if let Err(e) = &result {
+        app_log!("[LocalLlm] Stream {} failed: {}", stream_id, e);
+    }
+    result.map_err(AppError::from)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_local_stream(
+    app: &tauri::AppHandle,
+    stream_id: &str,
+    backend: LocalBackend,
+    base_url: &str,
+    model: &str,
+    system: Option<&str>,
+    prompt: &str,
+    cancel_rx: &mut oneshot::Receiver<()>,
+) -> Result<ChatCompletion, String> {
+    let request = match backend {
+        LocalBackend::Ollama => http::client()
+            .post(format!("{}/api/generate", base_url))
+            .json(&serde_json::json!({
+                "model": model,
+                "prompt": prompt,
+                "system": system,
+                "stream": true,
+            })),
+        // llama-server has one loaded model and no separate system field
+        LocalBackend::LlamaCpp => http::client()
+            .post(format!("{}/completion", base_url))
+            .json(&serde_json::json!({
+                "prompt": match system {
+                    Some(system) => format!("{}\n\n{}", system, prompt),
+                    None => prompt.to_string(),
+                },
+                "stream": true,
+            })),
+    };
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| format!("Local model error: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Local model error {}: {}", status, redact(&body)));
+    }
+
+    let mut sse = SseParser::default();
+    let mut ndjson: Vec<u8> = Vec::new();
+    let mut completion = ChatCompletion {
+        content: String::new(),
+        tokens_used: None,
+        model: Some(model.to_string()).filter(|m| !m.is_empty()),
+        stop_reason: None,
+        cancelled: false,
+    };
+
+    loop {
+        let chunk = tokio::select! {
+            _ = &mut *cancel_rx => {
+                completion.stop_reason = Some("cancelled".to_string());
+                completion.cancelled = true;
+                return Ok(completion);
+            }
+            chunk = response.chunk() => chunk.map_err(|e| format!("Stream error: {}", e))?,
+        };
+        let Some(bytes) = chunk else {
+            break;
+        };
+
+        // Normalize both wire formats to (text, done, tokens)
+        let mut pieces: Vec<(String, bool, Option<TokensUsed>)> = Vec::new();
+        match backend {
+            LocalBackend::Ollama => {
+                ndjson.extend_from_slice(&bytes);
+                while let Some(pos) = ndjson.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = ndjson.drain(..=pos).collect();
+                    let Ok(chunk) = serde_json::from_slice::<OllamaChunk>(&line) else {
+                        continue;
+                    };
+                    if let Some(error) = chunk.error {
+                        return Err(error);
+                    }
+                    let tokens = chunk
+                        .done
+                        .then(|| tokens_used(chunk.prompt_eval_count, chunk.eval_count));
+                    pieces.push((chunk.response, chunk.done, tokens));
+                }
+            }
+            LocalBackend::LlamaCpp => {
+                for payload in sse.push(&bytes) {
+                    let Ok(chunk) = serde_json::from_str::<LlamaCppChunk>(&payload) else {
+                        continue;
+                    };
+                    let tokens = chunk
+                        .stop
+                        .then(|| tokens_used(chunk.tokens_evaluated, chunk.tokens_predicted));
+                    pieces.push((chunk.content, chunk.stop, tokens));
+                }
+            }
+        }
+
+        for (text, done, tokens) in pieces {
+            if !text.is_empty() {
+                completion.content.push_str(&text);
+                events::emit_app_event(
+                    app,
+                    AppEvent::LocalLlmToken(ChatTokenEvent {
+                        stream_id: stream_id.to_string(),
+                        text,
+                    }),
+                );
+            }
+            if done {
+                completion.tokens_used = tokens;
+                completion.stop_reason = Some("end".to_string());
+                return Ok(completion);
+            }
+        }
+    }
+
+    // Server closed the stream without a final chunk
+    Ok(completion)
+}
+
+fn tokens_used(input: Option<u64>, output: Option<u64>) -> TokensUsed {
+    let input = input.unwrap_or(0);
+    let output = output.unwrap_or(0);
+    TokensUsed {
+        input,
+        output,
+        total: input + output,
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use crate::errors::{self, ErrorCode, Locale, ALL_CODES};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_locale() -> Result<Locale, String> {
+    Ok(errors::locale())
+}
+
+/// Switch the language of error messages produced by commands
+#[tauri::command]
+#[specta::specta]
+pub async fn set_locale(app: tauri::AppHandle, locale: String) -> Result<Locale, String> {
+    let locale = Locale::parse(&locale);
+    errors::save_locale(&app, locale)?;
+    Ok(locale)
+}
+
+/// Every error code with its message, for rendering codes the frontend
+/// receives outside a command error (e.g. in events)
+#[tauri::command]
+#[specta::specta]
+pub async fn get_error_catalog(
+    locale: Option<String>,
+) -> Result<HashMap<ErrorCode, &'static str>, String> {
+    let locale = locale.as_deref().map_or_else(errors::locale, Locale::parse);
+    Ok(ALL_CODES
+        .iter()
+        .map(|&code| (code, errors::message(code, locale)))
+        .collect())
+}
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::consent::{self, Feature};
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const LOCATION_KEY: &str = "location";
+pub(crate) const LAST_LOCATION_KEY: &str = "last_location";
+// Two decimal places is roughly 1km: enough for venues, not a street address
+const COORDINATE_PRECISION: f64 = 100.0;
+const MAX_RADIUS_KM: f64 = 50.0;
+
+/// Location sharing is off until the user turns it on
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct LocationSettings {
+    pub enabled: bool,
+    /// Keep the last coarse location across restarts
+    #[serde(default)]
+    pub remember: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, specta::Type)]
+pub struct CoarseLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl CoarseLocation {
+    fn from_precise(latitude: f64, longitude: f64) -> Self {
+        let round = |v: f64| (v * COORDINATE_PRECISION).round() / COORDINATE_PRECISION;
+        Self {
+            latitude: round(latitude),
+            longitude: round(longitude),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct Venue {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default, rename = "distanceKm")]
+    pub distance_km: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct NearbyMember {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "sharedInterests")]
+    pub shared_interests: Vec<String>,
+    /// Bucketed by the server, never an exact distance
+    #[serde(default, rename = "distanceKm")]
+    pub distance_km: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct NearbySuggestions {
+    #[serde(default)]
+    pub venues: Vec<Venue>,
+    #[serde(default)]
+    pub members: Vec<NearbyMember>,
+}
+
+// Held in memory only, unless the user opted to remember it
+static CURRENT_LOCATION: Mutex<Option<CoarseLocation>> = Mutex::new(None);
+
+fn load_settings(app: &tauri::AppHandle) -> Result<LocationSettings, String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+
+    match store.get(user_data::key(LOCATION_KEY)) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| format!("Failed to parse location settings: {}", e)),
+        None => Ok(LocationSettings::default()),
+    }
+}
+
+fn current_location(app: &tauri::AppHandle) -> Option<CoarseLocation> {
+    if let Some(location) = CURRENT_LOCATION.lock().ok().and_then(|l| *l) {
+        return Some(location);
+    }
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()?
+        .get(user_data::key(LAST_LOCATION_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// Drop the in-memory location, e.g. when another account's data is
+/// activated
+pub(crate) fn clear_location_cache() {
+    if let Ok(mut current) = CURRENT_LOCATION.lock() {
+        *current = None;
+    }
+}
+
+/// Drop the in-memory and any remembered location
+pub(crate) fn forget_location(app: &tauri::AppHandle) -> Result<(), String> {
+    clear_location_cache();
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    if store.delete(user_data::key(LAST_LOCATION_KEY)) {
+        stores::save(app, stores::SETTINGS_STORE)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_location_settings(app: tauri::AppHandle) -> Result<LocationSettings, String> {
+    load_settings(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_location_settings(
+    app: tauri::AppHandle,
+    settings: LocationSettings,
+) -> Result<(), String> {
+    if settings.enabled {
+        consent::require(&app, Feature::Location)?;
+    }
+    let store = stores::open(&app, stores::SETTINGS_STORE)?;
+
+    store.set(
+        user_data::key(LOCATION_KEY),
+        serde_json::to_value(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?,
+    );
+    stores::save(&app, stores::SETTINGS_STORE)?;
+
+    if !settings.enabled {
+        forget_location(&app)?;
+    } else if !settings.remember {
+        // Keep this session's location, but nothing on disk
+        let current = current_location(&app);
+        forget_location(&app)?;
+        if let Ok(mut slot) = CURRENT_LOCATION.lock() {
+            *slot = current;
+        }
+    }
+    app_log!(
+        "[Location] Settings updated: enabled={} remember={}",
+        settings.enabled,
+        settings.remember
+    );
+    Ok(())
+}
+
+/// Accept a position the user granted in the webview and keep only a
+/// coarse version of it. Ignored while location sharing is off.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_current_location(
+    app: tauri::AppHandle,
+    latitude: f64,
+    longitude: f64,
+) -> Result<Option<CoarseLocation>, AppError> {
+    let settings = load_settings(&app)?;
+    if !settings.enabled || !consent::is_granted(&app, Feature::Location) {
+        return Ok(None);
+    }
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid coordinates",
+        ));
+    }
+
+    let location = CoarseLocation::from_precise(latitude, longitude);
+    *CURRENT_LOCATION
+        .lock()
+        .map_err(|_| "Location lock poisoned".to_string())? = Some(location);
+
+    if settings.remember {
+        let store = stores::open(&app, stores::SETTINGS_STORE)?;
+        store.set(
+            user_data::key(LAST_LOCATION_KEY),
+            serde_json::to_value(location)
+                .map_err(|e| format!("Failed to serialize location: {}", e))?,
+        );
+        stores::save(&app, stores::SETTINGS_STORE)?;
+    }
+    Ok(Some(location))
+}
+
+/// Venues and compatible members within `radius_km` of the coarse location
+#[tauri::command]
+#[specta::specta]
+pub async fn get_nearby_suggestions(
+    app: tauri::AppHandle,
+    radius_km: f64,
+) -> Result<NearbySuggestions, AppError> {
+    if !load_settings(&app)?.enabled {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid request: location sharing is turned off",
+        ));
+    }
+    if !(radius_km > 0.0 && radius_km <= MAX_RADIUS_KM) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Invalid radius: must be between 0 and {} km", MAX_RADIUS_KM),
+        ));
+    }
+    let location = current_location(&app).ok_or_else(|| {
+        AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid request: no location has been shared yet",
+        )
+    })?;
+    let token = load_session(&app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/matching/nearby")
+                .bearer(&token)
+                .json(&serde_json::json!({
+                    "latitude": location.latitude,
+                    "longitude": location.longitude,
+                    "radiusKm": radius_km,
+                }))?,
+        )
+        .await?
+        .error_for_status()?;
+
+    Ok(response.json("/api/matching/nearby")?)
+}
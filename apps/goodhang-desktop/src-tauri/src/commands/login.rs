@@ -0,0 +1,329 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Manager, Url};
+use tauri_plugin_shell::ShellExt;
+use tokio::sync::oneshot;
+
+use super::auth::{
+    load_device_registration, save_device_registration, save_session, SessionData, SessionInfo,
+};
+use crate::api::{self, Api, ApiRequest};
+use crate::audit;
+use crate::db::now_secs;
+use crate::errors::{classify, AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::session::session_expiry;
+
+const CLIENT_ID: &str = "goodhang-desktop";
+const REDIRECT_URI: &str = "goodhang://auth/callback";
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const MAGIC_LINK_REDIRECT: &str = "goodhang://magic";
+
+struct PendingLogin {
+    state: String,
+    sender: oneshot::Sender<Result<String, String>>,
+}
+
+/// The browser login awaiting its deep-link callback, if any
+#[derive(Default)]
+pub struct PendingBrowserLogin {
+    pending: Mutex<Option<PendingLogin>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    token: String,
+    #[serde(rename = "userId")]
+    user_id: String,
+    #[serde(rename = "sessionId")]
+    session_id: String,
+    #[serde(default, rename = "expiresIn")]
+    expires_in: Option<i64>,
+    #[serde(default, rename = "refreshToken")]
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct MagicLinkFailedEvent {
+    pub code: ErrorCode,
+    pub error: String,
+}
+
+pub(crate) fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+/// S256 code challenge for a PKCE verifier (RFC 7636)
+fn code_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Persist a session issued by the server and hand it back in the shape
+/// `get_session` returns. Rotated refresh tokens update the existing device
+/// registration for the same user.
+pub(crate) async fn store_issued_session(
+    app: &tauri::AppHandle,
+    token: String,
+    user_id: String,
+    session_id: String,
+    expires_in: Option<i64>,
+    refresh_token: Option<String>,
+) -> Result<SessionInfo, String> {
+    let session = SessionData {
+        user_id,
+        session_id,
+        token,
+        expires_at: expires_in.map(|ttl| now_secs() + ttl),
+    };
+    save_session(app, &session).await?;
+
+    if let Some(refresh_token) = refresh_token {
+        if let Some(registration) =
+            load_device_registration(app)?.filter(|r| r.user_id == session.user_id)
+        {
+            save_device_registration(
+                app,
+                &super::auth::DeviceRegistration {
+                    refresh_token,
+                    ..registration
+                },
+            )
+            .await?;
+        }
+    }
+
+    let expires_at = session_expiry(&session);
+    Ok(SessionInfo {
+        user_id: session.user_id,
+        session_id: session.session_id,
+        token: session.token,
+        expires_at,
+    })
+}
+
+/// Called by the deep-link router for `goodhang://auth/callback?code=..&state=..`
+pub fn complete_browser_login(app: &tauri::AppHandle, url: &Url) {
+    let params: std::collections::HashMap<String, String> =
+        url.query_pairs().into_owned().collect();
+
+    let login = app.state::<PendingBrowserLogin>();
+    let Ok(mut slot) = login.pending.lock() else {
+        return;
+    };
+    // A callback with the wrong state isn't ours to answer, so it leaves the
+    // real login waiting rather than failing it
+    match slot.as_ref() {
+        None => {
+            app_log!("[Login] Ignoring OAuth callback with no login in progress");
+            return;
+        }
+        Some(pending) if params.get("state") != Some(&pending.state) => {
+            app_log!("[Login] Ignoring OAuth callback with mismatched state");
+            return;
+        }
+        Some(_) => {}
+    }
+    let Some(pending) = slot.take() else {
+        return;
+    };
+    drop(slot);
+
+    let result = if let Some(error) = params.get("error") {
+        Err(format!("Login was not completed: {}", error))
+    } else {
+        params
+            .get("code")
+            .cloned()
+            .ok_or_else(|| "Login callback did not include a code".to_string())
+    };
+
+    let _ = pending.sender.send(result);
+}
+
+/// Sign in through the system browser using the authorization code flow with
+/// PKCE. Resolves once the `goodhang://auth/callback` deep link arrives and
+/// the code has been exchanged for a session.
+#[tauri::command]
+#[specta::specta]
+pub async fn login_with_browser(
+    app: tauri::AppHandle,
+    login: tauri::State<'_, PendingBrowserLogin>,
+) -> Result<SessionInfo, AppError> {
+    audit::audited(app.clone(), "login_with_browser", async {
+        let verifier = random_string(64);
+        let state = random_string(32);
+        let (sender, receiver) = oneshot::channel();
+
+        // A new attempt supersedes any abandoned one
+        *login
+            .pending
+            .lock()
+            .map_err(|_| "Login lock poisoned".to_string())? = Some(PendingLogin {
+            state: state.clone(),
+            sender,
+        });
+
+        let mut authorize_url = Url::parse(&format!("{}/auth/desktop/authorize", api::base_url()))
+            .map_err(|e| format!("Invalid auth URL: {}", e))?;
+        authorize_url
+            .query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", CLIENT_ID)
+            .append_pair("redirect_uri", REDIRECT_URI)
+            .append_pair("code_challenge", &code_challenge(&verifier))
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", &state);
+
+        #[allow(deprecated)]
+        app.shell()
+            .open(authorize_url.as_str(), None)
+            .map_err(|e| format!("Failed to open browser: {}", e))?;
+        app_log!("[Login] Browser login started");
+
+        let code = match tokio::time::timeout(LOGIN_TIMEOUT, receiver).await {
+            Ok(Ok(result)) => result?,
+            Ok(Err(_)) => return Err(AppError::new(ErrorCode::LoginFailed, "Login was cancelled")),
+            Err(_) => {
+                // Only clear our own attempt, not one started after it
+                if let Ok(mut pending) = login.pending.lock() {
+                    if pending.as_ref().is_some_and(|p| p.state == state) {
+                        pending.take();
+                    }
+                }
+                return Err(AppError::new(
+                    ErrorCode::Timeout,
+                    "Timed out waiting for browser login",
+                ));
+            }
+        };
+
+        let tokens: TokenResponse = app
+            .state::<Api>()
+            .send(
+                ApiRequest::post("/api/auth/token").json(&serde_json::json!({
+                    "grantType": "authorization_code",
+                    "clientId": CLIENT_ID,
+                    "code": code,
+                    "codeVerifier": verifier,
+                    "redirectUri": REDIRECT_URI,
+                }))?,
+            )
+            .await?
+            .error_for_status()?
+            .json("/api/auth/token")?;
+
+        let session = store_issued_session(
+            &app,
+            tokens.token,
+            tokens.user_id,
+            tokens.session_id,
+            tokens.expires_in,
+            tokens.refresh_token,
+        )
+        .await?;
+        app_log!("[Login] Browser login complete: userId={}", session.user_id);
+        Ok(session)
+    })
+    .await
+}
+
+pub(crate) fn is_plausible_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.')
+}
+
+/// Email a sign-in link that opens `goodhang://magic/{token}` — the recovery
+/// path for users who lost their activation email
+#[tauri::command]
+#[specta::specta]
+pub async fn request_magic_link(
+    app: tauri::AppHandle,
+    email: String,
+) -> Result<(), AppError> {
+    audit::audited(app.clone(), "request_magic_link", async {
+        let email = email.trim().to_lowercase();
+        if !is_plausible_email(&email) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Please enter a valid email address",
+            ));
+        }
+
+        app.state::<Api>()
+            .send(
+                ApiRequest::post("/api/auth/magic-link").json(&serde_json::json!({
+                    "email": email,
+                    "redirectUri": MAGIC_LINK_REDIRECT,
+                }))?,
+            )
+            .await?
+            .error_for_status()?;
+
+        app_log!("[Login] Magic link requested");
+        Ok(())
+    })
+    .await
+}
+
+async fn exchange_magic_token(app: &tauri::AppHandle, token: &str) -> Result<SessionInfo, String> {
+    let tokens: TokenResponse = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/auth/magic-link/verify")
+                .json(&serde_json::json!({ "token": token }))?,
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/auth/magic-link/verify")?;
+
+    store_issued_session(
+        app,
+        tokens.token,
+        tokens.user_id,
+        tokens.session_id,
+        tokens.expires_in,
+        tokens.refresh_token,
+    )
+    .await
+}
+
+/// Called by the deep-link router for `goodhang://magic/{token}`. The
+/// exchange happens server-side; the frontend hears `magic-link-login` with
+/// the new session or `magic-link-failed`.
+pub fn complete_magic_link(app: &tauri::AppHandle, token: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match exchange_magic_token(&app, &token).await {
+            Ok(session) => {
+                app_log!(
+                    "[Login] Magic link login complete: userId={}",
+                    session.user_id
+                );
+                events::emit_app_event(&app, AppEvent::MagicLinkLogin(session));
+            }
+            Err(error) => {
+                app_log!("[Login] Magic link login failed: {}", error);
+                events::emit_app_event(
+                    &app,
+                    AppEvent::MagicLinkFailed(MagicLinkFailedEvent {
+                        code: classify(&error),
+                        error,
+                    }),
+                );
+            }
+        }
+    });
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::Manager;
+
+use crate::api::{require_token, Api, ApiRequest};
+use crate::audit;
+use crate::consent::{self, Feature};
+use crate::db::now_secs;
+use crate::errors::{AppError, ErrorCode};
+use crate::http;
+use crate::logging::{app_log, redact};
+
+const BRAVE_API_URL: &str = "https://api.search.brave.com/res/v1";
+const KEYCHAIN_SERVICE: &str = "com.goodhang.desktop";
+const KEYCHAIN_ACCOUNT: &str = "brave-search-key";
+const RESULTS_PER_QUERY: u32 = 10;
+const MAX_SNIPPET_CHARS: usize = 400;
+
+/// What to gather market context about
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct MarketEntity {
+    pub name: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub industry: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketResultKind {
+    News,
+    Web,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct MarketResult {
+    pub kind: MarketResultKind,
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+    /// Hostname of the publishing site
+    pub source: String,
+    /// As reported by Brave, e.g. "2 days ago"
+    pub age: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct MarketContext {
+    pub entity: MarketEntity,
+    pub results: Vec<MarketResult>,
+    #[serde(rename = "gatheredAt")]
+    pub gathered_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveWebResponse {
+    #[serde(default)]
+    web: Option<BraveResults>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BraveResults {
+    #[serde(default)]
+    results: Vec<BraveResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveResult {
+    title: String,
+    url: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    age: Option<String>,
+    #[serde(default)]
+    meta_url: Option<BraveMetaUrl>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveMetaUrl {
+    #[serde(default)]
+    hostname: Option<String>,
+}
+
+fn keychain_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))
+}
+
+/// How searches reach Brave: directly with the user's own key, or through
+/// the GoodHang backend, which holds the shared key
+enum SearchRoute {
+    OwnKey(String),
+    Backend(String),
+}
+
+/// The user's own key, if they saved one
+fn api_key() -> Option<String> {
+    keychain_entry()
+        .ok()
+        .and_then(|entry| entry.get_password().ok())
+        .filter(|key| !key.is_empty())
+}
+
+const ENTITIES: [(&str, char); 5] = [
+    ("&amp;", '&'),
+    ("&quot;", '"'),
+    ("&#x27;", '\''),
+    ("&lt;", '<'),
+    ("&gt;", '>'),
+];
+
+/// Brave marks matches with `<strong>`; keep text only. Entities are decoded
+/// in the same pass, so an escaped `&amp;lt;` stays the literal `&lt;`.
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let mut len = c.len_utf8();
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if in_tag => {}
+            '&' => match ENTITIES.iter().find(|(entity, _)| rest.starts_with(entity)) {
+                Some((entity, decoded)) => {
+                    out.push(*decoded);
+                    len = entity.len();
+                }
+                None => out.push('&'),
+            },
+            c => out.push(c),
+        }
+        rest = &rest[len..];
+    }
+    out
+}
+
+fn normalize(kind: MarketResultKind, result: BraveResult) -> MarketResult {
+    let source = result
+        .meta_url
+        .and_then(|m| m.hostname)
+        .or_else(|| {
+            reqwest::Url::parse(&result.url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+        })
+        .unwrap_or_default();
+    MarketResult {
+        kind,
+        title: strip_tags(&result.title),
+        url: result.url,
+        snippet: strip_tags(&result.description)
+            .chars()
+            .take(MAX_SNIPPET_CHARS)
+            .collect(),
+        source: source.trim_start_matches("www.").to_string(),
+        age: result.age,
+    }
+}
+
+/// One Brave search. `kind` is `news` or `web`; the backend relays Brave's
+/// response unchanged, so both routes parse the same way.
+async fn brave_search(
+    app: &tauri::AppHandle,
+    route: &SearchRoute,
+    kind: &str,
+    query: &str,
+) -> Result<Vec<BraveResult>, String> {
+    let body: serde_json::Value = match route {
+        SearchRoute::OwnKey(key) => {
+            let response = http::client()
+                .get(format!("{}/{}/search", BRAVE_API_URL, kind))
+                .query(&[("q", query), ("count", &RESULTS_PER_QUERY.to_string())])
+                .header("Accept", "application/json")
+                .header("X-Subscription-Token", key)
+                .send()
+                .await
+                .map_err(|e| format!("Network error: {}", e))?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(format!("Brave Search error {}: {}", status, redact(&body)));
+            }
+            http::parse_response(response, "brave search").await?
+        }
+        SearchRoute::Backend(token) => app
+            .state::<Api>()
+            .send(
+                ApiRequest::get("/api/market/search")
+                    .query("kind", kind)
+                    .query("q", query)
+                    .query("count", RESULTS_PER_QUERY)
+                    .bearer(token),
+            )
+            .await?
+            .error_for_status()?
+            .json("/api/market/search")?,
+    };
+    let parsed = match kind {
+        // News responses list results at the top level
+        "news" => serde_json::from_value::<BraveResults>(body),
+        _ => serde_json::from_value::<BraveWebResponse>(body).map(|r| r.web.unwrap_or_default()),
+    };
+    parsed
+        .map(|r| r.results)
+        .map_err(|e| format!("Failed to parse Brave {} results: {}", kind, e))
+}
+
+/// Recent news and market/industry pages about an entity from Brave Search.
+/// With the user's own key this works without the GoodHang backend.
+pub(crate) async fn gather(
+    app: &tauri::AppHandle,
+    entity: &MarketEntity,
+) -> Result<MarketContext, AppError> {
+    let name = entity.name.trim();
+    if name.is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid entity: name is required",
+        ));
+    }
+    consent::require(app, Feature::ExternalLookups)?;
+    let route = match api_key() {
+        Some(key) => SearchRoute::OwnKey(key),
+        None => SearchRoute::Backend(require_token(app)?),
+    };
+
+    let news_query = format!("\"{}\"", name);
+    let web_query = match (&entity.domain, &entity.industry) {
+        (_, Some(industry)) => format!("\"{}\" {} market competitors", name, industry),
+        (Some(domain), None) => format!("\"{}\" {} market", name, domain),
+        (None, None) => format!("\"{}\" market competitors funding", name),
+    };
+    let (news, web) = tokio::join!(
+        brave_search(app, &route, "news", &news_query),
+        brave_search(app, &route, "web", &web_query),
+    );
+    if let (Err(news_error), Err(_)) = (&news, &web) {
+        return Err(news_error.clone().into());
+    }
+
+    let mut seen = HashSet::new();
+    let results: Vec<MarketResult> = news
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| normalize(MarketResultKind::News, r))
+        .chain(
+            web.unwrap_or_default()
+                .into_iter()
+                .map(|r| normalize(MarketResultKind::Web, r)),
+        )
+        .filter(|r| seen.insert(r.url.clone()))
+        .collect();
+
+    app_log!("[Market] Gathered {} results for an entity", results.len());
+    Ok(MarketContext {
+        entity: entity.clone(),
+        results,
+        gathered_at: now_secs(),
+    })
+}
+
+/// Remove the user's key, e.g. on secure logout
+pub(crate) fn forget_search_key() {
+    if let Ok(entry) = keychain_entry() {
+        let _ = entry.delete_credential();
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn gather_market_context(
+    app: tauri::AppHandle,
+    entity: MarketEntity,
+) -> Result<MarketContext, AppError> {
+    gather(&app, &entity).await
+}
+
+/// Save (or with `None`, remove) the user's own Brave Search API key
+#[tauri::command]
+#[specta::specta]
+pub async fn set_brave_search_key(
+    app: tauri::AppHandle,
+    key: Option<String>,
+) -> Result<(), String> {
+    audit::audited(app.clone(), "set_brave_search_key", async {
+        let entry = keychain_entry()?;
+        match key.map(|k| k.trim().to_string()).filter(|k| !k.is_empty()) {
+            Some(key) => entry
+                .set_password(&key)
+                .map_err(|e| format!("Failed to store search key: {}", e)),
+            None => {
+                let _ = entry.delete_credential();
+                Ok(())
+            }
+        }
+    })
+    .await
+}
+
+/// Whether the user saved their own key; without one, searches go through
+/// the backend
+#[tauri::command]
+#[specta::specta]
+pub async fn has_market_search_key() -> Result<bool, String> {
+    Ok(api_key().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_highlight_tags() {
+        assert_eq!(strip_tags("<strong>Acme</strong> raises"), "Acme raises");
+    }
+
+    #[test]
+    fn decodes_entities_once() {
+        assert_eq!(strip_tags("R&amp;D &#x27;24 &lt;3"), "R&D '24 <3");
+        assert_eq!(strip_tags("&amp;lt;b&amp;gt;"), "&lt;b&gt;");
+        assert_eq!(strip_tags("AT&T & co"), "AT&T & co");
+    }
+}
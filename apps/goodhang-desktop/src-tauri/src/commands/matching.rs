@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tokio::sync::oneshot;
+
+use super::activation::MatchingProfile;
+use crate::api::{require_token, Api, ApiRequest};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::notifications;
+
+/// Group size buckets the matching engine understands. Assessment results
+/// may combine two, e.g. "2-4 or 20+".
+const GROUP_SIZES: &[&str] = &["1-on-1", "2-4", "5-8", "9-20", "20+"];
+const CONNECTION_STYLES: &[&str] = &[
+    "conversation_based",
+    "experience_based",
+    "activity_based",
+    "intellectual",
+];
+const MAX_AVOID_ENTRIES: usize = 10;
+const MAX_AVOID_ENTRY_LEN: usize = 60;
+
+#[derive(Debug, Serialize)]
+struct PreferencesUpdate<'a> {
+    ideal_group_size: &'a str,
+    connection_style: &'a str,
+    avoid_match_with: &'a [String],
+}
+
+fn invalid(detail: String) -> AppError {
+    AppError::new(ErrorCode::InvalidInput, detail)
+}
+
+fn validate_group_size(value: &str) -> Result<(), AppError> {
+    let parts: Vec<&str> = value.split(" or ").map(str::trim).collect();
+    if parts.len() > 2 || parts.iter().any(|p| !GROUP_SIZES.contains(p)) {
+        return Err(invalid(format!(
+            "Invalid group size '{}': expected one or two of {}",
+            value,
+            GROUP_SIZES.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Trim, drop blanks and case-insensitive duplicates, and enforce limits
+fn normalize_avoid_list(avoid_list: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for entry in avoid_list {
+        let entry = entry.trim().to_string();
+        if entry.is_empty() || normalized.iter().any(|e| e.eq_ignore_ascii_case(&entry)) {
+            continue;
+        }
+        if entry.chars().count() > MAX_AVOID_ENTRY_LEN {
+            return Err(invalid(format!(
+                "Invalid avoid list entry: keep entries under {} characters",
+                MAX_AVOID_ENTRY_LEN
+            )));
+        }
+        normalized.push(entry);
+    }
+    if normalized.len() > MAX_AVOID_ENTRIES {
+        return Err(invalid(format!(
+            "Invalid avoid list: at most {} entries",
+            MAX_AVOID_ENTRIES
+        )));
+    }
+    Ok(normalized)
+}
+
+/// Current matching preferences: the assessment's profile plus any edits
+#[tauri::command]
+#[specta::specta]
+pub async fn get_matching_preferences(app: tauri::AppHandle) -> Result<MatchingProfile, AppError> {
+    let token = require_token(&app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(ApiRequest::get("/api/matching/preferences").bearer(&token))
+        .await?;
+
+    Ok(response
+        .error_for_status()?
+        .json("/api/matching/preferences")?)
+}
+
+/// Tune matching without re-taking the assessment. Energy pattern and
+/// good-match traits stay assessment-derived.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_matching_preferences(
+    app: tauri::AppHandle,
+    ideal_group_size: String,
+    connection_style: String,
+    avoid_list: Vec<String>,
+) -> Result<MatchingProfile, AppError> {
+    validate_group_size(&ideal_group_size)?;
+    if !CONNECTION_STYLES.contains(&connection_style.as_str()) {
+        return Err(invalid(format!(
+            "Invalid connection style '{}': expected one of {}",
+            connection_style,
+            CONNECTION_STYLES.join(", ")
+        )));
+    }
+    let avoid_list = normalize_avoid_list(avoid_list)?;
+    let token = require_token(&app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::put("/api/matching/preferences")
+                .bearer(&token)
+                .json(&PreferencesUpdate {
+                    ideal_group_size: &ideal_group_size,
+                    connection_style: &connection_style,
+                    avoid_match_with: &avoid_list,
+                })?,
+        )
+        .await?;
+
+    let profile = response
+        .error_for_status()?
+        .json("/api/matching/preferences")?;
+    app_log!("[Matching] Preferences updated");
+    Ok(profile)
+}
+
+/// Why the matching engine paired us, for display and the notification body
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct MatchRationale {
+    #[serde(default, rename = "sharedInterests")]
+    pub shared_interests: Vec<String>,
+    /// Description of how social energies fit, when the engine considered it
+    #[serde(default, rename = "socialEnergy")]
+    pub social_energy: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct MatchFoundEvent {
+    pub id: String,
+    /// hang | person
+    pub kind: String,
+    pub title: String,
+    pub rationale: MatchRationale,
+    /// `goodhang://match/{id}/accept`, handled by the deep link router
+    #[serde(default, rename = "acceptUrl")]
+    pub accept_url: String,
+}
+
+impl MatchRationale {
+    fn notification_body(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.shared_interests.is_empty() {
+            lines.push(format!(
+                "Shared interests: {}",
+                self.shared_interests.join(", ")
+            ));
+        }
+        if let Some(energy) = &self.social_energy {
+            lines.push(format!("Compatible social energy: {}", energy));
+        }
+        if lines.is_empty() {
+            lines.extend(self.summary.clone());
+        }
+        lines.join("\n")
+    }
+}
+
+/// Realtime `match_found` push: re-emit as `match-found` and notify
+pub(crate) fn handle_match_found(app: &tauri::AppHandle, data: serde_json::Value) {
+    let mut event: MatchFoundEvent = match serde_json::from_value(data) {
+        Ok(event) => event,
+        Err(e) => {
+            app_log!("[Matching] Skipping malformed match: {}", e);
+            return;
+        }
+    };
+    event.accept_url = format!("goodhang://match/{}/accept", event.id);
+
+    app_log!(
+        "[Matching] Match found: id={} kind={}",
+        event.id,
+        event.kind
+    );
+    let title = match event.kind.as_str() {
+        "hang" => format!("A hang for you: {}", event.title),
+        _ => format!("You might click with {}", event.title),
+    };
+    notifications::notify(app, &title, &event.rationale.notification_body());
+    events::emit_app_event(app, AppEvent::MatchFound(event));
+}
+
+async fn send_accept(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    let token = require_token(app)?;
+
+    app.state::<Api>()
+        .send(ApiRequest::post(format!("/api/matching/matches/{}/accept", id)).bearer(&token))
+        .await?
+        .error_for_status()?;
+
+    app_log!("[Matching] Match accepted: id={}", id);
+    events::emit_app_event(app, AppEvent::MatchAccepted(id.to_string()));
+    Ok(())
+}
+
+/// Accept a match from `goodhang://match/{id}/accept` once the user
+/// confirms it: any page can open the link, so it mustn't accept on its own
+pub(crate) fn accept_from_deep_link(app: &tauri::AppHandle, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (tx, rx) = oneshot::channel();
+        let mut dialog = app
+            .dialog()
+            .message("Accept this match? They'll see that you're interested.")
+            .title("Accept match")
+            .kind(MessageDialogKind::Info)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                "Accept".to_string(),
+                "Not now".to_string(),
+            ));
+        if let Some(window) = app.get_webview_window("main") {
+            dialog = dialog.parent(&window);
+        }
+        dialog.show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+        if !rx.await.unwrap_or(false) {
+            app_log!("[Matching] Accept from link dismissed: id={}", id);
+            return;
+        }
+
+        if let Err(e) = send_accept(&app, &id).await {
+            app_log!("[Matching] Accept failed: {}", e);
+            events::emit_app_event(&app, AppEvent::MatchAcceptFailed(AppError::from(e)));
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn accept_match(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(send_accept(&app, &id).await?)
+}
@@ -0,0 +1,13 @@
+use crate::db::Database;
+use crate::migrations::{self, MigrationStatus};
+
+/// Dry run of the startup migrations: what's pending for the database and
+/// each store, and whether it would apply cleanly. Nothing is written.
+#[tauri::command]
+#[specta::specta]
+pub async fn check_migrations(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<Vec<MigrationStatus>, String> {
+    migrations::plan(&app, &db)
+}
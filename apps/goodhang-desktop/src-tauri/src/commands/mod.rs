@@ -1,3 +1,67 @@
+pub mod account;
 pub mod activation;
+pub mod analytics;
+pub mod app_info;
+pub mod appearance;
 pub mod auth;
+pub mod backup;
+pub mod batch;
+pub mod billing;
+pub mod bootstrap;
+pub mod browser_bridge;
+pub mod character_sheet;
+pub mod chat;
+pub mod chat_history;
+pub mod compatibility;
+pub mod consent;
+pub mod context_brief;
+pub mod data_export;
+pub mod data_location;
+pub mod devices;
+pub mod diagnostics;
+pub mod discord;
+pub mod downloads;
+pub mod email_signals;
+pub mod expert_content;
+pub mod feedback;
+pub mod focus;
+pub mod gifts;
+pub mod hangs;
+pub mod images;
+pub mod journal;
+pub mod library;
+pub mod listings;
+pub mod local_llm;
+pub mod localization;
+pub mod location;
+pub mod login;
+pub mod market;
+pub mod matching;
+pub mod migrations;
+pub mod natural_date;
+pub mod network;
+pub mod outbox;
+pub mod palette;
+pub mod profile;
+pub mod prompts;
+pub mod quick_capture;
+pub mod reactions;
+pub mod reassessment;
+pub mod referrals;
+pub mod relationship_brief;
+pub mod relationship_notes;
+pub mod reminders;
+pub mod renewals;
+pub mod retention;
+pub mod scheduling;
+pub mod search;
+pub mod security;
+pub mod semantic_search;
+pub mod signal_weights;
+pub mod slack;
+pub mod sync_policy;
+pub mod tokens;
+pub mod trash;
+pub mod uploads;
 pub mod user_status;
+pub mod voice_memos;
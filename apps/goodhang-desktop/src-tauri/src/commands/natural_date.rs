@@ -0,0 +1,418 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, TimeZone};
+use interim::{parse_date_string, Dialect};
+use serde::Serialize;
+
+use super::listings::{cached_relationships, Relationship};
+use crate::db::Database;
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+
+// Longest run of words tried as one date phrase, e.g. "next thursday at 3 pm"
+const MAX_PHRASE_WORDS: usize = 5;
+/// Words that read as dates on their own but are usually just words; a
+/// phrase led by one also needs a number, as in "Sat at 3"
+const AMBIGUOUS_WORDS: &[&str] = &["may", "mar", "sat", "sun", "wed", "second"];
+// Suggested length when a hang is proposed from a parsed time
+const DEFAULT_HANG_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct DateCandidate {
+    /// RFC 3339 in the zone the text was read in
+    #[serde(rename = "startsAt")]
+    pub starts_at: String,
+    /// `startsAt` plus an hour, so a candidate can become a hang time option
+    #[serde(rename = "endsAt")]
+    pub ends_at: String,
+    pub timestamp: i64,
+    /// False for bare dates ("next Thursday"), which resolve to midnight
+    #[serde(rename = "hasTime")]
+    pub has_time: bool,
+    /// The words this was read from
+    pub matched: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ParticipantCandidate {
+    #[serde(rename = "relationshipId")]
+    pub relationship_id: String,
+    pub name: String,
+    pub matched: String,
+    /// False when only a shared first name matched, so several people may
+    /// be candidates for the same words
+    pub exact: bool,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ParsedText {
+    pub dates: Vec<DateCandidate>,
+    pub participants: Vec<ParticipantCandidate>,
+    /// What's left with the date and people phrases removed, e.g. "coffee"
+    pub remainder: String,
+}
+
+/// `text` past its first `n` characters
+fn after_chars(text: &str, n: usize) -> Option<&str> {
+    match text.char_indices().nth(n) {
+        Some((i, _)) => Some(&text[i..]),
+        None => (text.chars().count() == n).then_some(""),
+    }
+}
+
+/// The rest of `text` if it starts with `name` as a whole word
+fn strip_name<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    let rest = after_chars(text, name.chars().count())?;
+    let head = &text[..text.len() - rest.len()];
+    let whole_word = !rest.starts_with(|c: char| c.is_alphanumeric());
+    (whole_word && head.to_lowercase() == name.to_lowercase()).then_some(rest)
+}
+
+fn first_word(text: &str) -> &str {
+    let end = text
+        .find(|c: char| !c.is_alphanumeric())
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+/// Relationships whose full name starts `text`, longest first; failing
+/// that, everyone whose first name is `text`'s first word
+fn people_at<'a>(text: &str, relationships: &'a [Relationship]) -> (Vec<&'a Relationship>, bool) {
+    let mut full: Vec<&Relationship> = relationships
+        .iter()
+        .filter(|r| r.name.chars().count() >= 2 && strip_name(text, &r.name).is_some())
+        .collect();
+    if !full.is_empty() {
+        full.sort_by_key(|r| std::cmp::Reverse(r.name.len()));
+        full.truncate(1);
+        return (full, true);
+    }
+
+    let word = first_word(text).to_lowercase();
+    if word.is_empty() {
+        return (Vec::new(), false);
+    }
+    let by_first_name = relationships
+        .iter()
+        .filter(|r| {
+            r.name
+                .split_whitespace()
+                .next()
+                .is_some_and(|first| first.to_lowercase() == word)
+        })
+        .collect();
+    (by_first_name, false)
+}
+
+/// Relationship whose full name starts `text`, else the only one whose
+/// first name is `text`'s first word. Returns the match and the remainder.
+pub(crate) fn match_person<'a>(
+    text: &'a str,
+    relationships: &'a [Relationship],
+) -> Option<(&'a Relationship, &'a str)> {
+    match people_at(text, relationships) {
+        (people, true) => {
+            let person = people.into_iter().next()?;
+            Some((person, strip_name(text, &person.name)?))
+        }
+        (people, false) if people.len() == 1 => Some((people[0], &text[first_word(text).len()..])),
+        _ => None,
+    }
+}
+
+fn is_connective(word: &str) -> bool {
+    matches!(
+        word.to_lowercase().as_str(),
+        "with" | "at" | "on" | "and" | "for"
+    )
+}
+
+/// Word spans as (start, end) byte offsets
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, text.len()));
+    }
+    spans
+}
+
+/// `word` lowercased, without trailing punctuation
+fn clean_word(word: &str) -> String {
+    word.trim_matches(|c: char| c == ',' || c == '.' || c == '!' || c == '?')
+        .to_lowercase()
+}
+
+/// Rewrite a phrase into something the date grammar reads: drop "at"/"on"
+/// and give bare hours a meridiem ("at 3" means 3pm, "at 9" 9am, "at 12"
+/// noon)
+fn normalize_phrase(phrase: &[&str]) -> Option<(String, bool)> {
+    let mut out = Vec::new();
+    let mut has_time = false;
+    let mut after_at = false;
+    for word in phrase {
+        let word = clean_word(word);
+        if word == "at" || word == "on" {
+            after_at = word == "at";
+            continue;
+        }
+        if let Ok(hour) = word.parse::<u32>() {
+            // A bare number is only a time right after "at"
+            if !after_at || !(1..=12).contains(&hour) {
+                return None;
+            }
+            // With minutes: the grammar reads "tomorrow 12pm" as midnight
+            let meridiem = if hour < 8 || hour == 12 { "pm" } else { "am" };
+            out.push(format!("{}:00{}", hour, meridiem));
+            has_time = true;
+        } else {
+            let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            has_time |= word.contains(':')
+                || (!digits.is_empty() && (word.ends_with("am") || word.ends_with("pm")))
+                || word == "noon"
+                || word == "midnight";
+            out.push(word);
+        }
+        after_at = false;
+    }
+    (!out.is_empty()).then(|| (out.join(" "), has_time))
+}
+
+/// Every date/time phrase in `text`, read relative to `now`. Phrases are
+/// matched greedily, longest first, and never overlap.
+fn find_dates<Z: TimeZone>(text: &str, now: DateTime<Z>) -> Vec<(DateCandidate, (usize, usize))> {
+    let spans = words(text);
+    let words: Vec<&str> = spans.iter().map(|(s, e)| &text[*s..*e]).collect();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let longest = MAX_PHRASE_WORDS.min(words.len() - i);
+        let hit = (1..=longest).rev().find_map(|len| {
+            let phrase = &words[i..i + len];
+            let lead = phrase
+                .iter()
+                .map(|w| clean_word(w))
+                .find(|w| w != "at" && w != "on");
+            let has_number = phrase
+                .iter()
+                .any(|w| w.contains(|c: char| c.is_ascii_digit()));
+            if !has_number && lead.is_some_and(|w| AMBIGUOUS_WORDS.contains(&w.as_str())) {
+                return None;
+            }
+            let (phrase, has_time) = normalize_phrase(phrase)?;
+            let parsed = parse_date_string(&phrase, now.clone(), Dialect::Us).ok()?;
+            Some((parsed.fixed_offset(), has_time, len))
+        });
+        match hit {
+            Some((at, has_time, len)) => {
+                let range = (spans[i].0, spans[i + len - 1].1);
+                found.push((candidate(at, has_time, &text[range.0..range.1]), range));
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    found
+}
+
+fn candidate(at: DateTime<FixedOffset>, has_time: bool, matched: &str) -> DateCandidate {
+    DateCandidate {
+        starts_at: at.to_rfc3339(),
+        ends_at: (at + Duration::minutes(DEFAULT_HANG_MINUTES)).to_rfc3339(),
+        timestamp: at.timestamp(),
+        has_time,
+        matched: matched.to_string(),
+    }
+}
+
+/// The first date phrase in `text` and `text` without it
+pub(crate) fn first_date(text: &str, now: i64) -> Option<(DateCandidate, String)> {
+    let now = Local.timestamp_opt(now, 0).single()?;
+    let (date, (start, end)) = find_dates(text, now).into_iter().next()?;
+    let rest = format!("{}{}", &text[..start], &text[end..]);
+    Some((date, rest.split_whitespace().collect::<Vec<_>>().join(" ")))
+}
+
+/// Dates, times and known people in free text, for quick capture and hang
+/// proposals
+pub(crate) fn parse_text<Z: TimeZone>(
+    text: &str,
+    now: DateTime<Z>,
+    relationships: &[Relationship],
+) -> ParsedText {
+    let found = find_dates(text, now);
+    let mut taken: Vec<(usize, usize)> = found.iter().map(|(_, range)| *range).collect();
+
+    let mut participants = Vec::new();
+    for (start, end) in words(text) {
+        if taken.iter().any(|(s, e)| start < *e && end > *s) {
+            continue;
+        }
+        let word = &text[start..end];
+        let (offset, rest) = match word.strip_prefix('@') {
+            Some(_) => (1, &text[start + 1..]),
+            // Only capitalised words, so "sam" in "same" or lowercase
+            // prose doesn't pull in people
+            None if word.starts_with(char::is_uppercase) => (0, &text[start..]),
+            None => continue,
+        };
+        let (people, exact) = people_at(rest, relationships);
+        let Some(first) = people.first() else {
+            continue;
+        };
+        // Measured in `text`, whose casing can differ in length from the name
+        let matched_len = if exact {
+            rest.len() - strip_name(rest, &first.name).map_or(0, str::len)
+        } else {
+            first_word(rest).len()
+        };
+        let range = (start, start + offset + matched_len);
+        if taken.iter().any(|(s, e)| range.0 < *e && range.1 > *s) {
+            continue;
+        }
+        participants.extend(people.iter().map(|person| ParticipantCandidate {
+            relationship_id: person.id.clone(),
+            name: person.name.clone(),
+            matched: text[range.0..range.1].to_string(),
+            exact,
+        }));
+        taken.push(range);
+    }
+
+    taken.sort();
+    let mut remainder = String::new();
+    let mut cursor = 0;
+    for (start, end) in taken {
+        if start >= cursor {
+            remainder.push_str(&text[cursor..start]);
+            cursor = end;
+        }
+    }
+    remainder.push_str(&text[cursor..]);
+    let mut remainder: Vec<&str> = remainder.split_whitespace().collect();
+    // Connectives left dangling once their object is gone
+    while remainder.last().is_some_and(|w| is_connective(w)) {
+        remainder.pop();
+    }
+
+    ParsedText {
+        dates: found.into_iter().map(|(date, _)| date).collect(),
+        participants,
+        remainder: remainder.join(" "),
+    }
+}
+
+/// Pull dates, times and participants out of text like "coffee with Sam
+/// next Thursday at 3". Read in the OS time zone unless `timeZone` (IANA)
+/// is given.
+#[tauri::command]
+#[specta::specta]
+pub async fn parse_natural_date(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    text: String,
+    time_zone: Option<String>,
+) -> Result<ParsedText, AppError> {
+    // Offline with nothing cached just means no people are matched
+    let relationships = match cached_relationships(&app, &db).await {
+        Ok(relationships) => relationships,
+        Err(e) => {
+            app_log!("[NaturalDate] Relationships unavailable: {}", e);
+            Vec::new()
+        }
+    };
+    match time_zone {
+        Some(name) => {
+            let tz: chrono_tz::Tz = name.parse().map_err(|_| {
+                AppError::new(
+                    ErrorCode::InvalidInput,
+                    format!("Invalid time zone: {}", name),
+                )
+            })?;
+            Ok(parse_text(
+                &text,
+                chrono::Utc::now().with_timezone(&tz),
+                &relationships,
+            ))
+        }
+        None => Ok(parse_text(&text, Local::now(), &relationships)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn person(id: &str, name: &str) -> Relationship {
+        Relationship {
+            id: id.to_string(),
+            name: name.to_string(),
+            relationship_type: None,
+            avatar_url: None,
+            last_interaction_at: None,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        // Thursday 15 October 2026, 10:00 UTC
+        Utc.with_ymd_and_hms(2026, 10, 15, 10, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn bare_hours_get_a_meridiem() {
+        let time = |t: &str| Some((t.to_string(), true));
+        assert_eq!(normalize_phrase(&["at", "3"]), time("3:00pm"));
+        assert_eq!(normalize_phrase(&["at", "9"]), time("9:00am"));
+        assert_eq!(normalize_phrase(&["at", "12"]), time("12:00pm"));
+        assert_eq!(normalize_phrase(&["3"]), None);
+    }
+
+    #[test]
+    fn at_twelve_is_noon() {
+        let parsed = parse_text("lunch tomorrow at 12", now(), &[]);
+        assert_eq!(parsed.dates.len(), 1);
+        assert_eq!(parsed.dates[0].starts_at, "2026-10-16T12:00:00+00:00");
+        assert_eq!(parsed.remainder, "lunch");
+    }
+
+    #[test]
+    fn ambiguous_words_are_skipped_in_any_case() {
+        for text in ["May I come", "may I come", "lunch on May", "SAT down"] {
+            assert!(parse_text(text, now(), &[]).dates.is_empty(), "{}", text);
+        }
+        let parsed = parse_text("lunch Sat at 3", now(), &[]);
+        assert_eq!(parsed.dates[0].matched, "Sat at 3");
+        assert_eq!(parsed.dates[0].starts_at, "2026-10-17T15:00:00+00:00");
+    }
+
+    #[test]
+    fn matches_people_by_full_and_first_name() {
+        let people = [person("1", "Sam Lee"), person("2", "Alex Kim")];
+        let parsed = parse_text("coffee with Sam Lee and Alex", now(), &people);
+        let matched: Vec<_> = parsed
+            .participants
+            .iter()
+            .map(|p| (p.relationship_id.as_str(), p.matched.as_str(), p.exact))
+            .collect();
+        assert_eq!(matched, [("1", "Sam Lee", true), ("2", "Alex", false)]);
+        assert_eq!(parsed.remainder, "coffee");
+    }
+
+    #[test]
+    fn matched_name_is_measured_in_the_text() {
+        // The Kelvin sign lowercases to "k" but is three bytes long
+        let people = [person("1", "Kate Lee")];
+        let parsed = parse_text("\u{212A}ate Lee coffee", now(), &people);
+        assert_eq!(parsed.participants[0].matched, "\u{212A}ate Lee");
+        assert_eq!(parsed.remainder, "coffee");
+    }
+}
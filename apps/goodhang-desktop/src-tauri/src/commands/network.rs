@@ -0,0 +1,52 @@
+use crate::api::{self, ApiBaseUrl};
+use crate::errors::AppError;
+use crate::network::{
+    self, connectivity, ApiCompatibility, ApiHealth, Connectivity, NetworkQuality,
+};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_connectivity() -> Result<Connectivity, AppError> {
+    Ok(connectivity())
+}
+
+/// Rolling latency and error rate from recent API requests, and whether
+/// background sync has dropped to low-bandwidth mode
+#[tauri::command]
+#[specta::specta]
+pub async fn get_network_quality() -> Result<NetworkQuality, AppError> {
+    Ok(network::network_quality())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn check_api_compatibility(app: tauri::AppHandle) -> Result<ApiCompatibility, AppError> {
+    network::check_api_compatibility(&app).await
+}
+
+/// Ping the API and report latency, TLS, clock skew and server status, to
+/// tell "can't reach the server" apart from a bad request
+#[tauri::command]
+#[specta::specta]
+pub async fn check_api_health() -> Result<ApiHealth, AppError> {
+    Ok(network::check_api_health().await)
+}
+
+/// The API base URL in use and whether it came from settings, the
+/// environment or the build default
+#[tauri::command]
+#[specta::specta]
+pub async fn get_api_base_url() -> Result<ApiBaseUrl, AppError> {
+    Ok(api::resolve_base_url())
+}
+
+/// Point the app at another API deployment, or pass `null` to go back to
+/// the environment or build default. Rejected unless the URL answers.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_api_base_url(
+    app: tauri::AppHandle,
+    url: Option<String>,
+) -> Result<ApiBaseUrl, AppError> {
+    api::set_base_url(&app, url).await
+}
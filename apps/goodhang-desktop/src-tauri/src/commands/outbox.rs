@@ -0,0 +1,9 @@
+use crate::db::Database;
+use crate::outbox::{self, QueueStatus};
+
+/// Everything still queued for delivery, including dead-lettered tasks
+#[tauri::command]
+#[specta::specta]
+pub async fn get_queue_status(db: tauri::State<'_, Database>) -> Result<QueueStatus, String> {
+    outbox::status(&db)
+}
@@ -0,0 +1,26 @@
+use crate::palette::{self, Palette, PaletteHit};
+
+const DEFAULT_LIMIT: usize = 12;
+
+/// Fuzzy search across actions, people, hangs, journal entries and settings
+/// for the Cmd+K palette. Answers from memory; a stale index is rebuilt in
+/// the background.
+#[tauri::command]
+#[specta::specta]
+pub async fn palette_search(
+    app: tauri::AppHandle,
+    palette: tauri::State<'_, Palette>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<PaletteHit>, String> {
+    palette::refresh_if_stale(&app);
+    Ok(palette.search(&query, limit.unwrap_or(DEFAULT_LIMIT)))
+}
+
+/// Rebuild the index now, e.g. after the user adds a person or hang
+#[tauri::command]
+#[specta::specta]
+pub async fn refresh_palette_index(app: tauri::AppHandle) -> Result<(), String> {
+    palette::rebuild(&app).await;
+    Ok(())
+}
@@ -0,0 +1,422 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{Manager, Url};
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+use super::auth::load_session;
+use super::uploads;
+use crate::api::{Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::outbox;
+use crate::paths;
+use crate::user_data;
+
+/// Outbox task sending a profile edit
+pub const PROFILE_TASK: &str = "profile_update";
+
+const MAX_DISPLAY_NAME_LEN: usize = 50;
+const MAX_BIO_LEN: usize = 500;
+const MAX_PRONOUNS_LEN: usize = 30;
+const MAX_LINKS: usize = 5;
+const MAX_LINK_LABEL_LEN: usize = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ProfileLink {
+    pub label: String,
+    pub url: String,
+}
+
+/// The user's public GoodHang profile
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Profile {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    #[serde(default)]
+    pub bio: Option<String>,
+    #[serde(default)]
+    pub pronouns: Option<String>,
+    #[serde(default)]
+    pub links: Vec<ProfileLink>,
+    #[serde(default, rename = "avatarUrl")]
+    pub avatar_url: Option<String>,
+}
+
+fn invalid(detail: String) -> AppError {
+    AppError::new(ErrorCode::InvalidInput, detail)
+}
+
+fn check_len(field: &str, value: &str, max: usize) -> Result<(), AppError> {
+    if value.chars().count() > max {
+        return Err(invalid(format!(
+            "Invalid {}: keep it under {} characters",
+            field, max
+        )));
+    }
+    Ok(())
+}
+
+/// Trim inputs, turn blanks into `None`, and enforce limits
+fn validate(
+    display_name: String,
+    bio: Option<String>,
+    pronouns: Option<String>,
+    links: Vec<ProfileLink>,
+) -> Result<(String, Option<String>, Option<String>, Vec<ProfileLink>), AppError> {
+    let display_name = display_name.trim().to_string();
+    if display_name.is_empty() {
+        return Err(invalid(
+            "Invalid display name: it can't be empty".to_string(),
+        ));
+    }
+    check_len("display name", &display_name, MAX_DISPLAY_NAME_LEN)?;
+
+    let non_blank = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let bio = non_blank(bio);
+    let pronouns = non_blank(pronouns);
+    check_len("bio", bio.as_deref().unwrap_or_default(), MAX_BIO_LEN)?;
+    check_len(
+        "pronouns",
+        pronouns.as_deref().unwrap_or_default(),
+        MAX_PRONOUNS_LEN,
+    )?;
+
+    if links.len() > MAX_LINKS {
+        return Err(invalid(format!(
+            "Invalid links: at most {} are allowed",
+            MAX_LINKS
+        )));
+    }
+    let links = links
+        .into_iter()
+        .map(|link| {
+            let label = link.label.trim().to_string();
+            check_len("link label", &label, MAX_LINK_LABEL_LEN)?;
+            let url = Url::parse(link.url.trim())
+                .ok()
+                .filter(|u| matches!(u.scheme(), "http" | "https"))
+                .ok_or_else(|| {
+                    invalid(format!("Invalid link: {} is not a web address", link.url))
+                })?;
+            Ok(ProfileLink {
+                label,
+                url: url.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    Ok((display_name, bio, pronouns, links))
+}
+
+pub(crate) fn cache_profile(db: &Database, profile: &Profile) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO profiles (user_id, payload, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(user_id) DO UPDATE SET payload = ?2, updated_at = ?3",
+            rusqlite::params![
+                profile.user_id,
+                serde_json::to_string(profile).unwrap_or_default(),
+                now_secs()
+            ],
+        )
+    })
+    .map(|_| ())
+}
+
+pub(crate) fn cached_profile(db: &Database, user_id: &str) -> Result<Option<Profile>, String> {
+    let payload: Option<String> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT payload FROM profiles WHERE user_id = ?1")?;
+        let mut rows = stmt.query_map([user_id], |row| row.get(0))?;
+        rows.next().transpose()
+    })?;
+    Ok(payload.and_then(|p| serde_json::from_str(&p).ok()))
+}
+
+async fn fetch_profile(api: &Api, token: &str) -> Result<Profile, String> {
+    let response = api
+        .send(ApiRequest::get("/api/profile").bearer(token))
+        .await?
+        .error_for_status()?;
+    response.json("/api/profile")
+}
+
+/// The signed-in user's profile, from the cache when offline
+#[tauri::command]
+#[specta::specta]
+pub async fn get_profile(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<Profile, AppError> {
+    let session = load_session(&app)?.ok_or_else(|| "Not signed in".to_string())?;
+
+    match fetch_profile(&app.state::<Api>(), &session.token).await {
+        Ok(profile) => {
+            cache_profile(&db, &profile)?;
+            Ok(profile)
+        }
+        Err(e) if e.starts_with("Network error") => match cached_profile(&db, &session.user_id)? {
+            Some(profile) => {
+                app_log!("[Profile] Offline, serving cached profile");
+                Ok(profile)
+            }
+            None => Err(e.into()),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Update the public profile. The cache and a `profile-updated` event reflect
+/// the edit immediately and the API write goes through the outbox; if the
+/// server rejects it, both roll back and `write-reverted` carries the
+/// previous profile.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_profile(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    display_name: String,
+    bio: Option<String>,
+    pronouns: Option<String>,
+    links: Vec<ProfileLink>,
+) -> Result<Profile, AppError> {
+    let (display_name, bio, pronouns, links) = validate(display_name, bio, pronouns, links)?;
+    let session = load_session(&app)?.ok_or_else(|| "Not signed in".to_string())?;
+
+    let previous = cached_profile(&db, &session.user_id)?;
+    let optimistic = Profile {
+        user_id: session.user_id.clone(),
+        display_name,
+        bio,
+        pronouns,
+        links,
+        avatar_url: previous.as_ref().and_then(|p| p.avatar_url.clone()),
+    };
+    cache_profile(&db, &optimistic)?;
+    events::emit_app_event(&app, AppEvent::ProfileUpdated(optimistic.clone()));
+
+    outbox::enqueue(
+        &app,
+        PROFILE_TASK,
+        Some(&format!("profile:{}", optimistic.user_id)),
+        serde_json::json!({
+            "userId": optimistic.user_id,
+            "update": {
+                "displayName": optimistic.display_name,
+                "bio": optimistic.bio,
+                "pronouns": optimistic.pronouns,
+                "links": optimistic.links,
+            },
+            "previous": previous,
+        }),
+    )?;
+    app_log!("[Profile] Profile updated locally, queued for sync");
+    Ok(optimistic)
+}
+
+/// Outbox handler: send a queued profile edit and cache what the server
+/// stored
+pub async fn deliver_profile(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let token = load_session(app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::patch("/api/profile")
+                .bearer(&token)
+                .json(&payload["update"])?,
+        )
+        .await?
+        .error_for_status()?;
+    let profile: Profile = response.json("/api/profile")?;
+    cache_profile(&app.state::<Database>(), &profile)?;
+    events::emit_app_event(app, AppEvent::ProfileUpdated(profile));
+    app_log!("[Profile] Profile update synced");
+    Ok(())
+}
+
+/// Outbox revert: restore the profile cached before a rejected edit.
+/// Returns the user id.
+pub fn revert_profile(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<String, String> {
+    let user_id = payload["userId"]
+        .as_str()
+        .ok_or_else(|| "Invalid task payload: missing userId".to_string())?;
+    let db = app.state::<Database>();
+    match serde_json::from_value::<Option<Profile>>(payload["previous"].clone()) {
+        Ok(Some(profile)) => {
+            cache_profile(&db, &profile)?;
+            events::emit_app_event(app, AppEvent::ProfileUpdated(profile));
+        }
+        _ => {
+            db.with_conn(|conn| {
+                conn.execute("DELETE FROM profiles WHERE user_id = ?1", [user_id])
+            })?;
+        }
+    }
+    Ok(user_id.to_string())
+}
+
+/// Square edge of the uploaded avatar, in pixels
+pub(crate) const PHOTO_DIR: &str = "profile_photos";
+const PHOTO_SIZE: u32 = 512;
+const PHOTO_JPEG_QUALITY: u8 = 85;
+const PHOTO_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+// Frames dropped after opening the camera so auto-exposure can settle
+const CAMERA_WARMUP_FRAMES: usize = 10;
+
+/// Center-crop to a square and shrink to the avatar size, so megapixel
+/// originals never leave the machine
+fn prepare_photo(image: image::DynamicImage) -> image::DynamicImage {
+    let side = image.width().min(image.height());
+    let x = (image.width() - side) / 2;
+    let y = (image.height() - side) / 2;
+    image.crop_imm(x, y, side, side).resize_exact(
+        PHOTO_SIZE,
+        PHOTO_SIZE,
+        image::imageops::FilterType::Lanczos3,
+    )
+}
+
+/// Write the prepared photo as a JPEG in the cache dir and return its path
+fn write_photo(app: &tauri::AppHandle, image: image::DynamicImage) -> Result<PathBuf, String> {
+    let dir = user_data::cache_dir(app)?.join(PHOTO_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let path = dir.join(format!("{}.jpg", uuid::Uuid::new_v4()));
+
+    let _writes = paths::hold_writes()?;
+    let file = std::fs::File::create(&path).map_err(|e| format!("Failed to write photo: {}", e))?;
+    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(
+        std::io::BufWriter::new(file),
+        PHOTO_JPEG_QUALITY,
+    );
+    prepare_photo(image)
+        .to_rgb8()
+        .write_with_encoder(encoder)
+        .map_err(|e| format!("Failed to encode photo: {}", e))?;
+    Ok(path)
+}
+
+/// Grab a single frame from the default camera
+fn capture_frame() -> Result<image::DynamicImage, String> {
+    use nokhwa::pixel_format::RgbFormat;
+    use nokhwa::utils::{CameraIndex, RequestedFormat, RequestedFormatType};
+
+    let format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+    let mut camera = nokhwa::Camera::new(CameraIndex::Index(0), format)
+        .map_err(|e| format!("Failed to open camera: {}", e))?;
+    camera
+        .open_stream()
+        .map_err(|e| format!("Failed to start camera: {}", e))?;
+
+    for _ in 0..CAMERA_WARMUP_FRAMES {
+        let _ = camera.frame();
+    }
+    let frame = camera
+        .frame()
+        .map_err(|e| format!("Failed to capture frame: {}", e));
+    let _ = camera.stop_stream();
+
+    let decoded = frame?
+        .decode_image::<RgbFormat>()
+        .map_err(|e| format!("Failed to decode frame: {}", e))?;
+    let (width, height) = (decoded.width(), decoded.height());
+    image::RgbImage::from_raw(width, height, decoded.into_raw())
+        .map(image::DynamicImage::ImageRgb8)
+        .ok_or_else(|| "Failed to decode frame: unexpected buffer size".to_string())
+}
+
+/// Upload a prepared photo, point the profile at it, and refresh the cache.
+/// Upload progress arrives as `upload-progress` events with kind
+/// `profile_photo`.
+async fn upload_photo(
+    app: &tauri::AppHandle,
+    db: &Database,
+    path: PathBuf,
+) -> Result<Profile, String> {
+    let session = load_session(app)?.ok_or_else(|| "Not signed in".to_string())?;
+    let upload = uploads::upload_and_wait(
+        app,
+        path.to_string_lossy().into_owned(),
+        "profile_photo",
+        "image/jpeg",
+    )
+    .await;
+    let _ = std::fs::remove_file(&path);
+    let file_id = upload?
+        .result_id
+        .ok_or_else(|| "Upload finished without a file id".to_string())?;
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::patch("/api/profile")
+                .bearer(&session.token)
+                .json(&serde_json::json!({ "avatarFileId": file_id }))?,
+        )
+        .await?
+        .error_for_status()?;
+    let profile: Profile = response.json("/api/profile")?;
+    cache_profile(db, &profile)?;
+    events::emit_app_event(app, AppEvent::ProfileUpdated(profile.clone()));
+    app_log!("[Profile] Profile photo updated");
+    Ok(profile)
+}
+
+/// Choose a photo with the native file dialog. Returns `None` if the user
+/// cancels.
+#[tauri::command]
+#[specta::specta]
+pub async fn pick_profile_photo(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<Option<Profile>, AppError> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter("Images", PHOTO_EXTENSIONS)
+        .pick_file(move |picked| {
+            let _ = tx.send(picked);
+        });
+    let Some(picked) = rx.await.ok().flatten() else {
+        return Ok(None);
+    };
+    let source = picked
+        .into_path()
+        .map_err(|e| format!("Failed to read selected file: {}", e))?;
+
+    let handle = app.clone();
+    let path = tauri::async_runtime::spawn_blocking(move || {
+        let image = image::open(&source).map_err(|e| format!("Invalid image: {}", e))?;
+        write_photo(&handle, image)
+    })
+    .await
+    .map_err(|e| format!("Image task failed: {}", e))??;
+
+    Ok(Some(upload_photo(&app, &db, path).await?))
+}
+
+/// Take a photo with the default webcam
+#[tauri::command]
+#[specta::specta]
+pub async fn capture_profile_photo(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<Profile, AppError> {
+    let handle = app.clone();
+    let path = tauri::async_runtime::spawn_blocking(move || write_photo(&handle, capture_frame()?))
+        .await
+        .map_err(|e| format!("Camera task failed: {}", e))??;
+
+    Ok(upload_photo(&app, &db, path).await?)
+}
@@ -0,0 +1,312 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::LazyLock;
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+
+const PROMPT_KINDS: &[&str] = &["check_in", "brief_format", "persona_system"];
+const MAX_TEMPLATE_CHARS: usize = 20_000;
+
+// `{{ name }}` placeholders
+static VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").expect("valid regex"));
+
+/// One version of a prompt template. Editing saves a new version; `shared`
+/// templates come from the API and stop syncing once the user edits them.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub version: i64,
+    pub kind: String,
+    pub name: String,
+    pub body: String,
+    /// user | shared
+    pub source: String,
+    /// Placeholder names used in `body`, sorted
+    pub variables: Vec<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SharedTemplate {
+    id: String,
+    version: i64,
+    kind: String,
+    name: String,
+    body: String,
+}
+
+#[derive(Debug, Default, Serialize, specta::Type)]
+pub struct PromptSyncSummary {
+    pub added: usize,
+    pub updated: usize,
+    /// Shared templates with a newer upstream version the user has edited
+    pub skipped: usize,
+}
+
+fn variables_in(body: &str) -> Vec<String> {
+    VARIABLE
+        .captures_iter(body)
+        .map(|c| c[1].to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn row_to_template(row: &rusqlite::Row) -> rusqlite::Result<PromptTemplate> {
+    let body: String = row.get("body")?;
+    Ok(PromptTemplate {
+        id: row.get("id")?,
+        version: row.get("version")?,
+        kind: row.get("kind")?,
+        name: row.get("name")?,
+        variables: variables_in(&body),
+        body,
+        source: row.get("source")?,
+        created_at: row.get("created_at")?,
+    })
+}
+
+fn latest(db: &Database, id: &str) -> Result<Option<PromptTemplate>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM prompt_templates WHERE id = ?1 ORDER BY version DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query_map([id], row_to_template)?;
+        rows.next().transpose()
+    })
+}
+
+fn insert(db: &Database, template: &PromptTemplate) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO prompt_templates (id, version, kind, name, body, source, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                template.id,
+                template.version,
+                template.kind,
+                template.name,
+                template.body,
+                template.source,
+                template.created_at
+            ],
+        )
+    })?;
+    Ok(())
+}
+
+fn invalid(detail: impl Into<String>) -> AppError {
+    AppError::new(ErrorCode::InvalidInput, detail)
+}
+
+/// Fill `{{name}}` placeholders. Every placeholder must have a value.
+pub(crate) fn render(body: &str, vars: &HashMap<String, String>) -> Result<String, AppError> {
+    let missing: Vec<String> = variables_in(body)
+        .into_iter()
+        .filter(|name| !vars.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(invalid(format!(
+            "Invalid prompt variables: missing {}",
+            missing.join(", ")
+        )));
+    }
+    Ok(VARIABLE
+        .replace_all(body, |c: &regex::Captures| vars[&c[1]].clone())
+        .into_owned())
+}
+
+/// Latest version of every template, optionally of one kind
+#[tauri::command]
+#[specta::specta]
+pub async fn list_prompt_templates(
+    db: tauri::State<'_, Database>,
+    kind: Option<String>,
+) -> Result<Vec<PromptTemplate>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT t.* FROM prompt_templates t
+             WHERE t.version = (SELECT MAX(version) FROM prompt_templates WHERE id = t.id)
+               AND (?1 IS NULL OR t.kind = ?1)
+             ORDER BY t.kind, t.name",
+        )?;
+        let rows = stmt.query_map([kind], row_to_template)?;
+        rows.collect()
+    })
+}
+
+/// A template at `version`, or its latest version
+#[tauri::command]
+#[specta::specta]
+pub async fn get_prompt_template(
+    db: tauri::State<'_, Database>,
+    id: String,
+    version: Option<i64>,
+) -> Result<Option<PromptTemplate>, String> {
+    match version {
+        None => latest(&db, &id),
+        Some(version) => db.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT * FROM prompt_templates WHERE id = ?1 AND version = ?2")?;
+            let mut rows = stmt.query_map(rusqlite::params![id, version], row_to_template)?;
+            rows.next().transpose()
+        }),
+    }
+}
+
+/// Every saved version of a template, newest first
+#[tauri::command]
+#[specta::specta]
+pub async fn list_prompt_template_versions(
+    db: tauri::State<'_, Database>,
+    id: String,
+) -> Result<Vec<PromptTemplate>, String> {
+    db.with_conn(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT * FROM prompt_templates WHERE id = ?1 ORDER BY version DESC")?;
+        let rows = stmt.query_map([id], row_to_template)?;
+        rows.collect()
+    })
+}
+
+/// Create a template (no `id`) or save a new version of one
+#[tauri::command]
+#[specta::specta]
+pub async fn save_prompt_template(
+    db: tauri::State<'_, Database>,
+    id: Option<String>,
+    kind: String,
+    name: String,
+    body: String,
+) -> Result<PromptTemplate, AppError> {
+    if !PROMPT_KINDS.contains(&kind.as_str()) {
+        return Err(invalid(format!("Invalid prompt kind: {}", kind)));
+    }
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(invalid("Invalid prompt template: name is required"));
+    }
+    if body.trim().is_empty() || body.chars().count() > MAX_TEMPLATE_CHARS {
+        return Err(invalid(format!(
+            "Invalid prompt template: body must be between 1 and {} characters",
+            MAX_TEMPLATE_CHARS
+        )));
+    }
+
+    let previous = match &id {
+        Some(id) => Some(
+            latest(&db, id)?.ok_or_else(|| invalid(format!("Unknown prompt template: {}", id)))?,
+        ),
+        None => None,
+    };
+    let template = PromptTemplate {
+        id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        version: previous.map_or(1, |p| p.version + 1),
+        kind,
+        name,
+        variables: variables_in(&body),
+        body,
+        source: "user".to_string(),
+        created_at: now_secs(),
+    };
+    insert(&db, &template)?;
+
+    app_log!("[Prompts] Saved {} v{}", template.id, template.version);
+    Ok(template)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_prompt_template(
+    db: tauri::State<'_, Database>,
+    id: String,
+) -> Result<(), String> {
+    db.with_conn(|conn| conn.execute("DELETE FROM prompt_templates WHERE id = ?1", [&id]))?;
+    Ok(())
+}
+
+/// Render a template's latest (or given) version with `vars`
+#[tauri::command]
+#[specta::specta]
+pub async fn render_prompt(
+    db: tauri::State<'_, Database>,
+    template_id: String,
+    vars: HashMap<String, String>,
+    version: Option<i64>,
+) -> Result<String, AppError> {
+    let template = get_prompt_template(db, template_id.clone(), version)
+        .await?
+        .ok_or_else(|| invalid(format!("Unknown prompt template: {}", template_id)))?;
+    render(&template.body, &vars)
+}
+
+/// Pull shared templates from the API. Newer upstream versions are added
+/// unless the user has edited that template locally.
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_prompt_templates(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<PromptSyncSummary, String> {
+    let token = load_session(&app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    let response = app
+        .state::<Api>()
+        .send(ApiRequest::get("/api/prompts/shared").bearer(&token))
+        .await?
+        .error_for_status()?;
+    let shared: Vec<SharedTemplate> = response.json("/api/prompts/shared")?;
+
+    let mut summary = PromptSyncSummary::default();
+    for upstream in shared {
+        if !PROMPT_KINDS.contains(&upstream.kind.as_str()) {
+            continue;
+        }
+        let current = latest(&db, &upstream.id)?;
+        let version = match &current {
+            None => {
+                summary.added += 1;
+                upstream.version
+            }
+            Some(current) if current.source == "user" => {
+                summary.skipped += 1;
+                continue;
+            }
+            Some(current) if upstream.version > current.version => {
+                summary.updated += 1;
+                upstream.version
+            }
+            Some(_) => continue,
+        };
+        insert(
+            &db,
+            &PromptTemplate {
+                id: upstream.id,
+                version,
+                kind: upstream.kind,
+                name: upstream.name,
+                variables: Vec::new(),
+                body: upstream.body,
+                source: "shared".to_string(),
+                created_at: now_secs(),
+            },
+        )?;
+    }
+
+    app_log!(
+        "[Prompts] Sync: {} added, {} updated, {} skipped",
+        summary.added,
+        summary.updated,
+        summary.skipped
+    );
+    Ok(summary)
+}
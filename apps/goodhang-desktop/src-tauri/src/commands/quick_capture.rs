@@ -0,0 +1,345 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use super::journal;
+use super::listings::{cached_relationships, Relationship};
+use super::natural_date::{first_date, match_person};
+use super::relationship_notes::add_relationship_note;
+use super::reminders::create_reminder;
+use super::security::AppLock;
+use crate::consent::{self, Feature};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::paths;
+use crate::stores;
+
+pub const WINDOW_LABEL: &str = "quick-capture";
+const SHORTCUT_KEY: &str = "quick_capture_shortcut";
+const DEFAULT_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+const MAX_CAPTURE_CHARS: usize = 4000;
+// Reminders captured without a time
+const DEFAULT_REMINDER_DELAY_SECS: i64 = 60 * 60;
+
+/// Prefixes that make a capture a reminder; the rest becomes its title
+const REMINDER_PREFIXES: &[&str] = &[
+    "remind me to ",
+    "remind me ",
+    "reminder:",
+    "reminder ",
+    "todo:",
+    "todo ",
+    "to do:",
+    "don't forget to ",
+    "dont forget to ",
+];
+
+static RELATIVE_TIME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:in (\d{1,3}) (minute|min|hour|hr|day|week)s?|(tomorrow))\b")
+        .expect("relative time pattern")
+});
+
+static NOTE_PREFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^note (?:for|about|on) ").expect("note prefix pattern"));
+
+/// Where a capture goes
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+#[serde(tag = "route", rename_all = "snake_case")]
+pub enum CaptureRoute {
+    Journal,
+    RelationshipNote {
+        #[serde(rename = "relationshipId")]
+        relationship_id: String,
+        name: String,
+        body: String,
+    },
+    Reminder {
+        title: String,
+        #[serde(rename = "dueAt")]
+        due_at: i64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CaptureResult {
+    #[serde(flatten)]
+    pub route: CaptureRoute,
+    /// Id of the journal entry, note, or reminder that was created
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct QuickCaptureSettings {
+    pub shortcut: String,
+}
+
+/// Due time from "in 2 hours", "in 3 days", or any date phrase such as
+/// "friday at 3", and the title with that phrase removed
+fn reminder_due(text: &str, now: i64) -> (String, i64) {
+    let Some(caps) = RELATIVE_TIME.captures(text) else {
+        return match first_date(text, now) {
+            Some((date, title)) if date.timestamp > now && !title.is_empty() => {
+                (title, date.timestamp)
+            }
+            _ => (text.to_string(), now + DEFAULT_REMINDER_DELAY_SECS),
+        };
+    };
+    let offset = match (caps.get(1), caps.get(2)) {
+        (Some(n), Some(unit)) => {
+            let n: i64 = n.as_str().parse().unwrap_or(1);
+            let unit_secs = match unit.as_str().to_lowercase().as_str() {
+                "minute" | "min" => 60,
+                "hour" | "hr" => 60 * 60,
+                "day" => 24 * 60 * 60,
+                _ => 7 * 24 * 60 * 60,
+            };
+            n * unit_secs
+        }
+        _ => 24 * 60 * 60,
+    };
+    let whole = caps.get(0).map_or(0..0, |m| m.range());
+    let title = format!("{}{}", &text[..whole.start], &text[whole.end..]);
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (title, now + offset)
+}
+
+fn trim_separator(text: &str) -> &str {
+    text.trim_start_matches(|c: char| c == ':' || c == ',' || c == '-' || c.is_whitespace())
+        .trim_end()
+}
+
+/// Route a capture: reminder phrasing wins, then an explicit mention of a
+/// known person (`@Sam ...`, `Sam Lee: ...`, `note about Sam ...`), and
+/// anything else is a journal entry
+pub(crate) fn classify(text: &str, relationships: &[Relationship], now: i64) -> CaptureRoute {
+    let text = text.trim();
+    let lower = text.to_lowercase();
+
+    if let Some(prefix) = REMINDER_PREFIXES.iter().find(|p| lower.starts_with(*p)) {
+        let rest = trim_separator(text.get(prefix.len()..).unwrap_or_default());
+        if !rest.is_empty() {
+            let (title, due_at) = reminder_due(rest, now);
+            return CaptureRoute::Reminder { title, due_at };
+        }
+    }
+
+    let mention = if let Some(rest) = text.strip_prefix('@') {
+        match_person(rest, relationships)
+    } else if let Some(m) = NOTE_PREFIX.find(text) {
+        match_person(&text[m.end()..], relationships)
+    } else {
+        match_person(text, relationships).filter(|(_, rest)| rest.starts_with(':'))
+    };
+    if let Some((person, rest)) = mention {
+        let body = trim_separator(rest);
+        return CaptureRoute::RelationshipNote {
+            relationship_id: person.id.clone(),
+            name: person.name.clone(),
+            body: if body.is_empty() { text } else { body }.to_string(),
+        };
+    }
+
+    CaptureRoute::Journal
+}
+
+fn saved_shortcut(app: &tauri::AppHandle) -> String {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(SHORTCUT_KEY))
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_SHORTCUT.to_string())
+}
+
+/// Register the saved (or default) shortcut at startup. Another app may
+/// already own it; that's logged rather than failing startup.
+pub fn register_shortcut(app: &tauri::AppHandle) {
+    let shortcut = saved_shortcut(app);
+    if let Err(e) = app.global_shortcut().register(shortcut.as_str()) {
+        app_log!("[QuickCapture] Failed to register {}: {}", shortcut, e);
+    }
+}
+
+/// Show the capture window, creating it on first use
+pub fn open_window(app: &tauri::AppHandle) -> Result<(), String> {
+    if app.state::<AppLock>().is_locked() {
+        app_log!("[QuickCapture] Ignoring shortcut while locked");
+        return Ok(());
+    }
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        window
+            .show()
+            .and_then(|_| window.set_focus())
+            .map_err(|e| format!("Failed to show quick capture: {}", e))?;
+        return Ok(());
+    }
+
+    let mut window = tauri::WebviewWindowBuilder::new(
+        app,
+        WINDOW_LABEL,
+        tauri::WebviewUrl::App("/quick-capture".into()),
+    )
+    .title("Quick capture")
+    .inner_size(560.0, 140.0)
+    .decorations(false)
+    .always_on_top(true)
+    .resizable(false)
+    .skip_taskbar(true)
+    .center()
+    .focused(true);
+    if let Some(dir) = paths::webview_data_dir() {
+        window = window.data_directory(dir);
+    }
+    window
+        .build()
+        .map_err(|e| format!("Failed to open quick capture: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn open_quick_capture(app: tauri::AppHandle) -> Result<(), String> {
+    open_window(&app)
+}
+
+/// Clipboard text to prefill the capture with. `None` unless the user opted
+/// in to clipboard watching, or when the clipboard holds no usable text.
+#[tauri::command]
+#[specta::specta]
+pub async fn quick_capture_clipboard(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    if !consent::is_granted(&app, Feature::ClipboardWatching) {
+        return Ok(None);
+    }
+    Ok(app
+        .clipboard()
+        .read_text()
+        .ok()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty() && text.chars().count() <= MAX_CAPTURE_CHARS))
+}
+
+/// Where `text` would go, so the capture window can show it while typing
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_quick_capture(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    text: String,
+) -> Result<CaptureRoute, String> {
+    let relationships = cached_relationships(&app, &db).await.unwrap_or_default();
+    Ok(classify(&text, &relationships, now_secs()))
+}
+
+/// Route the capture, save it, and close the capture window
+#[tauri::command]
+#[specta::specta]
+pub async fn submit_quick_capture(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    text: String,
+) -> Result<CaptureResult, AppError> {
+    let text = text.trim().to_string();
+    if text.is_empty() || text.chars().count() > MAX_CAPTURE_CHARS {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid capture: must be between 1 and {} characters",
+                MAX_CAPTURE_CHARS
+            ),
+        ));
+    }
+
+    // Offline with nothing cached just means no person can be matched
+    let relationships = match cached_relationships(&app, &db).await {
+        Ok(relationships) => relationships,
+        Err(e) => {
+            app_log!("[QuickCapture] Relationships unavailable: {}", e);
+            Vec::new()
+        }
+    };
+    let route = classify(&text, &relationships, now_secs());
+
+    let id = match &route {
+        CaptureRoute::Journal => journal::capture_entry(&app, &text)?.id,
+        CaptureRoute::RelationshipNote {
+            relationship_id,
+            body,
+            ..
+        } => {
+            add_relationship_note(
+                app.clone(),
+                app.state::<Database>(),
+                relationship_id.clone(),
+                body.clone(),
+                None,
+            )
+            .await?
+            .id
+        }
+        CaptureRoute::Reminder { title, due_at } => {
+            create_reminder(
+                app.clone(),
+                app.state::<Database>(),
+                title.clone(),
+                None,
+                *due_at,
+                None,
+                None,
+                None,
+            )
+            .await?
+            .id
+        }
+    };
+
+    let result = CaptureResult { route, id };
+    app_log!("[QuickCapture] Saved {}", result.id);
+    events::emit_app_event(&app, AppEvent::QuickCaptureSaved(result.clone()));
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.close();
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_quick_capture_settings(
+    app: tauri::AppHandle,
+) -> Result<QuickCaptureSettings, String> {
+    Ok(QuickCaptureSettings {
+        shortcut: saved_shortcut(&app),
+    })
+}
+
+/// Swap the global shortcut. The new one is registered before the old one
+/// is released, so a conflict leaves the current shortcut working.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_quick_capture_settings(
+    app: tauri::AppHandle,
+    settings: QuickCaptureSettings,
+) -> Result<QuickCaptureSettings, AppError> {
+    let shortcut: Shortcut = settings.shortcut.parse().map_err(|e| {
+        AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Invalid shortcut '{}': {}", settings.shortcut, e),
+        )
+    })?;
+    let previous = saved_shortcut(&app);
+    if previous != settings.shortcut {
+        app.global_shortcut()
+            .register(shortcut)
+            .map_err(|e| format!("Failed to register shortcut: {}", e))?;
+        let _ = app.global_shortcut().unregister(previous.as_str());
+    }
+
+    let store = stores::open(&app, stores::SETTINGS_STORE)?;
+    store.set(SHORTCUT_KEY, serde_json::json!(settings.shortcut));
+    stores::save(&app, stores::SETTINGS_STORE)?;
+    app_log!("[QuickCapture] Shortcut set to {}", settings.shortcut);
+    Ok(settings)
+}
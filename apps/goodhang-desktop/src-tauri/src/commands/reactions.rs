@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::db::now_secs;
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::notifications;
+use crate::outbox;
+
+/// Outbox task sending one reaction
+pub const REACTION_TASK: &str = "reaction";
+// Long enough for ZWJ sequences with skin tones and for tag flags
+const MAX_EMOJI_CHARS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Reaction {
+    /// Client-generated so retries from the outbox are idempotent
+    pub id: String,
+    /// The hang, journal share or message being reacted to
+    #[serde(rename = "targetId")]
+    pub target_id: String,
+    pub emoji: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ReactionReceivedEvent {
+    pub id: String,
+    #[serde(rename = "targetId")]
+    pub target_id: String,
+    pub emoji: String,
+    #[serde(rename = "fromUserId")]
+    pub from_user_id: String,
+    #[serde(default, rename = "fromName")]
+    pub from_name: Option<String>,
+    #[serde(default, rename = "createdAt")]
+    pub created_at: Option<String>,
+}
+
+const ZWJ: char = '\u{200D}';
+const KEYCAP: char = '\u{20E3}';
+const VARIATION_SELECTOR: char = '\u{FE0F}';
+
+/// Extended_Pictographic, as far as emoji pickers use it
+fn is_pictographic(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x00A9 | 0x00AE | 0x203C | 0x2049 | 0x2122 | 0x2139
+            | 0x2194..=0x21AA
+            | 0x231A..=0x23FF
+            | 0x24C2
+            | 0x25AA..=0x27BF
+            | 0x2934..=0x2935
+            | 0x2B05..=0x2B55
+            | 0x3030 | 0x303D | 0x3297 | 0x3299
+            | 0x1F000..=0x1FAFF
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+/// Characters that only modify the emoji before them: presentation
+/// selectors, skin tones and the tags of subdivision flags
+fn is_modifier(c: char) -> bool {
+    matches!(
+        c as u32,
+        0xFE0E | 0xFE0F | 0x1F3FB..=0x1F3FF | 0xE0020..=0xE007F
+    )
+}
+
+/// One emoji in a ZWJ sequence: a pictograph or flag with its modifiers
+fn is_emoji_element(element: &str) -> bool {
+    let mut chars = element.chars();
+    match chars.next() {
+        Some(c) if is_regional_indicator(c) => {
+            chars.next().is_some_and(is_regional_indicator) && chars.next().is_none()
+        }
+        Some(c) if is_pictographic(c) => chars.all(is_modifier),
+        _ => false,
+    }
+}
+
+/// A single emoji grapheme: a keycap such as 1️⃣, or pictographs and
+/// flags joined by ZWJ. Anything else, including plain text, is refused.
+fn validate_emoji(emoji: &str) -> Result<(), AppError> {
+    let keycap = match emoji.chars().collect::<Vec<_>>()[..] {
+        [base, VARIATION_SELECTOR, KEYCAP] | [base, KEYCAP] => {
+            base.is_ascii_digit() || base == '#' || base == '*'
+        }
+        _ => false,
+    };
+    let sequence = emoji.split(ZWJ).all(is_emoji_element);
+    if emoji.chars().count() > MAX_EMOJI_CHARS || !(keycap || sequence) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid emoji: expected a single emoji",
+        ));
+    }
+    Ok(())
+}
+
+/// React to something a hang participant shared. Queued locally, so a
+/// reaction sent offline is delivered once the app is back online.
+#[tauri::command]
+#[specta::specta]
+pub async fn send_reaction(
+    app: tauri::AppHandle,
+    target_id: String,
+    emoji: String,
+) -> Result<Reaction, AppError> {
+    let emoji = emoji.trim().to_string();
+    validate_emoji(&emoji)?;
+    if target_id.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid target: targetId is required",
+        ));
+    }
+    load_session(&app)?.ok_or_else(|| "Not signed in".to_string())?;
+
+    let reaction = Reaction {
+        id: uuid::Uuid::new_v4().to_string(),
+        target_id,
+        emoji,
+        created_at: now_secs(),
+    };
+    outbox::enqueue(
+        &app,
+        REACTION_TASK,
+        None,
+        serde_json::to_value(&reaction)
+            .map_err(|e| format!("Failed to serialize reaction: {}", e))?,
+    )?;
+    Ok(reaction)
+}
+
+/// Outbox handler: post one reaction
+pub(crate) async fn deliver_reaction(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let token = load_session(app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    app.state::<Api>()
+        .send(
+            ApiRequest::post("/api/reactions")
+                .bearer(&token)
+                .json(payload)?,
+        )
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Realtime handler for someone reacting to the user's content
+pub(crate) fn handle_reaction_received(app: &tauri::AppHandle, data: serde_json::Value) {
+    let event: ReactionReceivedEvent = match serde_json::from_value(data) {
+        Ok(event) => event,
+        Err(e) => {
+            app_log!("[Reactions] Skipping malformed reaction: {}", e);
+            return;
+        }
+    };
+    let who = event.from_name.as_deref().unwrap_or("Someone");
+    notifications::notify(app, &format!("{} reacted {}", who, event.emoji), "");
+    events::emit_app_event(app, AppEvent::ReactionReceived(event));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_single_emoji() {
+        for emoji in [
+            "👍",
+            "❤️",
+            "👍🏽",
+            "1️⃣",
+            "#️⃣",
+            "🇳🇱",
+            "👩‍💻",
+            "👨‍👩‍👧‍👦",
+            "🏴\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F}",
+        ] {
+            assert!(validate_emoji(emoji).is_ok(), "{}", emoji);
+        }
+    }
+
+    #[test]
+    fn rejects_text_and_several_emoji() {
+        for text in [
+            "",
+            "1",
+            "#",
+            "ok",
+            "👍 👍",
+            "👍👍",
+            "🇳",
+            "a\u{20E3}",
+            "\u{200D}",
+            "👍\u{200D}",
+        ] {
+            assert!(validate_emoji(text).is_err(), "{:?}", text);
+        }
+    }
+}
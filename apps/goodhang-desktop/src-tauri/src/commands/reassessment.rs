@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::logging::app_log;
+use crate::network;
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const REASSESSMENT_KEY: &str = "reassessment";
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+// Used when the API doesn't report a window of its own
+const DEFAULT_INTERVAL_SECS: i64 = 182 * 24 * 60 * 60;
+const SECS_PER_MONTH: i64 = 30 * 24 * 60 * 60;
+// The reminder engine keeps one reassessment reminder, moved as windows change
+const REMINDER_KEY: &str = "reassessment";
+
+/// Locally tracked so reminders work from the last known completion even
+/// when the API can't be reached
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ReassessmentState {
+    #[serde(default, rename = "lastCompletedAt")]
+    last_completed_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EligibilityResponse {
+    #[serde(default, rename = "lastCompletedAt")]
+    last_completed_at: Option<i64>,
+    #[serde(default, rename = "eligibleFrom")]
+    eligible_from: Option<i64>,
+    #[serde(default, rename = "eligibleUntil")]
+    eligible_until: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ReassessmentStatus {
+    /// Unix seconds
+    #[serde(rename = "lastCompletedAt")]
+    pub last_completed_at: Option<i64>,
+    #[serde(rename = "eligibleFrom")]
+    pub eligible_from: Option<i64>,
+    #[serde(rename = "eligibleUntil")]
+    pub eligible_until: Option<i64>,
+    pub eligible: bool,
+    #[serde(rename = "monthsSinceLast")]
+    pub months_since_last: Option<i64>,
+}
+
+fn load_state(app: &tauri::AppHandle) -> ReassessmentState {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(user_data::key(REASSESSMENT_KEY)))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(app: &tauri::AppHandle, state: &ReassessmentState) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    store.set(
+        user_data::key(REASSESSMENT_KEY),
+        serde_json::to_value(state).map_err(|e| format!("Failed to serialize state: {}", e))?,
+    );
+    stores::save(app, stores::SETTINGS_STORE)
+}
+
+/// Note a newly completed assessment, starting a fresh reminder window
+pub(crate) fn record_completion(app: &tauri::AppHandle) {
+    let state = ReassessmentState {
+        last_completed_at: Some(now_secs()),
+    };
+    if let Err(e) = save_state(app, &state) {
+        app_log!("[Reassessment] Failed to record completion: {}", e);
+    }
+}
+
+/// Drop local tracking, e.g. on secure logout
+pub(crate) fn forget(app: &tauri::AppHandle) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    if store.delete(user_data::key(REASSESSMENT_KEY)) {
+        stores::save(app, stores::SETTINGS_STORE)?;
+    }
+    Ok(())
+}
+
+async fn fetch_eligibility(
+    app: &tauri::AppHandle,
+    token: &str,
+) -> Result<EligibilityResponse, String> {
+    app.state::<Api>()
+        .send(ApiRequest::get("/api/assessment/reassessment").bearer(token))
+        .await?
+        .error_for_status()?
+        .json("/api/assessment/reassessment")
+}
+
+/// Combine the API's window with local tracking. Falls back to local state
+/// alone while offline.
+async fn resolve_status(app: &tauri::AppHandle) -> Result<ReassessmentStatus, String> {
+    let mut state = load_state(app);
+    let remote = match load_session(app)? {
+        Some(session) if network::is_online() => {
+            match fetch_eligibility(app, &session.token).await {
+                Ok(remote) => Some(remote),
+                Err(e) => {
+                    app_log!("[Reassessment] Eligibility check failed: {}", e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    if let Some(last) = remote.as_ref().and_then(|r| r.last_completed_at) {
+        if state.last_completed_at != Some(last) {
+            state.last_completed_at = Some(last);
+            save_state(app, &state)?;
+        }
+    }
+
+    let last = state.last_completed_at;
+    let eligible_from = remote
+        .as_ref()
+        .and_then(|r| r.eligible_from)
+        .or_else(|| last.map(|t| t + DEFAULT_INTERVAL_SECS));
+    let eligible_until = remote.as_ref().and_then(|r| r.eligible_until);
+    let now = now_secs();
+
+    Ok(ReassessmentStatus {
+        last_completed_at: last,
+        eligible_from,
+        eligible_until,
+        eligible: eligible_from.is_some_and(|from| from <= now)
+            && !eligible_until.is_some_and(|until| now > until),
+        months_since_last: last.map(|t| (now - t) / SECS_PER_MONTH),
+    })
+}
+
+/// Check eligibility periodically and keep a reminder scheduled for the
+/// start of the next window; delivery is up to the reminder engine
+pub fn spawn_reassessment_reminders(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let status = match resolve_status(&app).await {
+                Ok(status) => status,
+                Err(e) => {
+                    app_log!("[Reassessment] Status check failed: {}", e);
+                    continue;
+                }
+            };
+            let Some(eligible_from) = status.eligible_from else {
+                continue;
+            };
+            if status.eligible_until.is_some_and(|until| now_secs() > until) {
+                continue;
+            }
+
+            let months = status
+                .last_completed_at
+                .map(|last| (eligible_from - last) / SECS_PER_MONTH);
+            let body = match months {
+                Some(months) if months > 0 => format!(
+                    "It's been {} months — retake your assessment to see how you've changed.",
+                    months
+                ),
+                _ => "Retake your assessment to see how you've changed.".to_string(),
+            };
+            if let Err(e) = super::reminders::schedule(
+                &app.state::<Database>(),
+                "reassessment",
+                REMINDER_KEY,
+                "Time for a fresh look",
+                &body,
+                eligible_from,
+            ) {
+                app_log!("[Reassessment] Failed to schedule reminder: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_reassessment_status(app: tauri::AppHandle) -> Result<ReassessmentStatus, String> {
+    resolve_status(&app).await
+}
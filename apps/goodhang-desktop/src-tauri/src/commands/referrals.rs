@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::api::{self, require_token, Api, ApiRequest};
+use crate::logging::app_log;
+
+#[derive(Debug, Deserialize)]
+struct ReferralCodeResponse {
+    code: String,
+    /// Web landing page, when the server has a preferred one
+    #[serde(default, rename = "shareUrl")]
+    share_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ReferralLink {
+    pub code: String,
+    /// Opens the desktop app directly if installed
+    #[serde(rename = "deepLink")]
+    pub deep_link: String,
+    /// Works for anyone, with or without the app
+    #[serde(rename = "webUrl")]
+    pub web_url: String,
+    /// Whether the web URL made it to the clipboard
+    pub copied: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct Referral {
+    /// pending | redeemed | expired
+    pub status: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "redeemedAt")]
+    pub redeemed_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct ReferralStats {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub shared: u32,
+    #[serde(default)]
+    pub redeemed: u32,
+    #[serde(default)]
+    pub referrals: Vec<Referral>,
+}
+
+/// Get a referral code, build shareable links for it, and copy the web link
+/// to the clipboard
+#[tauri::command]
+#[specta::specta]
+pub async fn generate_referral_link(app: tauri::AppHandle) -> Result<ReferralLink, String> {
+    let token = require_token(&app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(ApiRequest::post("/api/referrals").bearer(&token))
+        .await?;
+    let referral: ReferralCodeResponse = response.error_for_status()?.json("/api/referrals")?;
+
+    let web_url = referral
+        .share_url
+        .unwrap_or_else(|| format!("{}/r/{}", api::base_url(), referral.code));
+    let copied = match app.clipboard().write_text(web_url.clone()) {
+        Ok(()) => true,
+        Err(e) => {
+            app_log!("[Referrals] Failed to copy link: {}", e);
+            false
+        }
+    };
+
+    app_log!("[Referrals] Referral link generated");
+    Ok(ReferralLink {
+        deep_link: format!("goodhang://referral/{}", referral.code),
+        code: referral.code,
+        web_url,
+        copied,
+    })
+}
+
+/// How many shared referrals have been redeemed
+#[tauri::command]
+#[specta::specta]
+pub async fn get_referral_stats(app: tauri::AppHandle) -> Result<ReferralStats, String> {
+    let token = require_token(&app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(ApiRequest::get("/api/referrals/stats").bearer(&token))
+        .await?;
+
+    response.error_for_status()?.json("/api/referrals/stats")
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use super::relationship_notes::{notes_for, RelationshipNote};
+use crate::api::{require_token, Api, ApiRequest};
+use crate::db::Database;
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+use crate::summary_cache;
+
+const BRIEF_CACHE_KIND: &str = "relationship_brief";
+
+/// Public profile data returned by the intelligence gatherer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedInProfile {
+    #[serde(rename = "profileUrl")]
+    pub profile_url: String,
+    pub name: String,
+    #[serde(default)]
+    pub headline: Option<String>,
+    #[serde(default)]
+    pub company: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntityResponse {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntityVersion {
+    #[serde(rename = "updatedAt")]
+    updated_at: String,
+}
+
+/// Everything needed to prepare for meeting someone: GFT's strategist
+/// output plus the user's own private notes
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct RelationshipBrief {
+    #[serde(rename = "entityId")]
+    pub entity_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub headline: Option<String>,
+    #[serde(default)]
+    pub company: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default, rename = "talkingPoints")]
+    pub talking_points: Vec<String>,
+    #[serde(default, rename = "sharedContext")]
+    pub shared_context: Vec<String>,
+    #[serde(default, rename = "lastInteractionAt")]
+    pub last_interaction_at: Option<String>,
+    /// Local notes; never sent by or to the brief endpoint
+    #[serde(default, skip_deserializing)]
+    pub notes: Vec<RelationshipNote>,
+}
+
+/// `https://www.linkedin.com/in/{slug}` for any public profile URL, so the
+/// same person always maps to the same entity
+fn canonical_profile_url(url: &str) -> Result<String, AppError> {
+    let invalid = || {
+        AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid LinkedIn URL: expected a linkedin.com/in/ profile link",
+        )
+    };
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|_| invalid())?;
+    let host = parsed.host_str().unwrap_or_default();
+    if !matches!(parsed.scheme(), "http" | "https")
+        || !(host == "linkedin.com" || host.ends_with(".linkedin.com"))
+    {
+        return Err(invalid());
+    }
+    let mut segments = parsed.path_segments().ok_or_else(invalid)?;
+    match (segments.next(), segments.next()) {
+        (Some("in"), Some(slug)) if !slug.is_empty() => Ok(format!(
+            "https://www.linkedin.com/in/{}",
+            slug.to_lowercase()
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+/// `https://x.com/{handle}` for a Twitter/X profile URL
+fn canonical_x_url(parsed: &reqwest::Url) -> Option<String> {
+    let host = parsed.host_str()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let host = host.strip_prefix("mobile.").unwrap_or(host);
+    if host != "x.com" && host != "twitter.com" {
+        return None;
+    }
+    let handle = parsed.path_segments()?.next()?;
+    let valid = !handle.is_empty()
+        && handle.len() <= 15
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| format!("https://x.com/{}", handle.to_lowercase()))
+}
+
+/// GFT's brief for an entity, with the user's local notes attached. The
+/// brief is regenerated only when the entity or the notes changed since it
+/// was cached.
+pub(crate) async fn assemble_brief(
+    app: &tauri::AppHandle,
+    token: &str,
+    entity_id: &str,
+) -> Result<RelationshipBrief, String> {
+    let db = app.state::<Database>();
+    let api = app.state::<Api>();
+    let notes = notes_for(&db, entity_id)?;
+
+    let version: EntityVersion = api
+        .send(ApiRequest::get(format!("/api/gft/entities/{}", entity_id)).bearer(token))
+        .await?
+        .error_for_status()?
+        .json("/api/gft/entities/{id}")?;
+    let hash = summary_cache::input_hash(
+        std::iter::once(version.updated_at.as_str())
+            .chain(notes.iter().flat_map(|n| [n.id.as_str(), n.body.as_str()])),
+    );
+
+    let cached = summary_cache::get(&db, BRIEF_CACHE_KIND, entity_id, &hash)
+        .and_then(|json| serde_json::from_str::<RelationshipBrief>(&json).ok());
+    let mut brief = match cached {
+        Some(brief) => brief,
+        None => {
+            let brief: RelationshipBrief = api
+                .send(
+                    ApiRequest::get(format!("/api/gft/entities/{}/brief", entity_id)).bearer(token),
+                )
+                .await?
+                .error_for_status()?
+                .json("/api/gft/entities/{id}/brief")?;
+            if let Ok(json) = serde_json::to_string(&brief) {
+                if let Err(e) = summary_cache::put(&db, BRIEF_CACHE_KIND, entity_id, &hash, &json) {
+                    app_log!("[RelationshipBrief] Failed to cache brief: {}", e);
+                }
+            }
+            brief
+        }
+    };
+    brief.notes = notes;
+    Ok(brief)
+}
+
+/// The brief for a GFT entity; `refresh` skips the cache
+#[tauri::command]
+#[specta::specta]
+pub async fn get_relationship_brief(
+    app: tauri::AppHandle,
+    entity_id: String,
+    refresh: Option<bool>,
+) -> Result<RelationshipBrief, AppError> {
+    let token = require_token(&app)?;
+    if refresh.unwrap_or(false) {
+        summary_cache::invalidate(&app.state::<Database>(), BRIEF_CACHE_KIND, &entity_id)?;
+    }
+    Ok(assemble_brief(&app, &token, &entity_id).await?)
+}
+
+/// The brief for whoever a LinkedIn or Twitter/X profile URL belongs to, or
+/// `None` when GFT has no entity for them yet
+pub(crate) async fn brief_for_profile_url(
+    app: &tauri::AppHandle,
+    url: &str,
+) -> Result<Option<RelationshipBrief>, AppError> {
+    let profile_url = match canonical_profile_url(url) {
+        Ok(linkedin) => linkedin,
+        Err(_) => reqwest::Url::parse(url.trim())
+            .ok()
+            .as_ref()
+            .and_then(canonical_x_url)
+            .ok_or_else(|| {
+                AppError::new(
+                    ErrorCode::InvalidInput,
+                    "Invalid profile URL: expected a LinkedIn or X profile link",
+                )
+            })?,
+    };
+    let token = require_token(app)?;
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::get("/api/gft/entities/lookup")
+                .query("url", &profile_url)
+                .bearer(&token),
+        )
+        .await?;
+    if response.status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let entity: EntityResponse = response
+        .error_for_status()?
+        .json("/api/gft/entities/lookup")?;
+
+    Ok(Some(assemble_brief(app, &token, &entity.id).await?))
+}
+
+/// "Help me prepare for this person" from a LinkedIn URL: gather the public
+/// profile, create or update the matching GFT entity, and return its brief
+#[tauri::command]
+#[specta::specta]
+pub async fn import_linkedin_profile(
+    app: tauri::AppHandle,
+    url: String,
+) -> Result<RelationshipBrief, AppError> {
+    let profile_url = canonical_profile_url(&url)?;
+    let token = require_token(&app)?;
+    let api = app.state::<Api>();
+
+    let profile: LinkedInProfile = api
+        .send(
+            ApiRequest::post("/api/gather-intelligence")
+                .bearer(&token)
+                .json(&serde_json::json!({ "source": "linkedin", "url": profile_url }))?,
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/gather-intelligence")?;
+
+    // Upserted by profile URL, so re-importing refreshes the same entity
+    let entity: EntityResponse = api
+        .send(
+            ApiRequest::put("/api/gft/entities")
+                .bearer(&token)
+                .json(&serde_json::json!({
+                    "type": "person",
+                    "linkedinUrl": profile_url,
+                    "name": profile.name,
+                    "headline": profile.headline,
+                    "company": profile.company,
+                    "location": profile.location,
+                    "summary": profile.summary,
+                }))?,
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/gft/entities")?;
+    app_log!(
+        "[RelationshipBrief] Imported LinkedIn profile as {}",
+        entity.id
+    );
+
+    Ok(assemble_brief(&app, &token, &entity.id).await?)
+}
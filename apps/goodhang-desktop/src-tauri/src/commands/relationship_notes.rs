@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::embeddings;
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+use crate::outbox;
+use crate::search_index;
+
+const MAX_NOTE_CHARS: usize = 10_000;
+// Shared by the search index and embeddings
+const SOURCE_TYPE: &str = "relationship_note";
+/// Outbox task pushing one note to GFT. Edits carry the replaced body as
+/// `previous` so a rejected push can be rolled back.
+pub const GFT_SYNC_TASK: &str = "relationship_note_gft";
+
+/// Private prep note about a person. Lives in the encrypted local database;
+/// copied to the GuyForThat (GFT) contact only when `syncToGft` is set.
+#[derive(Debug, Serialize, specta::Type)]
+pub struct RelationshipNote {
+    pub id: String,
+    #[serde(rename = "relationshipId")]
+    pub relationship_id: String,
+    pub body: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "syncToGft")]
+    pub sync_to_gft: bool,
+    /// local | pending | synced | failed
+    #[serde(rename = "syncStatus")]
+    pub sync_status: String,
+    #[serde(rename = "syncError")]
+    pub sync_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SyncResponse {
+    id: String,
+}
+
+fn row_to_note(row: &rusqlite::Row) -> rusqlite::Result<RelationshipNote> {
+    Ok(RelationshipNote {
+        id: row.get("id")?,
+        relationship_id: row.get("relationship_id")?,
+        body: row.get("body")?,
+        created_at: row.get("created_at")?,
+        sync_to_gft: row.get("sync_to_gft")?,
+        sync_status: row.get("sync_status")?,
+        sync_error: row.get("sync_error")?,
+    })
+}
+
+fn get_note(db: &Database, id: &str) -> Result<RelationshipNote, String> {
+    db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT * FROM relationship_notes WHERE id = ?1",
+            [id],
+            row_to_note,
+        )
+    })
+}
+
+fn validate_body(body: String) -> Result<String, AppError> {
+    let body = body.trim().to_string();
+    if body.is_empty() || body.chars().count() > MAX_NOTE_CHARS {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid note: must be between 1 and {} characters",
+                MAX_NOTE_CHARS
+            ),
+        ));
+    }
+    Ok(body)
+}
+
+/// Rewrite a note's body and refresh search and embeddings to match
+fn write_body(app: &tauri::AppHandle, note: &RelationshipNote, body: &str) -> Result<(), String> {
+    app.state::<Database>().with_conn(|conn| {
+        conn.execute(
+            "UPDATE relationship_notes SET body = ?1 WHERE id = ?2",
+            rusqlite::params![body, note.id],
+        )
+    })?;
+    search_index::upsert_quietly(app, SOURCE_TYPE, &note.id, &title_for(body), body);
+    embeddings::index_in_background(
+        app,
+        SOURCE_TYPE,
+        note.id.clone(),
+        body.to_string(),
+        note.created_at,
+    );
+    Ok(())
+}
+
+/// First line of the note, used as its search result title
+fn title_for(body: &str) -> String {
+    body.lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or_default()
+        .chars()
+        .take(80)
+        .collect()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn add_relationship_note(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    relationship_id: String,
+    body: String,
+    sync_to_gft: Option<bool>,
+) -> Result<RelationshipNote, AppError> {
+    let body = validate_body(body)?;
+    let sync_to_gft = sync_to_gft.unwrap_or(false);
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = now_secs();
+
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO relationship_notes
+             (id, relationship_id, body, created_at, sync_to_gft, sync_status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                id,
+                relationship_id,
+                body,
+                now,
+                sync_to_gft,
+                if sync_to_gft { "pending" } else { "local" }
+            ],
+        )
+    })?;
+    app_log!("[RelationshipNotes] Note added for {}", relationship_id);
+
+    // Makes the note available to search and to the relationship brief
+    search_index::upsert_quietly(&app, SOURCE_TYPE, &id, &title_for(&body), &body);
+    embeddings::index_in_background(&app, SOURCE_TYPE, id.clone(), body, now);
+
+    if sync_to_gft {
+        outbox::enqueue(
+            &app,
+            GFT_SYNC_TASK,
+            Some(&format!("relationship_note:{}", id)),
+            serde_json::json!({ "noteId": id }),
+        )?;
+    }
+
+    Ok(get_note(&db, &id)?)
+}
+
+/// Edit a note. The local copy changes right away; for notes synced to GFT
+/// the push is queued, and a rejected push restores the old body and emits
+/// `write-reverted`.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_relationship_note(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+    body: String,
+) -> Result<RelationshipNote, AppError> {
+    let body = validate_body(body)?;
+    let note = get_note(&db, &id)?;
+    if note.body == body {
+        return Ok(note);
+    }
+
+    write_body(&app, &note, &body)?;
+    if note.sync_to_gft {
+        db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE relationship_notes SET sync_status = 'pending', sync_error = NULL
+                 WHERE id = ?1",
+                [&id],
+            )
+        })?;
+        outbox::enqueue(
+            &app,
+            GFT_SYNC_TASK,
+            Some(&format!("relationship_note:{}", id)),
+            serde_json::json!({ "noteId": id, "previous": note.body }),
+        )?;
+    }
+    app_log!("[RelationshipNotes] Note {} edited", id);
+    Ok(get_note(&db, &id)?)
+}
+
+/// Notes about one person, newest first
+pub(crate) fn notes_for(
+    db: &Database,
+    relationship_id: &str,
+) -> Result<Vec<RelationshipNote>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM relationship_notes WHERE relationship_id = ?1
+             ORDER BY created_at DESC",
+        )?;
+        let rows = stmt.query_map([relationship_id], row_to_note)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })
+}
+
+/// Notes about one person, newest first. Served entirely from the local
+/// database so they're available offline.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_relationship_notes(
+    db: tauri::State<'_, Database>,
+    relationship_id: String,
+) -> Result<Vec<RelationshipNote>, String> {
+    notes_for(&db, &relationship_id)
+}
+
+/// Outbox handler: push one note to its GFT contact and record the outcome
+/// on the note
+pub async fn deliver_note(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let note_id = payload["noteId"]
+        .as_str()
+        .ok_or_else(|| "Invalid task payload: missing noteId".to_string())?;
+    let token = load_session(app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    let db = app.state::<Database>();
+    let Ok(note) = get_note(&db, note_id) else {
+        return Ok(()); // Deleted since it was queued
+    };
+
+    let result = push_note(&app.state::<Api>(), &token, &note).await;
+    let (status, remote_id, error) = match &result {
+        Ok(remote_id) => ("synced", Some(remote_id.as_str()), None),
+        Err(e) => ("failed", None, Some(e.as_str())),
+    };
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE relationship_notes
+             SET sync_status = ?1, remote_id = COALESCE(?2, remote_id), sync_error = ?3
+             WHERE id = ?4",
+            rusqlite::params![status, remote_id, error, note.id],
+        )
+    })?;
+    result.map(|_| ())
+}
+
+/// Outbox revert: put back the body a rejected edit replaced. Returns the
+/// note id.
+pub fn revert_note(app: &tauri::AppHandle, payload: &serde_json::Value) -> Result<String, String> {
+    let (Some(note_id), Some(previous)) =
+        (payload["noteId"].as_str(), payload["previous"].as_str())
+    else {
+        return Err("Invalid task payload: missing noteId or previous".to_string());
+    };
+    let Ok(note) = get_note(&app.state::<Database>(), note_id) else {
+        return Ok(note_id.to_string()); // Deleted since it was queued
+    };
+    write_body(app, &note, previous)?;
+    Ok(note.id)
+}
+
+async fn push_note(api: &Api, token: &str, note: &RelationshipNote) -> Result<String, String> {
+    let response = api
+        .send(
+            ApiRequest::post(format!("/api/gft/contacts/{}/notes", note.relationship_id))
+                .bearer(token)
+                .json(&serde_json::json!({
+                    "clientId": note.id,
+                    "body": note.body,
+                    "createdAt": note.created_at,
+                }))?,
+        )
+        .await?
+        .error_for_status()?;
+
+    response
+        .json::<SyncResponse>("/api/gft/contacts/{id}/notes")
+        .map(|r| r.id)
+}
@@ -0,0 +1,432 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::notifications;
+use crate::outbox;
+
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_SNOOZE_MINUTES: u32 = 7 * 24 * 60;
+const MAX_TITLE_CHARS: usize = 200;
+/// Kinds the frontend may create; others are scheduled by the app itself
+const USER_KINDS: &[&str] = &["user", "reconnect"];
+/// Outbox task sending a user reminder's current state to the API
+pub const REMINDER_TASK: &str = "reminder_sync";
+
+/// Repeat every `every` days, weeks, or calendar months
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RecurrenceRule {
+    pub every: u32,
+    /// day | week | month
+    pub unit: String,
+}
+
+impl RecurrenceRule {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.every == 0 || !["day", "week", "month"].contains(&self.unit.as_str()) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid recurrence: expected a positive interval of day, week or month",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Reminder {
+    pub id: String,
+    /// user | reconnect | reassessment
+    pub kind: String,
+    pub title: String,
+    pub body: Option<String>,
+    /// Unix seconds
+    #[serde(rename = "dueAt")]
+    pub due_at: i64,
+    pub recurrence: Option<RecurrenceRule>,
+    #[serde(rename = "snoozedUntil")]
+    pub snoozed_until: Option<i64>,
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<i64>,
+    /// Person a reconnect nudge is about
+    #[serde(rename = "relationshipId")]
+    pub relationship_id: Option<String>,
+}
+
+fn row_to_reminder(row: &rusqlite::Row) -> rusqlite::Result<Reminder> {
+    let recurrence: Option<String> = row.get("recurrence")?;
+    Ok(Reminder {
+        id: row.get("id")?,
+        kind: row.get("kind")?,
+        title: row.get("title")?,
+        body: row.get("body")?,
+        due_at: row.get("due_at")?,
+        recurrence: recurrence.and_then(|r| serde_json::from_str(&r).ok()),
+        snoozed_until: row.get("snoozed_until")?,
+        completed_at: row.get("completed_at")?,
+        relationship_id: row.get("relationship_id")?,
+    })
+}
+
+fn get_reminder(db: &Database, id: &str) -> Result<Reminder, String> {
+    db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT * FROM reminders WHERE id = ?1",
+            [id],
+            row_to_reminder,
+        )
+    })
+}
+
+/// Queue the reminder's new state for the API, remembering `previous` (or
+/// that it didn't exist) so a rejected write can be rolled back. Reminders
+/// the app schedules itself stay local.
+fn queue_sync(
+    app: &tauri::AppHandle,
+    previous: Option<&Reminder>,
+    reminder: &Reminder,
+) -> Result<(), String> {
+    if !USER_KINDS.contains(&reminder.kind.as_str()) {
+        return Ok(());
+    }
+    outbox::enqueue(
+        app,
+        REMINDER_TASK,
+        Some(&format!("reminder:{}", reminder.id)),
+        serde_json::json!({ "reminder": reminder, "previous": previous }),
+    )?;
+    Ok(())
+}
+
+/// Outbox handler: store a reminder's state on the server
+pub async fn deliver_reminder(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let id = payload["reminder"]["id"]
+        .as_str()
+        .ok_or_else(|| "Invalid task payload: missing reminder".to_string())?;
+    let token = load_session(app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    app.state::<Api>()
+        .send(
+            ApiRequest::put(format!("/api/reminders/{}", id))
+                .bearer(&token)
+                .json(&payload["reminder"])?,
+        )
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Outbox revert: restore a reminder to its state before a rejected write,
+/// or remove it if the write created it. Returns the reminder id.
+pub fn revert_reminder(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<String, String> {
+    let id = payload["reminder"]["id"]
+        .as_str()
+        .ok_or_else(|| "Invalid task payload: missing reminder".to_string())?;
+    let previous: Option<Reminder> = serde_json::from_value(payload["previous"].clone())
+        .map_err(|e| format!("Invalid task payload: {}", e))?;
+    let recurrence = previous
+        .as_ref()
+        .and_then(|r| r.recurrence.as_ref())
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| format!("Failed to serialize recurrence: {}", e))?;
+
+    app.state::<Database>().with_conn(|conn| match &previous {
+        Some(r) => conn.execute(
+            "UPDATE reminders
+             SET title = ?1, body = ?2, due_at = ?3, recurrence = ?4,
+                 snoozed_until = ?5, completed_at = ?6
+             WHERE id = ?7",
+            rusqlite::params![
+                r.title,
+                r.body,
+                r.due_at,
+                recurrence,
+                r.snoozed_until,
+                r.completed_at,
+                id
+            ],
+        ),
+        None => conn.execute("DELETE FROM reminders WHERE id = ?1", [id]),
+    })?;
+    Ok(id.to_string())
+}
+
+/// First occurrence of `rule` after `now`, stepping from `due_at`. Months
+/// use SQLite's date arithmetic rather than a fixed 30 days.
+fn next_occurrence(
+    db: &Database,
+    due_at: i64,
+    rule: &RecurrenceRule,
+    now: i64,
+) -> Result<i64, String> {
+    let days = match rule.unit.as_str() {
+        "day" => Some(rule.every as i64),
+        "week" => Some(rule.every as i64 * 7),
+        _ => None,
+    };
+    if let Some(days) = days {
+        let step = days * 24 * 60 * 60;
+        let missed = ((now - due_at) / step).max(0) + 1;
+        return Ok(due_at + missed * step);
+    }
+
+    db.with_conn(|conn| {
+        let mut months = rule.every;
+        loop {
+            let next: i64 = conn.query_row(
+                "SELECT CAST(strftime('%s', ?1, 'unixepoch', ?2) AS INTEGER)",
+                rusqlite::params![due_at, format!("+{} months", months)],
+                |row| row.get(0),
+            )?;
+            if next > now {
+                return Ok(next);
+            }
+            months += rule.every;
+        }
+    })
+}
+
+/// Create or move an app-scheduled reminder identified by `source_key`.
+/// Rescheduling to a new time re-arms it; the same time keeps any
+/// completion or snooze the user already applied.
+pub(crate) fn schedule(
+    db: &Database,
+    kind: &str,
+    source_key: &str,
+    title: &str,
+    body: &str,
+    due_at: i64,
+) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO reminders (id, kind, source_key, title, body, due_at, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(source_key) DO UPDATE SET
+                title = excluded.title,
+                body = excluded.body,
+                completed_at = CASE WHEN due_at = excluded.due_at THEN completed_at END,
+                notified_at = CASE WHEN due_at = excluded.due_at THEN notified_at END,
+                snoozed_until = CASE WHEN due_at = excluded.due_at THEN snoozed_until END,
+                due_at = excluded.due_at",
+            rusqlite::params![
+                uuid::Uuid::new_v4().to_string(),
+                kind,
+                source_key,
+                title,
+                body,
+                due_at,
+                now_secs()
+            ],
+        )
+    })?;
+    Ok(())
+}
+
+/// Notify about every reminder that has come due and hasn't been shown yet
+fn deliver_due(app: &tauri::AppHandle) -> Result<(), String> {
+    let db = app.state::<Database>();
+    let now = now_secs();
+    let due = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM reminders
+             WHERE completed_at IS NULL
+               AND COALESCE(snoozed_until, due_at) <= ?1
+               AND (notified_at IS NULL OR notified_at < COALESCE(snoozed_until, due_at))",
+        )?;
+        let rows = stmt.query_map([now], row_to_reminder)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })?;
+
+    for reminder in due {
+        notifications::notify(app, &reminder.title, reminder.body.as_deref().unwrap_or(""));
+        db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE reminders SET notified_at = ?1 WHERE id = ?2",
+                rusqlite::params![now, reminder.id],
+            )
+        })?;
+        app_log!("[Reminders] Delivered {} ({})", reminder.id, reminder.kind);
+        events::emit_app_event(app, AppEvent::ReminderDue(reminder));
+    }
+    Ok(())
+}
+
+pub fn spawn_reminder_delivery(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = deliver_due(&app) {
+                app_log!("[Reminders] Delivery failed: {}", e);
+            }
+            tokio::time::sleep(DELIVERY_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_reminder(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    title: String,
+    body: Option<String>,
+    due_at: i64,
+    recurrence: Option<RecurrenceRule>,
+    kind: Option<String>,
+    relationship_id: Option<String>,
+) -> Result<Reminder, AppError> {
+    let kind = kind.unwrap_or_else(|| "user".to_string());
+    if !USER_KINDS.contains(&kind.as_str()) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid reminder kind '{}': expected one of {}",
+                kind,
+                USER_KINDS.join(", ")
+            ),
+        ));
+    }
+    let title = title.trim().to_string();
+    if title.is_empty() || title.chars().count() > MAX_TITLE_CHARS {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid reminder: title must be between 1 and {} characters",
+                MAX_TITLE_CHARS
+            ),
+        ));
+    }
+    if let Some(rule) = &recurrence {
+        rule.validate()?;
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let recurrence_json = recurrence
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| format!("Failed to serialize recurrence: {}", e))?;
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO reminders
+             (id, kind, title, body, due_at, recurrence, relationship_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                id,
+                kind,
+                title,
+                body,
+                due_at,
+                recurrence_json,
+                relationship_id,
+                now_secs()
+            ],
+        )
+    })?;
+    app_log!("[Reminders] Created {} ({})", id, kind);
+    let reminder = get_reminder(&db, &id)?;
+    queue_sync(&app, None, &reminder)?;
+    Ok(reminder)
+}
+
+/// Open reminders soonest first, optionally including completed ones
+#[tauri::command]
+#[specta::specta]
+pub async fn list_reminders(
+    db: tauri::State<'_, Database>,
+    include_completed: Option<bool>,
+) -> Result<Vec<Reminder>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM reminders
+             WHERE ?1 OR completed_at IS NULL
+             ORDER BY completed_at IS NOT NULL, COALESCE(snoozed_until, due_at) ASC",
+        )?;
+        let rows = stmt.query_map([include_completed.unwrap_or(false)], row_to_reminder)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })
+}
+
+/// Mark a reminder done. Recurring reminders roll forward to their next
+/// occurrence instead of completing.
+#[tauri::command]
+#[specta::specta]
+pub async fn complete_reminder(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+) -> Result<Reminder, String> {
+    let reminder = get_reminder(&db, &id)?;
+    let now = now_secs();
+
+    match &reminder.recurrence {
+        Some(rule) => {
+            let next = next_occurrence(&db, reminder.due_at, rule, now)?;
+            db.with_conn(|conn| {
+                conn.execute(
+                    "UPDATE reminders
+                     SET due_at = ?1, snoozed_until = NULL, notified_at = NULL
+                     WHERE id = ?2",
+                    rusqlite::params![next, id],
+                )
+            })?;
+            app_log!("[Reminders] {} rolled forward to {}", id, next);
+        }
+        None => {
+            db.with_conn(|conn| {
+                conn.execute(
+                    "UPDATE reminders SET completed_at = ?1 WHERE id = ?2",
+                    rusqlite::params![now, id],
+                )
+            })?;
+            app_log!("[Reminders] {} completed", id);
+        }
+    }
+    let updated = get_reminder(&db, &id)?;
+    queue_sync(&app, Some(&reminder), &updated)?;
+    Ok(updated)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn snooze_reminder(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+    minutes: u32,
+) -> Result<Reminder, AppError> {
+    if minutes == 0 || minutes > MAX_SNOOZE_MINUTES {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid snooze: must be between 1 and {} minutes",
+                MAX_SNOOZE_MINUTES
+            ),
+        ));
+    }
+    let previous = get_reminder(&db, &id)?;
+    let until = now_secs() + minutes as i64 * 60;
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE reminders SET snoozed_until = ?1, notified_at = NULL WHERE id = ?2",
+            rusqlite::params![until, id],
+        )
+    })?;
+    app_log!("[Reminders] {} snoozed for {} minutes", id, minutes);
+    let updated = get_reminder(&db, &id)?;
+    queue_sync(&app, Some(&previous), &updated)?;
+    Ok(updated)
+}
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use super::context_brief::{self, days_since, ContextBrief, ContextSubject};
+use crate::api::{require_token, Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+
+const CACHE_KEY: &str = "renewals";
+// Renewals move over days, not minutes; a short TTL keeps stage changes
+// made in the web app visible without hammering the planner
+const CACHE_TTL_SECS: i64 = 15 * 60;
+
+/// Stages the renewal planner accepts, in pipeline order
+const RENEWAL_STAGES: &[&str] = &[
+    "discovery",
+    "planning",
+    "proposal_sent",
+    "negotiation",
+    "commit",
+    "renewed",
+    "churned",
+];
+
+fn renewal_api_url() -> String {
+    std::env::var("GOODHANG_RENUBU_URL").unwrap_or_else(|_| "https://app.renubu.com".to_string())
+}
+
+/// The account a renewal belongs to. The planner returns snake_case rows;
+/// the aliases let the same struct read both those and our cached copy.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RenewalAccount {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub industry: Option<String>,
+    #[serde(default, rename = "healthScore", alias = "health_score")]
+    pub health_score: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RenewalContract {
+    #[serde(default, rename = "contractNumber", alias = "contract_number")]
+    pub contract_number: Option<String>,
+    #[serde(default)]
+    pub arr: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Renewal {
+    pub id: String,
+    #[serde(rename = "accountId", alias = "customer_id")]
+    pub account_id: String,
+    #[serde(rename = "renewalDate", alias = "renewal_date")]
+    pub renewal_date: String,
+    #[serde(rename = "currentArr", alias = "current_arr")]
+    pub current_arr: f64,
+    #[serde(default, rename = "proposedArr", alias = "proposed_arr")]
+    pub proposed_arr: Option<f64>,
+    #[serde(default)]
+    pub probability: Option<i64>,
+    pub stage: String,
+    #[serde(default, rename = "riskLevel", alias = "risk_level")]
+    pub risk_level: Option<String>,
+    #[serde(default, rename = "nextAction", alias = "next_action")]
+    pub next_action: Option<String>,
+    #[serde(default, rename = "nextActionDate", alias = "next_action_date")]
+    pub next_action_date: Option<String>,
+    #[serde(default, alias = "customers")]
+    pub account: Option<RenewalAccount>,
+    #[serde(default, alias = "contracts")]
+    pub contract: Option<RenewalContract>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct RenewalList {
+    pub renewals: Vec<Renewal>,
+    /// True when the planner couldn't be reached and an expired cache was
+    /// served instead
+    pub stale: bool,
+    #[serde(rename = "fetchedAt")]
+    pub fetched_at: i64,
+}
+
+/// A renewal alongside everything we know about the account, for prep
+#[derive(Debug, Serialize, specta::Type)]
+pub struct RenewalBrief {
+    pub renewal: Renewal,
+    /// Negative once the renewal date has passed
+    #[serde(rename = "daysUntilRenewal")]
+    pub days_until_renewal: Option<i64>,
+    #[serde(rename = "arrChange")]
+    pub arr_change: Option<f64>,
+    pub context: ContextBrief,
+    pub stale: bool,
+}
+
+/// Cached renewals, with when they were fetched
+fn cache_get(db: &Database) -> Option<(Vec<Renewal>, i64)> {
+    let (payload, fetched_at): (String, i64) = db
+        .with_conn(|conn| {
+            conn.query_row(
+                "SELECT payload, fetched_at FROM analytics_cache WHERE key = ?1",
+                [CACHE_KEY],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+        })
+        .ok()?;
+    Some((serde_json::from_str(&payload).ok()?, fetched_at))
+}
+
+fn cache_put(db: &Database, renewals: &[Renewal], fetched_at: i64) -> Result<(), String> {
+    let payload =
+        serde_json::to_string(renewals).map_err(|e| format!("Failed to serialize cache: {}", e))?;
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO analytics_cache (key, payload, fetched_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET payload = ?2, fetched_at = ?3",
+            rusqlite::params![CACHE_KEY, payload, fetched_at],
+        )
+    })
+    .map(|_| ())
+}
+
+async fn fetch_renewals(app: &tauri::AppHandle, token: &str) -> Result<Vec<Renewal>, String> {
+    app.state::<Api>()
+        .send(
+            ApiRequest::get("/api/renewals")
+                .base_url(renewal_api_url())
+                .bearer(token),
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/renewals")
+}
+
+/// Fresh cache if we have it, else the planner, else a stale cache entry
+/// when the planner fails
+async fn load_renewals(
+    app: &tauri::AppHandle,
+    db: &Database,
+    refresh: bool,
+) -> Result<RenewalList, String> {
+    let cached = cache_get(db);
+    if let Some((renewals, fetched_at)) = &cached {
+        if !refresh && now_secs() - fetched_at <= CACHE_TTL_SECS {
+            return Ok(RenewalList {
+                renewals: renewals.clone(),
+                stale: false,
+                fetched_at: *fetched_at,
+            });
+        }
+    }
+
+    let token = require_token(app)?;
+    match fetch_renewals(app, &token).await {
+        Ok(renewals) => {
+            let fetched_at = now_secs();
+            cache_put(db, &renewals, fetched_at)?;
+            Ok(RenewalList {
+                renewals,
+                stale: false,
+                fetched_at,
+            })
+        }
+        Err(e) => match cached {
+            Some((renewals, fetched_at)) => {
+                app_log!("[Renewals] Serving stale renewals: {}", e);
+                Ok(RenewalList {
+                    renewals,
+                    stale: true,
+                    fetched_at,
+                })
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Renewals for the user's company from the renewal planner, soonest first
+#[tauri::command]
+#[specta::specta]
+pub async fn list_renewals(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    refresh: Option<bool>,
+) -> Result<RenewalList, AppError> {
+    let mut list = load_renewals(&app, &db, refresh.unwrap_or(false)).await?;
+    list.renewals
+        .sort_by(|a, b| a.renewal_date.cmp(&b.renewal_date));
+    Ok(list)
+}
+
+/// The account's next open renewal (or its most recent one) with a context
+/// brief on the account
+#[tauri::command]
+#[specta::specta]
+pub async fn get_renewal_brief(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    account_id: String,
+    refresh: Option<bool>,
+) -> Result<RenewalBrief, AppError> {
+    let refresh = refresh.unwrap_or(false);
+    let list = load_renewals(&app, &db, refresh).await?;
+
+    let mut renewals: Vec<Renewal> = list
+        .renewals
+        .into_iter()
+        .filter(|r| r.account_id == account_id)
+        .collect();
+    renewals.sort_by(|a, b| a.renewal_date.cmp(&b.renewal_date));
+    let upcoming = renewals
+        .iter()
+        .position(|r| days_since(&r.renewal_date).is_some_and(|d| d <= 0));
+    let renewal = match upcoming {
+        Some(i) => renewals.swap_remove(i),
+        None => renewals.pop().ok_or_else(|| {
+            AppError::new(
+                ErrorCode::NotFound,
+                format!("No renewal found for account {}", account_id),
+            )
+        })?,
+    };
+
+    let subject = match &renewal.account {
+        Some(account) => ContextSubject {
+            entity_id: None,
+            name: account.name.clone(),
+            domain: account.domain.clone(),
+            industry: account.industry.clone(),
+            emails: Vec::new(),
+            entity_type: "company".to_string(),
+        },
+        None => {
+            return Err(AppError::new(
+                ErrorCode::NotFound,
+                format!("Renewal {} has no account details", renewal.id),
+            ))
+        }
+    };
+    let context = context_brief::brief_for(&app, &subject, refresh).await;
+
+    Ok(RenewalBrief {
+        days_until_renewal: days_since(&renewal.renewal_date).map(|d| -d),
+        arr_change: renewal.proposed_arr.map(|p| p - renewal.current_arr),
+        renewal,
+        context,
+        stale: list.stale,
+    })
+}
+
+/// Move a renewal to another pipeline stage. The cached list is updated in
+/// place so the planner view reflects it without a refetch.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_renewal_stage(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    renewal_id: String,
+    stage: String,
+) -> Result<Renewal, AppError> {
+    if !RENEWAL_STAGES.contains(&stage.as_str()) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid stage: expected one of {}",
+                RENEWAL_STAGES.join(", ")
+            ),
+        ));
+    }
+    let token = require_token(&app)?;
+
+    let updated: Renewal = app
+        .state::<Api>()
+        .send(
+            ApiRequest::patch(format!("/api/renewals/{}", renewal_id))
+                .base_url(renewal_api_url())
+                .bearer(&token)
+                .json(&serde_json::json!({ "stage": stage }))?,
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/renewals/{id}")?;
+
+    if let Some((mut renewals, fetched_at)) = cache_get(&db) {
+        if let Some(cached) = renewals.iter_mut().find(|r| r.id == updated.id) {
+            // The PATCH response may not embed the account and contract
+            cached.stage = updated.stage.clone();
+            if let Err(e) = cache_put(&db, &renewals, fetched_at) {
+                app_log!("[Renewals] Failed to update cached renewal: {}", e);
+            }
+        }
+    }
+    app_log!("[Renewals] Renewal {} moved to {}", renewal_id, stage);
+    Ok(updated)
+}
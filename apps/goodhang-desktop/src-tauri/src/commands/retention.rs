@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tauri::Manager;
+
+use super::journal;
+use super::voice_memos::memo_dir;
+use crate::audit;
+use crate::db::{now_secs, Database, DB_FILENAME};
+use crate::embeddings;
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::image_cache;
+use crate::logging::app_log;
+use crate::search_index;
+use crate::stores;
+use crate::summary_cache;
+use crate::user_data;
+
+pub(crate) const RETENTION_KEY: &str = "retention";
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const MAX_TRANSCRIPT_DAYS: u32 = 10 * 365;
+const DEFAULT_JOURNAL_CACHE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// What the cleanup job is allowed to delete. Only data the server also
+/// holds (synced memos) is ever removed.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RetentionPolicy {
+    /// Clear local transcripts of synced memos older than this; `None` keeps
+    /// them indefinitely
+    #[serde(default, rename = "transcriptRetentionDays")]
+    pub transcript_retention_days: Option<u32>,
+    /// Delete the local audio of synced memos once they have a transcript
+    #[serde(default, rename = "purgeAudioAfterTranscription")]
+    pub purge_audio_after_transcription: bool,
+    /// Evict the oldest journal summaries beyond this many bytes; `None`
+    /// removes the cap
+    #[serde(
+        default = "default_journal_cache_bytes",
+        rename = "journalCacheMaxBytes"
+    )]
+    pub journal_cache_max_bytes: Option<u64>,
+}
+
+fn default_journal_cache_bytes() -> Option<u64> {
+    Some(DEFAULT_JOURNAL_CACHE_BYTES)
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            transcript_retention_days: None,
+            purge_audio_after_transcription: false,
+            journal_cache_max_bytes: default_journal_cache_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct StorageCategory {
+    pub key: String,
+    pub bytes: u64,
+    pub items: u64,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct StorageBreakdown {
+    pub categories: Vec<StorageCategory>,
+    /// On-disk size of the encrypted database, which holds every category
+    /// except audio and images
+    #[serde(rename = "databaseBytes")]
+    pub database_bytes: u64,
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct CleanupReport {
+    #[serde(rename = "transcriptsCleared")]
+    pub transcripts_cleared: usize,
+    #[serde(rename = "audioFilesDeleted")]
+    pub audio_files_deleted: usize,
+    #[serde(rename = "journalSummariesEvicted")]
+    pub journal_summaries_evicted: usize,
+    #[serde(rename = "bytesFreed")]
+    pub bytes_freed: u64,
+}
+
+impl CleanupReport {
+    fn is_empty(&self) -> bool {
+        self.transcripts_cleared == 0
+            && self.audio_files_deleted == 0
+            && self.journal_summaries_evicted == 0
+    }
+}
+
+fn load_policy(app: &tauri::AppHandle) -> RetentionPolicy {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(user_data::key(RETENTION_KEY)))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Total size and count of the files directly inside `dir`
+fn dir_usage(dir: &Path) -> (u64, u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .fold((0, 0), |(bytes, items), meta| {
+            (bytes + meta.len(), items + 1)
+        })
+}
+
+fn file_len(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Bytes and rows for a `SELECT SUM(..), COUNT(*)` query
+fn table_usage(db: &Database, sql: &str, params: &[&str]) -> Result<(u64, u64), String> {
+    db.with_conn(|conn| {
+        conn.query_row(sql, rusqlite::params_from_iter(params), |row| {
+            Ok((
+                row.get::<_, Option<i64>>(0)?.unwrap_or(0).max(0) as u64,
+                row.get::<_, i64>(1)?.max(0) as u64,
+            ))
+        })
+    })
+}
+
+fn clear_old_transcripts(db: &Database, days: u32) -> Result<(usize, u64), String> {
+    let cutoff = now_secs() - i64::from(days) * 24 * 60 * 60;
+    let expired: Vec<(String, i64)> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, length(transcript) FROM voice_memos
+             WHERE transcript IS NOT NULL AND sync_status = 'synced' AND created_at < ?1",
+        )?;
+        let rows = stmt.query_map([cutoff], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    })?;
+
+    let mut bytes = 0;
+    for (id, len) in &expired {
+        db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE voice_memos SET transcript = NULL WHERE id = ?1",
+                [id],
+            )
+        })?;
+        // Derived copies would otherwise keep the transcript searchable
+        embeddings::remove_document(db, "voice_memo", id)?;
+        search_index::remove(db, "voice_memo", id)?;
+        bytes += (*len).max(0) as u64;
+    }
+    Ok((expired.len(), bytes))
+}
+
+/// Migration: record when a memo's audio was removed, so cleanup only looks
+/// at memos that still have some
+pub(crate) fn add_audio_purged_at(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE voice_memos ADD COLUMN audio_purged_at INTEGER;")
+}
+
+fn purge_transcribed_audio(app: &tauri::AppHandle, db: &Database) -> Result<(usize, u64), String> {
+    let memos: Vec<(String, String)> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, file_name FROM voice_memos
+             WHERE sync_status = 'synced' AND COALESCE(transcript, '') != ''
+               AND audio_purged_at IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    })?;
+
+    let dir = memo_dir(app)?;
+    let (mut count, mut bytes) = (0, 0);
+    for (id, file_name) in memos {
+        let path = dir.join(&file_name);
+        match std::fs::metadata(&path) {
+            Ok(meta) => match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    count += 1;
+                    bytes += meta.len();
+                }
+                Err(e) => {
+                    app_log!("[Retention] Failed to delete {}: {}", file_name, e);
+                    continue;
+                }
+            },
+            // Already gone; still mark it so it isn't checked again
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                app_log!("[Retention] Failed to read {}: {}", file_name, e);
+                continue;
+            }
+        }
+        db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE voice_memos SET audio_purged_at = ?1 WHERE id = ?2",
+                rusqlite::params![now_secs(), id],
+            )
+        })?;
+    }
+    Ok((count, bytes))
+}
+
+fn cap_journal_cache(db: &Database, max_bytes: u64) -> Result<(usize, u64), String> {
+    let rows: Vec<(String, i64)> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT subject_id, length(output) FROM summary_cache
+             WHERE kind = ?1 ORDER BY created_at DESC",
+        )?;
+        let rows = stmt.query_map([journal::SUMMARY_CACHE_KIND], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        rows.collect()
+    })?;
+
+    // Newest first: keep summaries until the cap is reached, evict the rest
+    let mut kept = 0u64;
+    let (mut count, mut bytes) = (0, 0);
+    for (subject_id, len) in rows {
+        let len = len.max(0) as u64;
+        if kept + len <= max_bytes {
+            kept += len;
+            continue;
+        }
+        summary_cache::invalidate(db, journal::SUMMARY_CACHE_KIND, &subject_id)?;
+        count += 1;
+        bytes += len;
+    }
+    Ok((count, bytes))
+}
+
+fn run_cleanup(app: &tauri::AppHandle) -> Result<CleanupReport, String> {
+    let db = app.state::<Database>();
+    let policy = load_policy(app);
+    let mut report = CleanupReport::default();
+
+    if let Some(days) = policy.transcript_retention_days {
+        let (count, bytes) = clear_old_transcripts(&db, days)?;
+        report.transcripts_cleared = count;
+        report.bytes_freed += bytes;
+    }
+    if policy.purge_audio_after_transcription {
+        let (count, bytes) = purge_transcribed_audio(app, &db)?;
+        report.audio_files_deleted = count;
+        report.bytes_freed += bytes;
+    }
+    if let Some(max_bytes) = policy.journal_cache_max_bytes {
+        let (count, bytes) = cap_journal_cache(&db, max_bytes)?;
+        report.journal_summaries_evicted = count;
+        report.bytes_freed += bytes;
+    }
+
+    if !report.is_empty() {
+        app_log!(
+            "[Retention] Cleared {} transcript(s), {} audio file(s), {} journal summary(ies); {} bytes freed",
+            report.transcripts_cleared,
+            report.audio_files_deleted,
+            report.journal_summaries_evicted,
+            report.bytes_freed
+        );
+        events::emit_app_event(app, AppEvent::StorageCleaned(report.clone()));
+    }
+    Ok(report)
+}
+
+pub fn spawn_retention_cleanup(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_cleanup(&app) {
+                app_log!("[Retention] Cleanup failed: {}", e);
+            }
+            tokio::time::sleep(CLEANUP_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_retention_policy(app: tauri::AppHandle) -> Result<RetentionPolicy, String> {
+    Ok(load_policy(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_retention_policy(
+    app: tauri::AppHandle,
+    policy: RetentionPolicy,
+) -> Result<RetentionPolicy, AppError> {
+    if policy
+        .transcript_retention_days
+        .is_some_and(|days| days == 0 || days > MAX_TRANSCRIPT_DAYS)
+    {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid transcript retention: expected 1-{} days",
+                MAX_TRANSCRIPT_DAYS
+            ),
+        ));
+    }
+
+    let store = stores::open(&app, stores::SETTINGS_STORE)?;
+    store.set(
+        user_data::key(RETENTION_KEY),
+        serde_json::to_value(&policy).map_err(|e| format!("Failed to serialize policy: {}", e))?,
+    );
+    stores::save(&app, stores::SETTINGS_STORE)?;
+    app_log!("[Retention] Policy updated: {:?}", policy);
+    Ok(policy)
+}
+
+/// Local disk usage by category, for the storage settings page
+#[tauri::command]
+#[specta::specta]
+pub async fn get_storage_breakdown(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<StorageBreakdown, String> {
+    let mut categories = Vec::new();
+    let mut push = |key: &str, (bytes, items): (u64, u64)| {
+        categories.push(StorageCategory {
+            key: key.to_string(),
+            bytes,
+            items,
+        })
+    };
+
+    push("voice_audio", dir_usage(&memo_dir(&app)?));
+    push(
+        "transcripts",
+        table_usage(
+            &db,
+            "SELECT SUM(length(transcript)), COUNT(transcript) FROM voice_memos",
+            &[],
+        )?,
+    );
+    push(
+        "journal_cache",
+        table_usage(
+            &db,
+            "SELECT SUM(length(output)), COUNT(*) FROM summary_cache WHERE kind = ?1",
+            &[journal::SUMMARY_CACHE_KIND],
+        )?,
+    );
+    push(
+        "summary_cache",
+        table_usage(
+            &db,
+            "SELECT SUM(length(output)), COUNT(*) FROM summary_cache WHERE kind != ?1",
+            &[journal::SUMMARY_CACHE_KIND],
+        )?,
+    );
+    push(
+        "api_cache",
+        table_usage(
+            &db,
+            "SELECT SUM(length(payload)), COUNT(*) FROM analytics_cache",
+            &[],
+        )?,
+    );
+    push(
+        "embeddings",
+        table_usage(
+            &db,
+            "SELECT SUM(length(vector) + length(content)), COUNT(*) FROM embeddings",
+            &[],
+        )?,
+    );
+    push("images", dir_usage(&image_cache::cache_dir(&app)?));
+
+    let db_path = user_data::data_dir(&app)?.join(DB_FILENAME);
+    let database_bytes = file_len(&db_path) + file_len(&db_path.with_extension("db-wal"));
+
+    let file_bytes: u64 = categories
+        .iter()
+        .filter(|c| c.key == "voice_audio" || c.key == "images")
+        .map(|c| c.bytes)
+        .sum();
+    Ok(StorageBreakdown {
+        categories,
+        database_bytes,
+        total_bytes: database_bytes + file_bytes,
+    })
+}
+
+/// Apply the retention policy immediately instead of waiting for the
+/// scheduled run
+#[tauri::command]
+#[specta::specta]
+pub async fn run_cleanup_now(app: tauri::AppHandle) -> Result<CleanupReport, String> {
+    audit::audited(app.clone(), "run_cleanup_now", async { run_cleanup(&app) }).await
+}
@@ -0,0 +1,432 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+
+const DEFAULT_DAYS: u32 = 7;
+const MAX_DAYS: u32 = 31;
+const DEFAULT_DURATION_MINUTES: u32 = 60;
+const DEFAULT_EARLIEST_HOUR: u32 = 9;
+const DEFAULT_LATEST_HOUR: u32 = 18;
+const DEFAULT_MAX_WINDOWS: usize = 10;
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct MeetingParticipant {
+    /// GFT entity id
+    pub id: String,
+    /// IANA zone, e.g. "Europe/Berlin". Looked up in GFT when absent.
+    #[serde(default, rename = "timeZone")]
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, specta::Type)]
+pub struct MeetingConstraints {
+    /// First day to search, "YYYY-MM-DD" in `timeZone`; defaults to today
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub days: Option<u32>,
+    /// Zone the search range is read in, and the hours when no participant's
+    /// zone is known; defaults to UTC
+    #[serde(default, rename = "timeZone")]
+    pub time_zone: Option<String>,
+    #[serde(default, rename = "durationMinutes")]
+    pub duration_minutes: Option<u32>,
+    /// Everyone's acceptable local hours, [earliest, latest)
+    #[serde(default, rename = "earliestHour")]
+    pub earliest_hour: Option<u32>,
+    #[serde(default, rename = "latestHour")]
+    pub latest_hour: Option<u32>,
+    #[serde(default, rename = "includeWeekends")]
+    pub include_weekends: bool,
+    #[serde(default, rename = "maxWindows")]
+    pub max_windows: Option<usize>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct LocalTime {
+    #[serde(rename = "participantId")]
+    pub participant_id: String,
+    #[serde(rename = "timeZone")]
+    pub time_zone: String,
+    /// RFC 3339 with the participant's UTC offset on that day
+    #[serde(rename = "startsAt")]
+    pub starts_at: String,
+    #[serde(rename = "endsAt")]
+    pub ends_at: String,
+}
+
+/// A stretch of time inside everyone's local hours. `startsAt`/`endsAt`
+/// match a hang `TimeOption`.
+#[derive(Debug, Serialize, specta::Type)]
+pub struct MeetingWindow {
+    #[serde(rename = "startsAt")]
+    pub starts_at: String,
+    #[serde(rename = "endsAt")]
+    pub ends_at: String,
+    #[serde(rename = "durationMinutes")]
+    pub duration_minutes: i64,
+    pub local: Vec<LocalTime>,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct MeetingTimes {
+    pub windows: Vec<MeetingWindow>,
+    /// Participants with no known time zone; they don't constrain the windows
+    pub unresolved: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntityTimeZone {
+    #[serde(default, rename = "timeZone")]
+    time_zone: Option<String>,
+}
+
+fn parse_zone(name: &str) -> Result<Tz, AppError> {
+    name.parse::<Tz>().map_err(|_| {
+        AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Invalid time zone: {}", name),
+        )
+    })
+}
+
+async fn gft_time_zone(api: &Api, token: &str, entity_id: &str) -> Result<Option<String>, String> {
+    let response = api
+        .send(ApiRequest::get(format!("/api/gft/entities/{}", entity_id)).bearer(token))
+        .await?
+        .error_for_status()?;
+    let entity: EntityTimeZone = response.json("/api/gft/entities/{id}")?;
+    Ok(entity.time_zone)
+}
+
+/// `hour` on `date` in `tz`, as UTC. Skipped-over local times (spring
+/// forward) move to the first instant after the gap; repeated ones (fall
+/// back) resolve to the earlier instant for starts and the later for ends.
+fn local_to_utc(tz: &Tz, date: NaiveDate, hour: u32, is_end: bool) -> Option<DateTime<Utc>> {
+    let naive = if hour == 24 {
+        date.succ_opt()?.and_time(NaiveTime::MIN)
+    } else {
+        date.and_hms_opt(hour, 0, 0)?
+    };
+    let mapped = tz.from_local_datetime(&naive);
+    let resolved = if is_end {
+        mapped.latest()
+    } else {
+        mapped.earliest()
+    };
+    match resolved {
+        Some(dt) => Some(dt.with_timezone(&Utc)),
+        // No DST gap is longer than an hour; walk forward until time exists
+        None => (1..=4).find_map(|quarter| {
+            tz.from_local_datetime(&(naive + Duration::minutes(15 * quarter)))
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc))
+        }),
+    }
+}
+
+/// Each acceptable local-hours block for one participant, in UTC
+fn available(
+    tz: &Tz,
+    first: NaiveDate,
+    last: NaiveDate,
+    constraints: &Constraints,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .filter(|date| {
+            constraints.include_weekends || !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+        })
+        .filter_map(|date| {
+            let start = local_to_utc(tz, date, constraints.earliest_hour, false)?;
+            let end = local_to_utc(tz, date, constraints.latest_hour, true)?;
+            (end > start).then_some((start, end))
+        })
+        .collect()
+}
+
+/// Overlap of two sorted, non-overlapping interval lists
+fn intersect(
+    a: &[(DateTime<Utc>, DateTime<Utc>)],
+    b: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let (mut i, mut j) = (0, 0);
+    let mut overlap = Vec::new();
+    while i < a.len() && j < b.len() {
+        let start = a[i].0.max(b[j].0);
+        let end = a[i].1.min(b[j].1);
+        if start < end {
+            overlap.push((start, end));
+        }
+        if a[i].1 < b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    overlap
+}
+
+/// Constraints with defaults applied and validated
+struct Constraints {
+    /// The requester's zone, when given; used if no participant's is known
+    time_zone: Option<Tz>,
+    range_start: DateTime<Utc>,
+    range_end: DateTime<Utc>,
+    duration: Duration,
+    earliest_hour: u32,
+    latest_hour: u32,
+    include_weekends: bool,
+    max_windows: usize,
+}
+
+impl Constraints {
+    fn resolve(raw: MeetingConstraints) -> Result<Self, AppError> {
+        let time_zone = raw.time_zone.as_deref().map(parse_zone).transpose()?;
+        let range_zone = time_zone.unwrap_or(Tz::UTC);
+        let from = match raw.from.as_deref() {
+            Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                AppError::new(
+                    ErrorCode::InvalidInput,
+                    format!("Invalid date: expected YYYY-MM-DD, got {}", date),
+                )
+            })?,
+            None => Utc::now().with_timezone(&range_zone).date_naive(),
+        };
+        let days = raw.days.unwrap_or(DEFAULT_DAYS);
+        let duration_minutes = raw.duration_minutes.unwrap_or(DEFAULT_DURATION_MINUTES);
+        let earliest_hour = raw.earliest_hour.unwrap_or(DEFAULT_EARLIEST_HOUR);
+        let latest_hour = raw.latest_hour.unwrap_or(DEFAULT_LATEST_HOUR);
+        if days == 0 || days > MAX_DAYS {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Invalid range: days must be 1-{}", MAX_DAYS),
+            ));
+        }
+        if earliest_hour >= latest_hour || latest_hour > 24 {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid hours: need 0 <= earliestHour < latestHour <= 24",
+            ));
+        }
+        if duration_minutes == 0 || duration_minutes > (latest_hour - earliest_hour) * 60 {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid duration: must fit inside the daily hours",
+            ));
+        }
+
+        let range_start = local_to_utc(&range_zone, from, 0, false)
+            .ok_or_else(|| format!("Invalid date: {}", from))?;
+        let range_end = local_to_utc(&range_zone, from + Duration::days(days as i64), 0, false)
+            .ok_or_else(|| format!("Invalid date: {}", from))?;
+        Ok(Self {
+            time_zone,
+            // Nothing in the past, even when the range starts today
+            range_start: range_start.max(Utc::now()),
+            range_end,
+            duration: Duration::minutes(duration_minutes as i64),
+            earliest_hour,
+            latest_hour,
+            include_weekends: raw.include_weekends,
+            max_windows: raw.max_windows.unwrap_or(DEFAULT_MAX_WINDOWS).max(1),
+        })
+    }
+}
+
+/// Windows where every participant is inside their local hours, for the
+/// hang proposal flow. Offsets are computed per day, so ranges spanning a
+/// DST change stay correct for each side.
+#[tauri::command]
+#[specta::specta]
+pub async fn resolve_meeting_times(
+    app: tauri::AppHandle,
+    participants: Vec<MeetingParticipant>,
+    constraints: Option<MeetingConstraints>,
+) -> Result<MeetingTimes, AppError> {
+    if participants.is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid participants: at least one is required",
+        ));
+    }
+    let constraints = Constraints::resolve(constraints.unwrap_or_default())?;
+
+    let mut token = None;
+    let mut zones: Vec<(String, Tz)> = Vec::new();
+    let mut unresolved = Vec::new();
+    for participant in participants {
+        // A bad zone passed in is the caller's mistake; a bad one from GFT
+        // just leaves that participant unresolved
+        if let Some(name) = &participant.time_zone {
+            zones.push((participant.id, parse_zone(name)?));
+            continue;
+        }
+        if token.is_none() {
+            token = Some(
+                load_session(&app)?
+                    .map(|s| s.token)
+                    .ok_or_else(|| "Not signed in".to_string())?,
+            );
+        }
+        let looked_up = gft_time_zone(
+            &app.state::<Api>(),
+            token.as_deref().unwrap_or_default(),
+            &participant.id,
+        )
+        .await
+        .unwrap_or_else(|e| {
+            app_log!("[Scheduling] No time zone for {}: {}", participant.id, e);
+            None
+        });
+        match looked_up.as_deref().map(parse_zone) {
+            Some(Ok(tz)) => zones.push((participant.id, tz)),
+            _ => unresolved.push(participant.id),
+        }
+    }
+
+    // Local days can start up to 14h either side of UTC; pad by one day
+    let first = (constraints.range_start - Duration::days(1)).date_naive();
+    let last = (constraints.range_end + Duration::days(1)).date_naive();
+    // With nobody's zone known, every hour of the range would match; read
+    // the hours in the requester's zone instead, or refuse
+    let constraining: Vec<Tz> = if zones.is_empty() {
+        let tz = constraints.time_zone.ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid participants: no time zone is known for anyone; pass timeZone",
+            )
+        })?;
+        vec![tz]
+    } else {
+        zones.iter().map(|(_, tz)| *tz).collect()
+    };
+    let mut overlap = vec![(constraints.range_start, constraints.range_end)];
+    for tz in &constraining {
+        overlap = intersect(&overlap, &available(tz, first, last, &constraints));
+    }
+
+    let windows = overlap
+        .into_iter()
+        .filter(|(start, end)| *end - *start >= constraints.duration)
+        .take(constraints.max_windows)
+        .map(|(start, end)| MeetingWindow {
+            starts_at: start.to_rfc3339(),
+            ends_at: end.to_rfc3339(),
+            duration_minutes: (end - start).num_minutes(),
+            local: zones
+                .iter()
+                .map(|(id, tz)| LocalTime {
+                    participant_id: id.clone(),
+                    time_zone: tz.name().to_string(),
+                    starts_at: start.with_timezone(tz).to_rfc3339(),
+                    ends_at: end.with_timezone(tz).to_rfc3339(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(MeetingTimes {
+        windows,
+        unresolved,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn constraints(earliest_hour: u32, latest_hour: u32) -> Constraints {
+        Constraints {
+            time_zone: None,
+            range_start: utc("2026-01-01T00:00:00Z"),
+            range_end: utc("2027-01-01T00:00:00Z"),
+            duration: Duration::minutes(60),
+            earliest_hour,
+            latest_hour,
+            include_weekends: true,
+            max_windows: DEFAULT_MAX_WINDOWS,
+        }
+    }
+
+    #[test]
+    fn skipped_hour_moves_past_the_gap() {
+        // 02:00 doesn't exist in New York on 8 March 2026
+        let tz: Tz = "America/New_York".parse().unwrap();
+        assert_eq!(
+            local_to_utc(&tz, date("2026-03-08"), 2, false),
+            Some(utc("2026-03-08T07:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn repeated_hour_starts_early_and_ends_late() {
+        // 01:00 happens twice in New York on 1 November 2026
+        let tz: Tz = "America/New_York".parse().unwrap();
+        assert_eq!(
+            local_to_utc(&tz, date("2026-11-01"), 1, false),
+            Some(utc("2026-11-01T05:00:00Z"))
+        );
+        assert_eq!(
+            local_to_utc(&tz, date("2026-11-01"), 1, true),
+            Some(utc("2026-11-01T06:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn local_hours_follow_the_offset_across_a_change() {
+        // Berlin moves to summer time on 29 March 2026
+        let tz: Tz = "Europe/Berlin".parse().unwrap();
+        let blocks = available(
+            &tz,
+            date("2026-03-28"),
+            date("2026-03-30"),
+            &constraints(9, 17),
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                (utc("2026-03-28T08:00:00Z"), utc("2026-03-28T16:00:00Z")),
+                (utc("2026-03-29T07:00:00Z"), utc("2026-03-29T15:00:00Z")),
+                (utc("2026-03-30T07:00:00Z"), utc("2026-03-30T15:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlap_shifts_while_only_one_side_has_changed() {
+        // Between the US (8 March) and EU (29 March) changes the gap is 5h
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        let c = constraints(9, 17);
+        let day = |d| {
+            intersect(
+                &available(&berlin, date(d), date(d), &c),
+                &available(&new_york, date(d), date(d), &c),
+            )
+        };
+        assert_eq!(
+            day("2026-03-16"),
+            vec![(utc("2026-03-16T13:00:00Z"), utc("2026-03-16T16:00:00Z"))]
+        );
+        assert_eq!(
+            day("2026-04-06"),
+            vec![(utc("2026-04-06T13:00:00Z"), utc("2026-04-06T15:00:00Z"))]
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use crate::audit;
+use crate::db::Database;
+use crate::search_index::{self, SearchHit};
+
+const DEFAULT_LIMIT: usize = 20;
+
+/// Ranked full-text search for the command palette. An empty `scopes` list
+/// searches everything.
+#[tauri::command]
+#[specta::specta]
+pub async fn search(
+    db: tauri::State<'_, Database>,
+    query: String,
+    scopes: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let scopes = scopes.unwrap_or_default();
+    for scope in &scopes {
+        search_index::validate_scope(scope)?;
+    }
+    search_index::query(&db, &query, &scopes, limit.unwrap_or(DEFAULT_LIMIT))
+}
+
+/// Add or replace text for a journal entry, relationship note, or context file
+#[tauri::command]
+#[specta::specta]
+pub async fn index_search_document(
+    db: tauri::State<'_, Database>,
+    scope: String,
+    source_id: String,
+    title: String,
+    body: String,
+) -> Result<(), String> {
+    search_index::upsert(&db, &scope, &source_id, &title, &body)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_search_document(
+    db: tauri::State<'_, Database>,
+    scope: String,
+    source_id: String,
+) -> Result<(), String> {
+    let result = search_index::remove(&db, &scope, &source_id);
+    audit::record(&db, "remove_search_document", &result);
+    result
+}
@@ -0,0 +1,274 @@
+use robius_authentication::{
+    AndroidText, BiometricStrength, Context, PolicyBuilder, Text, WindowsText,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use user_idle::UserIdle;
+
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::stores;
+
+const BIOMETRIC_KEY: &str = "biometric";
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Which views require re-authentication after the app has been idle
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct BiometricSettings {
+    pub enabled: bool,
+    /// Seconds without a gated access before the next one prompts again
+    #[serde(rename = "idleTimeoutSecs")]
+    pub idle_timeout_secs: u64,
+    #[serde(rename = "gatedViews")]
+    pub gated_views: Vec<String>,
+    /// Lock the whole app after this many seconds of system-wide inactivity;
+    /// `None` disables auto-lock
+    #[serde(default, rename = "autoLockSecs")]
+    pub auto_lock_secs: Option<u64>,
+}
+
+impl Default for BiometricSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_timeout_secs: 5 * 60,
+            gated_views: vec![
+                "assessment_results".to_string(),
+                "journal".to_string(),
+                "relationships".to_string(),
+            ],
+            auto_lock_secs: None,
+        }
+    }
+}
+
+/// Time of the last successful unlock or gated access
+#[derive(Default)]
+pub struct BiometricGate {
+    last_active: Mutex<Option<Instant>>,
+}
+
+impl BiometricGate {
+    fn is_unlocked(&self, idle_timeout: Duration) -> bool {
+        self.last_active
+            .lock()
+            .ok()
+            .and_then(|guard| *guard)
+            .is_some_and(|at| at.elapsed() < idle_timeout)
+    }
+
+    fn touch(&self) {
+        if let Ok(mut guard) = self.last_active.lock() {
+            *guard = Some(Instant::now());
+        }
+    }
+
+    pub fn lock(&self) {
+        if let Ok(mut guard) = self.last_active.lock() {
+            *guard = None;
+        }
+    }
+}
+
+/// Whether the app is locked pending re-authentication. Background work that
+/// shouldn't run while the user is away (e.g. presence heartbeats) checks this.
+#[derive(Default)]
+pub struct AppLock {
+    locked: AtomicBool,
+}
+
+impl AppLock {
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+}
+
+/// Lock the app: sensitive views must re-authenticate and the frontend
+/// blurs its windows on `app-locked`
+fn lock(app: &tauri::AppHandle, reason: &str) {
+    if app.state::<AppLock>().locked.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    app.state::<BiometricGate>().lock();
+    app_log!("[Security] App locked: {}", reason);
+    events::emit_app_event(app, AppEvent::AppLocked);
+}
+
+/// Poll system idle time and lock once it exceeds the configured period
+pub fn spawn_idle_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+
+            let Some(limit) = load_biometric_settings(&app).ok().and_then(|s| s.auto_lock_secs)
+            else {
+                continue;
+            };
+            if app.state::<AppLock>().is_locked() {
+                continue;
+            }
+            match UserIdle::get_time() {
+                Ok(idle) if idle.as_seconds() >= limit => lock(&app, "idle"),
+                Ok(_) => {}
+                Err(e) => app_log!("[Security] Failed to read idle time: {:?}", e),
+            }
+        }
+    });
+}
+
+pub(crate) fn load_biometric_settings(app: &tauri::AppHandle) -> Result<BiometricSettings, String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+
+    match store.get(BIOMETRIC_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| format!("Failed to parse biometric settings: {}", e)),
+        None => Ok(BiometricSettings::default()),
+    }
+}
+
+/// Show the OS prompt (Touch ID / Windows Hello, falling back to the device
+/// password). Blocking; run off the async runtime.
+fn prompt_os_authentication(reason: &str) -> Result<(), String> {
+    let policy = PolicyBuilder::new()
+        .biometrics(Some(BiometricStrength::Strong))
+        .password(true)
+        .watch(true)
+        .build()
+        .ok_or_else(|| "Biometric authentication is not supported on this device".to_string())?;
+
+    let text = Text {
+        android: AndroidText {
+            title: "Good Hang",
+            subtitle: None,
+            description: Some(reason),
+        },
+        apple: reason,
+        windows: WindowsText::new("Good Hang", reason)
+            .ok_or_else(|| "Unlock reason is too long".to_string())?,
+    };
+
+    Context::new(())
+        .blocking_authenticate(text, &policy)
+        .map_err(|e| format!("Authentication failed: {:?}", e))
+}
+
+/// Gate a sensitive view. Returns immediately when the gate is disabled, the
+/// view isn't gated, or the user was active within the idle timeout;
+/// otherwise prompts for biometric unlock.
+#[tauri::command]
+#[specta::specta]
+pub async fn require_biometric_unlock(
+    app: tauri::AppHandle,
+    gate: tauri::State<'_, BiometricGate>,
+    reason: String,
+    view: Option<String>,
+) -> Result<bool, String> {
+    let settings = load_biometric_settings(&app)?;
+    let gated = view
+        .as_ref()
+        .is_none_or(|v| settings.gated_views.contains(v));
+
+    if !settings.enabled || !gated {
+        return Ok(true);
+    }
+    if gate.is_unlocked(Duration::from_secs(settings.idle_timeout_secs)) {
+        gate.touch();
+        return Ok(true);
+    }
+
+    let result = tauri::async_runtime::spawn_blocking(move || prompt_os_authentication(&reason))
+        .await
+        .map_err(|e| format!("Authentication task failed: {}", e))?;
+
+    match result {
+        Ok(()) => {
+            gate.touch();
+            app_log!("[Security] Biometric unlock succeeded");
+            Ok(true)
+        }
+        Err(e) => {
+            app_log!("[Security] Biometric unlock failed: {}", e);
+            Ok(false)
+        }
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_biometric_settings(app: tauri::AppHandle) -> Result<BiometricSettings, String> {
+    load_biometric_settings(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_biometric_settings(
+    app: tauri::AppHandle,
+    gate: tauri::State<'_, BiometricGate>,
+    settings: BiometricSettings,
+) -> Result<(), String> {
+    let store = stores::open(&app, stores::SETTINGS_STORE)?;
+
+    store.set(
+        BIOMETRIC_KEY,
+        serde_json::to_value(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?,
+    );
+    stores::save(&app, stores::SETTINGS_STORE)?;
+
+    // Changing the gate shouldn't leave a stale unlock window behind
+    gate.lock();
+    app_log!(
+        "[Security] Biometric settings updated: enabled={}",
+        settings.enabled
+    );
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn lock_app(app: tauri::AppHandle) -> Result<(), String> {
+    lock(&app, "requested");
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn is_app_locked(lock: tauri::State<'_, AppLock>) -> Result<bool, String> {
+    Ok(lock.is_locked())
+}
+
+/// Prompt for biometric or device-password unlock; emits `app-unlocked` on
+/// success
+#[tauri::command]
+#[specta::specta]
+pub async fn unlock_app(
+    app: tauri::AppHandle,
+    lock: tauri::State<'_, AppLock>,
+    gate: tauri::State<'_, BiometricGate>,
+    reason: String,
+) -> Result<bool, String> {
+    if !lock.is_locked() {
+        return Ok(true);
+    }
+
+    let result = tauri::async_runtime::spawn_blocking(move || prompt_os_authentication(&reason))
+        .await
+        .map_err(|e| format!("Authentication task failed: {}", e))?;
+
+    match result {
+        Ok(()) => {
+            lock.locked.store(false, Ordering::SeqCst);
+            gate.touch();
+            app_log!("[Security] App unlocked");
+            events::emit_app_event(&app, AppEvent::AppUnlocked);
+            Ok(true)
+        }
+        Err(e) => {
+            app_log!("[Security] App unlock failed: {}", e);
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tauri::Manager;
+
+use crate::audit;
+use crate::db::{now_secs, Database};
+use crate::embeddings::{self, Embedder, SemanticMatch};
+use crate::logging::app_log;
+
+const DEFAULT_K: usize = 10;
+
+#[derive(Debug, Default, Serialize, Deserialize, specta::Type)]
+pub struct ReindexSummary {
+    pub indexed: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn semantic_search(
+    app: tauri::AppHandle,
+    query: String,
+    k: Option<usize>,
+    source_types: Option<Vec<String>>,
+    since: Option<i64>,
+) -> Result<Vec<SemanticMatch>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        let embedder = app.state::<Arc<Embedder>>();
+        embeddings::search(
+            &db,
+            &embedder,
+            &query,
+            k.unwrap_or(DEFAULT_K),
+            source_types.as_deref(),
+            since,
+        )
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?
+}
+
+/// Index a journal entry, transcript, or context file pushed from the webview
+#[tauri::command]
+#[specta::specta]
+pub async fn index_document(
+    app: tauri::AppHandle,
+    source_type: String,
+    source_id: String,
+    text: String,
+    created_at: Option<i64>,
+) -> Result<bool, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        let embedder = app.state::<Arc<Embedder>>();
+        embeddings::index_document(
+            &db,
+            &embedder,
+            &source_type,
+            &source_id,
+            &text,
+            created_at.unwrap_or_else(now_secs),
+        )
+    })
+    .await
+    .map_err(|e| format!("Index task failed: {}", e))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_indexed_document(
+    db: tauri::State<'_, Database>,
+    source_type: String,
+    source_id: String,
+) -> Result<(), String> {
+    let result = embeddings::remove_document(&db, &source_type, &source_id);
+    audit::record(&db, "remove_indexed_document", &result);
+    result
+}
+
+/// Incrementally re-index locally held sources: unchanged content is skipped
+/// by hash, and embeddings whose voice memo no longer exists are dropped
+#[tauri::command]
+#[specta::specta]
+pub async fn reindex_embeddings(app: tauri::AppHandle) -> Result<ReindexSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        let embedder = app.state::<Arc<Embedder>>();
+        let mut summary = ReindexSummary::default();
+
+        let memos: Vec<(String, String, i64)> = db.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, transcript, created_at FROM voice_memos
+                 WHERE transcript IS NOT NULL AND transcript != ''",
+            )?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+
+        for (id, transcript, created_at) in &memos {
+            if embeddings::index_document(&db, &embedder, "voice_memo", id, transcript, *created_at)? {
+                summary.indexed += 1;
+            } else {
+                summary.unchanged += 1;
+            }
+        }
+
+        let live: HashSet<&str> = memos.iter().map(|(id, _, _)| id.as_str()).collect();
+        let indexed: Vec<String> = db.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT source_id FROM embeddings WHERE source_type = 'voice_memo'",
+            )?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+
+        for id in indexed.iter().filter(|id| !live.contains(id.as_str())) {
+            embeddings::remove_document(&db, "voice_memo", id)?;
+            summary.removed += 1;
+        }
+
+        app_log!(
+            "[Embeddings] Reindex complete: indexed={} unchanged={} removed={}",
+            summary.indexed, summary.unchanged, summary.removed
+        );
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("Reindex task failed: {}", e))?
+}
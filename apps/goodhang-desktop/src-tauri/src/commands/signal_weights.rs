@@ -0,0 +1,139 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::context_brief;
+use crate::db::Database;
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::scoring::{self, Weights};
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const WEIGHTS_KEY: &str = "signal_weights";
+// Points are 0-100; day thresholds can reasonably go up to a year
+const MAX_WEIGHT: f64 = 365.0;
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct SignalWeights {
+    pub domain: String,
+    /// Effective weights: defaults with the user's overrides applied
+    pub weights: Weights,
+    pub defaults: Weights,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BriefsRescoredEvent {
+    pub domain: String,
+    pub count: usize,
+}
+
+fn load_all(app: &tauri::AppHandle) -> HashMap<String, Weights> {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(user_data::key(WEIGHTS_KEY)))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// The user's overrides for a scoring domain (entity type)
+pub(crate) fn load_weights(app: &tauri::AppHandle, domain: &str) -> Weights {
+    load_all(app).remove(domain).unwrap_or_default()
+}
+
+fn defaults_for(domain: &str) -> Result<Weights, AppError> {
+    scoring::adapter(domain)
+        .map(|adapter| adapter.default_weights())
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Invalid scoring domain: {}", domain),
+            )
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_signal_weights(
+    app: tauri::AppHandle,
+    domain: String,
+) -> Result<SignalWeights, AppError> {
+    let defaults = defaults_for(&domain)?;
+    let mut weights = defaults.clone();
+    weights.extend(load_weights(&app, &domain));
+    Ok(SignalWeights {
+        domain,
+        weights,
+        defaults,
+    })
+}
+
+/// Replace a domain's overrides (an empty map restores the defaults) and
+/// re-score cached briefs of that type without refetching anything
+#[tauri::command]
+#[specta::specta]
+pub async fn update_signal_weights(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    domain: String,
+    weights: Weights,
+) -> Result<SignalWeights, AppError> {
+    let defaults = defaults_for(&domain)?;
+    for (key, value) in &weights {
+        if !defaults.contains_key(key) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Invalid signal weight: unknown key {}", key),
+            ));
+        }
+        if !value.is_finite() || !(0.0..=MAX_WEIGHT).contains(value) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!(
+                    "Invalid signal weight: {} must be between 0 and {}",
+                    key, MAX_WEIGHT
+                ),
+            ));
+        }
+    }
+    // Only keep values that differ, so future default changes still apply
+    let overrides: Weights = weights
+        .into_iter()
+        .filter(|(key, value)| defaults.get(key) != Some(value))
+        .collect();
+
+    let mut all = load_all(&app);
+    if overrides.is_empty() {
+        all.remove(&domain);
+    } else {
+        all.insert(domain.clone(), overrides.clone());
+    }
+    let store = stores::open(&app, stores::SETTINGS_STORE)?;
+    store.set(
+        user_data::key(WEIGHTS_KEY),
+        serde_json::to_value(&all).map_err(|e| format!("Failed to serialize weights: {}", e))?,
+    );
+    stores::save(&app, stores::SETTINGS_STORE)?;
+
+    let count = context_brief::rescore_cached(&db, &domain, &overrides)?;
+    app_log!(
+        "[Scoring] Weights for {} updated; {} briefs re-scored",
+        domain,
+        count
+    );
+    events::emit_app_event(
+        &app,
+        AppEvent::BriefsRescored(BriefsRescoredEvent {
+            domain: domain.clone(),
+            count,
+        }),
+    );
+
+    let mut effective = defaults.clone();
+    effective.extend(overrides);
+    Ok(SignalWeights {
+        domain,
+        weights: effective,
+        defaults,
+    })
+}
@@ -0,0 +1,378 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Manager, Url};
+use tauri_plugin_shell::ShellExt;
+use tokio::sync::oneshot;
+
+use super::login::random_string;
+use super::security::AppLock;
+use crate::api::{require_token, Api, ApiRequest};
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::network;
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const SLACK_KEY: &str = "slack";
+const REDIRECT_URI: &str = "goodhang://slack/callback";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const PRESENCE_INTERVAL: Duration = Duration::from_secs(2 * 60);
+const MAX_ETA_MINUTES: u32 = 24 * 60;
+
+// Last presence read from Slack, so the UI can ask without a round trip
+static PRESENCE: Mutex<Option<SlackPresence>> = Mutex::new(None);
+
+struct PendingConnect {
+    state: String,
+    sender: oneshot::Sender<Result<String, String>>,
+}
+
+/// The Slack authorization awaiting its deep-link callback, if any
+#[derive(Default)]
+pub struct PendingSlackConnect {
+    pending: Mutex<Option<PendingConnect>>,
+}
+
+/// The connected workspace. Slack tokens stay on the server; the app only
+/// remembers that the user opted in.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct SlackConnection {
+    #[serde(rename = "teamId")]
+    pub team_id: String,
+    #[serde(rename = "teamName")]
+    pub team_name: String,
+    #[serde(rename = "slackUserId")]
+    pub slack_user_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct SlackPresence {
+    /// active | away
+    pub presence: String,
+    #[serde(default, rename = "statusText")]
+    pub status_text: Option<String>,
+    #[serde(default, rename = "statusEmoji")]
+    pub status_emoji: Option<String>,
+}
+
+/// Where a message goes: a channel id (`C…`) or a user id (`U…`) for a DM
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct SlackDestination {
+    #[serde(default, rename = "channelId")]
+    pub channel_id: Option<String>,
+    #[serde(default, rename = "userId")]
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorizeResponse {
+    #[serde(rename = "authorizeUrl")]
+    authorize_url: String,
+}
+
+fn load_connection(app: &tauri::AppHandle) -> Option<SlackConnection> {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(user_data::key(SLACK_KEY)))
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+fn save_connection(
+    app: &tauri::AppHandle,
+    connection: Option<&SlackConnection>,
+) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    match connection {
+        Some(connection) => store.set(
+            user_data::key(SLACK_KEY),
+            serde_json::to_value(connection)
+                .map_err(|e| format!("Failed to serialize connection: {}", e))?,
+        ),
+        None => {
+            let _ = store.delete(user_data::key(SLACK_KEY));
+        }
+    }
+    stores::save(app, stores::SETTINGS_STORE)
+}
+
+/// Drop the cached presence, e.g. when another account's data is activated
+pub(crate) fn clear_presence_cache() {
+    if let Ok(mut presence) = PRESENCE.lock() {
+        presence.take();
+    }
+}
+
+/// Drop the local opt-in and cached presence, e.g. on secure logout. The
+/// server-side connection is left for the account to manage.
+pub(crate) fn forget_slack(app: &tauri::AppHandle) -> Result<(), String> {
+    clear_presence_cache();
+    save_connection(app, None)
+}
+
+/// Called by the deep-link router for `goodhang://slack/callback?code=..&state=..`
+pub fn complete_slack_connect(app: &tauri::AppHandle, url: &Url) {
+    let params: std::collections::HashMap<String, String> =
+        url.query_pairs().into_owned().collect();
+
+    let connect = app.state::<PendingSlackConnect>();
+    let Ok(mut slot) = connect.pending.lock() else {
+        return;
+    };
+    // A callback with the wrong state isn't ours to answer, so it leaves the
+    // real connection waiting rather than failing it
+    match slot.as_ref() {
+        None => {
+            app_log!("[Slack] Ignoring callback with no connection in progress");
+            return;
+        }
+        Some(pending) if params.get("state") != Some(&pending.state) => {
+            app_log!("[Slack] Ignoring callback with mismatched state");
+            return;
+        }
+        Some(_) => {}
+    }
+    let Some(pending) = slot.take() else {
+        return;
+    };
+    drop(slot);
+
+    let result = if let Some(error) = params.get("error") {
+        Err(format!("Slack connection was not completed: {}", error))
+    } else {
+        params
+            .get("code")
+            .cloned()
+            .ok_or_else(|| "Slack callback did not include a code".to_string())
+    };
+
+    let _ = pending.sender.send(result);
+}
+
+/// Opt in to Slack: authorize in the browser, then hand the code to the
+/// server to exchange. Resolves once `goodhang://slack/callback` arrives.
+#[tauri::command]
+#[specta::specta]
+pub async fn connect_slack(
+    app: tauri::AppHandle,
+    connect: tauri::State<'_, PendingSlackConnect>,
+) -> Result<SlackConnection, AppError> {
+    let token = require_token(&app)?;
+    let state = random_string(32);
+    let (sender, receiver) = oneshot::channel();
+
+    // A new attempt supersedes any abandoned one
+    *connect
+        .pending
+        .lock()
+        .map_err(|_| "Slack connect lock poisoned".to_string())? = Some(PendingConnect {
+        state: state.clone(),
+        sender,
+    });
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/integrations/slack/authorize")
+                .bearer(&token)
+                .json(&serde_json::json!({ "state": state, "redirectUri": REDIRECT_URI }))?,
+        )
+        .await?;
+    let authorize: AuthorizeResponse = response
+        .error_for_status()?
+        .json("/api/integrations/slack/authorize")?;
+
+    #[allow(deprecated)]
+    app.shell()
+        .open(&authorize.authorize_url, None)
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    app_log!("[Slack] Authorization started");
+
+    let code = match tokio::time::timeout(CONNECT_TIMEOUT, receiver).await {
+        Ok(Ok(result)) => result?,
+        Ok(Err(_)) => return Err("Slack connection was cancelled".to_string().into()),
+        Err(_) => {
+            // Only clear our own attempt, not one started after it
+            if let Ok(mut pending) = connect.pending.lock() {
+                if pending.as_ref().is_some_and(|p| p.state == state) {
+                    pending.take();
+                }
+            }
+            return Err(AppError::new(
+                ErrorCode::Timeout,
+                "Timed out waiting for Slack authorization",
+            ));
+        }
+    };
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/integrations/slack/connect")
+                .bearer(&token)
+                .json(&serde_json::json!({ "code": code, "redirectUri": REDIRECT_URI }))?,
+        )
+        .await?;
+    let connection: SlackConnection = response
+        .error_for_status()?
+        .json("/api/integrations/slack/connect")?;
+
+    save_connection(&app, Some(&connection))?;
+    app_log!("[Slack] Connected to {}", connection.team_name);
+    events::emit_app_event(&app, AppEvent::SlackConnected(connection.clone()));
+    Ok(connection)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_slack_connection(
+    app: tauri::AppHandle,
+) -> Result<Option<SlackConnection>, String> {
+    Ok(load_connection(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn disconnect_slack(app: tauri::AppHandle) -> Result<(), String> {
+    let token = require_token(&app)?;
+    app.state::<Api>()
+        .send(ApiRequest::delete("/api/integrations/slack").bearer(&token))
+        .await?
+        .error_for_status()?;
+
+    forget_slack(&app)?;
+    app_log!("[Slack] Disconnected");
+    events::emit_app_event(&app, AppEvent::SlackDisconnected);
+    Ok(())
+}
+
+fn validate_destination(destination: &SlackDestination) -> Result<(), AppError> {
+    match (&destination.channel_id, &destination.user_id) {
+        (Some(_), None) | (None, Some(_)) => Ok(()),
+        _ => Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "Invalid Slack destination: give exactly one of a channel or a user",
+        )),
+    }
+}
+
+async fn send_message(
+    app: &tauri::AppHandle,
+    destination: &SlackDestination,
+    message: serde_json::Value,
+) -> Result<(), AppError> {
+    validate_destination(destination)?;
+    if load_connection(app).is_none() {
+        return Err("Slack is not connected".to_string().into());
+    }
+    let token = require_token(app)?;
+
+    let mut body = message;
+    body["channelId"] = serde_json::json!(destination.channel_id);
+    body["userId"] = serde_json::json!(destination.user_id);
+
+    app.state::<Api>()
+        .send(
+            ApiRequest::post("/api/integrations/slack/messages")
+                .bearer(&token)
+                .json(&body)?,
+        )
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Post a hang invite, with RSVP buttons rendered by the server, to a Slack
+/// DM or channel
+#[tauri::command]
+#[specta::specta]
+pub async fn send_slack_hang_invite(
+    app: tauri::AppHandle,
+    hang_id: String,
+    destination: SlackDestination,
+    message: Option<String>,
+) -> Result<(), AppError> {
+    send_message(
+        &app,
+        &destination,
+        serde_json::json!({ "type": "hang_invite", "hangId": hang_id, "message": message }),
+    )
+    .await?;
+    app_log!("[Slack] Hang invite sent: hang={}", hang_id);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn send_slack_on_my_way(
+    app: tauri::AppHandle,
+    hang_id: String,
+    destination: SlackDestination,
+    eta_minutes: Option<u32>,
+) -> Result<(), AppError> {
+    if eta_minutes.is_some_and(|eta| eta > MAX_ETA_MINUTES) {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!("Invalid ETA: must be at most {} minutes", MAX_ETA_MINUTES),
+        ));
+    }
+    send_message(
+        &app,
+        &destination,
+        serde_json::json!({ "type": "on_my_way", "hangId": hang_id, "etaMinutes": eta_minutes }),
+    )
+    .await?;
+    app_log!("[Slack] On-my-way sent: hang={}", hang_id);
+    Ok(())
+}
+
+async fn fetch_presence(api: &Api, token: &str) -> Result<SlackPresence, String> {
+    let response = api
+        .send(ApiRequest::get("/api/integrations/slack/presence").bearer(token))
+        .await?;
+    response
+        .error_for_status()?
+        .json("/api/integrations/slack/presence")
+}
+
+/// Last Slack presence seen, if connected
+#[tauri::command]
+#[specta::specta]
+pub async fn get_slack_presence() -> Result<Option<SlackPresence>, String> {
+    Ok(PRESENCE.lock().ok().and_then(|p| p.clone()))
+}
+
+/// Poll Slack presence while connected and emit `slack-presence-changed`
+/// when it changes. Skipped while the app is locked or offline.
+pub fn spawn_presence_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(PRESENCE_INTERVAL).await;
+
+            if load_connection(&app).is_none()
+                || app.state::<AppLock>().is_locked()
+                || !network::is_online()
+            {
+                continue;
+            }
+            let Ok(token) = require_token(&app) else {
+                continue;
+            };
+
+            match fetch_presence(&app.state::<Api>(), &token).await {
+                Ok(presence) => {
+                    let changed = PRESENCE.lock().ok().is_some_and(|mut current| {
+                        let changed = current.as_ref() != Some(&presence);
+                        *current = Some(presence.clone());
+                        changed
+                    });
+                    if changed {
+                        events::emit_app_event(&app, AppEvent::SlackPresenceChanged(presence));
+                    }
+                }
+                Err(e) => app_log!("[Slack] Presence check failed: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::outbox;
+use crate::stores;
+
+const SYNC_POLICY_KEY: &str = "syncPolicy";
+// Some platforms are checked by spawning OS tools, so conditions are sampled
+// on an interval
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const LOW_BATTERY_PERCENT: u8 = 20;
+
+/// How eagerly background work uses the network and battery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPolicy {
+    /// Never defer, whatever the connection or battery
+    Aggressive,
+    /// Defer heavy work on metered connections or low battery
+    #[default]
+    Balanced,
+    /// Always defer heavy work
+    Conserve,
+}
+
+/// The policy and the conditions it was applied to
+#[derive(Debug, Clone, PartialEq, Serialize, specta::Type)]
+pub struct SyncConditions {
+    pub policy: SyncPolicy,
+    pub metered: bool,
+    #[serde(rename = "onBattery")]
+    pub on_battery: bool,
+    #[serde(rename = "batteryPercent")]
+    pub battery_percent: Option<u8>,
+    /// Large uploads, model downloads and background gathering are on hold
+    pub conserving: bool,
+}
+
+static POLICY: RwLock<SyncPolicy> = RwLock::new(SyncPolicy::Balanced);
+static CONDITIONS: Mutex<Option<SyncConditions>> = Mutex::new(None);
+
+struct Power {
+    on_battery: bool,
+    percent: Option<u8>,
+}
+
+#[cfg(target_os = "macos")]
+fn metered() -> bool {
+    // Low Data Mode is only exposed as NWPath.isConstrained in
+    // Network.framework, which has no command-line equivalent, so macOS
+    // connections are never treated as metered; battery still applies
+    false
+}
+
+#[cfg(target_os = "windows")]
+fn metered() -> bool {
+    use windows::Networking::Connectivity::{NetworkCostType, NetworkInformation};
+    NetworkInformation::GetInternetConnectionProfile()
+        .and_then(|profile| profile.GetConnectionCost())
+        .and_then(|cost| cost.NetworkCostType())
+        .is_ok_and(|cost| matches!(cost, NetworkCostType::Fixed | NetworkCostType::Variable))
+}
+
+#[cfg(target_os = "linux")]
+fn metered() -> bool {
+    // NMMetered: 1 = yes, 3 = guessed yes
+    std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .is_ok_and(|out| matches!(String::from_utf8_lossy(&out.stdout).trim(), "u 1" | "u 3"))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn metered() -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+fn power() -> Power {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+        .unwrap_or_default();
+    Power {
+        on_battery: output.contains("'Battery Power'"),
+        percent: output
+            .split_whitespace()
+            .find_map(|word| word.trim_end_matches(';').strip_suffix('%')?.parse().ok()),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn power() -> Power {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+    // ACLineStatus 0 means unplugged; BatteryFlag 128 means no battery;
+    // 255 means unknown for both the line status and the percentage
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // SAFETY: `status` is a valid, writable SYSTEM_POWER_STATUS for the call
+    if unsafe { GetSystemPowerStatus(&mut status) }.is_err() {
+        return Power {
+            on_battery: false,
+            percent: None,
+        };
+    }
+    Power {
+        on_battery: status.ACLineStatus == 0 && status.BatteryFlag & 128 == 0,
+        percent: (status.BatteryLifePercent != 255).then_some(status.BatteryLifePercent),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn power() -> Power {
+    let battery = std::fs::read_dir("/sys/class/power_supply")
+        .ok()
+        .and_then(|entries| {
+            entries.flatten().map(|e| e.path()).find(|p| {
+                p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with("BAT"))
+            })
+        });
+    let read = |name: &str| {
+        battery
+            .as_ref()
+            .and_then(|dir| std::fs::read_to_string(dir.join(name)).ok())
+            .map(|value| value.trim().to_string())
+    };
+    Power {
+        on_battery: read("status").is_some_and(|s| s == "Discharging"),
+        percent: read("capacity").and_then(|c| c.parse().ok()),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn power() -> Power {
+    Power {
+        on_battery: false,
+        percent: None,
+    }
+}
+
+fn policy() -> SyncPolicy {
+    POLICY.read().map(|p| *p).unwrap_or_default()
+}
+
+/// Sample the OS and apply the policy. Blocking.
+fn evaluate() -> SyncConditions {
+    let policy = policy();
+    let metered = metered();
+    let power = power();
+    let low_battery = power.on_battery && power.percent.is_some_and(|p| p <= LOW_BATTERY_PERCENT);
+    SyncConditions {
+        policy,
+        metered,
+        on_battery: power.on_battery,
+        battery_percent: power.percent,
+        conserving: match policy {
+            SyncPolicy::Aggressive => false,
+            SyncPolicy::Balanced => metered || low_battery,
+            SyncPolicy::Conserve => true,
+        },
+    }
+}
+
+/// Whether heavy background work (large uploads, model downloads,
+/// background gathering) should wait. Until the first sample, only an
+/// explicit `conserve` policy holds work back.
+pub fn conserving() -> bool {
+    match CONDITIONS.lock().ok().and_then(|c| c.clone()) {
+        Some(conditions) => conditions.conserving,
+        None => policy() == SyncPolicy::Conserve,
+    }
+}
+
+/// Store a fresh sample, emitting `sync-conditions-changed` and resuming
+/// deferred uploads when it differs from the last one
+fn refresh(app: &tauri::AppHandle) -> SyncConditions {
+    let conditions = evaluate();
+    let previous = CONDITIONS
+        .lock()
+        .ok()
+        .and_then(|mut c| c.replace(conditions.clone()));
+    // The first sample only establishes the baseline
+    if previous.as_ref().is_some_and(|p| p != &conditions) {
+        events::emit_app_event(app, AppEvent::SyncConditionsChanged(conditions.clone()));
+    }
+    if previous.is_some_and(|p| p.conserving) && !conditions.conserving {
+        app_log!("[SyncPolicy] Conditions improved, resuming deferred work");
+        outbox::wake_deferred(app);
+    }
+    conditions
+}
+
+/// Restore the saved policy and keep re-checking the connection and battery
+pub fn spawn_sync_policy_monitor(app: tauri::AppHandle) {
+    let saved = stores::open(&app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(SYNC_POLICY_KEY))
+        .and_then(|value| serde_json::from_value::<SyncPolicy>(value).ok());
+    if let (Some(saved), Ok(mut policy)) = (saved, POLICY.write()) {
+        *policy = saved;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let handle = app.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || refresh(&handle)).await;
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_policy() -> Result<SyncConditions, String> {
+    tauri::async_runtime::spawn_blocking(evaluate)
+        .await
+        .map_err(|e| format!("Sync policy check failed: {}", e))
+}
+
+/// Override automatic deferral: `aggressive` never waits, `conserve` always
+/// does, `balanced` follows the connection and battery
+#[tauri::command]
+#[specta::specta]
+pub async fn set_sync_policy(
+    app: tauri::AppHandle,
+    policy: SyncPolicy,
+) -> Result<SyncConditions, String> {
+    let store = stores::open(&app, stores::SETTINGS_STORE)?;
+    store.set(
+        SYNC_POLICY_KEY,
+        serde_json::to_value(policy).map_err(|e| format!("Failed to serialize policy: {}", e))?,
+    );
+    stores::save(&app, stores::SETTINGS_STORE)?;
+
+    *POLICY
+        .write()
+        .map_err(|_| "Sync policy lock poisoned".to_string())? = policy;
+    app_log!("[SyncPolicy] Policy set to {:?}", policy);
+
+    tauri::async_runtime::spawn_blocking(move || refresh(&app))
+        .await
+        .map_err(|e| format!("Sync policy check failed: {}", e))
+}
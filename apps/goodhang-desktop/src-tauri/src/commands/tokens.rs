@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+
+use crate::documents;
+use crate::errors::{AppError, ErrorCode};
+
+// Context window of the chat model, in tokens; uploads past this can't be
+// sent whole
+const CONTEXT_BUDGET: usize = 200_000;
+const MAX_CHUNK_TOKENS: usize = 8_192;
+const PREVIEW_CHARS: usize = 280;
+
+static TOKENIZER: OnceLock<CoreBPE> = OnceLock::new();
+
+/// cl100k is bundled with the binary; counts are an estimate for models
+/// with their own tokenizer, typically within a few percent
+fn tokenizer() -> Result<&'static CoreBPE, String> {
+    if let Some(bpe) = TOKENIZER.get() {
+        return Ok(bpe);
+    }
+    let bpe = tiktoken_rs::cl100k_base().map_err(|e| format!("Failed to load tokenizer: {}", e))?;
+    Ok(TOKENIZER.get_or_init(|| bpe))
+}
+
+fn count(text: &str) -> Result<usize, String> {
+    Ok(tokenizer()?.encode_ordinary(text).len())
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct TokenEstimate {
+    pub tokens: usize,
+    pub characters: usize,
+    #[serde(rename = "contextBudget")]
+    pub context_budget: usize,
+    #[serde(rename = "exceedsBudget")]
+    pub exceeds_budget: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, specta::Type)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    /// Fixed-size windows, each repeating the last `overlap` tokens of the
+    /// one before
+    Tokens { size: usize, overlap: usize },
+    /// Whole paragraphs packed up to `max_tokens`; a longer paragraph is
+    /// split into fixed windows
+    Paragraphs {
+        #[serde(rename = "maxTokens")]
+        max_tokens: usize,
+    },
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct ChunkPreview {
+    pub index: usize,
+    pub tokens: usize,
+    pub characters: usize,
+    pub preview: String,
+}
+
+fn preview(text: &str) -> String {
+    let trimmed = text.trim();
+    match trimmed.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &trimmed[..end]),
+        None => trimmed.to_string(),
+    }
+}
+
+/// Overlapping windows of `size` tokens, cut from `text` itself. A token can
+/// hold part of a multi-byte character, so each cut moves forward to the
+/// next character boundary rather than decoding a split character.
+fn token_windows(text: &str, size: usize, overlap: usize) -> Result<Vec<String>, String> {
+    let bpe = tokenizer()?;
+    let tokens = bpe.encode_ordinary(text);
+    // Byte offset in `text` where each token starts, plus the end
+    let mut offsets = Vec::with_capacity(tokens.len() + 1);
+    offsets.push(0);
+    for bytes in bpe._decode_native_and_split(tokens) {
+        offsets.push(offsets[offsets.len() - 1] + bytes.len());
+    }
+    let boundary = |token: usize| {
+        let mut at = offsets[token].min(text.len());
+        while !text.is_char_boundary(at) {
+            at += 1;
+        }
+        at
+    };
+
+    let token_count = offsets.len() - 1;
+    let step = size - overlap;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < token_count {
+        let end = (start + size).min(token_count);
+        let (from, to) = (boundary(start), boundary(end));
+        if from < to {
+            chunks.push(text[from..to].to_string());
+        }
+        if end == token_count {
+            break;
+        }
+        start += step;
+    }
+    Ok(chunks)
+}
+
+fn paragraph_chunks(text: &str, max_tokens: usize) -> Result<Vec<String>, String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let tokens = count(paragraph)?;
+        if tokens > max_tokens {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+                current_tokens = 0;
+            }
+            chunks.extend(token_windows(paragraph, max_tokens, 0)?);
+            continue;
+        }
+        if current_tokens + tokens > max_tokens && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+        current_tokens += tokens;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    Ok(chunks)
+}
+
+/// Token count of either `text` or the extracted text of the file at `path`
+#[tauri::command]
+#[specta::specta]
+pub async fn estimate_tokens(
+    text: Option<String>,
+    path: Option<String>,
+) -> Result<TokenEstimate, AppError> {
+    let text = match (text, path) {
+        (Some(text), None) => text,
+        (None, Some(path)) => documents::extract_text_in_background(PathBuf::from(path)).await?,
+        _ => {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                "Invalid input: pass either text or path",
+            ))
+        }
+    };
+
+    let (tokens, characters) = tauri::async_runtime::spawn_blocking(move || {
+        count(&text).map(|tokens| (tokens, text.chars().count()))
+    })
+    .await
+    .map_err(|e| format!("Tokenizer task failed: {}", e))??;
+    Ok(TokenEstimate {
+        tokens,
+        characters,
+        context_budget: CONTEXT_BUDGET,
+        exceeds_budget: tokens > CONTEXT_BUDGET,
+    })
+}
+
+/// Split a document as it would be chunked for the model, for previews
+#[tauri::command]
+#[specta::specta]
+pub async fn chunk_document(
+    path: String,
+    strategy: ChunkStrategy,
+) -> Result<Vec<ChunkPreview>, AppError> {
+    let valid = match strategy {
+        ChunkStrategy::Tokens { size, overlap } => {
+            (1..=MAX_CHUNK_TOKENS).contains(&size) && overlap < size
+        }
+        ChunkStrategy::Paragraphs { max_tokens } => (1..=MAX_CHUNK_TOKENS).contains(&max_tokens),
+    };
+    if !valid {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            format!(
+                "Invalid chunk strategy: sizes must be 1-{} tokens and overlap smaller than size",
+                MAX_CHUNK_TOKENS
+            ),
+        ));
+    }
+
+    let text = documents::extract_text_in_background(PathBuf::from(path)).await?;
+    let chunks = tauri::async_runtime::spawn_blocking(move || {
+        let chunks = match strategy {
+            ChunkStrategy::Tokens { size, overlap } => token_windows(&text, size, overlap)?,
+            ChunkStrategy::Paragraphs { max_tokens } => paragraph_chunks(&text, max_tokens)?,
+        };
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                Ok(ChunkPreview {
+                    index,
+                    tokens: count(chunk)?,
+                    characters: chunk.chars().count(),
+                    preview: preview(chunk),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()
+    })
+    .await
+    .map_err(|e| format!("Chunking task failed: {}", e))??;
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_never_split_characters() {
+        let text = "héllo 👋🏽 世界 ".repeat(20);
+        for size in 1..6 {
+            let windows = token_windows(&text, size, 0).unwrap();
+            assert_eq!(windows.concat(), text);
+        }
+    }
+
+    #[test]
+    fn windows_overlap() {
+        let text = "one two three four five six";
+        let windows = token_windows(text, 4, 2).unwrap();
+        assert_eq!(
+            windows.first().map(String::as_str),
+            Some("one two three four")
+        );
+        assert!(windows[1].starts_with(" three"));
+        assert!(windows.last().unwrap().ends_with("six"));
+    }
+}
@@ -0,0 +1,346 @@
+use serde::Serialize;
+use std::time::Duration;
+use tauri::Manager;
+
+use super::auth::load_session;
+use crate::api::{Api, ApiClient, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::embeddings;
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+use crate::outbox;
+use crate::search_index;
+
+const UNDO_WINDOW: Duration = Duration::from_secs(30);
+/// Outbox task issuing the API delete once the undo window has passed
+pub const DELETE_TASK: &str = "remote_delete";
+
+const JOURNAL_ENTRY: &str = "journal";
+const RELATIONSHIP_NOTE: &str = "relationship_note";
+const CONTEXT_FILE: &str = "context_file";
+
+/// A delete that can still be undone
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PendingDelete {
+    pub id: String,
+    /// journal | relationship_note | context_file
+    pub kind: String,
+    /// Unix seconds; after this the API delete is issued
+    #[serde(rename = "undoUntil")]
+    pub undo_until: i64,
+}
+
+/// Search text for an item, so it can be put back on undo
+fn indexed_text(db: &Database, scope: &str, id: &str) -> Result<serde_json::Value, String> {
+    let row: Option<(String, String)> = db.with_conn(|conn| {
+        let mut stmt = conn
+            .prepare("SELECT title, body FROM search_index WHERE scope = ?1 AND source_id = ?2")?;
+        let mut rows = stmt.query_map([scope, id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.next().transpose()
+    })?;
+    Ok(match row {
+        Some((title, body)) => serde_json::json!({ "title": title, "body": body }),
+        None => serde_json::Value::Null,
+    })
+}
+
+/// Take the item out of local views and record what's needed to bring it
+/// back. Returns the snapshot and the API path to delete, if any.
+fn hide(
+    db: &Database,
+    kind: &str,
+    id: &str,
+) -> Result<(serde_json::Value, Option<String>), AppError> {
+    let (snapshot, remote_path) = match kind {
+        RELATIONSHIP_NOTE => {
+            let note = db
+                .with_conn(|conn| {
+                    conn.query_row(
+                        "SELECT * FROM relationship_notes WHERE id = ?1",
+                        [id],
+                        |row| {
+                            Ok(serde_json::json!({
+                                "id": row.get::<_, String>("id")?,
+                                "relationshipId": row.get::<_, String>("relationship_id")?,
+                                "body": row.get::<_, String>("body")?,
+                                "createdAt": row.get::<_, i64>("created_at")?,
+                                "syncToGft": row.get::<_, bool>("sync_to_gft")?,
+                                "syncStatus": row.get::<_, String>("sync_status")?,
+                                "remoteId": row.get::<_, Option<String>>("remote_id")?,
+                                "syncError": row.get::<_, Option<String>>("sync_error")?,
+                            }))
+                        },
+                    )
+                })
+                .map_err(|_| {
+                    AppError::new(ErrorCode::NotFound, format!("No note with id {}", id))
+                })?;
+            db.with_conn(|conn| {
+                conn.execute("DELETE FROM relationship_notes WHERE id = ?1", [id])
+            })?;
+            // Only notes already pushed to GFT have anything to delete remotely
+            let remote_path = note["remoteId"].as_str().map(|remote_id| {
+                format!(
+                    "/api/gft/contacts/{}/notes/{}",
+                    note["relationshipId"].as_str().unwrap_or_default(),
+                    remote_id
+                )
+            });
+            (note, remote_path)
+        }
+        JOURNAL_ENTRY => (
+            indexed_text(db, kind, id)?,
+            Some(format!("/api/journal/entries/{}", id)),
+        ),
+        CONTEXT_FILE => (
+            indexed_text(db, kind, id)?,
+            Some(format!("/api/voice/context-files/{}", id)),
+        ),
+        other => return Err(format!("Unknown delete kind: {}", other).into()),
+    };
+    search_index::remove(db, kind, id)?;
+    embeddings::remove_document(db, kind, id)?;
+    Ok((snapshot, remote_path))
+}
+
+/// Put a hidden item back from its snapshot
+fn restore(
+    app: &tauri::AppHandle,
+    kind: &str,
+    id: &str,
+    snapshot: &serde_json::Value,
+) -> Result<(), String> {
+    if snapshot.is_null() {
+        return Ok(());
+    }
+    let db = app.state::<Database>();
+    let (title, body, created_at) = match kind {
+        RELATIONSHIP_NOTE => {
+            db.with_conn(|conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO relationship_notes
+                     (id, relationship_id, body, created_at, sync_to_gft, sync_status, remote_id, sync_error)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    rusqlite::params![
+                        id,
+                        snapshot["relationshipId"].as_str(),
+                        snapshot["body"].as_str(),
+                        snapshot["createdAt"].as_i64(),
+                        snapshot["syncToGft"].as_bool(),
+                        snapshot["syncStatus"].as_str(),
+                        snapshot["remoteId"].as_str(),
+                        snapshot["syncError"].as_str(),
+                    ],
+                )
+            })?;
+            let body = snapshot["body"].as_str().unwrap_or_default().to_string();
+            let title: String = body
+                .lines()
+                .find(|l| !l.trim().is_empty())
+                .unwrap_or_default()
+                .chars()
+                .take(80)
+                .collect();
+            (
+                title,
+                body,
+                snapshot["createdAt"].as_i64().unwrap_or_else(now_secs),
+            )
+        }
+        _ => (
+            snapshot["title"].as_str().unwrap_or_default().to_string(),
+            snapshot["body"].as_str().unwrap_or_default().to_string(),
+            now_secs(),
+        ),
+    };
+
+    search_index::upsert_quietly(app, kind, id, &title, &body);
+    match kind {
+        RELATIONSHIP_NOTE => embeddings::index_in_background(
+            app,
+            RELATIONSHIP_NOTE,
+            id.to_string(),
+            body,
+            created_at,
+        ),
+        CONTEXT_FILE => {
+            embeddings::index_in_background(app, CONTEXT_FILE, id.to_string(), body, created_at)
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Tombstone an item for the undo window, then let the delete go through
+fn soft_delete(app: &tauri::AppHandle, kind: &str, id: String) -> Result<PendingDelete, AppError> {
+    let db = app.state::<Database>();
+    let (snapshot, remote_path) = hide(&db, kind, &id)?;
+    let undo_until = now_secs() + UNDO_WINDOW.as_secs() as i64;
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO tombstones (id, kind, snapshot, remote_path, delete_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![id, kind, snapshot.to_string(), remote_path, undo_until],
+        )
+    })?;
+    app_log!("[Trash] {} {} deleted, undo until {}", kind, id, undo_until);
+
+    let handle = app.clone();
+    let tombstone = id.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(UNDO_WINDOW).await;
+        if let Err(e) = commit(&handle, Some(&tombstone)) {
+            app_log!("[Trash] Failed to finish delete of {}: {}", tombstone, e);
+        }
+    });
+
+    Ok(PendingDelete {
+        id,
+        kind: kind.to_string(),
+        undo_until,
+    })
+}
+
+/// Drop tombstones whose window has passed (or just `id`) and queue their
+/// API deletes
+fn commit(app: &tauri::AppHandle, id: Option<&str>) -> Result<(), String> {
+    let due: Vec<(String, String, Option<String>)> = app.state::<Database>().with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let due = {
+            let mut stmt = tx.prepare(
+                "SELECT id, kind, remote_path FROM tombstones
+                 WHERE delete_at <= ?1 AND (?2 IS NULL OR id = ?2)",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![now_secs(), id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for (id, _, _) in &due {
+            tx.execute("DELETE FROM tombstones WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+        Ok(due)
+    })?;
+
+    for (id, kind, remote_path) in due {
+        if let Some(path) = remote_path {
+            outbox::enqueue(
+                app,
+                DELETE_TASK,
+                Some(&format!("{}:{}", kind, id)),
+                serde_json::json!({ "path": path }),
+            )?;
+        }
+        app_log!("[Trash] {} {} permanently deleted", kind, id);
+    }
+    Ok(())
+}
+
+/// Outbox handler: issue an API delete. Already gone counts as done.
+pub async fn deliver_delete(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let path = payload["path"]
+        .as_str()
+        .ok_or_else(|| "Invalid task payload: missing path".to_string())?;
+    let token = load_session(app)?
+        .map(|s| s.token)
+        .ok_or_else(|| "Not signed in".to_string())?;
+    let api = app.state::<Api>();
+    delete_remote(&**api, &token, path).await
+}
+
+async fn delete_remote(api: &dyn ApiClient, token: &str, path: &str) -> Result<(), String> {
+    let response = api.send(ApiRequest::delete(path).bearer(token)).await?;
+    if response.status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(());
+    }
+    response.error_for_status()?;
+    Ok(())
+}
+
+/// Finish deletes whose undo window closed while the app wasn't running
+pub fn finish_pending_deletes(app: &tauri::AppHandle) {
+    if let Err(e) = commit(app, None) {
+        app_log!("[Trash] Failed to finish pending deletes: {}", e);
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_journal_entry(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<PendingDelete, AppError> {
+    soft_delete(&app, JOURNAL_ENTRY, id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_relationship_note(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<PendingDelete, AppError> {
+    soft_delete(&app, RELATIONSHIP_NOTE, id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_context_file(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<PendingDelete, AppError> {
+    soft_delete(&app, CONTEXT_FILE, id)
+}
+
+/// Bring back an item deleted less than 30 seconds ago
+#[tauri::command]
+#[specta::specta]
+pub async fn undo_delete(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    let tombstone: Option<(String, String)> = app.state::<Database>().with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let row = {
+            let mut stmt = tx.prepare("SELECT kind, snapshot FROM tombstones WHERE id = ?1")?;
+            let mut rows = stmt.query_map([&id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.next().transpose()?
+        };
+        tx.execute("DELETE FROM tombstones WHERE id = ?1", [&id])?;
+        tx.commit()?;
+        Ok(row)
+    })?;
+    let Some((kind, snapshot)) = tombstone else {
+        return Err(AppError::new(
+            ErrorCode::NotFound,
+            format!(
+                "Nothing to undo for {}: the delete has already gone through",
+                id
+            ),
+        ));
+    };
+
+    let snapshot: serde_json::Value =
+        serde_json::from_str(&snapshot).unwrap_or(serde_json::Value::Null);
+    restore(&app, &kind, &id, &snapshot)?;
+    app_log!("[Trash] {} {} restored", kind, id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::FixtureApiClient;
+
+    #[tokio::test]
+    async fn remote_delete_treats_already_gone_as_done() {
+        let api = FixtureApiClient::from_files(&[("DELETE_api_journal_j1", "{}")]);
+        assert!(delete_remote(&api, "token", "/api/journal/j1")
+            .await
+            .is_ok());
+        // No fixture answers 404, as the server does for a deleted entry
+        assert!(delete_remote(&api, "token", "/api/journal/j2")
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,676 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::db::{now_secs, Database};
+use crate::documents;
+use crate::embeddings;
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::outbox;
+use crate::search_index;
+
+const CHUNK_SIZE: u64 = 5 * 1024 * 1024;
+const MAX_CHUNK_ATTEMPTS: u32 = 4;
+// Uploads at least this big wait while the sync policy is conserving
+const LARGE_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+const UPLOAD_KINDS: &[&str] = &["context_file", "audio", "profile_photo"];
+/// Outbox task driving one upload to completion
+pub const UPLOAD_TASK: &str = "upload";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadStatus {
+    Pending,
+    Uploading,
+    Paused,
+    Completed,
+    Failed,
+}
+
+impl UploadStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            UploadStatus::Pending => "pending",
+            UploadStatus::Uploading => "uploading",
+            UploadStatus::Paused => "paused",
+            UploadStatus::Completed => "completed",
+            UploadStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "uploading" => UploadStatus::Uploading,
+            "paused" => UploadStatus::Paused,
+            "completed" => UploadStatus::Completed,
+            "failed" => UploadStatus::Failed,
+            _ => UploadStatus::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Upload {
+    pub id: String,
+    pub kind: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: u64,
+    #[serde(rename = "bytesUploaded")]
+    pub bytes_uploaded: u64,
+    pub status: UploadStatus,
+    /// Server-side file id once the upload has completed
+    #[serde(rename = "resultId")]
+    pub result_id: Option<String>,
+    pub error: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+    #[serde(skip)]
+    remote_id: Option<String>,
+    #[serde(skip)]
+    file_path: PathBuf,
+    #[serde(skip)]
+    chunk_size: u64,
+    #[serde(skip)]
+    file_sha256: String,
+}
+
+impl Upload {
+    fn total_chunks(&self) -> u64 {
+        self.size_bytes.div_ceil(self.chunk_size).max(1)
+    }
+
+    fn chunk_range(&self, index: u64) -> (u64, u64) {
+        let start = index * self.chunk_size;
+        (start, (start + self.chunk_size).min(self.size_bytes))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct UploadProgressEvent {
+    pub id: String,
+    pub kind: String,
+    #[serde(rename = "bytesUploaded")]
+    pub bytes_uploaded: u64,
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+    pub status: UploadStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InitResponse {
+    #[serde(rename = "uploadId")]
+    upload_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadState {
+    #[serde(default, rename = "receivedChunks")]
+    received_chunks: Vec<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompleteResponse {
+    #[serde(rename = "fileId")]
+    file_id: String,
+}
+
+/// Pause flags for uploads that currently have a worker running
+#[derive(Default)]
+pub struct Uploads {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl Uploads {
+    /// Register a worker for `id`, or `None` if one is already running
+    fn claim(&self, id: &str) -> Option<Arc<AtomicBool>> {
+        let mut active = self.active.lock().ok()?;
+        if active.contains_key(id) {
+            return None;
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        active.insert(id.to_string(), flag.clone());
+        Some(flag)
+    }
+
+    fn release(&self, id: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(id);
+        }
+    }
+
+    fn pause(&self, id: &str) -> bool {
+        self.active
+            .lock()
+            .ok()
+            .and_then(|active| {
+                active
+                    .get(id)
+                    .map(|flag| flag.store(true, Ordering::SeqCst))
+            })
+            .is_some()
+    }
+}
+
+fn row_to_upload(row: &rusqlite::Row) -> rusqlite::Result<Upload> {
+    let status: String = row.get("status")?;
+    let file_path: String = row.get("file_path")?;
+    Ok(Upload {
+        id: row.get("id")?,
+        kind: row.get("kind")?,
+        file_name: row.get("file_name")?,
+        mime_type: row.get("mime_type")?,
+        size_bytes: row.get::<_, i64>("size_bytes")? as u64,
+        bytes_uploaded: row.get::<_, i64>("bytes_uploaded")? as u64,
+        status: UploadStatus::parse(&status),
+        result_id: row.get("result_id")?,
+        error: row.get("error")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+        remote_id: row.get("remote_id")?,
+        file_path: PathBuf::from(file_path),
+        chunk_size: row.get::<_, i64>("chunk_size")? as u64,
+        file_sha256: row.get("file_sha256")?,
+    })
+}
+
+fn get_upload(db: &Database, id: &str) -> Result<Upload, String> {
+    db.with_conn(|conn| conn.query_row("SELECT * FROM uploads WHERE id = ?1", [id], row_to_upload))
+}
+
+fn update_upload(
+    app: &tauri::AppHandle,
+    id: &str,
+    status: UploadStatus,
+    error: Option<&str>,
+) -> Result<Upload, String> {
+    let db = app.state::<Database>();
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE uploads SET status = ?1, error = ?2, updated_at = ?3 WHERE id = ?4",
+            rusqlite::params![status.as_str(), error, now_secs(), id],
+        )
+    })?;
+    let upload = get_upload(&db, id)?;
+    emit_progress(app, &upload);
+    Ok(upload)
+}
+
+fn emit_progress(app: &tauri::AppHandle, upload: &Upload) {
+    events::emit_app_event(
+        app,
+        AppEvent::UploadProgress(UploadProgressEvent {
+            id: upload.id.clone(),
+            kind: upload.kind.clone(),
+            bytes_uploaded: upload.bytes_uploaded,
+            total_bytes: upload.size_bytes,
+            status: upload.status,
+            error: upload.error.clone(),
+        }),
+    );
+}
+
+/// Whole-file SHA-256, so the server can verify the reassembled upload
+pub(crate) fn file_sha256(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn bearer(app: &tauri::AppHandle) -> Result<String, String> {
+    // Re-read per request: long uploads can outlive a token refresh
+    load_session(app)?
+        .map(|s| format!("Bearer {}", s.token))
+        .ok_or_else(|| "Not signed in".to_string())
+}
+
+/// Chunk bodies are raw bytes, so chunk PUTs go straight through reqwest
+/// rather than the JSON `ApiClient`
+async fn init_remote(app: &tauri::AppHandle, upload: &Upload) -> Result<String, String> {
+    let init: InitResponse = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/uploads")
+                .header("Authorization", bearer(app)?)
+                .json(&serde_json::json!({
+                    "kind": upload.kind,
+                    "fileName": upload.file_name,
+                    "mimeType": upload.mime_type,
+                    "sizeBytes": upload.size_bytes,
+                    "chunkSize": upload.chunk_size,
+                    "sha256": upload.file_sha256,
+                }))?,
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/uploads")?;
+    Ok(init.upload_id)
+}
+
+async fn received_chunks(app: &tauri::AppHandle, remote_id: &str) -> Result<HashSet<u64>, String> {
+    let state: UploadState = app
+        .state::<Api>()
+        .send(
+            ApiRequest::get(format!("/api/uploads/{}", remote_id))
+                .header("Authorization", bearer(app)?),
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/uploads/{id}")?;
+    Ok(state.received_chunks.into_iter().collect())
+}
+
+async fn read_chunk(path: &Path, start: u64, end: u64) -> Result<Vec<u8>, String> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    file.seek(std::io::SeekFrom::Start(start))
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut chunk = vec![0u8; (end - start) as usize];
+    file.read_exact(&mut chunk)
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(chunk)
+}
+
+async fn put_chunk(
+    app: &tauri::AppHandle,
+    upload: &Upload,
+    remote_id: &str,
+    index: u64,
+    chunk: Vec<u8>,
+) -> Result<(), String> {
+    let (start, end) = upload.chunk_range(index);
+    if end <= start {
+        return Err(format!(
+            "Invalid chunk {} of {}: no bytes",
+            index, upload.id
+        ));
+    }
+    let checksum = hex::encode(Sha256::digest(&chunk));
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let result = async {
+            app.state::<Api>()
+                .send(
+                    ApiRequest::put(format!("/api/uploads/{}/chunks/{}", remote_id, index))
+                        .header("Authorization", bearer(app)?)
+                        .header("Content-Type", "application/octet-stream")
+                        .header(
+                            "Content-Range",
+                            format!("bytes {}-{}/{}", start, end - 1, upload.size_bytes),
+                        )
+                        .header("X-Chunk-Sha256", checksum.clone())
+                        .bytes(chunk.clone()),
+                )
+                .await?
+                .error_for_status()
+                .map(|_| ())
+        }
+        .await;
+
+        match result {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < MAX_CHUNK_ATTEMPTS => {
+                app_log!(
+                    "[Uploads] Chunk {} of {} failed (attempt {}): {}",
+                    index,
+                    upload.id,
+                    attempt,
+                    e
+                );
+                tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn complete_remote(
+    app: &tauri::AppHandle,
+    upload: &Upload,
+    remote_id: &str,
+    text: Option<&str>,
+) -> Result<String, String> {
+    let complete: CompleteResponse = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post(format!("/api/uploads/{}/complete", remote_id))
+                .header("Authorization", bearer(app)?)
+                .json(&serde_json::json!({ "sha256": upload.file_sha256, "text": text }))?,
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/uploads/{id}/complete")?;
+    Ok(complete.file_id)
+}
+
+/// Upload every chunk the server doesn't have yet. Returns `Ok(false)` if
+/// paused before finishing.
+async fn upload_chunks(
+    app: &tauri::AppHandle,
+    id: &str,
+    paused: &AtomicBool,
+) -> Result<bool, String> {
+    let db = app.state::<Database>();
+    let mut upload = get_upload(&db, id)?;
+
+    let size = std::fs::metadata(&upload.file_path)
+        .map_err(|e| format!("File is no longer available: {}", e))?
+        .len();
+    if size != upload.size_bytes {
+        return Err("File changed since the upload started".to_string());
+    }
+
+    let remote_id = match upload.remote_id.clone() {
+        Some(remote_id) => remote_id,
+        None => {
+            let remote_id = init_remote(app, &upload).await?;
+            db.with_conn(|conn| {
+                conn.execute(
+                    "UPDATE uploads SET remote_id = ?1 WHERE id = ?2",
+                    rusqlite::params![remote_id, id],
+                )
+            })?;
+            remote_id
+        }
+    };
+
+    // Ask the server what it already has, so resumes skip finished chunks
+    let received = received_chunks(app, &remote_id).await?;
+    let mut bytes_uploaded: u64 = received
+        .iter()
+        .filter(|&&i| i < upload.total_chunks())
+        .map(|&i| {
+            let (start, end) = upload.chunk_range(i);
+            end - start
+        })
+        .sum();
+
+    for index in 0..upload.total_chunks() {
+        if received.contains(&index) {
+            continue;
+        }
+        if paused.load(Ordering::SeqCst) {
+            return Ok(false);
+        }
+
+        let (start, end) = upload.chunk_range(index);
+        let chunk = read_chunk(&upload.file_path, start, end).await?;
+        put_chunk(app, &upload, &remote_id, index, chunk).await?;
+
+        bytes_uploaded += end - start;
+        db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE uploads SET bytes_uploaded = ?1, updated_at = ?2 WHERE id = ?3",
+                rusqlite::params![bytes_uploaded as i64, now_secs(), id],
+            )
+        })?;
+        upload.bytes_uploaded = bytes_uploaded;
+        emit_progress(app, &upload);
+    }
+
+    // Context files carry their text so the server doesn't have to parse them
+    let text = if upload.kind == "context_file" {
+        documents::extract_text_in_background(upload.file_path.clone())
+            .await
+            .map_err(|e| app_log!("[Uploads] No text extracted from {}: {}", upload.id, e))
+            .ok()
+    } else {
+        None
+    };
+
+    let file_id = complete_remote(app, &upload, &remote_id, text.as_deref()).await?;
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE uploads SET result_id = ?1 WHERE id = ?2",
+            rusqlite::params![file_id, id],
+        )
+    })?;
+
+    if let Some(text) = text.filter(|t| !t.is_empty()) {
+        search_index::upsert_quietly(app, "context_file", &file_id, &upload.file_name, &text);
+        embeddings::index_in_background(app, "context_file", file_id, text, now_secs());
+    }
+    Ok(true)
+}
+
+/// Drive a claimed upload until it completes, fails, or is paused
+async fn run_claimed(app: &tauri::AppHandle, id: &str, paused: &AtomicBool) {
+    let _ = update_upload(app, id, UploadStatus::Uploading, None);
+    let outcome = upload_chunks(app, id, paused).await;
+    app.state::<Uploads>().release(id);
+
+    let result = match outcome {
+        Ok(true) => {
+            app_log!("[Uploads] Upload complete: id={}", id);
+            update_upload(app, id, UploadStatus::Completed, None)
+        }
+        Ok(false) => {
+            app_log!("[Uploads] Upload paused: id={}", id);
+            update_upload(app, id, UploadStatus::Paused, None)
+        }
+        Err(e) => {
+            app_log!("[Uploads] Upload failed: id={} error={}", id, e);
+            update_upload(app, id, UploadStatus::Failed, Some(&e))
+        }
+    };
+    if let Err(e) = result {
+        app_log!("[Uploads] Failed to record upload state: {}", e);
+    }
+}
+
+fn queue_upload(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    let entity = format!("upload:{}", id);
+    // A failed upload may still have its task waiting on a retry
+    if !outbox::retry_entity(app, &entity)? {
+        outbox::enqueue(
+            app,
+            UPLOAD_TASK,
+            Some(&entity),
+            serde_json::json!({ "uploadId": id }),
+        )?;
+    }
+    Ok(())
+}
+
+/// Outbox handler: run an upload until it completes, resuming from the
+/// chunks the server already has. A pause counts as delivered; resuming
+/// queues it again.
+pub async fn deliver_upload(
+    app: &tauri::AppHandle,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let id = payload["uploadId"]
+        .as_str()
+        .ok_or_else(|| "Invalid task payload: missing uploadId".to_string())?;
+    let db = app.state::<Database>();
+    let Ok(upload) = get_upload(&db, id) else {
+        return Ok(()); // Removed since it was queued
+    };
+    if matches!(
+        upload.status,
+        UploadStatus::Completed | UploadStatus::Paused
+    ) {
+        return Ok(());
+    }
+    if upload.size_bytes >= LARGE_UPLOAD_BYTES && super::sync_policy::conserving() {
+        return Err(format!(
+            "{}: large upload while conserving data",
+            outbox::DEFERRED
+        ));
+    }
+    let Some(paused) = app.state::<Uploads>().claim(id) else {
+        return Err(format!("{}: upload already running", outbox::DEFERRED));
+    };
+
+    run_claimed(app, id, &paused).await;
+    let upload = get_upload(&db, id)?;
+    match upload.status {
+        UploadStatus::Completed | UploadStatus::Paused => Ok(()),
+        _ => Err(upload
+            .error
+            .unwrap_or_else(|| "Upload did not complete".to_string())),
+    }
+}
+
+/// Record a new upload of a local file, returning its id
+async fn create_upload(
+    db: &Database,
+    path: String,
+    kind: String,
+    mime_type: Option<String>,
+) -> Result<String, String> {
+    if !UPLOAD_KINDS.contains(&kind.as_str()) {
+        return Err(format!("Unknown upload kind: {}", kind));
+    }
+
+    let file_path = PathBuf::from(&path);
+    let size_bytes = std::fs::metadata(&file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .len();
+    // Chunks are addressed by byte range, and an empty file has none
+    if size_bytes == 0 {
+        return Err(format!("Invalid upload: {} is empty", path));
+    }
+    let file_name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| "Path has no file name".to_string())?;
+
+    let hash_path = file_path.clone();
+    let file_sha256 = tauri::async_runtime::spawn_blocking(move || file_sha256(&hash_path))
+        .await
+        .map_err(|e| format!("Hashing task failed: {}", e))??;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = now_secs();
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO uploads (id, kind, file_path, file_name, mime_type, size_bytes,
+                chunk_size, file_sha256, status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'pending', ?9, ?9)",
+            rusqlite::params![
+                id,
+                kind,
+                path,
+                file_name,
+                mime_type.unwrap_or_else(|| "application/octet-stream".to_string()),
+                size_bytes as i64,
+                CHUNK_SIZE as i64,
+                file_sha256,
+                now
+            ],
+        )
+    })?;
+
+    app_log!("[Uploads] Upload started: id={} size={}", id, size_bytes);
+    Ok(id)
+}
+
+/// Upload a file and wait for it to finish, for flows that need the
+/// server's file id before continuing. Still emits `upload-progress`.
+/// Runs outside the outbox; one cut short by a crash is adopted by it on
+/// the next start.
+pub(crate) async fn upload_and_wait(
+    app: &tauri::AppHandle,
+    path: String,
+    kind: &str,
+    mime_type: &str,
+) -> Result<Upload, String> {
+    let db = app.state::<Database>();
+    let id = create_upload(&db, path, kind.to_string(), Some(mime_type.to_string())).await?;
+    let paused = app
+        .state::<Uploads>()
+        .claim(&id)
+        .ok_or_else(|| "Upload is already running".to_string())?;
+    run_claimed(app, &id, &paused).await;
+
+    let upload = get_upload(&db, &id)?;
+    match upload.status {
+        UploadStatus::Completed => Ok(upload),
+        _ => Err(upload
+            .error
+            .unwrap_or_else(|| "Upload did not complete".to_string())),
+    }
+}
+
+/// Start a chunked, resumable upload of a local file. Progress arrives as
+/// `upload-progress` events.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_upload(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    path: String,
+    kind: String,
+    mime_type: Option<String>,
+) -> Result<Upload, String> {
+    let id = create_upload(&db, path, kind, mime_type).await?;
+    queue_upload(&app, &id)?;
+    get_upload(&db, &id)
+}
+
+/// Stop after the chunk currently in flight; `resume_upload` continues it
+#[tauri::command]
+#[specta::specta]
+pub async fn pause_upload(uploads: tauri::State<'_, Uploads>, id: String) -> Result<(), String> {
+    if !uploads.pause(&id) {
+        return Err("Upload is not running".to_string());
+    }
+    Ok(())
+}
+
+/// Continue a paused or failed upload from the chunks the server already has
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_upload(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+) -> Result<Upload, String> {
+    let upload = get_upload(&db, &id)?;
+    if upload.status == UploadStatus::Completed {
+        return Ok(upload);
+    }
+    queue_upload(&app, &id)?;
+    get_upload(&db, &id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_uploads(db: tauri::State<'_, Database>) -> Result<Vec<Upload>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT * FROM uploads ORDER BY created_at DESC")?;
+        let rows = stmt.query_map([], row_to_upload)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })
+}
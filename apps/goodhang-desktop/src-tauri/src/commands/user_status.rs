@@ -1,13 +1,20 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
 
-fn get_api_base_url() -> String {
-    std::env::var("GOODHANG_API_URL")
-        .unwrap_or_else(|_| "https://goodhang-staging.vercel.app".to_string())
-}
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest};
+use crate::errors::AppError;
+use crate::events::{self, AppEvent};
+use crate::http;
+use crate::logging::{app_log, redact};
+use crate::network;
+
+const MIN_WATCH_INTERVAL_SECS: u64 = 15;
 
 // Assessment status
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct GoodHangAssessment {
     pub completed: bool,
     pub status: String,
@@ -22,7 +29,7 @@ pub struct GoodHangAssessment {
 }
 
 // Sculptor status
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct SculptorStatus {
     pub completed: bool,
     pub status: String,
@@ -30,7 +37,7 @@ pub struct SculptorStatus {
 }
 
 // Identity profile status
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct IdentityProfile {
     pub completed: bool,
     pub annual_theme: Option<String>,
@@ -39,26 +46,26 @@ pub struct IdentityProfile {
 }
 
 // Product statuses
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct GoodHangProduct {
     pub enabled: bool,
     pub assessment: Option<GoodHangAssessment>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct FounderOSProduct {
     pub enabled: bool,
     pub sculptor: Option<SculptorStatus>,
     pub identity_profile: Option<IdentityProfile>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct VoiceOSProduct {
     pub enabled: bool,
     pub context_files_count: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct Products {
     pub goodhang: GoodHangProduct,
     pub founder_os: FounderOSProduct,
@@ -66,7 +73,7 @@ pub struct Products {
 }
 
 // User info
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct UserInfo {
     pub id: String,
     pub email: Option<String>,
@@ -74,14 +81,14 @@ pub struct UserInfo {
 }
 
 // Entities info
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct EntitiesInfo {
     pub count: i32,
     pub has_entity: bool,
 }
 
 // Contexts info
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
 pub struct ContextsInfo {
     #[serde(default)]
     pub available: Vec<String>,
@@ -89,7 +96,7 @@ pub struct ContextsInfo {
 }
 
 // Full user status response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct UserStatus {
     pub found: bool,
     pub user: Option<UserInfo>,
@@ -112,38 +119,284 @@ impl Default for UserStatus {
     }
 }
 
-#[tauri::command]
-pub async fn fetch_user_status(
+/// Last full status seen for a user, with the version tag the server gave it
+struct CachedStatus {
+    user_id: String,
+    etag: String,
+    status: serde_json::Value,
+}
+
+static STATUS_CACHE: Mutex<Option<CachedStatus>> = Mutex::new(None);
+// Product flags from the most recent successful fetch
+static PRODUCTS: Mutex<Option<Products>> = Mutex::new(None);
+
+/// Apply a JSON Merge Patch (RFC 7386): objects merge recursively, `null`
+/// removes a key, anything else replaces the target
+fn merge_patch(target: &mut serde_json::Value, patch: serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    let target = target.as_object_mut().expect("target is an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(&key);
+        } else {
+            merge_patch(target.entry(key).or_insert(serde_json::Value::Null), value);
+        }
+    }
+}
+
+fn cached_for(user_id: &str) -> Option<(String, serde_json::Value)> {
+    let cache = STATUS_CACHE.lock().ok()?;
+    cache
+        .as_ref()
+        .filter(|c| c.user_id == user_id)
+        .map(|c| (c.etag.clone(), c.status.clone()))
+}
+
+fn store_cached(user_id: &str, etag: Option<String>, status: &serde_json::Value) {
+    if let Ok(mut cache) = STATUS_CACHE.lock() {
+        *cache = etag.map(|etag| CachedStatus {
+            user_id: user_id.to_string(),
+            etag,
+            status: status.clone(),
+        });
+    }
+}
+
+/// Forget the cached status, e.g. when the user signs out
+pub(crate) fn clear_status_cache() {
+    if let Ok(mut cache) = STATUS_CACHE.lock() {
+        cache.take();
+    }
+    if let Ok(mut products) = PRODUCTS.lock() {
+        products.take();
+    }
+}
+
+/// Product flags from the last status fetch, if there has been one
+pub(crate) fn cached_products() -> Option<Products> {
+    PRODUCTS.lock().ok().and_then(|p| p.clone())
+}
+
+fn parse_status(value: serde_json::Value, api_version: Option<&str>) -> Result<UserStatus, String> {
+    // Round-trip through bytes so mismatches get path diagnostics and quarantine
+    let body = serde_json::to_vec(&value).map_err(|e| format!("Failed to encode status: {}", e))?;
+    http::decode(&body, "/api/user/status", api_version)
+}
+
+/// When a previous status for the same user is cached, its ETag is sent so
+/// the server can answer 304 Not Modified or a merge-patch delta
+/// (`X-Status-Delta: true`) instead of the full payload.
+async fn fetch_status(
+    api: &Api,
     token: String,
     user_id: Option<String>,
-) -> Result<UserStatus, String> {
-    let client = reqwest::Client::new();
-
-    // Build URL with query params
-    let mut url = format!("{}/api/user/status", get_api_base_url());
+) -> Result<UserStatus, AppError> {
+    let mut request = ApiRequest::get("/api/user/status")
+        .bearer(&token)
+        .header("Accept-Delta", "merge-patch");
     if let Some(id) = &user_id {
-        url = format!("{}?userId={}", url, id);
+        request = request.query("userId", id);
     }
 
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .await
-        .map_err(|e| format!("Network error: {}", e))?;
+    // Deltas are only safe against a status we know belongs to this user
+    let cached = user_id.as_deref().and_then(cached_for);
+    if let Some((etag, _)) = &cached {
+        request = request.header("If-None-Match", etag.clone());
+    }
 
-    if !response.status().is_success() {
-        let status = response.status();
+    // Components mounting together often ask at once; the client shares
+    // one round trip between identical reads
+    let response = api.send(request).await?;
+
+    if response.status == reqwest::StatusCode::NOT_MODIFIED {
+        if let Some((_, status)) = cached {
+            return Ok(parse_status(status, response.header(http::API_VERSION_HEADER))?);
+        }
+    }
+
+    if !response.status.is_success() {
+        let status = response.status;
         if status.as_u16() == 404 {
             // User not found, return default status
+            clear_status_cache();
             return Ok(UserStatus::default());
         }
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Server error {}: {}", status, body));
+        return Err(format!("Server error {}: {}", status, redact(&response.text())).into());
+    }
+
+    let etag = response
+        .header(reqwest::header::ETAG.as_str())
+        .map(str::to_string);
+    let is_delta = response
+        .header("X-Status-Delta")
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+
+    let body = response.json::<serde_json::Value>("/api/user/status")?;
+
+    let merged = match cached {
+        Some((_, mut status)) if is_delta => {
+            merge_patch(&mut status, body);
+            status
+        }
+        None if is_delta => {
+            // Nothing to apply the delta to; drop our tag so the next
+            // request gets a full payload
+            clear_status_cache();
+            return Err("Received a status delta without a cached status"
+                .to_string()
+                .into());
+        }
+        _ => body,
+    };
+
+    let status = parse_status(merged.clone(), response.header(http::API_VERSION_HEADER))?;
+    if let Some(id) = &user_id {
+        store_cached(id, etag, &merged);
+    }
+    Ok(status)
+}
+
+/// Fetch the user's status, remembering which products are enabled for the
+/// command capability guard
+#[tauri::command]
+#[specta::specta]
+pub async fn fetch_user_status(
+    app: tauri::AppHandle,
+    token: String,
+    user_id: Option<String>,
+) -> Result<UserStatus, AppError> {
+    use tauri::Manager;
+    let status = fetch_status(&app.state::<Api>(), token, user_id).await?;
+    if let Ok(mut products) = PRODUCTS.lock() {
+        *products = Some(status.products.clone());
+    }
+    Ok(status)
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ProductEnabledEvent {
+    pub product: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AssessmentCompletedEvent {
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+    pub archetype: Option<String>,
+    pub tier: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct RecommendedActionChangedEvent {
+    pub previous: String,
+    pub current: String,
+}
+
+/// Background poller for `/api/user/status` and the last status it saw
+#[derive(Default)]
+pub struct UserStatusWatch {
+    task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    last: Mutex<Option<UserStatus>>,
+}
+
+impl UserStatusWatch {
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().ok().and_then(|mut t| t.take()) {
+            task.abort();
+        }
+    }
+}
+
+fn assessment_completed(status: &UserStatus) -> bool {
+    status.products.goodhang.assessment.as_ref().is_some_and(|a| a.completed)
+}
+
+/// Emit granular events for what changed between two statuses
+fn emit_status_changes(app: &tauri::AppHandle, previous: &UserStatus, current: &UserStatus) {
+    let products = [
+        ("goodhang", previous.products.goodhang.enabled, current.products.goodhang.enabled),
+        ("founder_os", previous.products.founder_os.enabled, current.products.founder_os.enabled),
+        ("voice_os", previous.products.voice_os.enabled, current.products.voice_os.enabled),
+    ];
+    for (product, was_enabled, is_enabled) in products {
+        if is_enabled && !was_enabled {
+            events::emit_app_event(app, AppEvent::ProductEnabled(ProductEnabledEvent { product: product.to_string() }));
+        }
+    }
+
+    if assessment_completed(current) && !assessment_completed(previous) {
+        super::reassessment::record_completion(app);
+        let assessment = current.products.goodhang.assessment.as_ref();
+        events::emit_app_event(app, AppEvent::AssessmentCompleted(AssessmentCompletedEvent {
+            session_id: assessment.and_then(|a| a.session_id.clone()),
+            archetype: assessment.and_then(|a| a.archetype.clone()),
+            tier: assessment.and_then(|a| a.tier.clone()),
+        }));
     }
 
-    response
-        .json::<UserStatus>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))
+    if current.recommended_action != previous.recommended_action {
+        events::emit_app_event(app, AppEvent::RecommendedActionChanged(RecommendedActionChangedEvent {
+            previous: previous.recommended_action.clone(),
+            current: current.recommended_action.clone(),
+        }));
+    }
+}
+
+async fn poll_once(app: &tauri::AppHandle, watch: &UserStatusWatch) -> Result<(), String> {
+    let session = load_session(app)?.ok_or_else(|| "Not signed in".to_string())?;
+    let status = fetch_user_status(app.clone(), session.token, Some(session.user_id)).await?;
+
+    let previous = watch.last.lock()
+        .map_err(|_| "Status cache lock poisoned".to_string())?
+        .replace(status.clone());
+
+    // The first poll only establishes the baseline
+    if let Some(previous) = previous {
+        emit_status_changes(app, &previous, &status);
+    }
+    events::emit_app_event(app, AppEvent::UserStatusUpdated(status));
+    Ok(())
+}
+
+/// Poll user status in the background and emit change events, so the
+/// frontend doesn't have to poll with repeated invokes
+#[tauri::command]
+#[specta::specta]
+pub async fn start_user_status_watch(
+    app: tauri::AppHandle,
+    watch: tauri::State<'_, UserStatusWatch>,
+    interval_secs: Option<u64>,
+) -> Result<(), String> {
+    let interval = Duration::from_secs(interval_secs.unwrap_or(60).max(MIN_WATCH_INTERVAL_SECS));
+    watch.stop();
+
+    let handle = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        use tauri::Manager;
+        loop {
+            let watch = handle.state::<UserStatusWatch>();
+            if let Err(e) = poll_once(&handle, &watch).await {
+                app_log!("[UserStatus] Watch poll failed: {}", e);
+            }
+            tokio::time::sleep(network::poll_interval(interval)).await;
+        }
+    });
+
+    *watch.task.lock().map_err(|_| "Watch lock poisoned".to_string())? = Some(task);
+    app_log!("[UserStatus] Watch started: interval={}s", interval.as_secs());
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_user_status_watch(watch: tauri::State<'_, UserStatusWatch>) -> Result<(), String> {
+    watch.stop();
+    app_log!("[UserStatus] Watch stopped");
+    Ok(())
 }
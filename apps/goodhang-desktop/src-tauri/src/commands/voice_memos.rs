@@ -0,0 +1,859 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{Listener, Manager};
+
+use super::auth::load_session;
+use crate::api::{Api, ApiRequest, FormValue};
+use crate::audit;
+use crate::db::{now_secs, Database};
+use crate::embeddings;
+use crate::events::{self, AppEvent};
+use crate::http::{self, Page};
+use crate::search_index;
+use crate::logging::app_log;
+use crate::network;
+use crate::paths;
+use crate::user_data;
+
+const MEMO_DIR: &str = "voice_memos";
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+// Prevents the periodic loop and on-demand syncs from uploading the same memo twice
+static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Sync state of a memo against the VoiceOS API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    Pending,
+    Uploading,
+    Synced,
+    Failed,
+}
+
+impl SyncStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SyncStatus::Pending => "pending",
+            SyncStatus::Uploading => "uploading",
+            SyncStatus::Synced => "synced",
+            SyncStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "uploading" => SyncStatus::Uploading,
+            "synced" => SyncStatus::Synced,
+            "failed" => SyncStatus::Failed,
+            _ => SyncStatus::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct VoiceMemo {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "durationSecs")]
+    pub duration_secs: f64,
+    pub transcript: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    #[serde(rename = "playbackUrl")]
+    pub playback_url: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+    #[serde(rename = "syncStatus")]
+    pub sync_status: SyncStatus,
+    #[serde(rename = "remoteId")]
+    pub remote_id: Option<String>,
+    #[serde(rename = "syncError")]
+    pub sync_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct MemoSyncEvent {
+    pub id: String,
+    pub status: SyncStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadResponse {
+    id: String,
+}
+
+/// Row fields needed to upload a memo
+struct PendingMemo {
+    id: String,
+    /// As loaded, so an edit made during the upload isn't marked synced
+    updated_at: i64,
+    title: String,
+    duration_secs: f64,
+    transcript: Option<String>,
+    tags: String,
+    file_name: String,
+    mime_type: String,
+    remote_id: Option<String>,
+    /// Retention removed the audio after it was synced; edits since then
+    /// go up as metadata only
+    audio_purged: bool,
+}
+
+pub(crate) fn memo_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = user_data::data_dir(app)?.join(MEMO_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create memo dir: {}", e))?;
+    Ok(dir)
+}
+
+/// URL the webview can use in an <audio> element; custom schemes are
+/// exposed as http://<scheme>.localhost on Windows
+fn playback_url(id: &str) -> String {
+    if cfg!(windows) {
+        format!("http://memo.localhost/{}", id)
+    } else {
+        format!("memo://localhost/{}", id)
+    }
+}
+
+fn extension_for(mime_type: &str) -> Option<&'static str> {
+    match mime_type {
+        "audio/webm" => Some("webm"),
+        "audio/ogg" => Some("ogg"),
+        "audio/mpeg" => Some("mp3"),
+        "audio/mp4" | "audio/x-m4a" => Some("m4a"),
+        "audio/wav" | "audio/x-wav" => Some("wav"),
+        _ => None,
+    }
+}
+
+/// The bare type from a recorder's `audio/webm;codecs=opus` and its file
+/// extension, if it's one we can store and serve back
+fn audio_type(mime_type: &str) -> Option<(String, &'static str)> {
+    let essence = mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    extension_for(&essence).map(|extension| (essence, extension))
+}
+
+fn row_to_memo(row: &rusqlite::Row) -> rusqlite::Result<VoiceMemo> {
+    let id: String = row.get("id")?;
+    let tags: String = row.get("tags")?;
+    let status: String = row.get("sync_status")?;
+    Ok(VoiceMemo {
+        playback_url: playback_url(&id),
+        id,
+        title: row.get("title")?,
+        duration_secs: row.get("duration_secs")?,
+        transcript: row.get("transcript")?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        mime_type: row.get("mime_type")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+        sync_status: SyncStatus::parse(&status),
+        remote_id: row.get("remote_id")?,
+        sync_error: row.get("sync_error")?,
+    })
+}
+
+fn get_memo(db: &Database, id: &str) -> Result<VoiceMemo, String> {
+    db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT * FROM voice_memos WHERE id = ?1",
+            [id],
+            row_to_memo,
+        )
+    })
+}
+
+/// Set the sync status of `memo` as it was loaded. An edit since then has
+/// left it pending for the next pass, and that's kept.
+fn record_sync(
+    conn: &rusqlite::Connection,
+    memo: &PendingMemo,
+    status: SyncStatus,
+    remote_id: Option<&str>,
+    error: Option<&str>,
+) -> rusqlite::Result<bool> {
+    let changed = conn.execute(
+        "UPDATE voice_memos
+         SET sync_status = ?1, remote_id = COALESCE(?2, remote_id), sync_error = ?3
+         WHERE id = ?4 AND updated_at = ?5",
+        rusqlite::params![status.as_str(), remote_id, error, memo.id, memo.updated_at],
+    )?;
+    Ok(changed > 0)
+}
+
+fn set_sync_status(
+    app: &tauri::AppHandle,
+    memo: &PendingMemo,
+    status: SyncStatus,
+    remote_id: Option<&str>,
+    error: Option<String>,
+) -> Result<(), String> {
+    let db = app.state::<Database>();
+    let changed =
+        db.with_conn(|conn| record_sync(conn, memo, status, remote_id, error.as_deref()))?;
+    if !changed {
+        return Ok(());
+    }
+
+    events::emit_app_event(
+        app,
+        AppEvent::VoiceMemoSync(MemoSyncEvent {
+            id: memo.id.clone(),
+            status,
+            error,
+        }),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_voice_memo(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    title: String,
+    duration_secs: f64,
+    audio: Vec<u8>,
+    mime_type: String,
+    transcript: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<VoiceMemo, String> {
+    let Some((mime_type, extension)) = audio_type(&mime_type) else {
+        return Err(format!("Unsupported audio type: {}", mime_type));
+    };
+    let id = uuid::Uuid::new_v4().to_string();
+    let file_name = format!("{}.{}", id, extension);
+
+    let writes = paths::hold_writes()?;
+    std::fs::write(memo_dir(&app)?.join(&file_name), &audio)
+        .map_err(|e| format!("Failed to write audio file: {}", e))?;
+    drop(writes);
+
+    let tags = serde_json::to_string(&tags.unwrap_or_default())
+        .map_err(|e| format!("Failed to serialize tags: {}", e))?;
+    let now = now_secs();
+
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO voice_memos
+             (id, title, duration_secs, transcript, tags, file_name, mime_type, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+            rusqlite::params![id, title, duration_secs, transcript, tags, file_name, mime_type, now],
+        )
+    })?;
+
+    app_log!("[VoiceMemos] Memo created: id={}", id);
+
+    search_index::upsert_quietly(
+        &app,
+        "voice_memo",
+        &id,
+        &title,
+        transcript.as_deref().unwrap_or(""),
+    );
+
+    if let Some(transcript) = transcript.filter(|t| !t.is_empty()) {
+        embeddings::index_in_background(&app, "voice_memo", id.clone(), transcript, now);
+    }
+
+    // Kick off an upload right away rather than waiting for the next sync tick
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        sync_pending_memos(&handle).await;
+    });
+
+    get_memo(&db, &id)
+}
+
+/// Add an audio file from disk (e.g. dropped onto the window) to the memo
+/// library, titled after the file name
+pub(crate) async fn import_audio_file(
+    app: &tauri::AppHandle,
+    path: &std::path::Path,
+    mime_type: &str,
+) -> Result<VoiceMemo, String> {
+    let audio = std::fs::read(path).map_err(|e| format!("Failed to read audio file: {}", e))?;
+    let title = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Imported memo".to_string());
+    create_voice_memo(
+        app.clone(),
+        app.state::<Database>(),
+        title,
+        0.0,
+        audio,
+        mime_type.to_string(),
+        None,
+        None,
+    )
+    .await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_voice_memos(
+    db: tauri::State<'_, Database>,
+    tag: Option<String>,
+) -> Result<Vec<VoiceMemo>, String> {
+    let memos = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT * FROM voice_memos ORDER BY created_at DESC")?;
+        let rows = stmt.query_map([], row_to_memo)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })?;
+
+    Ok(match tag {
+        Some(tag) => memos.into_iter().filter(|m| m.tags.contains(&tag)).collect(),
+        None => memos,
+    })
+}
+
+/// Newest-first page of memos. The cursor is the `createdAt:id` of the last
+/// memo on the previous page, so inserts don't shift later pages.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_voice_memos_page(
+    db: tauri::State<'_, Database>,
+    tag: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Page<VoiceMemo>, String> {
+    let (before_at, before_id) = match cursor.as_deref() {
+        Some(cursor) => {
+            let (at, id) = cursor
+                .split_once(':')
+                .ok_or_else(|| "Invalid cursor".to_string())?;
+            let at = at.parse::<i64>().map_err(|_| "Invalid cursor".to_string())?;
+            (at, id.to_string())
+        }
+        None => (i64::MAX, String::new()),
+    };
+    let limit = http::page_size(limit);
+
+    let mut memos = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM voice_memos
+             WHERE (created_at < ?1 OR (created_at = ?1 AND id < ?2))
+               AND (?3 IS NULL OR EXISTS (SELECT 1 FROM json_each(tags) WHERE value = ?3))
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?4",
+        )?;
+        // One extra row tells us whether another page follows
+        let rows = stmt.query_map(
+            rusqlite::params![before_at, before_id, tag, limit + 1],
+            row_to_memo,
+        )?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })?;
+
+    let has_more = memos.len() > limit as usize;
+    memos.truncate(limit as usize);
+    let next_cursor = has_more
+        .then(|| memos.last().map(|m| format!("{}:{}", m.created_at, m.id)))
+        .flatten();
+
+    Ok(Page {
+        items: memos,
+        next_cursor,
+        has_more,
+    })
+}
+
+/// Edits mark the memo pending again so the new metadata gets uploaded.
+/// `updated_at` always moves, even within the second, so an upload already
+/// under way can tell its copy is stale.
+fn apply_edit(
+    conn: &rusqlite::Connection,
+    id: &str,
+    title: Option<&str>,
+    transcript: Option<&str>,
+    tags: Option<&str>,
+) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE voice_memos
+         SET title = COALESCE(?1, title),
+             transcript = COALESCE(?2, transcript),
+             tags = COALESCE(?3, tags),
+             updated_at = MAX(?4, updated_at + 1),
+             sync_status = 'pending'
+         WHERE id = ?5",
+        rusqlite::params![title, transcript, tags, now_secs(), id],
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_voice_memo(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+    title: Option<String>,
+    transcript: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<VoiceMemo, String> {
+    let tags = tags
+        .map(|t| serde_json::to_string(&t))
+        .transpose()
+        .map_err(|e| format!("Failed to serialize tags: {}", e))?;
+
+    db.with_conn(|conn| {
+        apply_edit(
+            conn,
+            &id,
+            title.as_deref(),
+            transcript.as_deref(),
+            tags.as_deref(),
+        )
+    })?;
+
+    let memo = get_memo(&db, &id)?;
+    search_index::upsert_quietly(
+        &app,
+        "voice_memo",
+        &memo.id,
+        &memo.title,
+        memo.transcript.as_deref().unwrap_or(""),
+    );
+    if let Some(transcript) = transcript.filter(|t| !t.is_empty()) {
+        embeddings::index_in_background(&app, "voice_memo", id, transcript, memo.created_at);
+    }
+    Ok(memo)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_voice_memo(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    id: String,
+) -> Result<(), String> {
+    audit::audited(app.clone(), "delete_voice_memo", async {
+        let file_name: Option<String> = db.with_conn(|conn| {
+            let file_name = conn
+                .query_row(
+                    "SELECT file_name FROM voice_memos WHERE id = ?1",
+                    [&id],
+                    |row| row.get(0),
+                )
+                .ok();
+            conn.execute("DELETE FROM voice_memos WHERE id = ?1", [&id])?;
+            Ok(file_name)
+        })?;
+
+        if let Some(file_name) = file_name {
+            match std::fs::remove_file(memo_dir(&app)?.join(file_name)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(format!("Failed to delete audio file: {}", e));
+                }
+                _ => {}
+            }
+        }
+        embeddings::remove_document(&db, "voice_memo", &id)?;
+        search_index::remove(&db, "voice_memo", &id)?;
+
+        app_log!("[VoiceMemos] Memo deleted: id={}", id);
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_voice_memos(app: tauri::AppHandle) -> Result<(), String> {
+    sync_pending_memos(&app).await;
+    Ok(())
+}
+
+/// A bodiless response; `Response::new` can't fail the way a builder can
+fn status_only(status: tauri::http::StatusCode) -> tauri::http::Response<Vec<u8>> {
+    let mut response = tauri::http::Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
+}
+
+/// Read `len` bytes of `path` starting at `start`
+fn read_slice(path: &std::path::Path, start: u64, len: u64) -> std::io::Result<Vec<u8>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::with_capacity(len as usize);
+    file.take(len).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Serve memo audio to the webview over the `memo://` protocol, honoring
+/// Range requests so the <audio> element can seek. Only the requested range
+/// is read from disk.
+pub fn serve_memo(
+    app: &tauri::AppHandle,
+    request: &tauri::http::Request<Vec<u8>>,
+) -> tauri::http::Response<Vec<u8>> {
+    use tauri::http::StatusCode;
+
+    let id = request.uri().path().trim_start_matches('/');
+    // Portable data that hasn't been unlocked yet has no database to read
+    let Some(db) = app.try_state::<Database>() else {
+        return status_only(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    let row: Option<(String, String)> = db
+        .with_conn(|conn| {
+            conn.query_row(
+                "SELECT file_name, mime_type FROM voice_memos WHERE id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+        })
+        .ok();
+
+    let Some((file_name, mime_type)) = row else {
+        return status_only(StatusCode::NOT_FOUND);
+    };
+    let Ok(path) = memo_dir(app).map(|dir| dir.join(file_name)) else {
+        return status_only(StatusCode::NOT_FOUND);
+    };
+    let Ok(total) = std::fs::metadata(&path).map(|meta| meta.len()) else {
+        return status_only(StatusCode::NOT_FOUND);
+    };
+
+    let range = request
+        .headers()
+        .get("range")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_range(v, total));
+    let (start, end) = range.unwrap_or((0, total.saturating_sub(1)));
+    let len = if total == 0 { 0 } else { end - start + 1 };
+    let Ok(bytes) = read_slice(&path, start, len) else {
+        return status_only(StatusCode::NOT_FOUND);
+    };
+
+    // Rows from before types were checked on the way in may hold anything
+    let content_type = audio_type(&mime_type).map_or_else(
+        || "application/octet-stream".to_string(),
+        |(essence, _)| essence,
+    );
+    let builder = tauri::http::Response::builder()
+        .header("Content-Type", content_type)
+        .header("Accept-Ranges", "bytes");
+    let response = match range {
+        Some((start, end)) => builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end, total),
+            )
+            .body(bytes),
+        None => builder.status(StatusCode::OK).body(bytes),
+    };
+    response.unwrap_or_else(|_| status_only(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+/// Parse a single `bytes=start-end` range into inclusive offsets
+fn parse_range(header: &str, total: u64) -> Option<(u64, u64)> {
+    if total == 0 {
+        return None;
+    }
+    let spec = header.strip_prefix("bytes=")?;
+    let (start, end) = spec.split_once('-')?;
+    let last = total - 1;
+
+    let (start, end) = if start.is_empty() {
+        // Suffix range: last N bytes
+        let len: u64 = end.parse().ok()?;
+        (total.saturating_sub(len), last)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() { last } else { end.parse().ok()? };
+        (start, end.min(last))
+    };
+
+    (start <= end).then_some((start, end))
+}
+
+/// Upload every memo that isn't synced yet; failures are recorded per memo
+pub async fn sync_pending_memos(app: &tauri::AppHandle) {
+    if SYNC_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    sync_pending_inner(app).await;
+    SYNC_RUNNING.store(false, Ordering::SeqCst);
+}
+
+async fn sync_pending_inner(app: &tauri::AppHandle) {
+    let session = match load_session(app) {
+        Ok(Some(session)) => session,
+        _ => return, // Not signed in, nothing to sync against
+    };
+    if !network::is_online() {
+        return; // Retried when the connectivity monitor reports back-online
+    }
+
+    let pending = app.state::<Database>().with_conn(load_pending);
+    let pending = match pending {
+        Ok(pending) => pending,
+        Err(e) => {
+            app_log!("[VoiceMemos] Failed to load pending memos: {}", e);
+            return;
+        }
+    };
+
+    for memo in pending {
+        let _ = set_sync_status(app, &memo, SyncStatus::Uploading, None, None);
+        let sent = match (&memo.remote_id, memo.audio_purged) {
+            (Some(remote_id), true) => update_memo_metadata(app, &session.token, remote_id, &memo)
+                .await
+                .map(|_| remote_id.clone()),
+            _ => upload_memo(app, &session.token, &memo).await,
+        };
+        match sent {
+            Ok(remote_id) => {
+                let _ = set_sync_status(app, &memo, SyncStatus::Synced, Some(&remote_id), None);
+                app_log!("[VoiceMemos] Memo synced: id={}", memo.id);
+            }
+            Err(e) => {
+                app_log!("[VoiceMemos] Memo sync failed: id={} error={}", memo.id, e);
+                let _ = set_sync_status(app, &memo, SyncStatus::Failed, None, Some(e));
+            }
+        }
+    }
+}
+
+fn load_pending(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<PendingMemo>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, updated_at, title, duration_secs, transcript, tags, file_name, mime_type,
+                remote_id, audio_purged_at IS NOT NULL
+         FROM voice_memos WHERE sync_status IN ('pending', 'failed')
+           AND (audio_purged_at IS NULL OR remote_id IS NOT NULL)
+         ORDER BY created_at ASC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(PendingMemo {
+            id: row.get(0)?,
+            updated_at: row.get(1)?,
+            title: row.get(2)?,
+            duration_secs: row.get(3)?,
+            transcript: row.get(4)?,
+            tags: row.get(5)?,
+            file_name: row.get(6)?,
+            mime_type: row.get(7)?,
+            remote_id: row.get(8)?,
+            audio_purged: row.get(9)?,
+        })
+    })?;
+    rows.collect()
+}
+
+async fn upload_memo(
+    app: &tauri::AppHandle,
+    token: &str,
+    memo: &PendingMemo,
+) -> Result<String, String> {
+    let audio = std::fs::read(memo_dir(app)?.join(&memo.file_name))
+        .map_err(|e| format!("Failed to read audio file: {}", e))?;
+
+    let text = |name: &str, value: String| (name.to_string(), FormValue::Text(value));
+    let mut form = vec![
+        (
+            "file".to_string(),
+            FormValue::File {
+                bytes: audio,
+                file_name: memo.file_name.clone(),
+                mime_type: memo.mime_type.clone(),
+            },
+        ),
+        text("clientId", memo.id.clone()),
+        text("title", memo.title.clone()),
+        text("durationSecs", memo.duration_secs.to_string()),
+        text("tags", memo.tags.clone()),
+    ];
+    if let Some(transcript) = &memo.transcript {
+        form.push(text("transcript", transcript.clone()));
+    }
+
+    let uploaded: UploadResponse = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/voice/memos")
+                .bearer(token)
+                .multipart(form),
+        )
+        .await?
+        .error_for_status()?
+        .json("/api/voice/memos")?;
+    Ok(uploaded.id)
+}
+
+/// The metadata of a memo the server already has the audio for
+fn metadata_request(
+    token: &str,
+    remote_id: &str,
+    memo: &PendingMemo,
+) -> Result<ApiRequest, String> {
+    let tags: Vec<String> = serde_json::from_str(&memo.tags).unwrap_or_default();
+    ApiRequest::patch(format!("/api/voice/memos/{}", remote_id))
+        .bearer(token)
+        .json(&serde_json::json!({
+            "title": memo.title,
+            "durationSecs": memo.duration_secs,
+            "tags": tags,
+            "transcript": memo.transcript,
+        }))
+}
+
+async fn update_memo_metadata(
+    app: &tauri::AppHandle,
+    token: &str,
+    remote_id: &str,
+    memo: &PendingMemo,
+) -> Result<(), String> {
+    app.state::<Api>()
+        .send(metadata_request(token, remote_id, memo)?)
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Background loop that retries unsynced memos periodically, and as soon as
+/// the API becomes reachable again
+pub fn spawn_memo_sync(app: tauri::AppHandle) {
+    // An upload cut short by a quit or crash never finished
+    if let Err(e) = app.state::<Database>().with_conn(|conn| {
+        conn.execute(
+            "UPDATE voice_memos SET sync_status = 'pending' WHERE sync_status = 'uploading'",
+            [],
+        )
+    }) {
+        app_log!("[VoiceMemos] Failed to requeue interrupted uploads: {}", e);
+    }
+
+    let handle = app.clone();
+    app.listen("back-online", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move { sync_pending_memos(&handle).await });
+    });
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            sync_pending_memos(&app).await;
+            tokio::time::sleep(network::poll_interval(SYNC_INTERVAL)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiBody;
+
+    fn memos() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::create_tables(&conn).unwrap();
+        crate::commands::retention::add_audio_purged_at(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO voice_memos (id, title, file_name, mime_type, created_at, updated_at)
+             VALUES ('m1', 'Standup', 'm1.webm', 'audio/webm', 100, 100)",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn status(conn: &rusqlite::Connection) -> String {
+        conn.query_row(
+            "SELECT sync_status FROM voice_memos WHERE id = 'm1'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    fn purge_synced(conn: &rusqlite::Connection, remote_id: Option<&str>) {
+        conn.execute(
+            "UPDATE voice_memos
+             SET sync_status = 'synced', remote_id = ?1, audio_purged_at = 200
+             WHERE id = 'm1'",
+            [remote_id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn edit_after_purge_sends_metadata_only() {
+        let conn = memos();
+        purge_synced(&conn, Some("r1"));
+        apply_edit(&conn, "m1", None, Some("Call the venue"), None).unwrap();
+
+        let memo = load_pending(&conn).unwrap().remove(0);
+        assert!(memo.audio_purged);
+        assert_eq!(memo.remote_id.as_deref(), Some("r1"));
+        let request = metadata_request("t", "r1", &memo).unwrap();
+        assert_eq!(request.method, reqwest::Method::PATCH);
+        assert_eq!(request.path, "/api/voice/memos/r1");
+        match request.body {
+            Some(ApiBody::Json(body)) => assert_eq!(body["transcript"], "Call the venue"),
+            other => panic!("expected a JSON body, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn purged_memo_without_a_remote_copy_is_not_retried() {
+        let conn = memos();
+        purge_synced(&conn, None);
+        apply_edit(&conn, "m1", Some("Standup notes"), None, None).unwrap();
+        assert!(load_pending(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn edit_during_upload_stays_pending() {
+        let conn = memos();
+        let memo = load_pending(&conn).unwrap().remove(0);
+        assert!(record_sync(&conn, &memo, SyncStatus::Uploading, None, None).unwrap());
+
+        apply_edit(&conn, "m1", Some("Standup notes"), None, None).unwrap();
+        assert!(!record_sync(&conn, &memo, SyncStatus::Synced, Some("r1"), None).unwrap());
+        assert_eq!(status(&conn), "pending");
+
+        let edited = load_pending(&conn).unwrap().remove(0);
+        assert_eq!(edited.title, "Standup notes");
+        assert!(record_sync(&conn, &edited, SyncStatus::Synced, Some("r1"), None).unwrap());
+        assert_eq!(status(&conn), "synced");
+    }
+
+    #[test]
+    fn parses_byte_ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=500-", 1000), Some((500, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        // Past the end is cut to the end; a suffix longer than the file is all of it
+        assert_eq!(parse_range("bytes=900-5000", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some((0, 999)));
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_ranges() {
+        for header in [
+            "bytes=1000-",
+            "bytes=50-10",
+            "bytes=0-1,5-6",
+            "bytes=a-b",
+            "bytes=",
+            "items=0-99",
+        ] {
+            assert_eq!(parse_range(header, 1000), None, "{}", header);
+        }
+        assert_eq!(parse_range("bytes=0-", 0), None);
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::db::now_secs;
+use crate::errors::AppError;
+use crate::stores;
+use crate::user_data;
+
+pub(crate) const CONSENTS_KEY: &str = "consents";
+
+/// Features that touch personal data and need the user's explicit opt-in.
+/// Anything not yet answered counts as refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Analytics,
+    ClipboardWatching,
+    ContactImport,
+    Location,
+    JournalAi,
+    /// Sending people's and companies' names to third-party search (Brave,
+    /// ARI) for market context
+    ExternalLookups,
+    /// Persona chat, whose messages are processed by the AI backend
+    AiChat,
+}
+
+impl Feature {
+    pub const ALL: &'static [Feature] = &[
+        Feature::Analytics,
+        Feature::ClipboardWatching,
+        Feature::ContactImport,
+        Feature::Location,
+        Feature::JournalAi,
+        Feature::ExternalLookups,
+        Feature::AiChat,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Feature::Analytics => "analytics",
+            Feature::ClipboardWatching => "clipboard_watching",
+            Feature::ContactImport => "contact_import",
+            Feature::Location => "location",
+            Feature::JournalAi => "journal_ai",
+            Feature::ExternalLookups => "external_lookups",
+            Feature::AiChat => "ai_chat",
+        }
+    }
+
+    pub fn parse(key: &str) -> Option<Feature> {
+        Feature::ALL.iter().copied().find(|f| f.key() == key)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentRecord {
+    pub granted: bool,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+}
+
+pub fn load_all(app: &tauri::AppHandle) -> HashMap<String, ConsentRecord> {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(user_data::key(CONSENTS_KEY)))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+pub fn is_granted(app: &tauri::AppHandle, feature: Feature) -> bool {
+    load_all(app)
+        .get(feature.key())
+        .is_some_and(|record| record.granted)
+}
+
+/// Fail with `E_CONSENT_REQUIRED` unless the user has opted in to `feature`
+pub fn require(app: &tauri::AppHandle, feature: Feature) -> Result<(), AppError> {
+    if is_granted(app, feature) {
+        Ok(())
+    } else {
+        Err(AppError::consent_required(feature.key()))
+    }
+}
+
+pub fn save(
+    app: &tauri::AppHandle,
+    feature: Feature,
+    granted: bool,
+) -> Result<ConsentRecord, String> {
+    let record = ConsentRecord {
+        granted,
+        updated_at: now_secs(),
+    };
+    let mut all = load_all(app);
+    all.insert(feature.key().to_string(), record.clone());
+
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    store.set(
+        user_data::key(CONSENTS_KEY),
+        serde_json::to_value(&all).map_err(|e| format!("Failed to serialize consents: {}", e))?,
+    );
+    stores::save(app, stores::SETTINGS_STORE)?;
+    Ok(record)
+}
+
+/// Drop every answer, e.g. on secure logout, so the next user is asked afresh
+pub fn forget(app: &tauri::AppHandle) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    if store.delete(user_data::key(CONSENTS_KEY)) {
+        stores::save(app, stores::SETTINGS_STORE)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,802 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::db::DB_FILENAME;
+use crate::logging::app_log;
+use crate::paths;
+use crate::user_data::USERS_DIR;
+
+const KEYCHAIN_SERVICE: &str = "com.goodhang.desktop";
+const KEYCHAIN_ACCOUNT: &str = "local-data-key";
+// Used when the OS keychain is unavailable (e.g. Linux without a secret
+// service)
+pub(crate) const FALLBACK_KEY_FILE: &str = ".data-key";
+// Portable mode's data key, sealed with a key derived from the user's
+// passphrase so a lost drive doesn't carry the key to its own data
+pub(crate) const PORTABLE_KEY_FILE: &str = ".data-key.sealed";
+// Prefix marking an encrypted blob; anything without it is legacy plaintext
+const MAGIC: &[u8] = b"GHENC1";
+// Prefix of a file sealed with a passphrase rather than the data key
+const PASSPHRASE_MAGIC: &[u8] = b"GHPWD1";
+// Prefix of the sealed portable key file
+const KEY_FILE_MAGIC: &[u8] = b"GHKEY1";
+// Prefix of a store file carrying a SHA-256 of the rest of the file
+const CHECKSUM_MAGIC: &[u8] = b"GHSUM1";
+const CHECKSUM_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const KEYCHAIN_ATTEMPTS: u32 = 3;
+const KEYCHAIN_RETRY_DELAY: Duration = Duration::from_millis(500);
+const SALT_LEN: usize = 16;
+
+static DATA_KEY: RwLock<Option<[u8; 32]>> = RwLock::new(None);
+// Passphrase-derived key and its salt, kept after a portable unlock so a
+// rotated data key can be sealed again
+static PORTABLE_SEAL: RwLock<Option<([u8; 32], [u8; SALT_LEN])>> = RwLock::new(None);
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Load the at-rest encryption key from the OS keychain, generating and
+/// storing one on first launch. In portable mode the key must already have
+/// been unlocked with `unlock_portable_key`.
+///
+/// The key file stands in for the keychain only where it already holds the
+/// key, or on a first launch with nothing encrypted yet. A new key is never
+/// generated over existing encrypted data, since that data could then never
+/// be read again; a keychain that's locked or briefly unreachable fails
+/// startup instead, so the next launch can try again.
+pub fn init_data_key(data_dir: &Path) -> Result<(), String> {
+    if data_key().is_ok() {
+        return Ok(());
+    }
+    if paths::is_portable() {
+        return Err("Portable data is locked; enter the passphrase to open it".to_string());
+    }
+
+    let key_file = data_dir.join(FALLBACK_KEY_FILE);
+    let key = match read_keychain() {
+        Ok(Some(key)) => key,
+        // Data from a launch where the keychain was unavailable
+        Ok(None) if key_file.exists() => load_from_file(data_dir)?,
+        Ok(None) => {
+            refuse_if_encrypted(data_dir, "no data key in the keychain")?;
+            create_in_keychain()?
+        }
+        Err(e) if key_file.exists() => {
+            app_log!("[Crypto] Keychain unavailable ({}), using key file", e);
+            load_from_file(data_dir)?
+        }
+        Err(e) => {
+            refuse_if_encrypted(data_dir, &e)?;
+            app_log!(
+                "[Crypto] Keychain unavailable ({}), using key file fallback",
+                e
+            );
+            load_from_file(data_dir)?
+        }
+    };
+
+    set_data_key(key)
+}
+
+/// Whether anything under `data_dir` is already encrypted: a database, in
+/// any account's namespace, or a store file
+fn has_encrypted_data(data_dir: &Path) -> bool {
+    let mut databases = std::fs::read_dir(data_dir.join(USERS_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path().join(DB_FILENAME))
+        .chain(std::iter::once(data_dir.join(DB_FILENAME)));
+    if databases.any(|db| db.exists()) {
+        return true;
+    }
+    std::fs::read_dir(data_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .any(|entry| std::fs::read(entry.path()).is_ok_and(|bytes| is_encrypted(&bytes)))
+}
+
+fn refuse_if_encrypted(data_dir: &Path, reason: &str) -> Result<(), String> {
+    if has_encrypted_data(data_dir) {
+        return Err(format!(
+            "Failed to load the data key ({}). Local data is encrypted with it, so \
+             unlock the system keychain and start the app again.",
+            reason
+        ));
+    }
+    Ok(())
+}
+
+/// Make `key` the data key in memory only. `replace_data_key` persists it.
+pub(crate) fn set_data_key(key: [u8; 32]) -> Result<(), String> {
+    *DATA_KEY
+        .write()
+        .map_err(|_| "Key lock poisoned".to_string())? = Some(key);
+    Ok(())
+}
+
+pub fn data_key() -> Result<[u8; 32], String> {
+    DATA_KEY
+        .read()
+        .map_err(|_| "Key lock poisoned".to_string())?
+        .ok_or_else(|| "Encryption key not initialized".to_string())
+}
+
+/// A fresh random data key, for use with `replace_data_key`
+pub fn new_data_key() -> [u8; 32] {
+    generate_key()
+}
+
+/// Persist `key` as the data key (keychain, the fallback file, or in portable
+/// mode the sealed key file) and make it current. Callers must re-key
+/// anything encrypted with the old key first; anything left behind becomes
+/// unreadable, which is the point when wiping a shared machine.
+pub fn replace_data_key(data_dir: &Path, key: [u8; 32]) -> Result<(), String> {
+    if paths::is_portable() {
+        let (seal_key, salt) = PORTABLE_SEAL
+            .read()
+            .map_err(|_| "Key lock poisoned".to_string())?
+            .ok_or_else(|| "Portable data is locked".to_string())?;
+        write_portable_key(data_dir, &key, &seal_key, &salt)?;
+        set_data_key(key)?;
+        app_log!("[Crypto] Data key rotated");
+        return Ok(());
+    }
+
+    let stored = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .and_then(|entry| entry.set_password(&hex::encode(key)))
+        .map_err(|e| e.to_string());
+    match stored {
+        Ok(()) => {
+            let _ = std::fs::remove_file(data_dir.join(FALLBACK_KEY_FILE));
+        }
+        Err(e) => {
+            app_log!("[Crypto] Keychain not used ({}), rotating key file", e);
+            write_key_file(
+                &data_dir.join(FALLBACK_KEY_FILE),
+                hex::encode(key).as_bytes(),
+            )?;
+        }
+    }
+
+    set_data_key(key)?;
+    app_log!("[Crypto] Data key rotated");
+    Ok(())
+}
+
+/// Hex form of the key, as SQLCipher's `PRAGMA key = "x'...'"` expects
+pub fn data_key_hex() -> Result<String, String> {
+    data_key().map(hex::encode)
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; 32], String> {
+    hex::decode(encoded.trim())
+        .map_err(|e| format!("Invalid stored key: {}", e))?
+        .try_into()
+        .map_err(|_| "Stored key has wrong length".to_string())
+}
+
+fn generate_key() -> [u8; 32] {
+    Aes256Gcm::generate_key(OsRng).into()
+}
+
+fn keychain_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))
+}
+
+/// The data key from the keychain, or `None` if it has none. A failing
+/// keychain is retried briefly: D-Bus and keychain hiccups tend to clear.
+fn read_keychain() -> Result<Option<[u8; 32]>, String> {
+    let mut attempt = 1;
+    loop {
+        let result = keychain_entry().and_then(|entry| match entry.get_password() {
+            Ok(encoded) => decode_key(&encoded).map(Some),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read keychain: {}", e)),
+        });
+        match result {
+            Err(e) if attempt < KEYCHAIN_ATTEMPTS => {
+                app_log!("[Crypto] {} (attempt {})", e, attempt);
+                std::thread::sleep(KEYCHAIN_RETRY_DELAY);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn create_in_keychain() -> Result<[u8; 32], String> {
+    let key = generate_key();
+    keychain_entry()?
+        .set_password(&hex::encode(key))
+        .map_err(|e| format!("Failed to store key in keychain: {}", e))?;
+    app_log!("[Crypto] Generated new data key in keychain");
+    Ok(key)
+}
+
+fn load_from_file(data_dir: &Path) -> Result<[u8; 32], String> {
+    let path = data_dir.join(FALLBACK_KEY_FILE);
+    if let Ok(encoded) = std::fs::read_to_string(&path) {
+        return decode_key(&encoded);
+    }
+
+    let key = generate_key();
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    write_key_file(&path, hex::encode(key).as_bytes())?;
+    Ok(key)
+}
+
+/// Create `path` readable only by this user, replacing any leftover file
+/// (which would keep its old permissions)
+pub(crate) fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Replace a key file in one step, owner-only from the moment it exists
+fn write_key_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let partial = path.with_extension("partial");
+    create_private(&partial)
+        .and_then(|mut file| file.write_all(contents).and_then(|_| file.sync_all()))
+        .and_then(|_| std::fs::rename(&partial, path))
+        .map_err(|e| format!("Failed to write key file: {}", e))
+}
+
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    verify_checksum(bytes).is_ok_and(|body| body.starts_with(MAGIC))
+}
+
+pub fn has_checksum(bytes: &[u8]) -> bool {
+    bytes.starts_with(CHECKSUM_MAGIC)
+}
+
+/// Prefix `body` with CHECKSUM_MAGIC and its SHA-256, so a torn or
+/// truncated write can be told apart from a key that no longer matches
+fn with_checksum(body: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(CHECKSUM_MAGIC.len() + CHECKSUM_LEN + body.len());
+    out.extend_from_slice(CHECKSUM_MAGIC);
+    out.extend_from_slice(&Sha256::digest(&body));
+    out.extend(body);
+    out
+}
+
+/// The body of a checksummed blob, once its checksum matches. Blobs written
+/// before checksums pass through unchanged.
+pub fn verify_checksum(bytes: &[u8]) -> Result<&[u8], String> {
+    let Some(rest) = bytes.strip_prefix(CHECKSUM_MAGIC) else {
+        return Ok(bytes);
+    };
+    if rest.len() < CHECKSUM_LEN {
+        return Err("Store is truncated".to_string());
+    }
+    let (checksum, body) = rest.split_at(CHECKSUM_LEN);
+    if Sha256::digest(body).as_slice() != checksum {
+        return Err("Store checksum mismatch (file is corrupted)".to_string());
+    }
+    Ok(body)
+}
+
+/// Encrypt with AES-256-GCM; output is MAGIC || nonce || ciphertext
+pub fn encrypt(plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key()?));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+pub fn decrypt(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let body = bytes
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| "Data is not encrypted".to_string())?;
+    if body.len() < NONCE_LEN {
+        return Err("Encrypted data is truncated".to_string());
+    }
+
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key()?));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Decryption failed (wrong key or corrupted data)".to_string())
+}
+
+fn passphrase_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+/// Whether portable data already has a passphrase; if not, the first
+/// unlock sets it
+pub fn has_portable_key(data_dir: &Path) -> bool {
+    data_dir.join(PORTABLE_KEY_FILE).exists()
+}
+
+fn write_portable_key(
+    data_dir: &Path,
+    key: &[u8; 32],
+    seal_key: &[u8; 32],
+    salt: &[u8; SALT_LEN],
+) -> Result<(), String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(seal_key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, key.as_slice())
+        .map_err(|_| "Encryption failed".to_string())?;
+    let mut sealed =
+        Vec::with_capacity(KEY_FILE_MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(KEY_FILE_MAGIC);
+    sealed.extend_from_slice(salt);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    write_key_file(&data_dir.join(PORTABLE_KEY_FILE), &sealed)
+}
+
+/// Open portable data with `passphrase`. The first unlock sets the
+/// passphrase, sealing a new key (or the unsealed key file an earlier
+/// version kept) under it; later ones must match it.
+pub fn unlock_portable_key(data_dir: &Path, passphrase: &str) -> Result<(), String> {
+    let (key, seal_key, salt) = match std::fs::read(data_dir.join(PORTABLE_KEY_FILE)) {
+        Ok(sealed) => {
+            let body = sealed
+                .strip_prefix(KEY_FILE_MAGIC)
+                .filter(|body| body.len() > SALT_LEN + NONCE_LEN)
+                .ok_or_else(|| "Portable key file is damaged".to_string())?;
+            let (salt, rest) = body.split_at(SALT_LEN);
+            let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+            let seal_key = passphrase_key(passphrase, salt)?;
+            let key: [u8; 32] = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&seal_key))
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .ok()
+                .and_then(|key| key.try_into().ok())
+                .ok_or_else(|| "Invalid passphrase: it doesn't open this data".to_string())?;
+            let salt: [u8; SALT_LEN] = salt
+                .try_into()
+                .map_err(|_| "Portable key file is damaged".to_string())?;
+            (key, seal_key, salt)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let legacy = data_dir.join(FALLBACK_KEY_FILE);
+            let key = match std::fs::read_to_string(&legacy) {
+                Ok(encoded) => decode_key(&encoded)?,
+                Err(_) => {
+                    refuse_if_encrypted(data_dir, "no portable key file")?;
+                    generate_key()
+                }
+            };
+            let salt: [u8; SALT_LEN] = rand::random();
+            let seal_key = passphrase_key(passphrase, &salt)?;
+            write_portable_key(data_dir, &key, &seal_key, &salt)?;
+            if legacy.exists() {
+                std::fs::remove_file(&legacy)
+                    .map_err(|e| format!("Failed to remove the unsealed key file: {}", e))?;
+                app_log!("[Crypto] Sealed the portable key file with the passphrase");
+            }
+            (key, seal_key, salt)
+        }
+        Err(e) => return Err(format!("Failed to read key file: {}", e)),
+    };
+
+    *PORTABLE_SEAL
+        .write()
+        .map_err(|_| "Key lock poisoned".to_string())? = Some((seal_key, salt));
+    set_data_key(key)
+}
+
+/// Writes a file sealed with a key derived from `passphrase` (Argon2id), for
+/// data that has to open on another machine. The plaintext is cut into
+/// `SEALED_CHUNK_LEN` chunks, each sealed on its own, so neither side ever
+/// holds the whole file in memory. Layout:
+/// PASSPHRASE_MAGIC || salt || (nonce || ciphertext)*, where each chunk's
+/// associated data is its index and whether it's the last one, so chunks
+/// can't be reordered or the file cut short.
+///
+/// Seeking back to patch earlier bytes (as a zip writer does) re-seals that
+/// chunk under a fresh nonce. Call `finish` to seal the last chunk.
+pub struct SealedWriter {
+    file: std::fs::File,
+    cipher: Aes256Gcm,
+    chunk: Vec<u8>,
+    index: u64,
+    dirty: bool,
+    pos: u64,
+    len: u64,
+}
+
+/// Reads a file written by `SealedWriter`, decrypting a chunk at a time
+pub struct SealedReader {
+    file: std::fs::File,
+    cipher: Aes256Gcm,
+    chunk: Vec<u8>,
+    index: Option<u64>,
+    chunks: u64,
+    pos: u64,
+    len: u64,
+}
+
+const SEALED_CHUNK_LEN: usize = 64 * 1024;
+const SEALED_TAG_LEN: usize = 16;
+const SEALED_HEADER_LEN: u64 = (PASSPHRASE_MAGIC.len() + SALT_LEN) as u64;
+const SEALED_RECORD_LEN: u64 = (NONCE_LEN + SEALED_CHUNK_LEN + SEALED_TAG_LEN) as u64;
+
+fn sealed_aad(index: u64, last: bool) -> [u8; 9] {
+    let mut aad = [0u8; 9];
+    aad[..8].copy_from_slice(&index.to_be_bytes());
+    aad[8] = last as u8;
+    aad
+}
+
+fn sealed_offset(index: u64) -> u64 {
+    SEALED_HEADER_LEN + index * SEALED_RECORD_LEN
+}
+
+fn passphrase_cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm, String> {
+    let key = passphrase_key(passphrase, salt)?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn seek_target(pos: u64, len: u64, from: SeekFrom) -> std::io::Result<u64> {
+    let target = match from {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::End(delta) => len.checked_add_signed(delta),
+        SeekFrom::Current(delta) => pos.checked_add_signed(delta),
+    };
+    target.ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid seek position")
+    })
+}
+
+impl SealedWriter {
+    /// Start a sealed file in `file`, which must be empty and opened for
+    /// reading and writing
+    pub fn new(mut file: std::fs::File, passphrase: &str) -> Result<Self, String> {
+        let salt: [u8; SALT_LEN] = rand::random();
+        let cipher = passphrase_cipher(passphrase, &salt)?;
+        file.write_all(PASSPHRASE_MAGIC)
+            .and_then(|_| file.write_all(&salt))
+            .map_err(|e| format!("Failed to write sealed file: {}", e))?;
+        Ok(Self {
+            file,
+            cipher,
+            chunk: Vec::new(),
+            index: 0,
+            dirty: false,
+            pos: 0,
+            len: 0,
+        })
+    }
+
+    fn seal(&mut self, last: bool) -> std::io::Result<()> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let aad = sealed_aad(self.index, last);
+        let payload = aes_gcm::aead::Payload {
+            msg: &self.chunk,
+            aad: &aad,
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| std::io::Error::other("Encryption failed"))?;
+        self.file.seek(SeekFrom::Start(sealed_offset(self.index)))?;
+        self.file.write_all(&nonce)?;
+        self.file.write_all(&ciphertext)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Make chunk `index` the current one, sealing the previous one and
+    /// reading back what's already been written of this one
+    fn load(&mut self, index: u64) -> std::io::Result<()> {
+        if index == self.index {
+            return Ok(());
+        }
+        if self.dirty {
+            self.seal(false)?;
+        }
+        let start = index * SEALED_CHUNK_LEN as u64;
+        let written = self.len.saturating_sub(start).min(SEALED_CHUNK_LEN as u64) as usize;
+        self.chunk.clear();
+        if written > 0 {
+            let mut record = vec![0u8; NONCE_LEN + written + SEALED_TAG_LEN];
+            self.file.seek(SeekFrom::Start(sealed_offset(index)))?;
+            self.file.read_exact(&mut record)?;
+            let (nonce, ciphertext) = record.split_at(NONCE_LEN);
+            let aad = sealed_aad(index, false);
+            let payload = aes_gcm::aead::Payload {
+                msg: ciphertext,
+                aad: &aad,
+            };
+            self.chunk = self
+                .cipher
+                .decrypt(Nonce::from_slice(nonce), payload)
+                .map_err(|_| invalid_data("Sealed chunk could not be read back"))?;
+        }
+        self.index = index;
+        Ok(())
+    }
+
+    /// Seal the last chunk and return the file
+    pub fn finish(mut self) -> std::io::Result<std::fs::File> {
+        let last = self.len.saturating_sub(1) / SEALED_CHUNK_LEN as u64;
+        self.load(last)?;
+        self.seal(true)?;
+        self.file.flush()?;
+        Ok(self.file)
+    }
+}
+
+impl Write for SealedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.load(self.pos / SEALED_CHUNK_LEN as u64)?;
+        let offset = (self.pos % SEALED_CHUNK_LEN as u64) as usize;
+        let n = buf.len().min(SEALED_CHUNK_LEN - offset);
+        if self.chunk.len() < offset + n {
+            self.chunk.resize(offset + n, 0);
+        }
+        self.chunk[offset..offset + n].copy_from_slice(&buf[..n]);
+        self.dirty = true;
+        self.pos += n as u64;
+        self.len = self.len.max(self.pos);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.dirty {
+            self.seal(false)?;
+        }
+        self.file.flush()
+    }
+}
+
+impl Seek for SealedWriter {
+    /// Seeking past the end isn't supported, since it would leave a gap
+    fn seek(&mut self, from: SeekFrom) -> std::io::Result<u64> {
+        let target = seek_target(self.pos, self.len, from)?;
+        if target > self.len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Cannot seek past the end of a sealed file",
+            ));
+        }
+        self.pos = target;
+        Ok(target)
+    }
+}
+
+impl SealedReader {
+    /// Open a file written by `SealedWriter`, checking the passphrase
+    /// against its first chunk
+    pub fn open(mut file: std::fs::File, passphrase: &str) -> Result<Self, String> {
+        let mut header = [0u8; SEALED_HEADER_LEN as usize];
+        file.read_exact(&mut header)
+            .ok()
+            .filter(|_| header.starts_with(PASSPHRASE_MAGIC))
+            .ok_or_else(|| "Invalid backup: not a GoodHang backup file".to_string())?;
+        let cipher = passphrase_cipher(passphrase, &header[PASSPHRASE_MAGIC.len()..])?;
+
+        let body = file
+            .metadata()
+            .map_err(|e| format!("Failed to read backup: {}", e))?
+            .len()
+            .saturating_sub(SEALED_HEADER_LEN);
+        let chunks = body.div_ceil(SEALED_RECORD_LEN);
+        let last_record = body - chunks.saturating_sub(1) * SEALED_RECORD_LEN;
+        if chunks == 0 || last_record < (NONCE_LEN + SEALED_TAG_LEN) as u64 {
+            return Err("Invalid backup: file is truncated".to_string());
+        }
+        let len = (chunks - 1) * SEALED_CHUNK_LEN as u64 + last_record
+            - (NONCE_LEN + SEALED_TAG_LEN) as u64;
+
+        let mut reader = Self {
+            file,
+            cipher,
+            chunk: Vec::new(),
+            index: None,
+            chunks,
+            pos: 0,
+            len,
+        };
+        reader
+            .load(0)
+            .map_err(|_| "Invalid backup: wrong passphrase or corrupted file".to_string())?;
+        Ok(reader)
+    }
+
+    fn load(&mut self, index: u64) -> std::io::Result<()> {
+        if self.index == Some(index) {
+            return Ok(());
+        }
+        let start = index * SEALED_CHUNK_LEN as u64;
+        let plain = (self.len - start).min(SEALED_CHUNK_LEN as u64) as usize;
+        let mut record = vec![0u8; NONCE_LEN + plain + SEALED_TAG_LEN];
+        self.file.seek(SeekFrom::Start(sealed_offset(index)))?;
+        self.file.read_exact(&mut record)?;
+        let (nonce, ciphertext) = record.split_at(NONCE_LEN);
+        let aad = sealed_aad(index, index + 1 == self.chunks);
+        let payload = aes_gcm::aead::Payload {
+            msg: ciphertext,
+            aad: &aad,
+        };
+        self.chunk = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| invalid_data("Invalid backup: corrupted file"))?;
+        self.index = Some(index);
+        Ok(())
+    }
+
+    /// Authenticate every chunk, so a damaged file is refused before any of
+    /// it is used
+    pub fn verify(&mut self) -> Result<(), String> {
+        for index in 0..self.chunks {
+            self.load(index).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl Read for SealedReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.pos >= self.len {
+            return Ok(0);
+        }
+        self.load(self.pos / SEALED_CHUNK_LEN as u64)?;
+        let offset = (self.pos % SEALED_CHUNK_LEN as u64) as usize;
+        let n = buf.len().min(self.chunk.len() - offset);
+        buf[..n].copy_from_slice(&self.chunk[offset..offset + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for SealedReader {
+    fn seek(&mut self, from: SeekFrom) -> std::io::Result<u64> {
+        self.pos = seek_target(self.pos, self.len, from)?;
+        Ok(self.pos)
+    }
+}
+
+/// Store serializer: every save of `auth.json` is written encrypted, behind
+/// a checksum
+pub fn serialize_store(cache: &HashMap<String, JsonValue>) -> Result<Vec<u8>, BoxError> {
+    let json = serde_json::to_vec(cache)?;
+    Ok(with_checksum(encrypt(&json)?))
+}
+
+/// Store deserializer: reads encrypted stores, and falls back to plaintext
+/// JSON so stores written before encryption still load (and are re-saved
+/// encrypted on the next write)
+pub fn deserialize_store(bytes: &[u8]) -> Result<HashMap<String, JsonValue>, BoxError> {
+    let bytes = verify_checksum(bytes)?;
+    if bytes.starts_with(MAGIC) {
+        let json = decrypt(bytes)?;
+        Ok(serde_json::from_slice(&json)?)
+    } else {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file() -> (std::path::PathBuf, std::fs::File) {
+        let path = std::env::temp_dir().join(format!("goodhang-sealed-{}", uuid::Uuid::new_v4()));
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .unwrap();
+        (path, file)
+    }
+
+    fn seal(plaintext: &[u8]) -> std::path::PathBuf {
+        let (path, file) = temp_file();
+        let mut writer = SealedWriter::new(file, "correct horse").unwrap();
+        writer.write_all(plaintext).unwrap();
+        writer.finish().unwrap();
+        path
+    }
+
+    fn open(path: &Path, passphrase: &str) -> Result<SealedReader, String> {
+        SealedReader::open(std::fs::File::open(path).unwrap(), passphrase)
+    }
+
+    #[test]
+    fn sealed_file_round_trips_across_chunks() {
+        for len in [0, 1, SEALED_CHUNK_LEN, 3 * SEALED_CHUNK_LEN + 17] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let path = seal(&plaintext);
+            let mut reader = open(&path, "correct horse").unwrap();
+            reader.verify().unwrap();
+            let mut read = Vec::new();
+            reader.read_to_end(&mut read).unwrap();
+            assert_eq!(read, plaintext);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn sealed_writer_patches_earlier_chunks() {
+        let (path, file) = temp_file();
+        let mut writer = SealedWriter::new(file, "correct horse").unwrap();
+        writer
+            .write_all(&vec![1u8; 2 * SEALED_CHUNK_LEN + 5])
+            .unwrap();
+        writer.seek(SeekFrom::Start(3)).unwrap();
+        writer.write_all(b"patched").unwrap();
+        writer.seek(SeekFrom::End(0)).unwrap();
+        writer.write_all(b"tail").unwrap();
+        writer.finish().unwrap();
+
+        let mut read = Vec::new();
+        open(&path, "correct horse")
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read.len(), 2 * SEALED_CHUNK_LEN + 9);
+        assert_eq!(&read[3..10], b"patched");
+        assert_eq!(&read[read.len() - 4..], b"tail");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn sealed_reader_rejects_wrong_passphrase_and_truncation() {
+        let path = seal(&vec![7u8; 2 * SEALED_CHUNK_LEN + 5]);
+        assert!(open(&path, "wrong horse").is_err());
+
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(sealed_offset(2)).unwrap();
+        assert!(open(&path, "correct horse").unwrap().verify().is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+    #[cfg(unix)]
+    #[test]
+    fn key_file_is_owner_only_even_when_replacing() {
+        use std::os::unix::fs::PermissionsExt;
+        let (path, _) = temp_file();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        write_key_file(&path, b"00ff").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read(&path).unwrap(), b"00ff");
+        let _ = std::fs::remove_file(&path);
+    }
+}
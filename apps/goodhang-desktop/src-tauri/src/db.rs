@@ -0,0 +1,526 @@
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::logging::app_log;
+use crate::migrations;
+
+pub(crate) const DB_FILENAME: &str = "goodhang.db";
+/// Version of the table layout, reached by running `migrations`. Bump it
+/// together with a new migration.
+pub(crate) const SCHEMA_VERSION: u32 = 4;
+
+/// Tables holding per-user data, cleared on secure logout
+pub(crate) const USER_TABLES: &[&str] = &[
+    "voice_memos",
+    "embeddings",
+    "search_index",
+    "uploads",
+    "hangs",
+    "profiles",
+    "analytics_cache",
+    "assessment_snapshots",
+    "bookmarks",
+    "relationship_notes",
+    "reminders",
+    "outbox",
+    "tombstones",
+    "email_contacts",
+    "prompt_templates",
+    "chat_turns",
+    "chat_conversations",
+    "summary_cache",
+];
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS voice_memos (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    duration_secs REAL NOT NULL DEFAULT 0,
+    transcript TEXT,
+    tags TEXT NOT NULL DEFAULT '[]',
+    file_name TEXT NOT NULL,
+    mime_type TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    sync_status TEXT NOT NULL DEFAULT 'pending',
+    remote_id TEXT,
+    sync_error TEXT
+);
+
+CREATE TABLE IF NOT EXISTS embeddings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_type TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    vector BLOB NOT NULL,
+    created_at INTEGER NOT NULL,
+    indexed_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_embeddings_source ON embeddings (source_type, source_id);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    scope UNINDEXED,
+    source_id UNINDEXED,
+    title,
+    body,
+    tokenize = 'porter unicode61'
+);
+
+CREATE TABLE IF NOT EXISTS uploads (
+    id TEXT PRIMARY KEY,
+    remote_id TEXT,
+    kind TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    mime_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    chunk_size INTEGER NOT NULL,
+    file_sha256 TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    bytes_uploaded INTEGER NOT NULL DEFAULT 0,
+    result_id TEXT,
+    error TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS hangs (
+    id TEXT PRIMARY KEY,
+    status TEXT NOT NULL,
+    starts_at TEXT,
+    payload TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS profiles (
+    user_id TEXT PRIMARY KEY,
+    payload TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS analytics_cache (
+    key TEXT PRIMARY KEY,
+    payload TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS assessment_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    UNIQUE (user_id, content_hash)
+);
+
+-- Reading list; payload is the saved item for offline reading, removed
+-- marks a tombstone until the server has seen the removal
+CREATE TABLE IF NOT EXISTS bookmarks (
+    item_id TEXT PRIMARY KEY,
+    payload TEXT,
+    bookmarked_at INTEGER NOT NULL,
+    removed INTEGER NOT NULL DEFAULT 0,
+    sync_status TEXT NOT NULL DEFAULT 'pending'
+);
+
+CREATE TABLE IF NOT EXISTS relationship_notes (
+    id TEXT PRIMARY KEY,
+    relationship_id TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    sync_to_gft INTEGER NOT NULL DEFAULT 0,
+    sync_status TEXT NOT NULL DEFAULT 'local',
+    remote_id TEXT,
+    sync_error TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_relationship_notes_person
+    ON relationship_notes (relationship_id, created_at);
+
+-- source_key identifies reminders the app schedules itself (one per key);
+-- recurrence is a JSON RecurrenceRule
+CREATE TABLE IF NOT EXISTS reminders (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    source_key TEXT UNIQUE,
+    title TEXT NOT NULL,
+    body TEXT,
+    due_at INTEGER NOT NULL,
+    recurrence TEXT,
+    relationship_id TEXT,
+    snoozed_until INTEGER,
+    notified_at INTEGER,
+    completed_at INTEGER,
+    created_at INTEGER NOT NULL
+);
+
+-- Queued writes awaiting delivery; status is pending, in_flight or dead
+CREATE TABLE IF NOT EXISTS outbox (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT,
+    created_at INTEGER NOT NULL
+);
+
+-- Items deleted but still inside their undo window; snapshot holds what's
+-- needed to restore them, remote_path the API delete issued afterwards
+CREATE TABLE IF NOT EXISTS tombstones (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    snapshot TEXT NOT NULL,
+    remote_path TEXT,
+    delete_at INTEGER NOT NULL
+);
+
+-- Last mail exchanged per address, derived from IMAP headers on device
+CREATE TABLE IF NOT EXISTS email_contacts (
+    address TEXT PRIMARY KEY,
+    last_contact_at INTEGER NOT NULL,
+    last_direction TEXT NOT NULL
+);
+
+-- Every saved version of a prompt template; source is user or shared
+CREATE TABLE IF NOT EXISTS prompt_templates (
+    id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    body TEXT NOT NULL,
+    source TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (id, version)
+);
+
+CREATE TABLE IF NOT EXISTS chat_conversations (
+    id TEXT PRIMARY KEY,
+    persona TEXT NOT NULL,
+    title TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+-- tokens_used is a JSON TokensUsed for assistant turns
+CREATE TABLE IF NOT EXISTS chat_turns (
+    id TEXT PRIMARY KEY,
+    conversation_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    content TEXT NOT NULL,
+    model TEXT,
+    tokens_used TEXT,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_chat_turns_conversation
+    ON chat_turns (conversation_id, created_at);
+
+-- Latest AI output per subject and the hash of the inputs it came from
+CREATE TABLE IF NOT EXISTS summary_cache (
+    kind TEXT NOT NULL,
+    subject_id TEXT NOT NULL,
+    input_hash TEXT NOT NULL,
+    output TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (kind, subject_id)
+);
+
+-- Append-only record of sensitive commands; deliberately kept across
+-- logouts so it can answer 'who cleared my session'
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    at INTEGER NOT NULL,
+    command TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    error_code TEXT
+);
+CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;
+CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;
+";
+
+/// Key columns that identify a row when merging tables that have no
+/// primary key to conflict on
+const MERGE_KEYS: &[(&str, &[&str])] = &[
+    ("search_index", &["scope", "source_id"]),
+    ("embeddings", &["source_type", "source_id", "chunk_index"]),
+];
+
+/// The baseline tables; later changes are migrations on top
+pub(crate) fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+/// Bring a database's tables up to the current layout
+pub(crate) fn upgrade(conn: &Connection) -> rusqlite::Result<()> {
+    migrations::migrate_db(conn).map(|_| ())
+}
+
+/// Column names of `schema.table`, flagging an INTEGER PRIMARY KEY (a rowid
+/// alias, renumbered rather than copied when merging)
+fn table_columns(
+    conn: &Connection,
+    schema: &str,
+    table: &str,
+) -> rusqlite::Result<Vec<(String, bool)>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {}.table_info({})", schema, table))?;
+    let rows = stmt.query_map([], |row| {
+        let kind: String = row.get(2)?;
+        let pk: i64 = row.get(5)?;
+        Ok((row.get(1)?, pk == 1 && kind.eq_ignore_ascii_case("INTEGER")))
+    })?;
+    rows.collect()
+}
+
+/// Local SQLite database shared by all commands via Tauri managed state
+pub struct Database {
+    conn: Mutex<Connection>,
+}
+
+impl Database {
+    /// Open (or create) the SQLCipher-encrypted database. `key_hex` is the
+    /// 32-byte data key in hex; see `crypto::data_key_hex`.
+    pub fn open(data_dir: &Path, key_hex: &str) -> Result<Self, String> {
+        Ok(Self {
+            conn: Mutex::new(open_configured(data_dir, key_hex)?),
+        })
+    }
+
+    /// Close the current database and open (or create) the one in
+    /// `data_dir` in its place, e.g. when another account signs in
+    pub fn switch_to(&self, data_dir: &Path, key_hex: &str) -> Result<(), String> {
+        let conn = open_configured(data_dir, key_hex)?;
+        *self
+            .conn
+            .lock()
+            .map_err(|_| "Database lock poisoned".to_string())? = conn;
+        Ok(())
+    }
+
+    /// Delete all user rows, overwrite freed pages, compact the file, and
+    /// re-encrypt it under `new_key_hex`
+    pub fn wipe_user_data(&self, new_key_hex: &str) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute_batch("PRAGMA secure_delete = ON;")?;
+            for table in USER_TABLES {
+                conn.execute(&format!("DELETE FROM {}", table), [])?;
+            }
+            conn.execute_batch("VACUUM;")?;
+            conn.execute_batch(&format!("PRAGMA rekey = \"x'{}'\";", new_key_hex))
+        })?;
+        app_log!("[DB] User data wiped");
+        Ok(())
+    }
+
+    /// Write a copy of the database to `path` encrypted under `key_hex`
+    /// instead of the data key, for backups that must open on another
+    /// machine
+    pub fn export_copy(&self, path: &Path, key_hex: &str) -> Result<(), String> {
+        let attach = format!(
+            "ATTACH DATABASE '{}' AS backup KEY \"x'{}'\";",
+            path.display().to_string().replace('\'', "''"),
+            key_hex
+        );
+        self.with_conn(|conn| {
+            conn.execute_batch(&attach)?;
+            let exported = conn.query_row("SELECT sqlcipher_export('backup')", [], |_| Ok(()));
+            conn.execute_batch("DETACH DATABASE backup;")?;
+            exported
+        })
+    }
+
+    /// Copy the user tables of the database at `path`, encrypted under
+    /// `key_hex`, into this one, after upgrading it to the current layout.
+    /// `replace` empties each table first; otherwise local rows win on
+    /// conflict. Returns the number of rows copied.
+    pub fn import_copy(&self, path: &Path, key_hex: &str, replace: bool) -> Result<usize, String> {
+        {
+            let source = Connection::open(path)
+                .map_err(|e| format!("Failed to open backup database: {}", e))?;
+            apply_key(&source, key_hex)
+                .and_then(|_| upgrade(&source))
+                .map_err(|e| format!("Failed to upgrade backup database: {}", e))?;
+        }
+
+        let attach = format!(
+            "ATTACH DATABASE '{}' AS source KEY \"x'{}'\";",
+            path.display().to_string().replace('\'', "''"),
+            key_hex
+        );
+        self.with_conn(|conn| {
+            conn.execute_batch(&attach)?;
+            let copy = || -> rusqlite::Result<usize> {
+                let tx = conn.unchecked_transaction()?;
+                let mut copied = 0;
+                for table in USER_TABLES {
+                    let theirs: Vec<String> = table_columns(&tx, "source", table)?
+                        .into_iter()
+                        .map(|(name, _)| name)
+                        .collect();
+                    let columns: Vec<String> = table_columns(&tx, "main", table)?
+                        .into_iter()
+                        .filter(|(name, rowid)| theirs.contains(name) && (replace || !rowid))
+                        .map(|(name, _)| name)
+                        .collect();
+                    let list = columns.join(", ");
+
+                    let merge_keys = MERGE_KEYS.iter().find(|(t, _)| t == table);
+                    let sql = if replace {
+                        tx.execute(&format!("DELETE FROM main.{}", table), [])?;
+                        format!(
+                            "INSERT INTO main.{0} ({1}) SELECT {1} FROM source.{0}",
+                            table, list
+                        )
+                    } else if let Some((_, keys)) = merge_keys {
+                        let matches: Vec<String> =
+                            keys.iter().map(|k| format!("m.{0} = s.{0}", k)).collect();
+                        format!(
+                            "INSERT INTO main.{0} ({1}) SELECT {1} FROM source.{0} s
+                             WHERE NOT EXISTS (SELECT 1 FROM main.{0} m WHERE {2})",
+                            table,
+                            list,
+                            matches.join(" AND ")
+                        )
+                    } else {
+                        format!(
+                            "INSERT OR IGNORE INTO main.{0} ({1}) SELECT {1} FROM source.{0}",
+                            table, list
+                        )
+                    };
+                    copied += tx.execute(&sql, [])?;
+                }
+                tx.commit()?;
+                Ok(copied)
+            };
+            let result = copy();
+            conn.execute_batch("DETACH DATABASE source;")?;
+            result
+        })
+    }
+
+    /// Flush the WAL into the database file and run `f` with the connection
+    /// held, so the file on disk is complete and stays unchanged while `f`
+    /// copies it
+    pub fn with_checkpoint<T>(&self, f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Database lock poisoned".to_string())?;
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(|e| format!("Database error: {}", e))?;
+        f()
+    }
+
+    /// Run a closure against the connection, mapping SQLite errors to strings
+    pub fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Database lock poisoned".to_string())?;
+        f(&conn).map_err(|e| format!("Database error: {}", e))
+    }
+}
+
+fn open_configured(data_dir: &Path, key_hex: &str) -> Result<Connection, String> {
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+
+    let conn = open_encrypted(&data_dir.join(DB_FILENAME), key_hex)?;
+
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")
+        .map_err(|e| format!("Failed to configure database: {}", e))?;
+
+    upgrade(&conn).map_err(|e| format!("Failed to initialize schema: {}", e))?;
+
+    app_log!("[DB] Database opened at {}", data_dir.join(DB_FILENAME).display());
+    Ok(conn)
+}
+
+fn apply_key(conn: &Connection, key_hex: &str) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key_hex))
+}
+
+fn is_readable(conn: &Connection) -> bool {
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .is_ok()
+}
+
+fn open_encrypted(path: &Path, key_hex: &str) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| format!("Failed to open database: {}", e))?;
+    apply_key(&conn, key_hex).map_err(|e| format!("Failed to apply database key: {}", e))?;
+    if is_readable(&conn) {
+        return Ok(conn);
+    }
+    drop(conn);
+
+    // Not readable with the key: either a plaintext database from before
+    // encryption shipped, or genuinely unreadable
+    encrypt_plaintext_db(path, key_hex)?;
+
+    let conn = Connection::open(path).map_err(|e| format!("Failed to open database: {}", e))?;
+    apply_key(&conn, key_hex).map_err(|e| format!("Failed to apply database key: {}", e))?;
+    if !is_readable(&conn) {
+        return Err("Database could not be decrypted".to_string());
+    }
+    Ok(conn)
+}
+
+/// One-time migration of a plaintext database into an encrypted copy that
+/// replaces the original
+fn encrypt_plaintext_db(path: &Path, key_hex: &str) -> Result<(), String> {
+    let plain = Connection::open(path).map_err(|e| format!("Failed to open database: {}", e))?;
+    if !is_readable(&plain) {
+        return Err("Database is neither encrypted with this key nor plaintext".to_string());
+    }
+
+    app_log!("[DB] Encrypting existing plaintext database");
+    let encrypted_path = sibling(path, "encrypting");
+    let _ = std::fs::remove_file(&encrypted_path);
+
+    let attach = format!(
+        "ATTACH DATABASE '{}' AS encrypted KEY \"x'{}'\";",
+        encrypted_path.display().to_string().replace('\'', "''"),
+        key_hex
+    );
+    plain
+        .execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+        .and_then(|_| plain.execute_batch(&attach))
+        .and_then(|_| plain.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(())))
+        .and_then(|_| plain.execute_batch("DETACH DATABASE encrypted;"))
+        .map_err(|e| format!("Failed to encrypt database: {}", e))?;
+    drop(plain);
+
+    for suffix in ["wal", "shm"] {
+        let _ = std::fs::remove_file(sibling(path, suffix));
+    }
+    std::fs::rename(&encrypted_path, path)
+        .map_err(|e| format!("Failed to replace plaintext database: {}", e))?;
+
+    app_log!("[DB] Database encrypted");
+    Ok(())
+}
+
+/// `goodhang.db` -> `goodhang.db-<suffix>`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!("-{}", suffix));
+    PathBuf::from(name)
+}
+
+/// Current time as unix seconds
+pub fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
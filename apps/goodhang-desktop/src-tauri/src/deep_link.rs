@@ -0,0 +1,95 @@
+use tauri::{Manager, Url};
+
+use crate::commands::{hangs, login, matching, slack};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+
+/// Route name and remaining path segments. `goodhang://auth/callback` parses
+/// with host "auth" on macOS/Linux, but Windows may deliver the same link
+/// with no host and the route as the first path segment.
+fn route(url: &Url) -> (String, Vec<String>) {
+    let mut segments: Vec<String> = url
+        .path()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let name = match url.host_str().filter(|h| !h.is_empty()) {
+        Some(host) => host.to_string(),
+        None if !segments.is_empty() => segments.remove(0),
+        None => String::new(),
+    };
+    (name, segments)
+}
+
+fn focus_main(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_focus();
+    }
+}
+
+pub fn handle_url(app: &tauri::AppHandle, url: &Url) {
+    if url.scheme() != "goodhang" {
+        return;
+    }
+
+    let (name, segments) = route(url);
+    match (name.as_str(), segments.first().map(String::as_str)) {
+        ("auth", Some("callback")) => {
+            app_log!("[DeepLink] OAuth callback received");
+            login::complete_browser_login(app, url);
+            focus_main(app);
+        }
+        ("slack", Some("callback")) => {
+            app_log!("[DeepLink] Slack callback received");
+            slack::complete_slack_connect(app, url);
+            focus_main(app);
+        }
+        ("magic", Some(token)) => {
+            app_log!("[DeepLink] Magic link received");
+            login::complete_magic_link(app, token.to_string());
+            focus_main(app);
+        }
+        ("invite", Some(id)) => {
+            app_log!("[DeepLink] Invite received: id={}", id);
+            hangs::open_from_deep_link(app, id.to_string());
+            focus_main(app);
+        }
+        ("match", Some(id)) if segments.get(1).map(String::as_str) == Some("accept") => {
+            app_log!("[DeepLink] Match accept received: id={}", id);
+            focus_main(app);
+            matching::accept_from_deep_link(app, id.to_string());
+        }
+        ("referral", Some(code)) => {
+            app_log!("[DeepLink] Referral link received");
+            events::emit_app_event(app, AppEvent::ReferralOpened(code.to_string()));
+            focus_main(app);
+        }
+        _ => handle_activation(app, url),
+    }
+}
+
+fn handle_activation(app: &tauri::AppHandle, url: &Url) {
+    // Extract activation code from path
+    // URL format: goodhang://activate/XXXX-XXXX-XXXX
+    let path = url.path();
+    // Handle both "/activate/CODE" and "activate/CODE" (Windows may omit leading slash)
+    let code = path
+        .strip_prefix("/activate/")
+        .or_else(|| path.strip_prefix("activate/"))
+        .or_else(|| path.strip_prefix("/"))
+        .unwrap_or(path);
+
+    if !code.is_empty() {
+        if let Some(window) = app.get_webview_window("main") {
+            app_log!("Deep link received: code={}", code);
+            events::emit_app_event(
+                window.app_handle(),
+                AppEvent::ActivationCode(code.to_string()),
+            );
+            // Focus the window
+            let _ = window.set_focus();
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Local plain-text extraction for context files (PDF, DOCX, Markdown and
+//! plain text), so they can be searched and measured without the server
+//! having to parse them.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// Bigger files are almost certainly scans or archives, not prose
+const MAX_DOCUMENT_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Pdf,
+    Docx,
+    Markdown,
+    Text,
+}
+
+impl DocumentFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        match extension.as_str() {
+            "pdf" => Some(DocumentFormat::Pdf),
+            "docx" => Some(DocumentFormat::Docx),
+            "md" | "markdown" => Some(DocumentFormat::Markdown),
+            "txt" | "text" => Some(DocumentFormat::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Plain text of a document, with runs of blank lines collapsed. Blocking.
+pub fn extract_text(path: &Path) -> Result<String, String> {
+    let format =
+        DocumentFormat::from_path(path).ok_or_else(|| "Unsupported document type".to_string())?;
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .len();
+    if size > MAX_DOCUMENT_BYTES {
+        return Err("Document is too large to extract".to_string());
+    }
+
+    let text = match format {
+        DocumentFormat::Pdf => pdf_text(path)?,
+        DocumentFormat::Docx => docx_text(path)?,
+        DocumentFormat::Markdown => markdown_text(&read_utf8(path)?),
+        DocumentFormat::Text => read_utf8(path)?,
+    };
+    Ok(normalize(&text))
+}
+
+/// `extract_text` off the async runtime
+pub async fn extract_text_in_background(path: PathBuf) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || extract_text(&path))
+        .await
+        .map_err(|e| format!("Extraction task failed: {}", e))?
+}
+
+/// `pdf_extract` panics on some malformed files rather than returning an
+/// error; those are reported as unreadable instead of taking the app down
+fn pdf_text(path: &Path) -> Result<String, String> {
+    std::panic::catch_unwind(|| pdf_extract::extract_text(path))
+        .map_err(|_| "Failed to read PDF: the file is malformed".to_string())?
+        .map_err(|e| format!("Failed to read PDF: {}", e))
+}
+
+fn read_utf8(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Text runs (`w:t`) from the main document part, one line per paragraph
+fn docx_text(path: &Path) -> Result<String, String> {
+    use quick_xml::events::Event;
+
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Invalid DOCX file: {}", e))?;
+    let mut xml = String::new();
+    // The size on disk says nothing about the size inflated, so cap that too
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| format!("Invalid DOCX file: {}", e))?
+        .take(MAX_DOCUMENT_BYTES + 1)
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("Invalid DOCX file: {}", e))?;
+    if xml.len() as u64 > MAX_DOCUMENT_BYTES {
+        return Err("Document is too large to extract".to_string());
+    }
+
+    let mut reader = quick_xml::Reader::from_str(&xml);
+    let mut text = String::new();
+    let mut in_run_text = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.name().as_ref() == b"w:t" => in_run_text = true,
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"w:t" => in_run_text = false,
+                b"w:p" => text.push('\n'),
+                _ => {}
+            },
+            Ok(Event::Empty(e)) => match e.name().as_ref() {
+                b"w:tab" => text.push('\t'),
+                b"w:br" | b"w:cr" => text.push('\n'),
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_run_text => {
+                let run = e
+                    .unescape()
+                    .map_err(|e| format!("Invalid DOCX file: {}", e))?;
+                text.push_str(&run);
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(format!("Invalid DOCX file: {}", e)),
+        }
+    }
+    Ok(text)
+}
+
+/// Rendered text of Markdown, without syntax
+fn markdown_text(source: &str) -> String {
+    use pulldown_cmark::{Event, Parser, TagEnd};
+
+    let mut text = String::new();
+    for event in Parser::new(source) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::CodeBlock,
+            ) => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Trim trailing whitespace and collapse blank-line runs to one
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank_run = false;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            if !blank_run && !out.is_empty() {
+                out.push('\n');
+            }
+            blank_run = true;
+        } else {
+            out.push_str(line);
+            out.push('\n');
+            blank_run = false;
+        }
+    }
+    out.trim_end().to_string()
+}
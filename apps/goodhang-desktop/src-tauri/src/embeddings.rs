@@ -0,0 +1,289 @@
+use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::commands::sync_policy;
+use crate::db::{now_secs, Database};
+use crate::logging::app_log;
+
+// Target chunk size in characters; roughly 200 tokens for the MiniLM model
+const CHUNK_CHARS: usize = 800;
+
+/// Lazily-loaded local embedding model. The ONNX weights are downloaded into
+/// the app cache dir on first use and reused offline afterwards.
+pub struct Embedder {
+    cache_dir: PathBuf,
+    model: Mutex<Option<TextEmbedding>>,
+}
+
+impl Embedder {
+    pub fn new(cache_dir: PathBuf) -> Arc<Self> {
+        Arc::new(Self {
+            cache_dir,
+            model: Mutex::new(None),
+        })
+    }
+
+    /// Whether embedding can run without first downloading the model
+    pub fn is_ready(&self) -> bool {
+        self.model.lock().is_ok_and(|m| m.is_some())
+            || std::fs::read_dir(&self.cache_dir).is_ok_and(|mut d| d.next().is_some())
+    }
+
+    /// Embed a batch of texts. Blocking; call from `spawn_blocking`.
+    pub fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+        let mut guard = self
+            .model
+            .lock()
+            .map_err(|_| "Embedding model lock poisoned".to_string())?;
+
+        if guard.is_none() {
+            app_log!("[Embeddings] Loading embedding model");
+            let model = TextEmbedding::try_new(
+                InitOptions::new(EmbeddingModel::AllMiniLML6V2)
+                    .with_cache_dir(self.cache_dir.clone()),
+            )
+            .map_err(|e| format!("Failed to load embedding model: {}", e))?;
+            *guard = Some(model);
+        }
+
+        guard
+            .as_ref()
+            .expect("model initialized above")
+            .embed(texts, None)
+            .map_err(|e| format!("Failed to embed text: {}", e))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct SemanticMatch {
+    #[serde(rename = "sourceType")]
+    pub source_type: String,
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    #[serde(rename = "chunkIndex")]
+    pub chunk_index: i64,
+    pub content: String,
+    pub score: f32,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+pub fn content_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+/// Split text into chunks on paragraph, then sentence boundaries
+pub fn chunk_text(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if current.len() + paragraph.len() > CHUNK_CHARS && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+
+        if paragraph.len() > CHUNK_CHARS {
+            // Oversized paragraph: fall back to sentence boundaries
+            for sentence in paragraph.split_inclusive(['.', '!', '?']) {
+                if current.len() + sentence.len() > CHUNK_CHARS && !current.is_empty() {
+                    chunks.push(std::mem::take(&mut current));
+                }
+                current.push_str(sentence);
+            }
+        } else {
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(paragraph);
+        }
+    }
+
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn vector_to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn blob_to_vector(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Index (or re-index) a document. Returns false when the stored content hash
+/// already matches and nothing needed embedding.
+pub fn index_document(
+    db: &Database,
+    embedder: &Embedder,
+    source_type: &str,
+    source_id: &str,
+    text: &str,
+    created_at: i64,
+) -> Result<bool, String> {
+    let hash = content_hash(text);
+    let existing: Option<String> = db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT content_hash FROM embeddings
+             WHERE source_type = ?1 AND source_id = ?2 LIMIT 1",
+            [source_type, source_id],
+            |row| row.get(0),
+        )
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })
+    })?;
+
+    if existing.as_deref() == Some(hash.as_str()) {
+        return Ok(false);
+    }
+
+    let chunks = chunk_text(text);
+    let vectors = if chunks.is_empty() {
+        Vec::new()
+    } else {
+        embedder.embed(chunks.clone())?
+    };
+
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM embeddings WHERE source_type = ?1 AND source_id = ?2",
+            [source_type, source_id],
+        )?;
+        for (index, (chunk, vector)) in chunks.iter().zip(&vectors).enumerate() {
+            tx.execute(
+                "INSERT INTO embeddings
+                 (source_type, source_id, chunk_index, content, content_hash, vector, created_at, indexed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                rusqlite::params![
+                    source_type,
+                    source_id,
+                    index as i64,
+                    chunk,
+                    hash,
+                    vector_to_blob(vector),
+                    created_at,
+                    now_secs()
+                ],
+            )?;
+        }
+        tx.commit()
+    })?;
+
+    app_log!(
+        "[Embeddings] Indexed {}:{} ({} chunks)",
+        source_type,
+        source_id,
+        chunks.len()
+    );
+    Ok(true)
+}
+
+pub fn remove_document(db: &Database, source_type: &str, source_id: &str) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "DELETE FROM embeddings WHERE source_type = ?1 AND source_id = ?2",
+            [source_type, source_id],
+        )
+    })?;
+    Ok(())
+}
+
+/// Brute-force cosine search; personal-scale corpora stay well under the
+/// size where an ANN index would pay off
+pub fn search(
+    db: &Database,
+    embedder: &Embedder,
+    query: &str,
+    k: usize,
+    source_types: Option<&[String]>,
+    since: Option<i64>,
+) -> Result<Vec<SemanticMatch>, String> {
+    let query_vector = embedder
+        .embed(vec![query.to_string()])?
+        .pop()
+        .ok_or_else(|| "Embedding model returned no vector".to_string())?;
+
+    let rows = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT source_type, source_id, chunk_index, content, vector, created_at
+             FROM embeddings WHERE created_at >= ?1",
+        )?;
+        let rows = stmt.query_map([since.unwrap_or(0)], |row| {
+            Ok((
+                SemanticMatch {
+                    source_type: row.get(0)?,
+                    source_id: row.get(1)?,
+                    chunk_index: row.get(2)?,
+                    content: row.get(3)?,
+                    score: 0.0,
+                    created_at: row.get(5)?,
+                },
+                row.get::<_, Vec<u8>>(4)?,
+            ))
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })?;
+
+    let mut matches: Vec<SemanticMatch> = rows
+        .into_iter()
+        .filter(|(m, _)| source_types.is_none_or(|types| types.contains(&m.source_type)))
+        .map(|(mut m, blob)| {
+            m.score = cosine_similarity(&query_vector, &blob_to_vector(&blob));
+            m
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(k);
+    Ok(matches)
+}
+
+/// Index a document off the async runtime without blocking the caller
+pub fn index_in_background(
+    app: &tauri::AppHandle,
+    source_type: &'static str,
+    source_id: String,
+    text: String,
+    created_at: i64,
+) {
+    // `reindex_embeddings` catches up on anything skipped here
+    if !app.state::<Arc<Embedder>>().is_ready() && sync_policy::conserving() {
+        app_log!(
+            "[Embeddings] Model download deferred; not indexing {}:{}",
+            source_type,
+            source_id
+        );
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        let embedder = app.state::<Arc<Embedder>>();
+        if let Err(e) = index_document(&db, &embedder, source_type, &source_id, &text, created_at) {
+            app_log!("[Embeddings] Background index failed for {}:{}: {}", source_type, source_id, e);
+        }
+    });
+}
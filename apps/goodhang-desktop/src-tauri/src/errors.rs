@@ -0,0 +1,386 @@
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+use crate::network;
+use crate::stores;
+
+const LOCALE_KEY: &str = "locale";
+
+/// Stable identifiers the frontend can branch on and translate, instead of
+/// matching English error text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, specta::Type)]
+pub enum ErrorCode {
+    #[serde(rename = "E_NETWORK_OFFLINE")]
+    NetworkOffline,
+    #[serde(rename = "E_NETWORK")]
+    Network,
+    #[serde(rename = "E_TIMEOUT")]
+    Timeout,
+    #[serde(rename = "E_NOT_SIGNED_IN")]
+    NotSignedIn,
+    #[serde(rename = "E_SESSION_EXPIRED")]
+    SessionExpired,
+    #[serde(rename = "E_UNAUTHORIZED")]
+    Unauthorized,
+    #[serde(rename = "E_DEVICE_REVOKED")]
+    DeviceRevoked,
+    #[serde(rename = "E_ACTIVATION_INVALID")]
+    ActivationInvalid,
+    #[serde(rename = "E_ACTIVATION_EXPIRED")]
+    ActivationExpired,
+    #[serde(rename = "E_ACTIVATION_CLAIMED")]
+    ActivationClaimed,
+    #[serde(rename = "E_LOGIN_FAILED")]
+    LoginFailed,
+    #[serde(rename = "E_NOT_FOUND")]
+    NotFound,
+    #[serde(rename = "E_RATE_LIMITED")]
+    RateLimited,
+    #[serde(rename = "E_SERVER")]
+    Server,
+    #[serde(rename = "E_REQUEST_REJECTED")]
+    RequestRejected,
+    #[serde(rename = "E_SCHEMA_MISMATCH")]
+    SchemaMismatch,
+    #[serde(rename = "E_CLIENT_OUTDATED")]
+    ClientOutdated,
+    #[serde(rename = "E_STORAGE")]
+    Storage,
+    #[serde(rename = "E_INVALID_INPUT")]
+    InvalidInput,
+    #[serde(rename = "E_PRODUCT_NOT_ENABLED")]
+    ProductNotEnabled,
+    #[serde(rename = "E_CONSENT_REQUIRED")]
+    ConsentRequired,
+    #[serde(rename = "E_DATA_LOCKED")]
+    DataLocked,
+    #[serde(rename = "E_UNKNOWN")]
+    Unknown,
+}
+
+pub const ALL_CODES: &[ErrorCode] = &[
+    ErrorCode::NetworkOffline,
+    ErrorCode::Network,
+    ErrorCode::Timeout,
+    ErrorCode::NotSignedIn,
+    ErrorCode::SessionExpired,
+    ErrorCode::Unauthorized,
+    ErrorCode::DeviceRevoked,
+    ErrorCode::ActivationInvalid,
+    ErrorCode::ActivationExpired,
+    ErrorCode::ActivationClaimed,
+    ErrorCode::LoginFailed,
+    ErrorCode::NotFound,
+    ErrorCode::RateLimited,
+    ErrorCode::Server,
+    ErrorCode::RequestRejected,
+    ErrorCode::SchemaMismatch,
+    ErrorCode::ClientOutdated,
+    ErrorCode::Storage,
+    ErrorCode::InvalidInput,
+    ErrorCode::ProductNotEnabled,
+    ErrorCode::ConsentRequired,
+    ErrorCode::DataLocked,
+    ErrorCode::Unknown,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    Es,
+    De,
+}
+
+impl Locale {
+    /// Accepts tags like `es`, `es-MX` or `de_DE`; anything unsupported is English
+    pub fn parse(tag: &str) -> Self {
+        match tag.get(..2).map(str::to_ascii_lowercase).as_deref() {
+            Some("es") => Locale::Es,
+            Some("de") => Locale::De,
+            _ => Locale::En,
+        }
+    }
+}
+
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::En);
+
+pub fn locale() -> Locale {
+    LOCALE.read().map(|l| *l).unwrap_or(Locale::En)
+}
+
+/// Restore the saved locale at startup
+pub fn load_locale(app: &tauri::AppHandle) {
+    let saved = stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(LOCALE_KEY))
+        .and_then(|value| value.as_str().map(Locale::parse));
+    if let (Some(saved), Ok(mut locale)) = (saved, LOCALE.write()) {
+        *locale = saved;
+    }
+}
+
+pub fn save_locale(app: &tauri::AppHandle, new_locale: Locale) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    store.set(
+        LOCALE_KEY,
+        serde_json::to_value(new_locale)
+            .map_err(|e| format!("Failed to serialize locale: {}", e))?,
+    );
+    stores::save(app, stores::SETTINGS_STORE)?;
+
+    *LOCALE
+        .write()
+        .map_err(|_| "Locale lock poisoned".to_string())? = new_locale;
+    Ok(())
+}
+
+/// User-facing text for a code
+pub fn message(code: ErrorCode, locale: Locale) -> &'static str {
+    use ErrorCode::*;
+    use Locale::*;
+    match (code, locale) {
+        (NetworkOffline, En) => "You're offline. Check your connection and try again.",
+        (NetworkOffline, Es) => "Estás sin conexión. Revisa tu conexión e inténtalo de nuevo.",
+        (NetworkOffline, De) => "Du bist offline. Prüfe deine Verbindung und versuche es erneut.",
+        (Network, En) => "We couldn't reach Good Hang. Please try again.",
+        (Network, Es) => "No pudimos conectar con Good Hang. Inténtalo de nuevo.",
+        (Network, De) => "Good Hang ist nicht erreichbar. Bitte versuche es erneut.",
+        (Timeout, En) => "This is taking too long. Please try again.",
+        (Timeout, Es) => "Esto está tardando demasiado. Inténtalo de nuevo.",
+        (Timeout, De) => "Das dauert zu lange. Bitte versuche es erneut.",
+        (NotSignedIn, En) => "Please sign in to continue.",
+        (NotSignedIn, Es) => "Inicia sesión para continuar.",
+        (NotSignedIn, De) => "Bitte melde dich an, um fortzufahren.",
+        (SessionExpired, En) => "Your session has expired. Please sign in again.",
+        (SessionExpired, Es) => "Tu sesión ha caducado. Vuelve a iniciar sesión.",
+        (SessionExpired, De) => "Deine Sitzung ist abgelaufen. Bitte melde dich erneut an.",
+        (Unauthorized, En) => "You don't have access to this.",
+        (Unauthorized, Es) => "No tienes acceso a esto.",
+        (Unauthorized, De) => "Du hast keinen Zugriff darauf.",
+        (DeviceRevoked, En) => "This device was signed out. Please sign in again.",
+        (DeviceRevoked, Es) => "Se cerró la sesión en este dispositivo. Vuelve a iniciar sesión.",
+        (DeviceRevoked, De) => "Dieses Gerät wurde abgemeldet. Bitte melde dich erneut an.",
+        (ActivationInvalid, En) => "That activation code isn't valid.",
+        (ActivationInvalid, Es) => "Ese código de activación no es válido.",
+        (ActivationInvalid, De) => "Dieser Aktivierungscode ist ungültig.",
+        (ActivationExpired, En) => "That activation code has expired.",
+        (ActivationExpired, Es) => "Ese código de activación ha caducado.",
+        (ActivationExpired, De) => "Dieser Aktivierungscode ist abgelaufen.",
+        (ActivationClaimed, En) => "That activation code has already been used.",
+        (ActivationClaimed, Es) => "Ese código de activación ya se ha utilizado.",
+        (ActivationClaimed, De) => "Dieser Aktivierungscode wurde bereits verwendet.",
+        (LoginFailed, En) => "Sign-in didn't complete. Please try again.",
+        (LoginFailed, Es) => "No se completó el inicio de sesión. Inténtalo de nuevo.",
+        (LoginFailed, De) => "Die Anmeldung wurde nicht abgeschlossen. Bitte versuche es erneut.",
+        (NotFound, En) => "We couldn't find that.",
+        (NotFound, Es) => "No pudimos encontrarlo.",
+        (NotFound, De) => "Das konnten wir nicht finden.",
+        (RateLimited, En) => "Too many requests. Please wait a moment and try again.",
+        (RateLimited, Es) => "Demasiadas solicitudes. Espera un momento e inténtalo de nuevo.",
+        (RateLimited, De) => "Zu viele Anfragen. Bitte warte kurz und versuche es erneut.",
+        (Server, En) => "Something went wrong on our end. Please try again later.",
+        (Server, Es) => "Algo salió mal en nuestro lado. Inténtalo más tarde.",
+        (Server, De) => "Bei uns ist etwas schiefgelaufen. Bitte versuche es später erneut.",
+        (RequestRejected, En) => "The request couldn't be completed.",
+        (RequestRejected, Es) => "No se pudo completar la solicitud.",
+        (RequestRejected, De) => "Die Anfrage konnte nicht abgeschlossen werden.",
+        (SchemaMismatch, En) => "We received an unexpected response. Please update the app.",
+        (SchemaMismatch, Es) => "Recibimos una respuesta inesperada. Actualiza la aplicación.",
+        (SchemaMismatch, De) => {
+            "Wir haben eine unerwartete Antwort erhalten. Bitte aktualisiere die App."
+        }
+        (ClientOutdated, En) => "This version of Good Hang is out of date. Please update.",
+        (ClientOutdated, Es) => "Esta versión de Good Hang está desactualizada. Actualízala.",
+        (ClientOutdated, De) => "Diese Version von Good Hang ist veraltet. Bitte aktualisiere sie.",
+        (Storage, En) => "We couldn't save your data on this device.",
+        (Storage, Es) => "No pudimos guardar tus datos en este dispositivo.",
+        (Storage, De) => "Deine Daten konnten auf diesem Gerät nicht gespeichert werden.",
+        (InvalidInput, En) => "Please check what you entered and try again.",
+        (InvalidInput, Es) => "Revisa lo que escribiste e inténtalo de nuevo.",
+        (InvalidInput, De) => "Bitte prüfe deine Eingabe und versuche es erneut.",
+        (ProductNotEnabled, En) => "This feature isn't enabled for your account yet.",
+        (ProductNotEnabled, Es) => "Esta función aún no está activada para tu cuenta.",
+        (ProductNotEnabled, De) => "Diese Funktion ist für dein Konto noch nicht freigeschaltet.",
+        (ConsentRequired, En) => "This needs your permission first. You can allow it in Privacy settings.",
+        (ConsentRequired, Es) => "Esto necesita tu permiso. Puedes permitirlo en los ajustes de privacidad.",
+        (ConsentRequired, De) => "Dafür brauchen wir deine Erlaubnis. Du kannst sie in den Datenschutzeinstellungen erteilen.",
+        (DataLocked, En) => "Your data is locked. Enter your passphrase to open it.",
+        (DataLocked, Es) => "Tus datos están bloqueados. Introduce tu frase de contraseña para abrirlos.",
+        (DataLocked, De) => "Deine Daten sind gesperrt. Gib deine Passphrase ein, um sie zu öffnen.",
+        (Unknown, En) => "Something went wrong. Please try again.",
+        (Unknown, Es) => "Algo salió mal. Inténtalo de nuevo.",
+        (Unknown, De) => "Etwas ist schiefgelaufen. Bitte versuche es erneut.",
+    }
+}
+
+/// Best-effort code for an internal error string, based on the prefixes the
+/// command modules already use
+pub fn classify(detail: &str) -> ErrorCode {
+    if let Some(rest) = detail.strip_prefix("Server error ") {
+        return match rest.get(..3) {
+            Some("401") => ErrorCode::Unauthorized,
+            Some("403") => ErrorCode::Unauthorized,
+            Some("404") => ErrorCode::NotFound,
+            Some("426") => ErrorCode::ClientOutdated,
+            Some("429") => ErrorCode::RateLimited,
+            Some(status) if status.starts_with('5') => ErrorCode::Server,
+            _ => ErrorCode::RequestRejected,
+        };
+    }
+
+    let lower = detail.to_lowercase();
+    if detail.starts_with("Network error") {
+        if !network::is_online() {
+            ErrorCode::NetworkOffline
+        } else if lower.contains("timed out") {
+            ErrorCode::Timeout
+        } else {
+            ErrorCode::Network
+        }
+    } else if detail.starts_with("Schema mismatch") {
+        ErrorCode::SchemaMismatch
+    } else if detail.starts_with("Not signed in") {
+        ErrorCode::NotSignedIn
+    } else if lower.contains("revoked") {
+        ErrorCode::DeviceRevoked
+    } else if lower.contains("timed out") {
+        ErrorCode::Timeout
+    } else if detail.starts_with("Login") {
+        ErrorCode::LoginFailed
+    } else if detail.starts_with("Database error")
+        || detail.contains("store:")
+        || detail.starts_with("Failed to write")
+    {
+        ErrorCode::Storage
+    } else if detail.starts_with("Consent required") {
+        ErrorCode::ConsentRequired
+    } else if detail.starts_with("Portable data is locked") {
+        ErrorCode::DataLocked
+    } else if detail.starts_with("Invalid") || detail.starts_with("Please enter") {
+        ErrorCode::InvalidInput
+    } else {
+        ErrorCode::Unknown
+    }
+}
+
+/// Command error carrying a stable code, a message in the user's locale, and
+/// the untranslated technical detail for logs and bug reports
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub detail: String,
+    /// Product the failed command needs, for `E_PRODUCT_NOT_ENABLED`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, detail: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message(code, locale()).to_string(),
+            detail: detail.into(),
+            product: None,
+        }
+    }
+
+    pub fn product_not_enabled(product: &str) -> Self {
+        Self {
+            product: Some(product.to_string()),
+            ..AppError::new(
+                ErrorCode::ProductNotEnabled,
+                format!("Product not enabled: {}", product),
+            )
+        }
+    }
+
+    pub fn consent_required(feature: &str) -> Self {
+        AppError::new(
+            ErrorCode::ConsentRequired,
+            format!("Consent required: {}", feature),
+        )
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.detail)
+    }
+}
+
+impl From<String> for AppError {
+    fn from(detail: String) -> Self {
+        AppError::new(classify(&detail), detail)
+    }
+}
+
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.detail
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::{self, Visitor};
+
+    /// Captures the variant names serde derived for an enum
+    struct VariantNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> de::Deserializer<'de> for VariantNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not an enum"))
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            variants: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = variants;
+            Err(de::Error::custom("variants captured"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+            byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct identifier ignored_any
+        }
+    }
+
+    fn name(code: ErrorCode) -> String {
+        serde_json::to_value(code)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap()
+    }
+
+    #[test]
+    fn all_codes_lists_every_variant_once() {
+        let mut variants: &'static [&'static str] = &[];
+        let _ = ErrorCode::deserialize(VariantNames(&mut variants));
+        assert!(!variants.is_empty());
+
+        let listed: Vec<String> = ALL_CODES.iter().map(|code| name(*code)).collect();
+        assert_eq!(listed, variants);
+    }
+
+    #[test]
+    fn every_code_is_translated() {
+        for code in ALL_CODES {
+            let en = message(*code, Locale::En);
+            assert!(!en.trim().is_empty(), "{:?}", code);
+            for locale in [Locale::Es, Locale::De] {
+                let translated = message(*code, locale);
+                assert!(!translated.trim().is_empty(), "{:?} {:?}", code, locale);
+                assert_ne!(translated, en, "{:?} {:?} is untranslated", code, locale);
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::browser_bridge::ConsentRequestedEvent;
+use crate::commands::account::DeletionStatus;
+use crate::commands::appearance::Appearance;
+use crate::commands::auth::SessionInfo;
+use crate::commands::billing::SubscriptionStatus;
+use crate::commands::chat::ChatTokenEvent;
+use crate::commands::consent::Consent;
+use crate::commands::data_export::ExportProgress;
+use crate::commands::data_location::DataMoveProgress;
+use crate::commands::diagnostics::LogLinesEvent;
+use crate::commands::downloads::Download;
+use crate::commands::email_signals::EmailScanSummary;
+use crate::commands::focus::{FocusEndedEvent, FocusSession};
+use crate::commands::hangs::{Invite, InviteRespondedEvent};
+use crate::commands::login::MagicLinkFailedEvent;
+use crate::commands::matching::MatchFoundEvent;
+use crate::commands::profile::Profile;
+use crate::commands::quick_capture::CaptureResult;
+use crate::commands::reactions::ReactionReceivedEvent;
+use crate::commands::reminders::Reminder;
+use crate::commands::retention::CleanupReport;
+use crate::commands::signal_weights::BriefsRescoredEvent;
+use crate::commands::slack::{SlackConnection, SlackPresence};
+use crate::commands::sync_policy::SyncConditions;
+use crate::commands::uploads::UploadProgressEvent;
+use crate::commands::user_status::{
+    AssessmentCompletedEvent, ProductEnabledEvent, RecommendedActionChangedEvent, UserStatus,
+};
+use crate::commands::voice_memos::MemoSyncEvent;
+use crate::errors::AppError;
+use crate::file_drop::FileImportResult;
+use crate::logging::app_log;
+use crate::network::{ApiCompatibility, Connectivity, NetworkQuality};
+use crate::outbox::WriteRevertedEvent;
+use crate::session::SessionExpiryEvent;
+use crate::store_integrity::StoreRecoveredEvent;
+
+/// Every event the backend sends to the webview. The kebab-case variant
+/// name is the event name the frontend listens for, and the variant's data
+/// is its payload.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(tag = "event", content = "payload", rename_all = "kebab-case")]
+pub enum AppEvent {
+    // Connectivity and session
+    BackOnline(Connectivity),
+    WentOffline(Connectivity),
+    NetworkQualityChanged(NetworkQuality),
+    SyncConditionsChanged(SyncConditions),
+    ClientOutdated(ApiCompatibility),
+    SessionExpiring(SessionExpiryEvent),
+    SessionRefreshed,
+    SessionExpired(SessionExpiryEvent),
+    MagicLinkLogin(SessionInfo),
+    MagicLinkFailed(MagicLinkFailedEvent),
+    DeviceRevoked,
+    LoggedOut,
+    AppLocked,
+    AppUnlocked,
+
+    // Account and settings
+    UserStatusUpdated(UserStatus),
+    ProductEnabled(ProductEnabledEvent),
+    AssessmentCompleted(AssessmentCompletedEvent),
+    RecommendedActionChanged(RecommendedActionChangedEvent),
+    ProfileUpdated(Profile),
+    SubscriptionChanged(SubscriptionStatus),
+    ConsentChanged(Consent),
+    AppearanceChanged(Appearance),
+    AccountDeletionScheduled(DeletionStatus),
+    StorageCleaned(CleanupReport),
+    DataExportProgress(ExportProgress),
+    DataMoveProgress(DataMoveProgress),
+    StoreRecovered(StoreRecoveredEvent),
+
+    // Links and imports
+    ActivationCode(String),
+    ReferralOpened(String),
+    FileImportResult(FileImportResult),
+    BrowserBridgeConsentRequested(ConsentRequestedEvent),
+
+    // Hangs and people
+    InviteOpened(Invite),
+    InviteOpenFailed(AppError),
+    InviteResponded(InviteRespondedEvent),
+    MatchFound(MatchFoundEvent),
+    MatchAccepted(String),
+    MatchAcceptFailed(AppError),
+    ReactionReceived(ReactionReceivedEvent),
+    BriefsRescored(BriefsRescoredEvent),
+    EmailSignalsUpdated(EmailScanSummary),
+
+    // Integrations
+    SlackConnected(SlackConnection),
+    SlackDisconnected,
+    SlackPresenceChanged(SlackPresence),
+
+    // Productivity
+    FocusStarted(FocusSession),
+    FocusEnded(FocusEndedEvent),
+    ReminderDue(Reminder),
+    QuickCaptureSaved(CaptureResult),
+
+    // Background work
+    ChatToken(ChatTokenEvent),
+    LocalLlmToken(ChatTokenEvent),
+    DownloadProgress(Download),
+    UploadProgress(UploadProgressEvent),
+    VoiceMemoSync(MemoSyncEvent),
+    BookmarksSynced,
+    TaskDeadLettered(String),
+    WriteReverted(WriteRevertedEvent),
+    LogLines(LogLinesEvent),
+}
+
+/// Send `event` to every window. Delivery is best-effort, like the raw
+/// `emit` it wraps: a failure is logged, never returned.
+pub fn emit_app_event(app: &tauri::AppHandle, event: AppEvent) {
+    // Logging a failure to deliver log lines would feed the tail that sent
+    // them, so those go unlogged
+    let quiet = matches!(event, AppEvent::LogLines(_));
+    let tagged = match serde_json::to_value(&event) {
+        Ok(tagged) => tagged,
+        Err(e) => {
+            if !quiet {
+                app_log!("[Events] Failed to serialize event: {}", e);
+            }
+            return;
+        }
+    };
+    let name = tagged["event"].as_str().unwrap_or_default();
+    if let Err(e) = app.emit(name, &tagged["payload"]) {
+        if !quiet {
+            app_log!("[Events] Failed to emit {}: {}", name, e);
+        }
+    }
+}
+
+/// Write `AppEvent` and its payload types for the frontend, so listeners
+/// are typed from the same definitions that emit them
+#[cfg(test)]
+pub fn export_bindings(
+    typescript: &specta_typescript::Typescript,
+    path: &std::path::Path,
+) -> Result<(), specta_typescript::ExportError> {
+    let mut types = specta::TypeCollection::default();
+    types.register::<AppEvent>();
+    typescript.export_to(path, &types)
+}
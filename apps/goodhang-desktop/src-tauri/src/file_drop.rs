@@ -0,0 +1,186 @@
+//! Files dropped onto the main window, routed by type: documents become
+//! VoiceOS context uploads, audio joins the voice memo library, and images
+//! are scanned for an activation QR code. Each file gets a
+//! `file-import-result` event.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{Manager, Url};
+
+use crate::capabilities::{self, Product};
+use crate::commands::security::AppLock;
+use crate::commands::{uploads, voice_memos};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+
+// Larger images are unlikely to be a QR code screenshot and slow to scan
+const MAX_QR_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportKind {
+    Document,
+    Audio,
+    Activation,
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FileImportResult {
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub kind: ImportKind,
+    pub success: bool,
+    /// Upload result id for documents, memo id for audio
+    #[serde(rename = "resultId", skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Map a file extension to where it goes and the mime type to send
+fn classify(path: &Path) -> (ImportKind, &'static str) {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "pdf" => (ImportKind::Document, "application/pdf"),
+        "docx" => (
+            ImportKind::Document,
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        ),
+        "md" | "markdown" => (ImportKind::Document, "text/markdown"),
+        "txt" => (ImportKind::Document, "text/plain"),
+        "webm" => (ImportKind::Audio, "audio/webm"),
+        "ogg" => (ImportKind::Audio, "audio/ogg"),
+        "mp3" => (ImportKind::Audio, "audio/mpeg"),
+        "m4a" => (ImportKind::Audio, "audio/mp4"),
+        "wav" => (ImportKind::Audio, "audio/wav"),
+        "png" => (ImportKind::Activation, "image/png"),
+        "jpg" | "jpeg" => (ImportKind::Activation, "image/jpeg"),
+        "webp" => (ImportKind::Activation, "image/webp"),
+        _ => (ImportKind::Unsupported, "application/octet-stream"),
+    }
+}
+
+/// Activation code from a QR payload: `goodhang://activate/CODE`, an
+/// `https://.../activate/CODE` link, or the bare code
+fn activation_code(payload: &str) -> Option<String> {
+    let payload = payload.trim();
+    let code = match Url::parse(payload) {
+        Ok(url) => {
+            let mut parts = url
+                .host_str()
+                .into_iter()
+                .chain(url.path().split('/'))
+                .filter(|s| !s.is_empty());
+            parts.find(|s| *s == "activate")?;
+            parts.next()?.to_string()
+        }
+        Err(_) => payload.to_string(),
+    };
+    let pattern = regex::Regex::new(r"^[A-Za-z0-9]{4}(-[A-Za-z0-9]{4})+$").ok()?;
+    pattern.is_match(&code).then(|| code.to_uppercase())
+}
+
+/// Decode every QR code in an image, returning the first activation code
+fn scan_activation_qr(path: &Path) -> Result<String, String> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .len();
+    if size > MAX_QR_IMAGE_BYTES {
+        return Err("Image is too large to scan".to_string());
+    }
+
+    let luma = image::open(path)
+        .map_err(|e| format!("Invalid image: {}", e))?
+        .into_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        luma.width() as usize,
+        luma.height() as usize,
+        |x, y| luma.get_pixel(x as u32, y as u32).0[0],
+    );
+    prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok())
+        .find_map(|(_, content)| activation_code(&content))
+        .ok_or_else(|| "No activation QR code found in image".to_string())
+}
+
+async fn import_file(app: &tauri::AppHandle, path: &Path) -> FileImportResult {
+    let (kind, mime_type) = classify(path);
+    let outcome: Result<Option<String>, String> = match kind {
+        ImportKind::Document => match capabilities::require(Product::VoiceOs) {
+            Ok(()) => uploads::upload_and_wait(
+                app,
+                path.to_string_lossy().into_owned(),
+                "context_file",
+                mime_type,
+            )
+            .await
+            .map(|upload| upload.result_id),
+            Err(e) => Err(e.message),
+        },
+        ImportKind::Audio => match capabilities::require(Product::VoiceOs) {
+            Ok(()) => voice_memos::import_audio_file(app, path, mime_type)
+                .await
+                .map(|memo| Some(memo.id)),
+            Err(e) => Err(e.message),
+        },
+        ImportKind::Activation => {
+            let scan_path = path.to_path_buf();
+            tauri::async_runtime::spawn_blocking(move || scan_activation_qr(&scan_path))
+                .await
+                .map_err(|e| format!("QR scan task failed: {}", e))
+                .and_then(|result| result)
+                .map(|code| {
+                    // Same event as an activation deep link
+                    events::emit_app_event(app, AppEvent::ActivationCode(code));
+                    None
+                })
+        }
+        ImportKind::Unsupported => Err("Unsupported file type".to_string()),
+    };
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match outcome {
+        Ok(result_id) => FileImportResult {
+            file_name,
+            kind,
+            success: true,
+            result_id,
+            error: None,
+        },
+        Err(error) => {
+            app_log!("[FileDrop] Import of {} failed: {}", file_name, error);
+            FileImportResult {
+                file_name,
+                kind,
+                success: false,
+                result_id: None,
+                error: Some(error),
+            }
+        }
+    }
+}
+
+/// Import dropped files one at a time, in the order they were dropped
+pub fn handle_drop(app: &tauri::AppHandle, paths: Vec<PathBuf>) {
+    if app.state::<AppLock>().is_locked() {
+        app_log!("[FileDrop] Ignoring drop while locked");
+        return;
+    }
+
+    app_log!("[FileDrop] {} file(s) dropped", paths.len());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for path in paths.iter().filter(|p| p.is_file()) {
+            let result = import_file(&app, path).await;
+            events::emit_app_event(&app, AppEvent::FileImportResult(result));
+        }
+    });
+}
@@ -0,0 +1,428 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+use crate::api::{Api, ApiBody, ApiRequest};
+use crate::db::now_secs;
+use crate::logging::{self, app_log, redact};
+use crate::network;
+
+pub const API_VERSION_HEADER: &str = "X-API-Version";
+const CLIENT_VERSION_HEADER: &str = "X-Client-Version";
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const QUARANTINE_DIR: &str = "quarantine";
+const MAX_QUARANTINE_FILES: usize = 20;
+const EXCERPT_RADIUS: usize = 80;
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 200;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// Keep idle connections warm between bursts of commands
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const POOL_MAX_IDLE_PER_HOST: usize = 8;
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+// Default page size on a poor connection, so each request stays small
+const LOW_BANDWIDTH_PAGE_SIZE: u32 = 20;
+
+/// A fully read response that can be handed to several callers
+#[derive(Debug, Clone)]
+pub struct BufferedResponse {
+    pub status: reqwest::StatusCode,
+    pub headers: reqwest::header::HeaderMap,
+    pub body: Arc<Vec<u8>>,
+}
+
+impl BufferedResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: serde::de::DeserializeOwned>(&self, endpoint: &str) -> Result<T, String> {
+        decode(&self.body, endpoint, self.header(API_VERSION_HEADER))
+    }
+
+    /// Turn a non-2xx status into the usual `Server error` message
+    pub fn error_for_status(self) -> Result<Self, String> {
+        if self.status.is_success() {
+            return Ok(self);
+        }
+        Err(format!(
+            "Server error {}: {}",
+            self.status,
+            redact(&self.text())
+        ))
+    }
+}
+
+/// A response body that didn't match the shape we expected
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SchemaMismatch {
+    pub endpoint: String,
+    /// JSON path of the offending field, e.g. `products.goodhang.enabled`
+    pub path: String,
+    pub message: String,
+    #[serde(rename = "apiVersion")]
+    pub api_version: Option<String>,
+    #[serde(rename = "quarantineFile")]
+    pub quarantine_file: Option<String>,
+}
+
+impl std::fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Schema mismatch from {} at `{}`: {} (API version {})",
+            self.endpoint,
+            self.path,
+            self.message,
+            self.api_version.as_deref().unwrap_or("unknown")
+        )
+    }
+}
+
+impl From<SchemaMismatch> for String {
+    fn from(mismatch: SchemaMismatch) -> Self {
+        mismatch.to_string()
+    }
+}
+
+fn quarantine_dir() -> Option<PathBuf> {
+    Some(logging::log_path()?.parent()?.join(QUARANTINE_DIR))
+}
+
+/// Up to `EXCERPT_RADIUS` bytes either side of where parsing stopped,
+/// redacted. `line` and `column` are serde_json's, both one-based.
+fn excerpt(body: &[u8], line: usize, column: usize) -> String {
+    let line_start: usize = body
+        .split(|b| *b == b'\n')
+        .take(line.saturating_sub(1))
+        .map(|l| l.len() + 1)
+        .sum();
+    let at = (line_start + column.saturating_sub(1)).min(body.len());
+    let start = at.saturating_sub(EXCERPT_RADIUS);
+    let end = (at + EXCERPT_RADIUS).min(body.len());
+    redact(&String::from_utf8_lossy(&body[start..end]))
+}
+
+/// Note a body that failed to parse next to the logs, so it can be attached
+/// to a bug report. Only the failing path and an excerpt around it are kept,
+/// never the whole body. Oldest files are pruned.
+fn quarantine(mismatch: &SchemaMismatch, excerpt: &str) -> Option<PathBuf> {
+    let dir = quarantine_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+
+    let slug: String = mismatch
+        .endpoint
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{}-{}.json", now_secs(), slug.trim_matches('_')));
+    let note = serde_json::json!({
+        "endpoint": mismatch.endpoint,
+        "path": mismatch.path,
+        "message": redact(&mismatch.message),
+        "apiVersion": mismatch.api_version,
+        "excerpt": excerpt,
+    });
+    std::fs::write(&path, serde_json::to_vec_pretty(&note).ok()?).ok()?;
+
+    if let Ok(entries) = std::fs::read_dir(&dir) {
+        let mut files: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        files.sort();
+        let excess = files.len().saturating_sub(MAX_QUARANTINE_FILES);
+        for old in &files[..excess] {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+    Some(path)
+}
+
+/// Delete every quarantined response, e.g. on secure logout
+pub fn clear_quarantine() -> Result<(), String> {
+    match quarantine_dir().map(std::fs::remove_dir_all) {
+        Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to remove quarantined responses: {}", e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Deserialize a response body, reporting where the shape diverged instead
+/// of serde's bare message
+pub fn decode<T: serde::de::DeserializeOwned>(
+    body: &[u8],
+    endpoint: &str,
+    api_version: Option<&str>,
+) -> Result<T, String> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+        let mut mismatch = SchemaMismatch {
+            endpoint: endpoint.to_string(),
+            path,
+            message: inner.to_string(),
+            api_version: api_version.map(str::to_string),
+            quarantine_file: None,
+        };
+        let excerpt = excerpt(body, inner.line(), inner.column());
+        mismatch.quarantine_file = quarantine(&mismatch, &excerpt).map(|p| p.display().to_string());
+        app_log!(
+            "[HTTP] {} (raw body: {})",
+            mismatch,
+            mismatch.quarantine_file.as_deref().unwrap_or("not saved")
+        );
+        mismatch.into()
+    })
+}
+
+/// Read and deserialize a response, with schema diagnostics on failure
+pub async fn parse_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    endpoint: &str,
+) -> Result<T, String> {
+    let api_version = response
+        .headers()
+        .get(API_VERSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    decode(&body, endpoint, api_version.as_deref())
+}
+
+type InFlight = Arc<OnceCell<Result<BufferedResponse, String>>>;
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        CLIENT_VERSION_HEADER,
+        reqwest::header::HeaderValue::from_static(CLIENT_VERSION),
+    );
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .gzip(true)
+        .brotli(true)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .tcp_keepalive(KEEP_ALIVE_INTERVAL)
+        // HTTP/2 is negotiated over ALPN where the server offers it
+        .http2_adaptive_window(true)
+        .http2_keep_alive_interval(KEEP_ALIVE_INTERVAL)
+        .http2_keep_alive_while_idle(true)
+        .build()
+        .unwrap_or_default()
+});
+static IN_FLIGHT: LazyLock<Mutex<HashMap<String, InFlight>>> = LazyLock::new(Default::default);
+
+/// Shared client so connections are pooled across commands, with gzip and
+/// brotli responses and HTTP/2 where available. Every request carries
+/// `X-Client-Version` so the API can tell which build is calling.
+pub fn client() -> &'static reqwest::Client {
+    &CLIENT
+}
+
+/// Identity of a request for coalescing. Credentials are hashed so tokens
+/// aren't held as map keys.
+fn request_key(method: &reqwest::Method, url: &str, headers: &[(&str, String)]) -> String {
+    let mut hasher = Sha256::new();
+    for (name, value) in headers {
+        hasher.update(name.to_ascii_lowercase().as_bytes());
+        hasher.update(b":");
+        hasher.update(value.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!(
+        "{} {} {}",
+        method.as_str(),
+        url,
+        hex::encode(hasher.finalize())
+    )
+}
+
+/// Send a request, with an optional body, and return once the response
+/// headers arrive
+pub async fn open(
+    method: reqwest::Method,
+    url: &str,
+    headers: &[(&str, String)],
+    body: Option<&ApiBody>,
+) -> Result<reqwest::Response, String> {
+    let mut request = client().request(method, url);
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+    request = match body {
+        Some(ApiBody::Json(value)) => request.json(value),
+        Some(ApiBody::Bytes(bytes)) => request.body(bytes.clone()),
+        Some(ApiBody::Multipart(parts)) => request.multipart(ApiBody::multipart_form(parts)?),
+        None => request,
+    };
+
+    let started = Instant::now();
+    let response = request.send().await;
+    network::record_request(
+        started.elapsed(),
+        response
+            .as_ref()
+            .is_ok_and(|response| !response.status().is_server_error()),
+    );
+    response.map_err(|e| format!("Network error: {}", e))
+}
+
+/// Send a request and read the whole response, with an optional body
+pub async fn send(
+    method: reqwest::Method,
+    url: &str,
+    headers: &[(&str, String)],
+    body: Option<&ApiBody>,
+) -> Result<BufferedResponse, String> {
+    let response = open(method, url, headers, body).await?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+
+    Ok(BufferedResponse {
+        status,
+        headers,
+        body: Arc::new(body.to_vec()),
+    })
+}
+
+/// Send a body-less request, sharing one network round trip between
+/// concurrent identical calls (same method, URL, auth and headers). Only use
+/// this for idempotent reads; the response is not cached once it completes.
+pub async fn send_coalesced(
+    method: reqwest::Method,
+    url: &str,
+    headers: &[(&str, String)],
+) -> Result<BufferedResponse, String> {
+    let key = request_key(&method, url, headers);
+    let cell = IN_FLIGHT
+        .lock()
+        .map_err(|_| "Request table lock poisoned".to_string())?
+        .entry(key.clone())
+        .or_default()
+        .clone();
+
+    // If the caller driving the request is dropped, a waiting caller takes over
+    let result = cell
+        .get_or_init(|| send(method, url, headers, None))
+        .await
+        .clone();
+
+    // Later calls start a fresh request rather than reusing this response
+    if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+        if in_flight.get(&key).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+            in_flight.remove(&key);
+        }
+    }
+    result
+}
+
+/// Splits a byte stream into SSE `data:` payloads, buffering partial lines
+/// so multi-byte characters split across chunks decode correctly
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut payloads = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            if let Some(data) = Self::data_line(&line) {
+                payloads.push(data);
+            }
+        }
+        payloads
+    }
+
+    pub fn finish(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.buffer);
+        Self::data_line(&line)
+    }
+
+    fn data_line(line: &[u8]) -> Option<String> {
+        let line = String::from_utf8_lossy(line);
+        let trimmed = line.trim();
+        trimmed
+            .strip_prefix("data:")
+            .map(|data| data.trim_start().to_string())
+            .filter(|data| !data.is_empty())
+    }
+}
+
+/// One page of a cursor-paginated listing. Pass `next_cursor` back to get the
+/// following page; it is `None` once `has_more` is false.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    #[serde(default, rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+    #[serde(default, rename = "hasMore")]
+    pub has_more: bool,
+}
+
+/// The caller's limit, or a default that shrinks on a poor connection
+pub fn page_size(limit: Option<u32>) -> u32 {
+    let default = if network::low_bandwidth() {
+        LOW_BANDWIDTH_PAGE_SIZE
+    } else {
+        DEFAULT_PAGE_SIZE
+    };
+    limit.unwrap_or(default).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// GET one page of a paginated API listing. `request` carries the path, auth
+/// and any filters; the paging parameters are added here.
+pub async fn fetch_page<T: serde::de::DeserializeOwned>(
+    api: &Api,
+    request: ApiRequest,
+    cursor: Option<&str>,
+    limit: Option<u32>,
+) -> Result<Page<T>, String> {
+    let endpoint = request.path.clone();
+    let mut request = request.query("limit", page_size(limit));
+    if let Some(cursor) = cursor {
+        request = request.query("cursor", cursor);
+    }
+
+    api.send(request).await?.error_for_status()?.json(&endpoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excerpt_is_centred_on_the_failure() {
+        let body = format!("{{\"padding\":\"{}\",\n\"id\":tru}}", "x".repeat(500));
+        let error = serde_json::from_str::<serde_json::Value>(&body).unwrap_err();
+        let excerpt = excerpt(body.as_bytes(), error.line(), error.column());
+        assert!(excerpt.ends_with("\"id\":tru}"));
+        assert!(excerpt.len() < EXCERPT_RADIUS + 20);
+    }
+
+    #[test]
+    fn excerpt_handles_positions_past_the_end() {
+        assert_eq!(excerpt(b"{}", 9, 9), "{}");
+        assert_eq!(excerpt(b"", 0, 0), "");
+    }
+}
@@ -0,0 +1,192 @@
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::Manager;
+
+use crate::commands::listings;
+use crate::http;
+use crate::logging::app_log;
+use crate::network;
+use crate::paths;
+use crate::user_data;
+
+pub(crate) const IMAGE_DIR: &str = "images";
+const MAX_CACHE_BYTES: u64 = 100 * 1024 * 1024;
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+const KNOWN_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "avif"];
+// How many relationships/badges to warm on launch
+const PREFETCH_PAGE_SIZE: u32 = 100;
+
+pub(crate) fn cache_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = user_data::cache_dir(app)?.join(IMAGE_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Let the webview load the active account's cache through the asset
+/// protocol. It moves with the account, the chosen data folder and portable
+/// mode, so it can't be a fixed scope in the config.
+pub(crate) fn allow_asset_access(app: &tauri::AppHandle) {
+    let allowed = cache_dir(app).and_then(|dir| {
+        app.asset_protocol_scope()
+            .allow_directory(dir, true)
+            .map_err(|e| format!("Failed to allow image cache: {}", e))
+    });
+    if let Err(e) = allowed {
+        app_log!("[ImageCache] {}", e);
+    }
+}
+
+/// Cache file for a URL: hashed so any URL maps to a safe file name, keeping
+/// the extension so the webview can infer the content type
+fn cached_path(dir: &Path, url: &reqwest::Url) -> PathBuf {
+    let ext = Path::new(url.path())
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .filter(|e| KNOWN_EXTENSIONS.contains(&e.as_str()))
+        .unwrap_or_else(|| "img".to_string());
+    let hash = hex::encode(Sha256::digest(url.as_str().as_bytes()));
+    dir.join(format!("{}.{}", hash, ext))
+}
+
+/// Mark a cache entry as recently used; eviction goes by modification time
+fn touch(path: &Path) {
+    if let Ok(file) = std::fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Delete least recently used images until the cache fits its budget
+fn evict(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(PathBuf, u64, SystemTime)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file().then(|| {
+                (
+                    entry.path(),
+                    meta.len(),
+                    meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                )
+            })
+        })
+        .collect();
+
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    if total <= MAX_CACHE_BYTES {
+        return;
+    }
+
+    files.sort_by_key(|(_, _, modified)| *modified);
+    let mut evicted = 0;
+    for (path, len, _) in files {
+        if total <= MAX_CACHE_BYTES {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= len;
+            evicted += 1;
+        }
+    }
+    app_log!("[ImageCache] Evicted {} image(s)", evicted);
+}
+
+/// Local path of the cached image for `url`, downloading it on a miss
+pub async fn get_or_fetch(app: &tauri::AppHandle, url: &str) -> Result<PathBuf, String> {
+    let url = reqwest::Url::parse(url).map_err(|e| format!("Invalid image URL: {}", e))?;
+    if url.scheme() != "https" {
+        return Err("Only https images can be cached".to_string());
+    }
+
+    let dir = cache_dir(app)?;
+    let path = cached_path(&dir, &url);
+    if path.exists() {
+        touch(&path);
+        return Ok(path);
+    }
+
+    let response = http::client()
+        .get(url.as_str())
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Server error {}", response.status()));
+    }
+    let is_image = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("image/"));
+    if !is_image {
+        return Err("URL did not return an image".to_string());
+    }
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err("Image is too large to cache".to_string());
+    }
+
+    // Write then rename so a concurrent reader never sees a partial image
+    let tmp = dir.join(format!("{}.tmp", uuid::Uuid::new_v4()));
+    let _writes = paths::hold_writes()?;
+    std::fs::write(&tmp, &bytes).map_err(|e| format!("Failed to write image: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write image: {}", e))?;
+
+    evict(&dir);
+    Ok(path)
+}
+
+/// Warm the cache for a batch of URLs, ignoring individual failures
+pub async fn prefetch(app: &tauri::AppHandle, urls: Vec<String>) -> usize {
+    let mut cached = 0;
+    for url in urls {
+        match get_or_fetch(app, &url).await {
+            Ok(_) => cached += 1,
+            Err(e) => app_log!("[ImageCache] Prefetch skipped: {}", e),
+        }
+    }
+    cached
+}
+
+/// Prefetch relationship avatars and badge icons so lists render from disk
+pub fn spawn_prefetch(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // Images still load on demand; only the warm-up waits for a better link
+        if network::low_bandwidth() {
+            app_log!("[ImageCache] Prefetch deferred on a poor connection");
+            return;
+        }
+        let mut urls = Vec::new();
+        if let Ok(page) =
+            listings::list_relationships(app.clone(), None, Some(PREFETCH_PAGE_SIZE)).await
+        {
+            urls.extend(page.items.into_iter().filter_map(|r| r.avatar_url));
+        }
+        if let Ok(page) = listings::list_badges(app.clone(), None, Some(PREFETCH_PAGE_SIZE)).await {
+            urls.extend(page.items.into_iter().filter_map(|b| b.icon));
+        }
+
+        // Badge icons may be emoji or icon names rather than URLs
+        urls.retain(|u| u.starts_with("https://"));
+        urls.sort();
+        urls.dedup();
+
+        let cached = prefetch(&app, urls).await;
+        app_log!("[ImageCache] Prefetched {} image(s)", cached);
+    });
+}
+
+/// Drop every cached image, e.g. on logout
+pub fn clear(app: &tauri::AppHandle) -> Result<(), String> {
+    let dir = cache_dir(app)?;
+    std::fs::remove_dir_all(&dir).map_err(|e| format!("Failed to clear image cache: {}", e))
+}
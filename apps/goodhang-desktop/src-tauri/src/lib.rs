@@ -1,64 +1,368 @@
+mod api;
+mod audit;
+mod browser_bridge;
+mod capabilities;
 mod commands;
+mod consent;
+mod crypto;
+mod db;
+mod deep_link;
+mod documents;
+mod embeddings;
+mod errors;
+mod events;
+mod file_drop;
+mod http;
+mod image_cache;
+mod logging;
+mod migrations;
+mod network;
+mod notifications;
+mod outbox;
+mod palette;
+mod paths;
+mod realtime;
+mod scoring;
+mod search_index;
+mod session;
+mod startup;
+mod store_integrity;
+mod stores;
+mod summary_cache;
+mod user_data;
 
-use tauri::{Emitter, Manager};
-use tauri_plugin_deep_link::DeepLinkExt;
+use logging::app_log;
+use tauri::Manager;
+use tauri_specta::collect_commands;
+
+pub use browser_bridge::{
+    host_origin as native_messaging_origin, run_host as run_native_messaging_host,
+};
+
+/// Every command the webview can invoke, with the types tauri-specta
+/// exports for them
+fn specta_builder() -> tauri_specta::Builder<tauri::Wry> {
+    tauri_specta::Builder::<tauri::Wry>::new().commands(collect_commands![
+        commands::account::request_account_deletion,
+        commands::account::confirm_account_deletion,
+        commands::account::get_account_deletion_status,
+        commands::account::cancel_account_deletion,
+        commands::activation::validate_activation_key,
+        commands::activation::claim_activation_key,
+        commands::activation::fetch_assessment_results,
+        commands::analytics::get_attribute_chart_data,
+        commands::analytics::get_question_analytics,
+        commands::analytics::get_assessment_history,
+        commands::analytics::get_cohort_comparison,
+        commands::analytics::track_event,
+        commands::appearance::get_appearance,
+        commands::appearance::set_appearance,
+        commands::auth::store_session,
+        commands::auth::get_session,
+        commands::auth::clear_session,
+        commands::auth::store_device_registration,
+        commands::auth::get_device_registration,
+        commands::auth::clear_device_registration,
+        commands::auth::secure_logout,
+        commands::auth::get_audit_log,
+        commands::backup::create_backup,
+        commands::backup::restore_backup,
+        commands::data_location::get_data_directory,
+        commands::data_location::set_data_directory,
+        commands::data_location::get_portable_status,
+        commands::data_location::unlock_portable_data,
+        commands::batch::batch_fetch,
+        commands::billing::get_subscription_status,
+        commands::billing::open_billing_portal,
+        commands::bootstrap::bootstrap,
+        commands::browser_bridge::respond_to_bridge_consent,
+        commands::browser_bridge::list_browser_bridge_origins,
+        commands::browser_bridge::revoke_browser_bridge_origin,
+        commands::character_sheet::export_character_sheet,
+        commands::chat::stream_chat,
+        commands::chat::cancel_chat_stream,
+        commands::chat_history::save_chat_turn,
+        commands::chat_history::get_conversation,
+        commands::chat_history::list_conversations,
+        commands::chat_history::delete_conversation,
+        commands::chat_history::export_conversation,
+        commands::compatibility::compute_compatibility,
+        commands::consent::get_consents,
+        commands::consent::set_consent,
+        commands::context_brief::get_context_brief,
+        commands::data_export::export_my_data,
+        commands::devices::list_registered_devices,
+        commands::devices::list_registered_devices_page,
+        commands::devices::revoke_device,
+        commands::devices::logout_everywhere,
+        commands::diagnostics::export_diagnostics,
+        commands::diagnostics::tail_logs,
+        commands::diagnostics::stop_tail_logs,
+        commands::diagnostics::get_log_files,
+        commands::app_info::get_app_info,
+        commands::app_info::check_for_updates,
+        commands::feedback::capture_window_screenshot,
+        commands::feedback::submit_feedback,
+        commands::discord::get_discord_presence_settings,
+        commands::discord::update_discord_presence_settings,
+        commands::downloads::start_download,
+        commands::downloads::list_downloads,
+        commands::downloads::cancel_download,
+        commands::email_signals::get_email_signal_settings,
+        commands::email_signals::connect_email_signals,
+        commands::email_signals::disconnect_email_signals,
+        commands::email_signals::scan_email_signals,
+        commands::email_signals::get_last_contacts,
+        commands::expert_content::get_expert_content,
+        commands::expert_content::purchase_expert_content,
+        commands::focus::start_focus_session,
+        commands::focus::end_focus_session,
+        commands::focus::get_focus_session,
+        commands::gifts::create_gift_checkout,
+        commands::gifts::poll_gift_status,
+        commands::hangs::propose_hang,
+        commands::hangs::list_upcoming_hangs,
+        commands::hangs::cancel_hang,
+        commands::hangs::get_invite,
+        commands::hangs::respond_to_invite,
+        commands::images::get_cached_image,
+        commands::images::prefetch_images,
+        commands::journal::summarize_journal,
+        commands::library::browse_library,
+        commands::library::bookmark_item,
+        commands::library::remove_bookmark,
+        commands::library::list_bookmarks,
+        commands::listings::list_entities,
+        commands::listings::list_badges,
+        commands::listings::list_relationships,
+        commands::listings::list_context_files,
+        commands::local_llm::detect_local_llm,
+        commands::local_llm::local_generate,
+        commands::localization::get_locale,
+        commands::localization::set_locale,
+        commands::localization::get_error_catalog,
+        commands::location::get_location_settings,
+        commands::location::update_location_settings,
+        commands::location::set_current_location,
+        commands::location::get_nearby_suggestions,
+        commands::login::login_with_browser,
+        commands::login::request_magic_link,
+        commands::market::gather_market_context,
+        commands::market::set_brave_search_key,
+        commands::market::has_market_search_key,
+        commands::matching::get_matching_preferences,
+        commands::matching::update_matching_preferences,
+        commands::matching::accept_match,
+        commands::migrations::check_migrations,
+        commands::natural_date::parse_natural_date,
+        commands::network::get_connectivity,
+        commands::network::get_network_quality,
+        commands::network::check_api_compatibility,
+        commands::network::check_api_health,
+        commands::network::get_api_base_url,
+        commands::network::set_api_base_url,
+        commands::outbox::get_queue_status,
+        commands::palette::palette_search,
+        commands::palette::refresh_palette_index,
+        commands::profile::get_profile,
+        commands::profile::update_profile,
+        commands::profile::pick_profile_photo,
+        commands::profile::capture_profile_photo,
+        commands::prompts::list_prompt_templates,
+        commands::prompts::get_prompt_template,
+        commands::prompts::list_prompt_template_versions,
+        commands::prompts::save_prompt_template,
+        commands::prompts::delete_prompt_template,
+        commands::prompts::render_prompt,
+        commands::prompts::sync_prompt_templates,
+        commands::quick_capture::open_quick_capture,
+        commands::quick_capture::preview_quick_capture,
+        commands::quick_capture::quick_capture_clipboard,
+        commands::quick_capture::submit_quick_capture,
+        commands::quick_capture::get_quick_capture_settings,
+        commands::quick_capture::update_quick_capture_settings,
+        commands::reactions::send_reaction,
+        commands::reassessment::get_reassessment_status,
+        commands::referrals::generate_referral_link,
+        commands::referrals::get_referral_stats,
+        commands::relationship_brief::import_linkedin_profile,
+        commands::relationship_brief::get_relationship_brief,
+        commands::relationship_notes::add_relationship_note,
+        commands::relationship_notes::list_relationship_notes,
+        commands::relationship_notes::update_relationship_note,
+        commands::reminders::create_reminder,
+        commands::reminders::list_reminders,
+        commands::reminders::complete_reminder,
+        commands::reminders::snooze_reminder,
+        commands::renewals::list_renewals,
+        commands::renewals::get_renewal_brief,
+        commands::renewals::update_renewal_stage,
+        commands::retention::get_retention_policy,
+        commands::retention::update_retention_policy,
+        commands::retention::get_storage_breakdown,
+        commands::retention::run_cleanup_now,
+        commands::scheduling::resolve_meeting_times,
+        commands::search::search,
+        commands::search::index_search_document,
+        commands::search::remove_search_document,
+        commands::security::require_biometric_unlock,
+        commands::security::get_biometric_settings,
+        commands::security::update_biometric_settings,
+        commands::security::lock_app,
+        commands::security::is_app_locked,
+        commands::security::unlock_app,
+        commands::semantic_search::semantic_search,
+        commands::semantic_search::index_document,
+        commands::semantic_search::remove_indexed_document,
+        commands::semantic_search::reindex_embeddings,
+        commands::signal_weights::get_signal_weights,
+        commands::signal_weights::update_signal_weights,
+        commands::slack::connect_slack,
+        commands::slack::get_slack_connection,
+        commands::slack::disconnect_slack,
+        commands::slack::send_slack_hang_invite,
+        commands::slack::send_slack_on_my_way,
+        commands::slack::get_slack_presence,
+        commands::sync_policy::get_sync_policy,
+        commands::sync_policy::set_sync_policy,
+        commands::tokens::estimate_tokens,
+        commands::tokens::chunk_document,
+        commands::trash::delete_journal_entry,
+        commands::trash::delete_relationship_note,
+        commands::trash::delete_context_file,
+        commands::trash::undo_delete,
+        commands::uploads::start_upload,
+        commands::uploads::pause_upload,
+        commands::uploads::resume_upload,
+        commands::uploads::list_uploads,
+        commands::user_status::fetch_user_status,
+        commands::user_status::start_user_status_watch,
+        commands::user_status::stop_user_status_watch,
+        commands::voice_memos::create_voice_memo,
+        commands::voice_memos::list_voice_memos,
+        commands::voice_memos::list_voice_memos_page,
+        commands::voice_memos::update_voice_memo,
+        commands::voice_memos::delete_voice_memo,
+        commands::voice_memos::sync_voice_memos,
+    ])
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let bindings = specta_builder();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_deep_link::init())
-        .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(
+            tauri_plugin_store::Builder::new()
+                .default_serialize_fn(crypto::serialize_store)
+                .default_deserialize_fn(crypto::deserialize_store)
+                .build(),
+        )
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, _shortcut, event| {
+                    if event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                        if let Err(e) = commands::quick_capture::open_window(app) {
+                            app_log!("[QuickCapture] {}", e);
+                        }
+                    }
+                })
+                .build(),
+        )
+        .register_uri_scheme_protocol("memo", |ctx, request| {
+            commands::voice_memos::serve_memo(ctx.app_handle(), &request)
+        })
+        .on_page_load(|webview, payload| match payload.event() {
+            tauri::webview::PageLoadEvent::Started => {
+                commands::appearance::apply_to_window(&webview.window());
+            }
+            tauri::webview::PageLoadEvent::Finished => {
+                store_integrity::announce_recoveries(webview.app_handle());
+            }
+        })
+        .on_window_event(|window, event| {
+            match event {
+                tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                    file_drop::handle_drop(window.app_handle(), paths.clone());
+                }
+                tauri::WindowEvent::ThemeChanged(_) => {
+                    commands::appearance::handle_theme_changed(window.app_handle());
+                }
+                _ => {}
+            }
+        })
         .setup(|app| {
+            logging::init(&paths::log_dir(app.handle())?)?;
+            if let Some(root) = paths::portable_root() {
+                app_log!("[Paths] Portable mode: data in {}", root.display());
+            }
+
+            app.manage(api::from_env()?);
+            app.manage(commands::chat::ChatStreams::default());
+            app.manage(commands::diagnostics::LogTail::default());
+            app.manage(commands::security::BiometricGate::default());
+            app.manage(commands::security::AppLock::default());
+            app.manage(commands::login::PendingBrowserLogin::default());
+            app.manage(browser_bridge::BridgeConsents::default());
+            app.manage(commands::slack::PendingSlackConnect::default());
+            app.manage(commands::user_status::UserStatusWatch::default());
+            app.manage(commands::uploads::Uploads::default());
+            app.manage(commands::downloads::Downloads::default());
+            app.manage(commands::focus::Focus::default());
+            app.manage(palette::Palette::default());
+            app.manage(embeddings::Embedder::new(
+                paths::cache_dir(app.handle())?.join("models"),
+            ));
+
+            // Created here rather than from the config, so a portable
+            // webview keeps its profile beside the data too
+            if let Some(config) = app.config().app.windows.first() {
+                let mut main = tauri::WebviewWindowBuilder::from_config(app.handle(), config)?;
+                if let Some(dir) = paths::webview_data_dir() {
+                    main = main.data_directory(dir);
+                }
+                main.build()?;
+            }
+
+            if paths::is_portable() {
+                app_log!("[Paths] Waiting for the passphrase to open portable data");
+            } else {
+                startup::open_local_data(app.handle())?;
+            }
+
             // Open devtools (temporarily enabled for debugging)
             if let Some(window) = app.get_webview_window("main") {
                 window.open_devtools();
             }
 
-            // Handle deep links
-            #[cfg(desktop)]
-            {
-                let handle = app.handle().clone();
-                app.deep_link().on_open_url(move |event: tauri_plugin_deep_link::OpenUrlEvent| {
-                    for url in event.urls() {
-                        if url.scheme() == "goodhang" {
-                            // Extract activation code from path
-                            // URL format: goodhang://activate/XXXX-XXXX-XXXX
-                            let path = url.path();
-                            // Handle both "/activate/CODE" and "activate/CODE" (Windows may omit leading slash)
-                            let code = path
-                                .strip_prefix("/activate/")
-                                .or_else(|| path.strip_prefix("activate/"))
-                                .or_else(|| path.strip_prefix("/"))
-                                .unwrap_or(path);
-
-                            if !code.is_empty() {
-                                if let Some(window) = handle.get_webview_window("main") {
-                                    println!("Deep link received: code={}", code);
-                                    let _ = window.emit("activation-code", code);
-                                    // Focus the window
-                                    let _ = window.set_focus();
-                                }
-                            }
-                        }
-                    }
-                });
-            }
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            commands::activation::validate_activation_key,
-            commands::activation::claim_activation_key,
-            commands::activation::fetch_assessment_results,
-            commands::auth::store_session,
-            commands::auth::get_session,
-            commands::auth::clear_session,
-            commands::auth::store_device_registration,
-            commands::auth::get_device_registration,
-            commands::auth::clear_device_registration,
-            commands::user_status::fetch_user_status,
-        ])
+        .invoke_handler(capabilities::guard(bindings.invoke_handler()))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    /// Regenerates the committed TypeScript bindings in `src/lib/bindings`;
+    /// run `cargo test export_bindings` after changing a command or event
+    #[test]
+    fn export_bindings() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/lib/bindings");
+        let typescript = specta_typescript::Typescript::default()
+            .bigint(specta_typescript::BigIntExportBehavior::Number);
+        super::specta_builder()
+            .export(typescript.clone(), dir.join("commands.ts"))
+            .expect("failed to export command bindings");
+        super::events::export_bindings(&typescript, &dir.join("events.ts"))
+            .expect("failed to export event bindings");
+    }
+}
@@ -2,5 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // Launched by the browser as the extension's native-messaging host
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(origin) = goodhang_desktop_lib::native_messaging_origin(&args) {
+        std::process::exit(goodhang_desktop_lib::run_native_messaging_host(origin));
+    }
+
     goodhang_desktop_lib::run()
 }
@@ -0,0 +1,253 @@
+//! Versioned upgrades for data already on disk. The database records its
+//! version in `PRAGMA user_version` and each store in a `schema_version`
+//! key; pending migrations run in order at startup. `plan` runs them
+//! against a copy (or inside a rolled-back transaction) so a shape change
+//! that would break parsing of `session` or `device_registration` shows up
+//! before anything is written.
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::audit;
+use crate::commands::auth::{self, DeviceRegistration, SessionData};
+use crate::commands::retention;
+use crate::db::{self, Database};
+use crate::logging::app_log;
+use crate::outbox;
+use crate::stores;
+
+pub const STORE_VERSION_KEY: &str = "schema_version";
+
+type Entries = serde_json::Map<String, serde_json::Value>;
+
+struct DbMigration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+struct StoreMigration {
+    store: &'static str,
+    version: u32,
+    description: &'static str,
+    apply: fn(&mut Entries) -> Result<(), String>,
+}
+
+/// Append only; each version must be one higher than the last, and the
+/// last must equal `db::SCHEMA_VERSION`
+const DB_MIGRATIONS: &[DbMigration] = &[
+    DbMigration {
+        version: 1,
+        description: "Create the baseline tables",
+        apply: db::create_tables,
+    },
+    DbMigration {
+        version: 2,
+        description: "Order outbox tasks and key them by entity",
+        apply: outbox::add_ordering,
+    },
+    DbMigration {
+        version: 3,
+        description: "Track voice memos whose audio was purged",
+        apply: retention::add_audio_purged_at,
+    },
+    DbMigration {
+        version: 4,
+        description: "Let audit log retention delete old entries",
+        apply: audit::allow_retention_prune,
+    },
+];
+
+/// Append only, versioned per store
+const STORE_MIGRATIONS: &[StoreMigration] = &[
+    StoreMigration {
+        store: auth::STORE_FILENAME,
+        version: 1,
+        description: "Record a version on stores written before versioning",
+        apply: stamp_only,
+    },
+    StoreMigration {
+        store: stores::SETTINGS_STORE,
+        version: 1,
+        description: "Record a version on stores written before versioning",
+        apply: stamp_only,
+    },
+];
+
+const _: () = assert!(DB_MIGRATIONS[DB_MIGRATIONS.len() - 1].version == db::SCHEMA_VERSION);
+
+fn stamp_only(_: &mut Entries) -> Result<(), String> {
+    Ok(())
+}
+
+/// Pending and applied work for the database or one store
+#[derive(Debug, Serialize, specta::Type)]
+pub struct MigrationStatus {
+    /// `database`, or the store file name
+    pub target: String,
+    #[serde(rename = "currentVersion")]
+    pub current_version: u32,
+    #[serde(rename = "latestVersion")]
+    pub latest_version: u32,
+    /// Descriptions of the migrations that would run, in order
+    pub pending: Vec<String>,
+    /// Why migrating would fail, if it would
+    pub error: Option<String>,
+}
+
+fn db_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Run every database migration newer than the recorded version, each in
+/// its own transaction. Returns the version reached.
+pub fn migrate_db(conn: &Connection) -> rusqlite::Result<u32> {
+    let mut current = db_version(conn)?;
+    if current > db::SCHEMA_VERSION {
+        app_log!(
+            "[Migrations] Database is at version {}, newer than this build ({})",
+            current,
+            db::SCHEMA_VERSION
+        );
+        return Ok(current);
+    }
+    for migration in DB_MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.apply)(&tx)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+        current = migration.version;
+        app_log!(
+            "[Migrations] Database migrated to {}: {}",
+            current,
+            migration.description
+        );
+    }
+    Ok(current)
+}
+
+fn store_version(entries: &Entries) -> u32 {
+    entries
+        .get(STORE_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32
+}
+
+fn store_migrations(store: &str) -> impl Iterator<Item = &'static StoreMigration> + '_ {
+    STORE_MIGRATIONS.iter().filter(move |m| m.store == store)
+}
+
+fn store_names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = Vec::new();
+    for migration in STORE_MIGRATIONS {
+        if !names.contains(&migration.store) {
+            names.push(migration.store);
+        }
+    }
+    names
+}
+
+/// Entries the rest of the app parses into typed values must still parse
+fn check_parses(store: &str, entries: &Entries) -> Result<(), String> {
+    if store != auth::STORE_FILENAME {
+        return Ok(());
+    }
+    if let Some(session) = entries.get("session") {
+        serde_json::from_value::<SessionData>(session.clone())
+            .map_err(|e| format!("Failed to parse session: {}", e))?;
+    }
+    if let Some(registration) = entries.get("device_registration") {
+        serde_json::from_value::<DeviceRegistration>(registration.clone())
+            .map_err(|e| format!("Failed to parse registration: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Apply pending migrations to `entries` in memory, returning the
+/// descriptions of those applied
+fn migrate_entries(store: &str, entries: &mut Entries) -> Result<Vec<String>, String> {
+    let current = store_version(entries);
+    let mut applied = Vec::new();
+    for migration in store_migrations(store).filter(|m| m.version > current) {
+        (migration.apply)(entries)
+            .map_err(|e| format!("Migration {} failed: {}", migration.version, e))?;
+        entries.insert(STORE_VERSION_KEY.to_string(), migration.version.into());
+        applied.push(migration.description.to_string());
+    }
+    check_parses(store, entries)?;
+    Ok(applied)
+}
+
+fn read_entries(app: &tauri::AppHandle, store: &str) -> Result<Entries, String> {
+    let store = stores::open(app, store)?;
+    Ok(store.entries().into_iter().collect())
+}
+
+/// Bring every store up to date. A store whose migrated entries wouldn't
+/// parse is left untouched and reported instead of being half-written.
+pub fn migrate_stores(app: &tauri::AppHandle) -> Result<(), String> {
+    for name in store_names() {
+        let mut entries = read_entries(app, name)?;
+        let applied = migrate_entries(name, &mut entries)
+            .map_err(|e| format!("Failed to migrate {}: {}", name, e))?;
+        if applied.is_empty() {
+            continue;
+        }
+        let version = store_version(&entries);
+
+        let store = stores::open(app, name)?;
+        store.clear();
+        for (key, value) in entries {
+            store.set(key, value);
+        }
+        stores::save(app, name)?;
+        app_log!("[Migrations] {} migrated to {}", name, version);
+    }
+    Ok(())
+}
+
+/// What startup migration would do right now, without keeping any of it
+pub fn plan(app: &tauri::AppHandle, db: &Database) -> Result<Vec<MigrationStatus>, String> {
+    let database = db.with_conn(|conn| {
+        let current = db_version(conn)?;
+        let pending: Vec<String> = DB_MIGRATIONS
+            .iter()
+            .filter(|m| m.version > current)
+            .map(|m| m.description.to_string())
+            .collect();
+        // DDL is transactional in SQLite, so a rolled-back run is a dry run
+        let tx = conn.unchecked_transaction()?;
+        let error = DB_MIGRATIONS
+            .iter()
+            .filter(|m| m.version > current)
+            .try_for_each(|m| (m.apply)(&tx))
+            .err()
+            .map(|e| e.to_string());
+        tx.rollback()?;
+        Ok(MigrationStatus {
+            target: "database".to_string(),
+            current_version: current,
+            latest_version: db::SCHEMA_VERSION,
+            pending,
+            error,
+        })
+    })?;
+
+    let mut statuses = vec![database];
+    for name in store_names() {
+        let mut entries = read_entries(app, name)?;
+        let current_version = store_version(&entries);
+        let pending = store_migrations(name)
+            .filter(|m| m.version > current_version)
+            .map(|m| m.description.to_string())
+            .collect();
+        statuses.push(MigrationStatus {
+            target: name.to_string(),
+            current_version,
+            latest_version: store_migrations(name).map(|m| m.version).max().unwrap_or(0),
+            pending,
+            error: migrate_entries(name, &mut entries).err(),
+        });
+    }
+    Ok(statuses)
+}
@@ -0,0 +1,495 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::api;
+use crate::db::now_secs;
+use crate::errors::{AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::http;
+use crate::logging::{app_log, redact};
+
+// Probe less often while things are working, more often while waiting to recover
+const ONLINE_INTERVAL: Duration = Duration::from_secs(30);
+const OFFLINE_INTERVAL: Duration = Duration::from_secs(10);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+// Beyond this, freshly issued tokens can look expired or not yet valid
+const MAX_CLOCK_SKEW_SECS: i64 = 5 * 60;
+// Quality is judged on the most recent requests only, and only on recent
+// ones: a burst of failures an hour ago says nothing about the link now
+const QUALITY_WINDOW: usize = 30;
+const QUALITY_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+const MIN_QUALITY_SAMPLES: usize = 5;
+const DEGRADED_LATENCY_MS: u64 = 800;
+const POOR_LATENCY_MS: u64 = 2500;
+const DEGRADED_ERROR_RATE: f64 = 0.1;
+const POOR_ERROR_RATE: f64 = 0.3;
+// How much longer background polls wait in low-bandwidth mode
+const LOW_BANDWIDTH_POLL_FACTOR: u32 = 4;
+
+/// Whether the API host is reachable, as last observed by the monitor
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Connectivity {
+    pub online: bool,
+    #[serde(rename = "lastCheckedAt")]
+    pub last_checked_at: Option<i64>,
+    #[serde(rename = "lastOnlineAt")]
+    pub last_online_at: Option<i64>,
+}
+
+// Assume online until the first probe says otherwise, so startup work isn't held back
+static CONNECTIVITY: Mutex<Connectivity> = Mutex::new(Connectivity {
+    online: true,
+    last_checked_at: None,
+    last_online_at: None,
+});
+
+pub fn connectivity() -> Connectivity {
+    CONNECTIVITY
+        .lock()
+        .map(|c| c.clone())
+        .unwrap_or(Connectivity {
+            online: true,
+            last_checked_at: None,
+            last_online_at: None,
+        })
+}
+
+pub fn is_online() -> bool {
+    connectivity().online
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityLevel {
+    Good,
+    Degraded,
+    Poor,
+}
+
+/// Rolling estimate from the app's own API requests
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct NetworkQuality {
+    pub level: QualityLevel,
+    #[serde(rename = "medianLatencyMs")]
+    pub median_latency_ms: Option<u64>,
+    #[serde(rename = "errorRate")]
+    pub error_rate: f64,
+    pub samples: usize,
+    /// Background work should send less, poll less and defer downloads
+    #[serde(rename = "lowBandwidth")]
+    pub low_bandwidth: bool,
+}
+
+struct Sample {
+    at: Instant,
+    latency_ms: u64,
+    ok: bool,
+}
+
+fn prune(samples: &mut VecDeque<Sample>) {
+    while samples
+        .front()
+        .is_some_and(|s| s.at.elapsed() > QUALITY_MAX_AGE)
+    {
+        samples.pop_front();
+    }
+}
+
+static SAMPLES: Mutex<VecDeque<Sample>> = Mutex::new(VecDeque::new());
+static LAST_LEVEL: Mutex<Option<QualityLevel>> = Mutex::new(None);
+
+/// Called by the HTTP layer for every API request, with the time until the
+/// response headers arrived so large downloads don't read as slow links.
+/// `ok` is false for transport failures and 5xx responses; other statuses
+/// say nothing about the connection.
+pub fn record_request(latency: Duration, ok: bool) {
+    if let Ok(mut samples) = SAMPLES.lock() {
+        prune(&mut samples);
+        if samples.len() == QUALITY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(Sample {
+            at: Instant::now(),
+            latency_ms: latency.as_millis() as u64,
+            ok,
+        });
+    }
+}
+
+pub fn network_quality() -> NetworkQuality {
+    let (mut latencies, failures, samples) = match SAMPLES.lock() {
+        Ok(mut samples) => {
+            prune(&mut samples);
+            (
+                samples
+                    .iter()
+                    .filter(|s| s.ok)
+                    .map(|s| s.latency_ms)
+                    .collect::<Vec<_>>(),
+                samples.iter().filter(|s| !s.ok).count(),
+                samples.len(),
+            )
+        }
+        Err(_) => (Vec::new(), 0, 0),
+    };
+    latencies.sort_unstable();
+    let median_latency_ms = latencies.get(latencies.len() / 2).copied();
+    let error_rate = if samples == 0 {
+        0.0
+    } else {
+        failures as f64 / samples as f64
+    };
+
+    // Too few requests to judge; assume the best rather than throttle startup
+    let level = if samples < MIN_QUALITY_SAMPLES {
+        QualityLevel::Good
+    } else if error_rate >= POOR_ERROR_RATE
+        || median_latency_ms.is_some_and(|ms| ms >= POOR_LATENCY_MS)
+    {
+        QualityLevel::Poor
+    } else if error_rate >= DEGRADED_ERROR_RATE
+        || median_latency_ms.is_some_and(|ms| ms >= DEGRADED_LATENCY_MS)
+    {
+        QualityLevel::Degraded
+    } else {
+        QualityLevel::Good
+    };
+
+    NetworkQuality {
+        level,
+        median_latency_ms,
+        error_rate,
+        samples,
+        low_bandwidth: level == QualityLevel::Poor || !is_online(),
+    }
+}
+
+pub fn low_bandwidth() -> bool {
+    network_quality().low_bandwidth
+}
+
+/// `base`, stretched while the connection is poor
+pub fn poll_interval(base: Duration) -> Duration {
+    if low_bandwidth() {
+        base * LOW_BANDWIDTH_POLL_FACTOR
+    } else {
+        base
+    }
+}
+
+/// Emit `network-quality-changed` when the level moved since last time
+fn emit_quality_change(app: &tauri::AppHandle) {
+    let quality = network_quality();
+    let changed = LAST_LEVEL
+        .lock()
+        .map(|mut last| last.replace(quality.level).unwrap_or(QualityLevel::Good) != quality.level)
+        .unwrap_or(false);
+    if changed {
+        app_log!(
+            "[Network] Quality is now {:?} (median {:?}ms, {:.0}% errors)",
+            quality.level,
+            quality.median_latency_ms,
+            quality.error_rate * 100.0
+        );
+        events::emit_app_event(app, AppEvent::NetworkQualityChanged(quality));
+    }
+}
+
+/// Reachability of the API host itself, not just the OS network state: a
+/// captive portal or a down API both count as offline. Only a 2xx from the
+/// health endpoint means the API is answering; a portal's login page or
+/// another server's 404 doesn't.
+pub async fn probe(base_url: &str) -> bool {
+    match http::client()
+        .head(format!("{}/api/health", base_url))
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+/// Record a probe result, returning the new state if it changed
+fn record(online: bool) -> Option<Connectivity> {
+    let mut state = CONNECTIVITY.lock().ok()?;
+    let now = now_secs();
+    let changed = state.online != online;
+    state.online = online;
+    state.last_checked_at = Some(now);
+    if online {
+        state.last_online_at = Some(now);
+    }
+    changed.then(|| state.clone())
+}
+
+/// The first thing wrong with the connection to the API, most fundamental first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthProblem {
+    Unreachable,
+    Tls,
+    ServerError,
+    ClockSkew,
+}
+
+/// What `check_api_health` found, so a failure can be blamed on the network,
+/// TLS, the server or the local clock rather than on what the user typed
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ApiHealth {
+    pub healthy: bool,
+    pub url: String,
+    pub status: Option<u16>,
+    #[serde(rename = "latencyMs")]
+    pub latency_ms: Option<u64>,
+    /// `None` for plain-http URLs
+    #[serde(rename = "tlsOk")]
+    pub tls_ok: Option<bool>,
+    /// Server clock minus ours, from the `Date` header
+    #[serde(rename = "clockSkewSecs")]
+    pub clock_skew_secs: Option<i64>,
+    pub problem: Option<HealthProblem>,
+    pub detail: Option<String>,
+    #[serde(rename = "checkedAt")]
+    pub checked_at: i64,
+}
+
+/// Whether a send error came from certificate validation or the handshake
+fn is_tls_error(error: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(error);
+    while let Some(e) = source {
+        let text = e.to_string().to_lowercase();
+        if ["certificate", "tls", "ssl", "handshake"]
+            .iter()
+            .any(|needle| text.contains(needle))
+        {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+/// GET `/api/health` once, timing the round trip and checking TLS and the
+/// server's clock against ours
+pub async fn check_api_health() -> ApiHealth {
+    let url = api::base_url();
+    let https = url.starts_with("https://");
+    let mut health = ApiHealth {
+        healthy: false,
+        url: url.clone(),
+        status: None,
+        latency_ms: None,
+        tls_ok: None,
+        clock_skew_secs: None,
+        problem: None,
+        detail: None,
+        checked_at: now_secs(),
+    };
+
+    let started = Instant::now();
+    let result = http::client()
+        .get(format!("{}/api/health", url))
+        .timeout(HEALTH_TIMEOUT)
+        .send()
+        .await;
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            let tls = https && is_tls_error(&e);
+            // A connection that never got as far as the handshake says nothing about TLS
+            health.tls_ok = tls.then_some(false);
+            health.problem = Some(if tls {
+                HealthProblem::Tls
+            } else {
+                HealthProblem::Unreachable
+            });
+            health.detail = Some(format!("Network error: {}", e));
+            app_log!("[Network] Health check failed: {}", e);
+            return health;
+        }
+    };
+
+    health.latency_ms = Some(started.elapsed().as_millis() as u64);
+    health.status = Some(response.status().as_u16());
+    health.tls_ok = https.then_some(true);
+    health.clock_skew_secs = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(|date| chrono::DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.timestamp() - now_secs());
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        health.problem = Some(HealthProblem::ServerError);
+        health.detail = Some(format!("Server error {}: {}", status, redact(&body)));
+    } else if health
+        .clock_skew_secs
+        .is_some_and(|skew| skew.abs() > MAX_CLOCK_SKEW_SECS)
+    {
+        health.problem = Some(HealthProblem::ClockSkew);
+        health.detail = health.clock_skew_secs.map(|skew| {
+            let direction = if skew > 0 { "behind" } else { "ahead of" };
+            format!(
+                "This computer's clock is {}s {} the server's",
+                skew.abs(),
+                direction
+            )
+        });
+    }
+    health.healthy = health.problem.is_none();
+
+    app_log!(
+        "[Network] Health check: status={:?} latency={:?}ms problem={:?}",
+        health.status,
+        health.latency_ms,
+        health.problem
+    );
+    health
+}
+
+/// Probe the API host periodically, emitting `went-offline` and
+/// `back-online` on transitions
+pub fn spawn_connectivity_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let online = probe(&api::base_url()).await;
+            if let Some(state) = record(online) {
+                if state.online {
+                    app_log!("[Network] API reachable again");
+                    events::emit_app_event(&app, AppEvent::BackOnline(state));
+                } else {
+                    app_log!("[Network] API unreachable");
+                    events::emit_app_event(&app, AppEvent::WentOffline(state));
+                }
+            }
+            emit_quality_change(&app);
+
+            tokio::time::sleep(if online {
+                ONLINE_INTERVAL
+            } else {
+                OFFLINE_INTERVAL
+            })
+            .await;
+        }
+    });
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionResponse {
+    #[serde(rename = "apiVersion")]
+    api_version: String,
+    #[serde(rename = "minClientVersion")]
+    min_client_version: String,
+    #[serde(default, rename = "latestClientVersion")]
+    latest_client_version: Option<String>,
+}
+
+/// Whether this build can talk to the deployed API
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ApiCompatibility {
+    pub compatible: bool,
+    #[serde(rename = "clientVersion")]
+    pub client_version: String,
+    #[serde(rename = "apiVersion")]
+    pub api_version: String,
+    #[serde(rename = "minClientVersion")]
+    pub min_client_version: String,
+    #[serde(rename = "latestClientVersion")]
+    pub latest_client_version: Option<String>,
+}
+
+/// Numeric components of a dotted version; pre-release suffixes are ignored
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(['.', '-', '+'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+fn is_at_least(version: &str, minimum: &str) -> bool {
+    let (mut version, mut minimum) = (version_parts(version), version_parts(minimum));
+    let len = version.len().max(minimum.len());
+    version.resize(len, 0);
+    minimum.resize(len, 0);
+    version >= minimum
+}
+
+/// Ask `/api/version` whether this build is still supported, emitting
+/// `client-outdated` when it isn't
+pub async fn check_api_compatibility(app: &tauri::AppHandle) -> Result<ApiCompatibility, AppError> {
+    let response = http::client()
+        .get(format!("{}/api/version", api::base_url()))
+        .send()
+        .await
+        .map_err(unreachable)?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let code = if status.is_server_error() {
+            ErrorCode::Server
+        } else {
+            ErrorCode::RequestRejected
+        };
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::new(
+            code,
+            format!("Server error {}: {}", status, redact(&body)),
+        ));
+    }
+
+    let version: VersionResponse = http::parse_response(response, "/api/version")
+        .await
+        .map_err(|e| AppError::new(ErrorCode::SchemaMismatch, e))?;
+    let compatibility = ApiCompatibility {
+        compatible: is_at_least(http::CLIENT_VERSION, &version.min_client_version),
+        client_version: http::CLIENT_VERSION.to_string(),
+        api_version: version.api_version,
+        min_client_version: version.min_client_version,
+        latest_client_version: version.latest_client_version,
+    };
+
+    if !compatibility.compatible {
+        app_log!(
+            "[Network] Client {} is older than the API minimum {}",
+            compatibility.client_version,
+            compatibility.min_client_version
+        );
+        events::emit_app_event(app, AppEvent::ClientOutdated(compatibility.clone()));
+    }
+    Ok(compatibility)
+}
+
+/// Code for a request that got no response at all
+pub fn unreachable_code(timed_out: bool) -> ErrorCode {
+    if !is_online() {
+        ErrorCode::NetworkOffline
+    } else if timed_out {
+        ErrorCode::Timeout
+    } else {
+        ErrorCode::Network
+    }
+}
+
+fn unreachable(e: reqwest::Error) -> AppError {
+    AppError::new(
+        unreachable_code(e.is_timeout()),
+        format!("Network error: {}", e),
+    )
+}
+
+/// Run the compatibility check once at startup
+pub fn spawn_compatibility_check(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = check_api_compatibility(&app).await {
+            app_log!("[Network] Compatibility check failed: {}", e);
+        }
+    });
+}
@@ -0,0 +1,31 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::logging::app_log;
+
+// Set while a focus session is running
+static SUPPRESSED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_suppressed(suppressed: bool) {
+    SUPPRESSED.store(suppressed, Ordering::SeqCst);
+}
+
+pub fn is_suppressed() -> bool {
+    SUPPRESSED.load(Ordering::SeqCst)
+}
+
+/// Show a native notification unless focus mode is suppressing them
+pub fn notify(app: &tauri::AppHandle, title: &str, body: &str) {
+    if is_suppressed() {
+        app_log!("[Notifications] Suppressed during focus: {}", title);
+        return;
+    }
+    force_notify(app, title, body);
+}
+
+/// Show a notification even during focus, for the few that end it
+pub fn force_notify(app: &tauri::AppHandle, title: &str, body: &str) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        app_log!("[Notifications] Failed to show notification: {}", e);
+    }
+}
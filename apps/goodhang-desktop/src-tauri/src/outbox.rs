@@ -0,0 +1,697 @@
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+use tauri::{Listener, Manager};
+
+use crate::commands::{
+    activation, analytics, feedback, journal, library, profile, reactions, relationship_notes,
+    reminders, trash, uploads,
+};
+use crate::db::{now_secs, Database};
+use crate::errors::{self, AppError, ErrorCode};
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::network;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+// Attempts before a task is moved to the dead-letter state
+const MAX_ATTEMPTS: i64 = 8;
+const BASE_BACKOFF_SECS: i64 = 5;
+const MAX_BACKOFF_SECS: i64 = 60 * 60;
+
+/// Handlers return errors starting with this when the task can't run yet
+/// for local reasons (e.g. a large upload on a metered connection). The
+/// task is retried later without using up an attempt.
+pub const DEFERRED: &str = "Deferred";
+
+/// A queued write. Delivery is at-least-once: a task is only removed after
+/// its handler succeeds, so handlers must be idempotent. Tasks for the same
+/// entity are delivered one at a time, in the order they were queued.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct OutboxTask {
+    pub id: String,
+    pub kind: String,
+    /// What the task writes to, e.g. `reminder:{id}`; `None` for tasks
+    /// that don't need ordering against others
+    pub entity: Option<String>,
+    pub payload: serde_json::Value,
+    /// pending | in_flight | dead
+    pub status: String,
+    pub attempts: i64,
+    #[serde(rename = "nextAttemptAt")]
+    pub next_attempt_at: i64,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+/// An optimistic write the server refused. The local copy is back to
+/// `previous`, which is `null` when the write created the record.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct WriteRevertedEvent {
+    /// profile | relationship_note | reminder
+    pub kind: String,
+    pub id: String,
+    pub previous: serde_json::Value,
+    pub error: AppError,
+}
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct QueueStatus {
+    pub pending: usize,
+    #[serde(rename = "inFlight")]
+    pub in_flight: usize,
+    pub dead: usize,
+    /// Every task still in the queue, in delivery order
+    pub tasks: Vec<OutboxTask>,
+}
+
+fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<OutboxTask> {
+    let payload: String = row.get("payload")?;
+    Ok(OutboxTask {
+        id: row.get("id")?,
+        kind: row.get("kind")?,
+        entity: row.get("entity")?,
+        payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null),
+        status: row.get("status")?,
+        attempts: row.get("attempts")?,
+        next_attempt_at: row.get("next_attempt_at")?,
+        last_error: row.get("last_error")?,
+        created_at: row.get("created_at")?,
+    })
+}
+
+/// Migration: give every task a sequence number (creation times only have
+/// second resolution, and VACUUM may renumber rowids) and an entity key
+pub(crate) fn add_ordering(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE outbox ADD COLUMN seq INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE outbox ADD COLUMN entity TEXT;
+         UPDATE outbox SET seq = (
+             SELECT COUNT(*) FROM outbox o
+             WHERE o.created_at < outbox.created_at
+                OR (o.created_at = outbox.created_at AND o.rowid <= outbox.rowid)
+         );
+         CREATE INDEX IF NOT EXISTS idx_outbox_entity ON outbox (entity, seq);",
+    )?;
+    for (kind, path) in [
+        (
+            library::BOOKMARK_TASK,
+            "'bookmark:' || json_extract(payload, '$.itemId')",
+        ),
+        (
+            journal::ENTRY_TASK,
+            "'journal:' || json_extract(payload, '$.id')",
+        ),
+        (
+            relationship_notes::GFT_SYNC_TASK,
+            "'relationship_note:' || json_extract(payload, '$.noteId')",
+        ),
+        (
+            profile::PROFILE_TASK,
+            "'profile:' || json_extract(payload, '$.userId')",
+        ),
+        (
+            reminders::REMINDER_TASK,
+            "'reminder:' || json_extract(payload, '$.reminder.id')",
+        ),
+    ] {
+        conn.execute(
+            &format!("UPDATE outbox SET entity = {} WHERE kind = ?1", path),
+            [kind],
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_task(
+    conn: &rusqlite::Connection,
+    kind: &str,
+    entity: Option<&str>,
+    payload: &serde_json::Value,
+    status: &str,
+) -> rusqlite::Result<String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO outbox (id, kind, entity, payload, status, next_attempt_at, created_at, seq)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, (SELECT COALESCE(MAX(seq), 0) + 1 FROM outbox))",
+        rusqlite::params![id, kind, entity, payload.to_string(), status, now_secs()],
+    )?;
+    Ok(id)
+}
+
+/// Persist a task and try to deliver it right away. `entity` orders the
+/// task after any queued earlier for the same record.
+pub fn enqueue(
+    app: &tauri::AppHandle,
+    kind: &str,
+    entity: Option<&str>,
+    payload: serde_json::Value,
+) -> Result<String, String> {
+    let id = app
+        .state::<Database>()
+        .with_conn(|conn| insert_task(conn, kind, entity, &payload, "pending"))?;
+    app_log!("[Outbox] Enqueued {} ({})", id, kind);
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move { process(&handle).await });
+    Ok(id)
+}
+
+/// Persist a task and make its first attempt inline, for callers that need
+/// the response. A failed attempt stays queued and is retried like any
+/// other task; calling again for the same entity replaces a task still
+/// waiting for its retry.
+pub async fn deliver_now<T, Fut>(
+    app: &tauri::AppHandle,
+    kind: &str,
+    entity: &str,
+    payload: serde_json::Value,
+    attempt: impl FnOnce(serde_json::Value) -> Fut,
+) -> Result<T, String>
+where
+    Fut: Future<Output = Result<T, String>>,
+{
+    let db = app.state::<Database>();
+    let id = db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM outbox WHERE kind = ?1 AND entity = ?2 AND status = 'pending'",
+            [kind, entity],
+        )?;
+        let id = insert_task(&tx, kind, Some(entity), &payload, "in_flight")?;
+        tx.commit()?;
+        Ok(id)
+    })?;
+    let task = db.with_conn(|conn| {
+        conn.query_row("SELECT * FROM outbox WHERE id = ?1", [&id], row_to_task)
+    })?;
+
+    let result = attempt(payload).await;
+    let outcome = result.as_ref().map(|_| ()).map_err(String::clone);
+    if let Err(e) = finish(app, &task, outcome) {
+        app_log!("[Outbox] Failed to record {}: {}", task.id, e);
+    }
+    result
+}
+
+/// Make an entity's queued or dead-lettered tasks due now with a fresh
+/// set of attempts. Returns false when nothing was queued for it.
+pub fn retry_entity(app: &tauri::AppHandle, entity: &str) -> Result<bool, String> {
+    let revived = app.state::<Database>().with_conn(|conn| {
+        conn.execute(
+            "UPDATE outbox SET status = 'pending', attempts = 0, next_attempt_at = ?1
+             WHERE entity = ?2 AND status IN ('pending', 'dead')",
+            rusqlite::params![now_secs(), entity],
+        )
+    })?;
+    if revived > 0 {
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move { process(&handle).await });
+    }
+    Ok(revived > 0)
+}
+
+/// Drop every queued task of `kind` that isn't already being delivered,
+/// e.g. when the user withdraws consent for what they'd send. Returns how
+/// many were dropped.
+pub fn discard_kind(app: &tauri::AppHandle, kind: &str) -> Result<usize, String> {
+    app.state::<Database>().with_conn(|conn| {
+        conn.execute(
+            "DELETE FROM outbox WHERE kind = ?1 AND status IN ('pending', 'dead')",
+            [kind],
+        )
+    })
+}
+
+/// Run deferred tasks now, e.g. once the sync policy stops conserving
+pub fn wake_deferred(app: &tauri::AppHandle) {
+    let woken = app.state::<Database>().with_conn(|conn| {
+        conn.execute(
+            "UPDATE outbox SET next_attempt_at = ?1
+             WHERE status = 'pending' AND last_error LIKE ?2",
+            rusqlite::params![now_secs(), format!("{}%", DEFERRED)],
+        )
+    });
+    if let Err(e) = woken {
+        app_log!("[Outbox] Failed to wake deferred tasks: {}", e);
+    }
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move { process(&handle).await });
+}
+
+/// Route a task to the module that owns its kind
+async fn deliver(app: &tauri::AppHandle, task: &OutboxTask) -> Result<(), String> {
+    match task.kind.as_str() {
+        library::BOOKMARK_TASK => library::deliver_bookmark(app, &task.payload).await,
+        journal::ENTRY_TASK => journal::deliver_entry(app, &task.payload).await,
+        reactions::REACTION_TASK => reactions::deliver_reaction(app, &task.payload).await,
+        feedback::FEEDBACK_TASK => feedback::deliver_feedback(app, &task.payload).await,
+        relationship_notes::GFT_SYNC_TASK => {
+            relationship_notes::deliver_note(app, &task.payload).await
+        }
+        profile::PROFILE_TASK => profile::deliver_profile(app, &task.payload).await,
+        reminders::REMINDER_TASK => reminders::deliver_reminder(app, &task.payload).await,
+        trash::DELETE_TASK => trash::deliver_delete(app, &task.payload).await,
+        uploads::UPLOAD_TASK => uploads::deliver_upload(app, &task.payload).await,
+        activation::CLAIM_TASK => activation::deliver_claim(app, &task.payload).await,
+        analytics::EVENT_TASK => analytics::deliver_event(app, &task.payload).await,
+        other => Err(format!("Unknown task kind: {}", other)),
+    }
+}
+
+/// Optimistic tasks carry the value they replaced under `previous`
+fn is_optimistic(task: &OutboxTask) -> bool {
+    task.payload.get("previous").is_some()
+}
+
+/// The server understood the write and refused it, so retrying won't help
+fn is_rejection(error: &str) -> bool {
+    matches!(
+        errors::classify(error),
+        ErrorCode::RequestRejected | ErrorCode::NotFound
+    )
+}
+
+/// Failures that say nothing about the task itself: no one is signed in,
+/// or the handler asked to wait. These don't count toward dead-lettering.
+fn is_postponed(error: &str) -> bool {
+    error.starts_with(DEFERRED)
+        || matches!(
+            errors::classify(error),
+            ErrorCode::NotSignedIn | ErrorCode::SessionExpired
+        )
+}
+
+/// Put back the local value an optimistic task replaced and tell the UI
+fn revert(app: &tauri::AppHandle, task: &OutboxTask, error: &str) -> Result<(), String> {
+    let (kind, id) = match task.kind.as_str() {
+        profile::PROFILE_TASK => ("profile", profile::revert_profile(app, &task.payload)?),
+        relationship_notes::GFT_SYNC_TASK => (
+            "relationship_note",
+            relationship_notes::revert_note(app, &task.payload)?,
+        ),
+        reminders::REMINDER_TASK => ("reminder", reminders::revert_reminder(app, &task.payload)?),
+        _ => return Ok(()),
+    };
+    app_log!("[Outbox] Reverted {} {} after {}", kind, id, task.id);
+    events::emit_app_event(
+        app,
+        AppEvent::WriteReverted(WriteRevertedEvent {
+            kind: kind.to_string(),
+            id,
+            previous: task.payload["previous"].clone(),
+            error: AppError::from(error.to_string()),
+        }),
+    );
+    Ok(())
+}
+
+/// When a newer optimistic write for the same entity is still queued, pass
+/// it this task's `previous`, so a rollback skips over the value this task
+/// failed to write. Returns false when there is no such write.
+fn hand_down_previous(conn: &rusqlite::Connection, task: &OutboxTask) -> rusqlite::Result<bool> {
+    let Some(entity) = &task.entity else {
+        return Ok(false);
+    };
+    let updated = conn.execute(
+        "UPDATE outbox SET payload = json_set(payload, '$.previous', json(?1))
+         WHERE id = (
+             SELECT id FROM outbox
+             WHERE entity = ?2 AND status != 'dead'
+               AND seq > (SELECT seq FROM outbox WHERE id = ?3)
+             ORDER BY seq ASC LIMIT 1
+         )
+           AND json_type(payload, '$.previous') IS NOT NULL",
+        rusqlite::params![task.payload["previous"].to_string(), entity, task.id],
+    )?;
+    if updated > 0 {
+        return Ok(true);
+    }
+    // A newer write that isn't optimistic still supersedes the local value
+    conn.query_row(
+        "SELECT EXISTS(
+             SELECT 1 FROM outbox
+             WHERE entity = ?1 AND status != 'dead'
+               AND seq > (SELECT seq FROM outbox WHERE id = ?2)
+         )",
+        rusqlite::params![entity, task.id],
+        |row| row.get(0),
+    )
+}
+
+fn backoff_secs(attempts: i64) -> i64 {
+    BASE_BACKOFF_SECS
+        .saturating_mul(1 << attempts.clamp(0, 20))
+        .min(MAX_BACKOFF_SECS)
+}
+
+/// Start every task that's due and at the head of its entity's queue.
+/// Each runs on its own, so a long upload doesn't hold up a bookmark.
+pub async fn process(app: &tauri::AppHandle) {
+    if let Err(e) = process_inner(app) {
+        app_log!("[Outbox] Processing failed: {}", e);
+    }
+}
+
+fn process_inner(app: &tauri::AppHandle) -> Result<(), String> {
+    if !network::is_online() {
+        return Ok(()); // Retried when the connectivity monitor reports back-online
+    }
+    let db = app.state::<Database>();
+    let due = db.with_conn(|conn| due_tasks(conn, now_secs()))?;
+
+    for task in due {
+        // Claim it, unless another pass got there first
+        let claimed = db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE outbox SET status = 'in_flight' WHERE id = ?1 AND status = 'pending'",
+                [&task.id],
+            )
+        })?;
+        if claimed == 0 {
+            continue;
+        }
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let result = deliver(&app, &task).await;
+            if let Err(e) = finish(&app, &task, result) {
+                app_log!("[Outbox] Failed to record {}: {}", task.id, e);
+            }
+            // Whatever was queued behind it may be due now
+            process(&app).await;
+        });
+    }
+    Ok(())
+}
+
+/// Pending tasks that are due and first in line for their entity. Anything
+/// earlier for the same entity, even in flight or waiting on a retry, holds
+/// the rest back; only a dead-lettered task stops counting.
+fn due_tasks(conn: &rusqlite::Connection, now: i64) -> rusqlite::Result<Vec<OutboxTask>> {
+    let mut stmt = conn.prepare(
+        "SELECT * FROM outbox o
+         WHERE status = 'pending' AND next_attempt_at <= ?1
+           AND NOT EXISTS (
+               SELECT 1 FROM outbox e
+               WHERE e.entity = o.entity AND e.seq < o.seq AND e.status != 'dead'
+           )
+         ORDER BY seq ASC",
+    )?;
+    let rows = stmt.query_map([now], row_to_task)?;
+    rows.collect()
+}
+
+/// Record a delivery attempt: remove the task, schedule a retry, or
+/// dead-letter it
+fn finish(
+    app: &tauri::AppHandle,
+    task: &OutboxTask,
+    result: Result<(), String>,
+) -> Result<(), String> {
+    let db = app.state::<Database>();
+    let e = match result {
+        Ok(()) => {
+            db.with_conn(|conn| conn.execute("DELETE FROM outbox WHERE id = ?1", [&task.id]))?;
+            return Ok(());
+        }
+        Err(e) => e,
+    };
+
+    if is_postponed(&e) {
+        db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE outbox SET status = 'pending', next_attempt_at = ?1, last_error = ?2
+                 WHERE id = ?3",
+                rusqlite::params![now_secs() + POLL_INTERVAL.as_secs() as i64, e, task.id],
+            )
+        })?;
+        app_log!("[Outbox] {} ({}) postponed: {}", task.id, task.kind, e);
+        return Ok(());
+    }
+
+    let attempts = task.attempts + 1;
+    let optimistic = is_optimistic(task);
+    let dead = attempts >= MAX_ATTEMPTS || (optimistic && is_rejection(&e));
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE outbox
+             SET status = ?1, attempts = ?2, next_attempt_at = ?3, last_error = ?4
+             WHERE id = ?5",
+            rusqlite::params![
+                if dead { "dead" } else { "pending" },
+                attempts,
+                now_secs() + backoff_secs(attempts),
+                e,
+                task.id
+            ],
+        )
+    })?;
+    if dead {
+        app_log!("[Outbox] {} ({}) dead-lettered: {}", task.id, task.kind, e);
+        events::emit_app_event(app, AppEvent::TaskDeadLettered(task.id.clone()));
+        if optimistic {
+            let reverted = match db.with_conn(|conn| hand_down_previous(conn, task)) {
+                // A newer write owns the local value now; it takes over ours
+                // as its rollback target instead
+                Ok(true) => Ok(()),
+                Ok(false) => revert(app, task, &e),
+                Err(e) => Err(e),
+            };
+            if let Err(revert_error) = reverted {
+                app_log!("[Outbox] Failed to revert {}: {}", task.id, revert_error);
+            }
+        }
+    } else {
+        app_log!(
+            "[Outbox] {} ({}) failed, attempt {}: {}",
+            task.id,
+            task.kind,
+            attempts,
+            e
+        );
+    }
+    Ok(())
+}
+
+pub fn status(db: &Database) -> Result<QueueStatus, String> {
+    let tasks = db.with_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT * FROM outbox ORDER BY seq ASC")?;
+        let rows = stmt.query_map([], row_to_task)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })?;
+    let count = |status: &str| tasks.iter().filter(|t| t.status == status).count();
+    Ok(QueueStatus {
+        pending: count("pending"),
+        in_flight: count("in_flight"),
+        dead: count("dead"),
+        tasks,
+    })
+}
+
+/// Queue work that's waiting on the server but has no task: changes made
+/// before the outbox existed, and uploads a crash interrupted while running
+/// outside it. Entities with any task, even a dead one, are left alone.
+fn adopt_orphans(app: &tauri::AppHandle) -> Result<usize, String> {
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let orphans: Vec<(&str, String, serde_json::Value)> = {
+            let mut orphans = Vec::new();
+            let mut stmt =
+                tx.prepare("SELECT item_id, removed FROM bookmarks WHERE sync_status = 'pending'")?;
+            for row in stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+            })? {
+                let (item_id, removed) = row?;
+                orphans.push((
+                    library::BOOKMARK_TASK,
+                    format!("bookmark:{}", item_id),
+                    serde_json::json!({ "itemId": item_id, "removed": removed }),
+                ));
+            }
+            let mut stmt = tx.prepare(
+                "SELECT id FROM relationship_notes
+                 WHERE sync_to_gft = 1 AND sync_status IN ('pending', 'failed')",
+            )?;
+            for id in stmt.query_map([], |row| row.get::<_, String>(0))? {
+                let id = id?;
+                orphans.push((
+                    relationship_notes::GFT_SYNC_TASK,
+                    format!("relationship_note:{}", id),
+                    serde_json::json!({ "noteId": id }),
+                ));
+            }
+            let mut stmt =
+                tx.prepare("SELECT id FROM uploads WHERE status IN ('pending', 'uploading')")?;
+            for id in stmt.query_map([], |row| row.get::<_, String>(0))? {
+                let id = id?;
+                orphans.push((
+                    uploads::UPLOAD_TASK,
+                    format!("upload:{}", id),
+                    serde_json::json!({ "uploadId": id }),
+                ));
+            }
+            orphans
+        };
+
+        let mut adopted = 0;
+        for (kind, entity, payload) in orphans {
+            let queued: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM outbox WHERE entity = ?1)",
+                [&entity],
+                |row| row.get(0),
+            )?;
+            if !queued {
+                insert_task(&tx, kind, Some(&entity), &payload, "pending")?;
+                adopted += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(adopted)
+    })
+}
+
+/// Requeue tasks a crash left in flight and adopt orphaned work, then keep
+/// draining the queue periodically and whenever the API becomes reachable
+/// again
+pub fn spawn_outbox(app: tauri::AppHandle) {
+    if let Err(e) = app.state::<Database>().with_conn(|conn| {
+        conn.execute(
+            "UPDATE outbox SET status = 'pending' WHERE status = 'in_flight'",
+            [],
+        )
+    }) {
+        app_log!("[Outbox] Failed to requeue in-flight tasks: {}", e);
+    }
+    match adopt_orphans(&app) {
+        Ok(0) => {}
+        Ok(adopted) => app_log!("[Outbox] Adopted {} unqueued changes", adopted),
+        Err(e) => app_log!("[Outbox] Failed to adopt unqueued changes: {}", e),
+    }
+
+    let handle = app.clone();
+    app.listen("back-online", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move { process(&handle).await });
+    });
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            process(&app).await;
+            tokio::time::sleep(network::poll_interval(POLL_INTERVAL)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::create_tables(&conn).unwrap();
+        add_ordering(&conn).unwrap();
+        conn
+    }
+
+    fn add(conn: &rusqlite::Connection, entity: Option<&str>) -> String {
+        insert_task(conn, "test", entity, &serde_json::Value::Null, "pending").unwrap()
+    }
+
+    fn due_ids(conn: &rusqlite::Connection) -> Vec<String> {
+        due_tasks(conn, i64::MAX)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect()
+    }
+
+    #[test]
+    fn only_the_head_of_each_entity_is_due() {
+        let conn = queue();
+        let first = add(&conn, Some("reminder:1"));
+        let _second = add(&conn, Some("reminder:1"));
+        let other = add(&conn, Some("reminder:2"));
+        let loose = add(&conn, None);
+        assert_eq!(due_ids(&conn), [first, other, loose]);
+    }
+
+    #[test]
+    fn a_retrying_head_holds_back_later_tasks() {
+        let conn = queue();
+        let first = add(&conn, Some("bookmark:1"));
+        add(&conn, Some("bookmark:1"));
+        conn.execute(
+            "UPDATE outbox SET next_attempt_at = ?1 WHERE id = ?2",
+            rusqlite::params![i64::MAX, first],
+        )
+        .unwrap();
+        assert!(due_tasks(&conn, now_secs()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn a_dead_head_releases_later_tasks() {
+        let conn = queue();
+        let first = add(&conn, Some("bookmark:1"));
+        let second = add(&conn, Some("bookmark:1"));
+        conn.execute("UPDATE outbox SET status = 'dead' WHERE id = ?1", [&first])
+            .unwrap();
+        assert_eq!(due_ids(&conn), [second]);
+    }
+
+    #[test]
+    fn a_newer_write_inherits_the_rollback_target() {
+        let conn = queue();
+        let failed = insert_task(
+            &conn,
+            "test",
+            Some("reminder:1"),
+            &serde_json::json!({ "previous": { "title": "original" } }),
+            "dead",
+        )
+        .unwrap();
+        let newer = insert_task(
+            &conn,
+            "test",
+            Some("reminder:1"),
+            &serde_json::json!({ "previous": { "title": "failed edit" } }),
+            "pending",
+        )
+        .unwrap();
+        let task = conn
+            .query_row("SELECT * FROM outbox WHERE id = ?1", [&failed], row_to_task)
+            .unwrap();
+
+        assert!(hand_down_previous(&conn, &task).unwrap());
+        let newer = conn
+            .query_row("SELECT * FROM outbox WHERE id = ?1", [&newer], row_to_task)
+            .unwrap();
+        assert_eq!(newer.payload["previous"]["title"], "original");
+    }
+
+    #[test]
+    fn the_last_write_is_rolled_back_itself() {
+        let conn = queue();
+        let only = insert_task(
+            &conn,
+            "test",
+            Some("reminder:1"),
+            &serde_json::json!({ "previous": null }),
+            "dead",
+        )
+        .unwrap();
+        let task = conn
+            .query_row("SELECT * FROM outbox WHERE id = ?1", [&only], row_to_task)
+            .unwrap();
+        assert!(!hand_down_previous(&conn, &task).unwrap());
+    }
+
+    #[test]
+    fn sign_in_and_deferral_failures_are_postponed() {
+        assert!(is_postponed("Not signed in"));
+        assert!(is_postponed(&format!("{}: large upload", DEFERRED)));
+        assert!(!is_postponed("Server error 500 Internal Server Error"));
+    }
+}
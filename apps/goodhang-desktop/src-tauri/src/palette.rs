@@ -0,0 +1,298 @@
+use serde::Serialize;
+use std::sync::RwLock;
+use tauri::Manager;
+
+use crate::commands::{hangs, listings};
+use crate::db::{now_secs, Database};
+use crate::logging::app_log;
+use crate::search_index;
+
+/// How long a built index is served before a search triggers a rebuild
+const STALE_AFTER_SECS: i64 = 2 * 60;
+const MAX_JOURNAL_ENTRIES: usize = 500;
+
+/// Things the palette can run; the frontend maps each id to its handler
+const ACTIONS: &[(&str, &str, &str)] = &[
+    ("quick_capture", "Quick capture", "note jot capture"),
+    ("new_journal_entry", "New journal entry", "write diary"),
+    ("propose_hang", "Propose a hang", "meet schedule invite"),
+    ("start_focus", "Start focus session", "dnd do not disturb"),
+    ("end_focus", "End focus session", "dnd stop"),
+    ("summarize_journal", "Summarize journal", "recap week"),
+    ("record_voice_memo", "Record voice memo", "audio dictate"),
+    ("lock_app", "Lock app", "security"),
+    ("export_data", "Export my data", "download backup zip"),
+    ("check_for_updates", "Check for updates", "upgrade version"),
+    ("sign_out", "Sign out", "logout log out"),
+];
+
+/// Settings pages, keyed by route
+const SETTINGS: &[(&str, &str, &str)] = &[
+    ("/settings/account", "Account", "profile delete"),
+    (
+        "/settings/notifications",
+        "Notifications",
+        "alerts reminders",
+    ),
+    (
+        "/settings/privacy",
+        "Privacy & consent",
+        "consents analytics location",
+    ),
+    (
+        "/settings/storage",
+        "Storage & retention",
+        "cleanup disk cache",
+    ),
+    ("/settings/security", "Security", "lock biometric idle"),
+    (
+        "/settings/quick-capture",
+        "Quick capture shortcut",
+        "hotkey keyboard",
+    ),
+    ("/settings/language", "Language", "locale translation"),
+    (
+        "/settings/integrations",
+        "Integrations",
+        "slack discord email",
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Action,
+    Person,
+    Hang,
+    JournalEntry,
+    Setting,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PaletteItem {
+    pub kind: ItemKind,
+    pub id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PaletteHit {
+    #[serde(flatten)]
+    pub item: PaletteItem,
+    pub score: i64,
+}
+
+/// An item with its text pre-lowercased so searching doesn't allocate per item
+struct Entry {
+    item: PaletteItem,
+    title: Vec<char>,
+    keywords: Vec<char>,
+}
+
+impl Entry {
+    fn new(item: PaletteItem, keywords: &str) -> Self {
+        Self {
+            title: item.title.to_lowercase().chars().collect(),
+            keywords: keywords.to_lowercase().chars().collect(),
+            item,
+        }
+    }
+}
+
+/// In-memory index behind the command palette
+#[derive(Default)]
+pub struct Palette {
+    index: RwLock<(Vec<Entry>, i64)>,
+}
+
+impl Palette {
+    fn is_stale(&self) -> bool {
+        self.index
+            .read()
+            .map(|index| now_secs() - index.1 > STALE_AFTER_SECS)
+            .unwrap_or(true)
+    }
+
+    pub fn search(&self, query: &str, limit: usize) -> Vec<PaletteHit> {
+        let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+        let Ok(index) = self.index.read() else {
+            return Vec::new();
+        };
+
+        let mut hits: Vec<PaletteHit> = index
+            .0
+            .iter()
+            .filter_map(|entry| {
+                let score = if query.is_empty() {
+                    // No query: list actions first, in their declared order
+                    (entry.item.kind == ItemKind::Action).then_some(0)?
+                } else {
+                    let title = fuzzy_score(&query, &entry.title);
+                    // Keyword matches count, but never beat a title match
+                    let keywords = fuzzy_score(&query, &entry.keywords).map(|s| s / 2);
+                    title.max(keywords)?
+                };
+                Some(PaletteHit {
+                    item: entry.item.clone(),
+                    score: score + kind_bonus(entry.item.kind),
+                })
+            })
+            .collect();
+        // Stable sort keeps index order among equal scores
+        hits.sort_by(|a, b| b.score.cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+fn kind_bonus(kind: ItemKind) -> i64 {
+    match kind {
+        ItemKind::Action => 8,
+        ItemKind::Person => 6,
+        ItemKind::Hang => 4,
+        ItemKind::Setting => 2,
+        ItemKind::JournalEntry => 0,
+    }
+}
+
+/// Score `query` as a subsequence of `candidate` (both lowercased), favouring
+/// contiguous runs, word starts and early matches. `None` if it doesn't match.
+fn fuzzy_score(query: &[char], candidate: &[char]) -> Option<i64> {
+    if query.len() > candidate.len() {
+        return None;
+    }
+    if let Some(pos) = candidate.windows(query.len()).position(|w| w == query) {
+        let at_word_start = pos == 0 || !candidate[pos - 1].is_alphanumeric();
+        let bonus = if pos == 0 {
+            60
+        } else if at_word_start {
+            40
+        } else {
+            20
+        };
+        return Some(100 + bonus - pos.min(20) as i64 - candidate.len().min(40) as i64 / 4);
+    }
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for &c in query {
+        let offset = candidate[next..].iter().position(|&x| x == c)?;
+        let pos = next + offset;
+        score += if previous == Some(pos.wrapping_sub(1)) {
+            6
+        } else if pos == 0 || !candidate[pos - 1].is_alphanumeric() {
+            4
+        } else {
+            1
+        };
+        score -= offset.min(5) as i64;
+        previous = Some(pos);
+        next = pos + 1;
+    }
+    Some(score.max(1))
+}
+
+fn static_entries(table: &[(&str, &str, &str)], kind: ItemKind) -> Vec<Entry> {
+    table
+        .iter()
+        .map(|(id, title, keywords)| {
+            Entry::new(
+                PaletteItem {
+                    kind,
+                    id: id.to_string(),
+                    title: title.to_string(),
+                    subtitle: None,
+                },
+                keywords,
+            )
+        })
+        .collect()
+}
+
+/// Rebuild the index from cached relationships, hangs and journal entries.
+/// A source that fails is left out rather than failing the whole build.
+pub async fn rebuild(app: &tauri::AppHandle) {
+    let db = app.state::<Database>();
+    let mut entries = static_entries(ACTIONS, ItemKind::Action);
+
+    match listings::cached_relationships(app, &db).await {
+        Ok(relationships) => entries.extend(relationships.into_iter().map(|r| {
+            Entry::new(
+                PaletteItem {
+                    kind: ItemKind::Person,
+                    id: r.id,
+                    title: r.name,
+                    subtitle: r.relationship_type,
+                },
+                "",
+            )
+        })),
+        Err(e) => app_log!("[Palette] Skipping people: {}", e),
+    }
+
+    match hangs::cached_upcoming(&db) {
+        Ok(upcoming) => entries.extend(upcoming.into_iter().map(|hang| {
+            let names: Vec<&str> = hang
+                .participants
+                .iter()
+                .filter_map(|p| p.name.as_deref())
+                .collect();
+            Entry::new(
+                PaletteItem {
+                    kind: ItemKind::Hang,
+                    title: hang.title.clone().unwrap_or_else(|| "Hang".to_string()),
+                    subtitle: hang.location.clone(),
+                    id: hang.id,
+                },
+                &names.join(" "),
+            )
+        })),
+        Err(e) => app_log!("[Palette] Skipping hangs: {}", e),
+    }
+
+    match search_index::titles(&db, "journal", MAX_JOURNAL_ENTRIES) {
+        Ok(journal) => entries.extend(journal.into_iter().map(|(id, title)| {
+            Entry::new(
+                PaletteItem {
+                    kind: ItemKind::JournalEntry,
+                    id,
+                    title,
+                    subtitle: None,
+                },
+                "journal",
+            )
+        })),
+        Err(e) => app_log!("[Palette] Skipping journal: {}", e),
+    }
+
+    entries.extend(static_entries(SETTINGS, ItemKind::Setting));
+
+    let palette = app.state::<Palette>();
+    if let Ok(mut index) = palette.index.write() {
+        *index = (entries, now_secs());
+    }
+}
+
+/// Drop every indexed person, hang and journal entry, e.g. on logout. The
+/// next search finds the index stale and rebuilds it.
+pub fn clear(app: &tauri::AppHandle) {
+    if let Ok(mut index) = app.state::<Palette>().index.write() {
+        *index = (Vec::new(), 0);
+    }
+}
+
+/// Rebuild in the background if the index is out of date, so searches never
+/// wait on the network
+pub fn refresh_if_stale(app: &tauri::AppHandle) {
+    if app.state::<Palette>().is_stale() {
+        // Mark fresh now so concurrent searches don't each start a rebuild
+        if let Ok(mut index) = app.state::<Palette>().index.write() {
+            index.1 = now_secs();
+        }
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move { rebuild(&app).await });
+    }
+}
@@ -0,0 +1,217 @@
+//! Where local data lives. Normally the OS app-data, cache and log
+//! directories; in portable mode (a `portable` file beside the executable,
+//! or `--portable` on the command line) the stores, database, logs and
+//! caches all go in a `GoodHangData` folder next to it instead, for running
+//! from a USB stick or on a machine where the profile isn't writable.
+//! Outside portable mode the data and caches can be moved to a folder of
+//! the user's choosing (see `commands::data_location`), recorded in a
+//! location file that stays in the OS data directory.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tauri::Manager;
+
+use crate::logging::app_log;
+
+const PORTABLE_FLAG_FILE: &str = "portable";
+const PORTABLE_ARG: &str = "--portable";
+const PORTABLE_DIR: &str = "GoodHangData";
+const LOCATION_FILE: &str = "data-location.json";
+
+static PORTABLE_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+// Read once: a new location takes effect on the next launch
+static CHOSEN_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+// Held shared while anything writes into the data or cache folders, and
+// exclusively while a move copies them. Set once a move is committed: the
+// app is about to restart, and a write now would land in the old copy.
+static WRITES: RwLock<bool> = RwLock::new(false);
+
+/// Contents of the location file
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Location {
+    /// Folder holding `data/` and `cache/`; absent for the OS directories
+    pub root: Option<PathBuf>,
+    /// What a move copied from, removed once the app has started from the
+    /// new location
+    #[serde(default)]
+    pub leftover: Vec<PathBuf>,
+}
+
+/// The folder holding the executable, or for a macOS bundle the folder
+/// holding the `.app`, so data isn't written inside the signed bundle
+fn exe_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    match dir
+        .ancestors()
+        .find(|a| a.extension().is_some_and(|ext| ext == "app"))
+    {
+        Some(bundle) => bundle.parent().map(Path::to_path_buf),
+        None => Some(dir.to_path_buf()),
+    }
+}
+
+fn detect() -> Option<PathBuf> {
+    let dir = exe_dir()?;
+    let requested =
+        std::env::args().any(|arg| arg == PORTABLE_ARG) || dir.join(PORTABLE_FLAG_FILE).exists();
+    requested.then(|| dir.join(PORTABLE_DIR))
+}
+
+/// The portable data folder, when running in portable mode
+pub fn portable_root() -> Option<&'static Path> {
+    PORTABLE_ROOT.get_or_init(detect).as_deref()
+}
+
+pub fn is_portable() -> bool {
+    portable_root().is_some()
+}
+
+/// Where webviews keep their profile in portable mode, so cookies and
+/// local storage travel with the data instead of landing on the host.
+/// WKWebView on macOS has no per-app location and ignores this.
+pub fn webview_data_dir() -> Option<PathBuf> {
+    portable_root().map(|root| root.join("webview"))
+}
+
+fn os_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))
+}
+
+fn os_cache_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to resolve cache dir: {}", e))
+}
+
+pub(crate) fn read_location(app: &tauri::AppHandle) -> Location {
+    os_data_dir(app)
+        .ok()
+        .and_then(|dir| std::fs::read(dir.join(LOCATION_FILE)).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn write_location(app: &tauri::AppHandle, location: &Location) -> Result<(), String> {
+    let dir = os_data_dir(app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let json = serde_json::to_vec_pretty(location)
+        .map_err(|e| format!("Failed to serialize data location: {}", e))?;
+    let partial = dir.join(LOCATION_FILE).with_extension("partial");
+    std::fs::write(&partial, json).map_err(|e| format!("Failed to write data location: {}", e))?;
+    std::fs::rename(&partial, dir.join(LOCATION_FILE))
+        .map_err(|e| format!("Failed to write data location: {}", e))
+}
+
+/// Hold off a move while writing into the data or cache folders. Fails
+/// once a move has been committed, until the restart that follows it.
+pub fn hold_writes() -> Result<RwLockReadGuard<'static, bool>, String> {
+    let moved = WRITES
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if *moved {
+        return Err("Failed to write: data is moving to a new folder".to_string());
+    }
+    Ok(moved)
+}
+
+/// Wait for writers in progress and keep new ones out while a move copies.
+/// Set the guard to true once the move is committed.
+pub(crate) fn pause_writes() -> RwLockWriteGuard<'static, bool> {
+    WRITES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The folder chosen with `set_data_directory`, if there is one and it's
+/// there. A missing folder (an unplugged drive, say) falls back to the OS
+/// directories rather than failing to start.
+pub fn chosen_root(app: &tauri::AppHandle) -> Option<&'static Path> {
+    CHOSEN_ROOT
+        .get_or_init(|| {
+            let root = read_location(app).root?;
+            if root.is_dir() {
+                return Some(root);
+            }
+            app_log!(
+                "[Paths] Data folder {} is missing, using the default",
+                root.display()
+            );
+            None
+        })
+        .as_deref()
+}
+
+/// The data and cache directories for a data folder at `root`, or the OS
+/// ones for `None`
+pub(crate) fn dirs_at(
+    app: &tauri::AppHandle,
+    root: Option<&Path>,
+) -> Result<(PathBuf, PathBuf), String> {
+    match root {
+        Some(root) => Ok((root.join("data"), root.join("cache"))),
+        None => Ok((os_data_dir(app)?, os_cache_dir(app)?)),
+    }
+}
+
+fn current_root(app: &tauri::AppHandle) -> Option<&'static Path> {
+    portable_root().or_else(|| chosen_root(app))
+}
+
+/// Stores, the database and recorded assets
+pub fn data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    dirs_at(app, current_root(app)).map(|(data, _)| data)
+}
+
+/// Anything that can be downloaded or rebuilt again
+pub fn cache_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    dirs_at(app, current_root(app)).map(|(_, cache)| cache)
+}
+
+pub fn log_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    match portable_root() {
+        Some(root) => Ok(root.join("logs")),
+        None => app
+            .path()
+            .app_log_dir()
+            .map_err(|e| format!("Failed to resolve log dir: {}", e)),
+    }
+}
+
+/// Remove what the last move copied from, now that the app has started from
+/// the new location. Left alone if the new folder went missing, since the
+/// old copy is then the only one.
+pub fn finish_move(app: &tauri::AppHandle) {
+    let mut location = read_location(app);
+    if location.leftover.is_empty() || (location.root.is_some() && chosen_root(app).is_none()) {
+        return;
+    }
+
+    for path in &location.leftover {
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+        if let Err(e) = removed.or_else(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Ok(()),
+            _ => Err(e),
+        }) {
+            app_log!("[Paths] Failed to remove {}: {}", path.display(), e);
+        }
+        // The old `data/` or `cache/` folder, once empty
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::remove_dir(parent);
+        }
+    }
+    location.leftover.clear();
+    match write_location(app, &location) {
+        Ok(()) => app_log!("[Paths] Removed data left at the previous location"),
+        Err(e) => app_log!("[Paths] {}", e),
+    }
+}
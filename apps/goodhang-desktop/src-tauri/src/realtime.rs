@@ -0,0 +1,120 @@
+use serde::Deserialize;
+use std::time::Duration;
+use tauri::Manager;
+use tokio::sync::Notify;
+
+use crate::api::{Api, ApiRequest};
+use crate::commands::auth::load_session;
+use crate::commands::{billing, matching, reactions};
+use crate::http::SseParser;
+use crate::logging::app_log;
+use crate::network;
+
+// Reconnect backoff after the stream drops or fails to open
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// How often to re-check while signed out or offline
+const IDLE_INTERVAL: Duration = Duration::from_secs(15);
+
+static RECONNECT: Notify = Notify::const_new();
+
+/// Envelope for every message on the realtime stream
+#[derive(Debug, Deserialize)]
+struct RealtimeMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+/// Route one server push to the module that owns it
+fn dispatch(app: &tauri::AppHandle, message: RealtimeMessage) {
+    match message.kind.as_str() {
+        "match_found" => matching::handle_match_found(app, message.data),
+        "subscription_changed" => billing::handle_subscription_changed(app, message.data),
+        "reaction_received" => reactions::handle_reaction_received(app, message.data),
+        "ping" => {}
+        other => app_log!("[Realtime] Ignoring unknown message type: {}", other),
+    }
+}
+
+/// Read the stream until it closes. Returns Ok once at least one chunk was
+/// received, so a healthy connection that later drops resets the backoff.
+async fn run_stream(app: &tauri::AppHandle, token: &str) -> Result<(), String> {
+    let request = ApiRequest::get("/api/realtime")
+        .bearer(token)
+        .header("Accept", "text/event-stream");
+    let mut response = app.state::<Api>().stream(request).await?;
+    app_log!("[Realtime] Connected");
+
+    let mut parser = SseParser::default();
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk?,
+            _ = RECONNECT.notified() => {
+                app_log!("[Realtime] Reconnecting with the current session");
+                return Ok(());
+            }
+        };
+        let done = chunk.is_none();
+        let payloads = match chunk {
+            Some(bytes) => parser.push(&bytes),
+            None => parser.finish().into_iter().collect(),
+        };
+
+        for payload in payloads {
+            match serde_json::from_str::<RealtimeMessage>(&payload) {
+                Ok(message) => dispatch(app, message),
+                Err(e) => app_log!("[Realtime] Skipping unparseable message: {}", e),
+            }
+        }
+
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+/// Drop the stream and reopen it with whatever session is stored now, e.g.
+/// after a restore replaced the account's data
+pub fn reconnect() {
+    RECONNECT.notify_one();
+}
+
+/// Wait `duration`, or less if a reconnect is requested
+async fn pause(duration: Duration) {
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => {}
+        _ = RECONNECT.notified() => {}
+    }
+}
+
+/// Hold a server-sent event stream open while signed in and online,
+/// reconnecting with exponential backoff
+pub fn spawn_realtime(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            let token = match load_session(&app) {
+                Ok(Some(session)) if network::is_online() => session.token,
+                _ => {
+                    pause(IDLE_INTERVAL).await;
+                    continue;
+                }
+            };
+
+            match run_stream(&app, &token).await {
+                Ok(()) => {
+                    app_log!("[Realtime] Stream closed, reconnecting");
+                    backoff = MIN_BACKOFF;
+                }
+                Err(e) => {
+                    app_log!("[Realtime] Stream failed: {}", e);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+            pause(backoff).await;
+        }
+    });
+}
@@ -0,0 +1,356 @@
+//! Risk/opportunity scoring for entities in desktop-side briefs. Adapters
+//! are looked up by entity type and classify into the same strategic
+//! quadrants (and 50-point thresholds) as the backend's customer scoring.
+//! Signal points and day thresholds are weights the user can tune.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::LazyLock;
+
+// Scores at or above these are "high", matching QUADRANT_THRESHOLDS upstream
+const RISK_HIGH: f64 = 50.0;
+const OPPORTUNITY_HIGH: f64 = 50.0;
+
+/// Facts a brief knows about an entity; anything unknown is `None`/zero
+#[derive(Debug, Clone, Default)]
+pub struct ScoringInput {
+    pub days_since_contact: Option<i64>,
+    pub note_count: usize,
+    pub shared_context_count: usize,
+    pub talking_point_count: usize,
+    /// 0-100 compatibility with the user
+    pub compatibility: Option<f64>,
+    /// 0-100 AI Recommendation Index
+    pub ari_score: Option<f64>,
+    pub recent_news_count: usize,
+}
+
+/// One reason a score went up, with its contribution in points
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Signal {
+    pub key: String,
+    pub label: String,
+    pub points: f64,
+}
+
+impl Signal {
+    fn new(key: &str, label: impl Into<String>, points: f64) -> Self {
+        Self {
+            key: key.to_string(),
+            label: label.into(),
+            points,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Quadrant {
+    /// High risk, high opportunity
+    Invest,
+    /// Low risk, high opportunity
+    Expand,
+    /// High risk, low opportunity
+    Rescue,
+    /// Low risk, low opportunity
+    Maintain,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Classification {
+    #[serde(rename = "entityType")]
+    pub entity_type: String,
+    pub quadrant: Quadrant,
+    #[serde(rename = "riskScore")]
+    pub risk_score: f64,
+    #[serde(rename = "opportunityScore")]
+    pub opportunity_score: f64,
+    #[serde(rename = "riskSignals")]
+    pub risk_signals: Vec<Signal>,
+    #[serde(rename = "opportunitySignals")]
+    pub opportunity_signals: Vec<Signal>,
+}
+
+/// Tunable numbers for one adapter, by key: signal points and thresholds
+pub type Weights = BTreeMap<String, f64>;
+
+fn total(signals: &[Signal]) -> f64 {
+    signals
+        .iter()
+        .map(|s| s.points)
+        .sum::<f64>()
+        .clamp(0.0, 100.0)
+}
+
+fn weights_from(defaults: &[(&str, f64)]) -> Weights {
+    defaults.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+}
+
+fn w(weights: &Weights, key: &str) -> f64 {
+    weights.get(key).copied().unwrap_or(0.0)
+}
+
+pub trait ScoringAdapter: Send + Sync {
+    fn entity_type(&self) -> &'static str;
+    fn default_weights(&self) -> Weights;
+    fn risk_signals(&self, input: &ScoringInput, weights: &Weights) -> Vec<Signal>;
+    fn opportunity_signals(&self, input: &ScoringInput, weights: &Weights) -> Vec<Signal>;
+
+    /// Quadrant from the summed signal points. `overrides` replace default
+    /// weights; unknown keys are ignored.
+    fn classify(&self, input: &ScoringInput, overrides: &Weights) -> Classification {
+        let mut weights = self.default_weights();
+        for (key, value) in overrides {
+            if let Some(weight) = weights.get_mut(key) {
+                *weight = *value;
+            }
+        }
+        let risk_signals = self.risk_signals(input, &weights);
+        let opportunity_signals = self.opportunity_signals(input, &weights);
+        let risk_score = total(&risk_signals);
+        let opportunity_score = total(&opportunity_signals);
+        let quadrant = match (
+            risk_score >= RISK_HIGH,
+            opportunity_score >= OPPORTUNITY_HIGH,
+        ) {
+            (true, true) => Quadrant::Invest,
+            (false, true) => Quadrant::Expand,
+            (true, false) => Quadrant::Rescue,
+            (false, false) => Quadrant::Maintain,
+        };
+        Classification {
+            entity_type: self.entity_type().to_string(),
+            quadrant,
+            risk_score,
+            opportunity_score,
+            risk_signals,
+            opportunity_signals,
+        }
+    }
+}
+
+/// People the user knows: risk is drift ("going cold"), opportunity is
+/// common ground
+pub struct RelationshipScoringAdapter;
+
+impl ScoringAdapter for RelationshipScoringAdapter {
+    fn entity_type(&self) -> &'static str {
+        "person"
+    }
+
+    fn default_weights(&self) -> Weights {
+        weights_from(&[
+            ("cooling_days", 30.0),
+            ("lapsed_days", 90.0),
+            ("recent_days", 14.0),
+            ("contact_cooling", 25.0),
+            ("contact_lapsed", 45.0),
+            ("no_contact_history", 20.0),
+            ("no_notes", 10.0),
+            ("no_shared_context", 15.0),
+            ("shared_context_each", 10.0),
+            ("shared_context_max", 30.0),
+            ("high_compatibility_min", 70.0),
+            ("high_compatibility", 30.0),
+            ("recent_contact", 20.0),
+            ("talking_points_min", 3.0),
+            ("talking_points", 10.0),
+        ])
+    }
+
+    fn risk_signals(&self, input: &ScoringInput, weights: &Weights) -> Vec<Signal> {
+        let mut signals = Vec::new();
+        match input.days_since_contact {
+            Some(days) if days as f64 > w(weights, "lapsed_days") => signals.push(Signal::new(
+                "contact_lapsed",
+                format!("No contact in {} days", days),
+                w(weights, "contact_lapsed"),
+            )),
+            Some(days) if days as f64 > w(weights, "cooling_days") => signals.push(Signal::new(
+                "contact_cooling",
+                format!("No contact in {} days", days),
+                w(weights, "contact_cooling"),
+            )),
+            Some(_) => {}
+            None => signals.push(Signal::new(
+                "no_contact_history",
+                "No recorded contact",
+                w(weights, "no_contact_history"),
+            )),
+        }
+        if input.note_count == 0 {
+            signals.push(Signal::new(
+                "no_notes",
+                "No notes about them yet",
+                w(weights, "no_notes"),
+            ));
+        }
+        if input.shared_context_count == 0 {
+            signals.push(Signal::new(
+                "no_shared_context",
+                "Nothing in common found",
+                w(weights, "no_shared_context"),
+            ));
+        }
+        signals
+    }
+
+    fn opportunity_signals(&self, input: &ScoringInput, weights: &Weights) -> Vec<Signal> {
+        let mut signals = Vec::new();
+        if input.shared_context_count > 0 {
+            signals.push(Signal::new(
+                "shared_context",
+                format!("{} things in common", input.shared_context_count),
+                (input.shared_context_count as f64 * w(weights, "shared_context_each"))
+                    .min(w(weights, "shared_context_max")),
+            ));
+        }
+        if let Some(compatibility) = input
+            .compatibility
+            .filter(|c| *c >= w(weights, "high_compatibility_min"))
+        {
+            signals.push(Signal::new(
+                "high_compatibility",
+                format!("{:.0}% compatible", compatibility),
+                w(weights, "high_compatibility"),
+            ));
+        }
+        if input
+            .days_since_contact
+            .is_some_and(|days| days as f64 <= w(weights, "recent_days"))
+        {
+            signals.push(Signal::new(
+                "recent_contact",
+                "In touch recently",
+                w(weights, "recent_contact"),
+            ));
+        }
+        if input.talking_point_count as f64 >= w(weights, "talking_points_min") {
+            signals.push(Signal::new(
+                "talking_points",
+                "Plenty to talk about",
+                w(weights, "talking_points"),
+            ));
+        }
+        signals
+    }
+}
+
+/// Companies: risk is low AI visibility and silence, opportunity is
+/// momentum and fit
+pub struct AlignmentScoringAdapter;
+
+impl ScoringAdapter for AlignmentScoringAdapter {
+    fn entity_type(&self) -> &'static str {
+        "company"
+    }
+
+    fn default_weights(&self) -> Weights {
+        weights_from(&[
+            ("low_ari_below", 30.0),
+            ("low_ari", 30.0),
+            ("no_news", 15.0),
+            ("quiet_days", 60.0),
+            ("no_recent_contact", 20.0),
+            ("high_ari_min", 60.0),
+            ("high_ari", 30.0),
+            ("news_min", 3.0),
+            ("in_the_news", 20.0),
+            ("shared_context_each", 10.0),
+            ("shared_context_max", 30.0),
+            ("recent_days", 30.0),
+            ("recent_contact", 20.0),
+        ])
+    }
+
+    fn risk_signals(&self, input: &ScoringInput, weights: &Weights) -> Vec<Signal> {
+        let mut signals = Vec::new();
+        if let Some(ari) = input.ari_score.filter(|s| *s < w(weights, "low_ari_below")) {
+            signals.push(Signal::new(
+                "low_ari",
+                format!("Low AI visibility ({:.0})", ari),
+                w(weights, "low_ari"),
+            ));
+        }
+        if input.recent_news_count == 0 {
+            signals.push(Signal::new(
+                "no_news",
+                "No recent news",
+                w(weights, "no_news"),
+            ));
+        }
+        if !input
+            .days_since_contact
+            .is_some_and(|days| days as f64 <= w(weights, "quiet_days"))
+        {
+            signals.push(Signal::new(
+                "no_recent_contact",
+                "No recent contact",
+                w(weights, "no_recent_contact"),
+            ));
+        }
+        signals
+    }
+
+    fn opportunity_signals(&self, input: &ScoringInput, weights: &Weights) -> Vec<Signal> {
+        let mut signals = Vec::new();
+        if let Some(ari) = input.ari_score.filter(|s| *s >= w(weights, "high_ari_min")) {
+            signals.push(Signal::new(
+                "high_ari",
+                format!("Strong AI visibility ({:.0})", ari),
+                w(weights, "high_ari"),
+            ));
+        }
+        if input.recent_news_count as f64 >= w(weights, "news_min") {
+            signals.push(Signal::new(
+                "in_the_news",
+                format!("{} recent news stories", input.recent_news_count),
+                w(weights, "in_the_news"),
+            ));
+        }
+        if input.shared_context_count > 0 {
+            signals.push(Signal::new(
+                "shared_context",
+                format!("{} connections in common", input.shared_context_count),
+                (input.shared_context_count as f64 * w(weights, "shared_context_each"))
+                    .min(w(weights, "shared_context_max")),
+            ));
+        }
+        if input
+            .days_since_contact
+            .is_some_and(|days| days as f64 <= w(weights, "recent_days"))
+        {
+            signals.push(Signal::new(
+                "recent_contact",
+                "In touch recently",
+                w(weights, "recent_contact"),
+            ));
+        }
+        signals
+    }
+}
+
+static ADAPTERS: LazyLock<HashMap<&'static str, Box<dyn ScoringAdapter>>> = LazyLock::new(|| {
+    let adapters: Vec<Box<dyn ScoringAdapter>> = vec![
+        Box::new(RelationshipScoringAdapter),
+        Box::new(AlignmentScoringAdapter),
+    ];
+    adapters
+        .into_iter()
+        .map(|adapter| (adapter.entity_type(), adapter))
+        .collect()
+});
+
+/// The adapter registered for `entity_type`, if any
+pub fn adapter(entity_type: &str) -> Option<&'static dyn ScoringAdapter> {
+    ADAPTERS.get(entity_type).map(|adapter| adapter.as_ref())
+}
+
+/// Classify with the adapter registered for `entity_type`, if any
+pub fn classify(
+    entity_type: &str,
+    input: &ScoringInput,
+    overrides: &Weights,
+) -> Option<Classification> {
+    adapter(entity_type).map(|adapter| adapter.classify(input, overrides))
+}
@@ -0,0 +1,154 @@
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::db::Database;
+use crate::logging::app_log;
+
+/// Scopes the full-text index knows about
+pub const SCOPES: &[&str] = &[
+    "assessment",
+    "journal",
+    "relationship_note",
+    "context_file",
+    "voice_memo",
+    "chat",
+];
+
+#[derive(Debug, Serialize, Deserialize, specta::Type)]
+pub struct SearchHit {
+    pub scope: String,
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    pub title: String,
+    pub snippet: String,
+    pub rank: f64,
+}
+
+pub fn validate_scope(scope: &str) -> Result<(), String> {
+    if SCOPES.contains(&scope) {
+        Ok(())
+    } else {
+        Err(format!("Unknown search scope: {}", scope))
+    }
+}
+
+/// Turn free-form user input into a safe FTS5 query: every term is quoted
+/// (so punctuation can't break the syntax) and prefix-matched
+fn to_fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|t| t.replace('"', ""))
+        .filter(|t| !t.is_empty())
+        .map(|t| format!("\"{}\"*", t))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+pub fn upsert(
+    db: &Database,
+    scope: &str,
+    source_id: &str,
+    title: &str,
+    body: &str,
+) -> Result<(), String> {
+    validate_scope(scope)?;
+    db.with_conn(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM search_index WHERE scope = ?1 AND source_id = ?2",
+            [scope, source_id],
+        )?;
+        tx.execute(
+            "INSERT INTO search_index (scope, source_id, title, body) VALUES (?1, ?2, ?3, ?4)",
+            [scope, source_id, title, body],
+        )?;
+        tx.commit()
+    })
+}
+
+pub fn remove(db: &Database, scope: &str, source_id: &str) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "DELETE FROM search_index WHERE scope = ?1 AND source_id = ?2",
+            [scope, source_id],
+        )
+    })?;
+    Ok(())
+}
+
+/// Best-effort upsert for callers that shouldn't fail because indexing did
+pub fn upsert_quietly(
+    app: &tauri::AppHandle,
+    scope: &str,
+    source_id: &str,
+    title: &str,
+    body: &str,
+) {
+    let db = app.state::<Database>();
+    if let Err(e) = upsert(&db, scope, source_id, title, body) {
+        app_log!("[Search] Failed to index {}:{}: {}", scope, source_id, e);
+    }
+}
+
+/// Titles in a scope, most recently indexed first
+pub fn titles(db: &Database, scope: &str, limit: usize) -> Result<Vec<(String, String)>, String> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT source_id, title FROM search_index WHERE scope = ?1
+             ORDER BY rowid DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![scope, limit as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })
+}
+
+pub fn query(
+    db: &Database,
+    input: &str,
+    scopes: &[String],
+    limit: usize,
+) -> Result<Vec<SearchHit>, String> {
+    let Some(fts_query) = to_fts_query(input) else {
+        return Ok(Vec::new());
+    };
+
+    // Filter by scope in SQL so LIMIT counts only hits the caller wants
+    let scope_filter = if scopes.is_empty() {
+        String::new()
+    } else {
+        let placeholders = (0..scopes.len())
+            .map(|i| format!("?{}", i + 3))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("AND scope IN ({})", placeholders)
+    };
+    let sql = format!(
+        "SELECT scope, source_id, title,
+                snippet(search_index, 3, '<mark>', '</mark>', '…', 12),
+                bm25(search_index, 0.0, 0.0, 5.0, 1.0) AS rank
+         FROM search_index
+         WHERE search_index MATCH ?1 {}
+         ORDER BY rank
+         LIMIT ?2",
+        scope_filter
+    );
+    let mut params = vec![Value::from(fts_query), Value::from(limit as i64)];
+    params.extend(scopes.iter().cloned().map(Value::from));
+
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+            Ok(SearchHit {
+                scope: row.get(0)?,
+                source_id: row.get(1)?,
+                title: row.get(2)?,
+                snippet: row.get(3)?,
+                rank: row.get(4)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    })
+}
@@ -0,0 +1,167 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::api::{Api, ApiRequest};
+use crate::commands::auth::{
+    handle_device_revoked, load_device_registration, load_session, save_device_registration,
+    save_session, SessionData,
+};
+use crate::db::now_secs;
+use crate::events::{self, AppEvent};
+use crate::logging::{app_log, redact};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+// Refresh this long before the token expires
+const REFRESH_LEAD_SECS: i64 = 5 * 60;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SessionExpiryEvent {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtClaims {
+    exp: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefreshResponse {
+    token: String,
+    #[serde(default, rename = "sessionId")]
+    session_id: Option<String>,
+    #[serde(default, rename = "expiresIn")]
+    expires_in: Option<i64>,
+    #[serde(default, rename = "refreshToken")]
+    refresh_token: Option<String>,
+}
+
+/// Read `exp` from a JWT payload without verifying the signature; we only
+/// use it to schedule refreshes, never to trust the token
+pub fn jwt_expiry(token: &str) -> Option<i64> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    serde_json::from_slice::<JwtClaims>(&bytes).ok()?.exp
+}
+
+/// Server-provided expiry when we have one, otherwise the token's own `exp`
+pub fn session_expiry(session: &SessionData) -> Option<i64> {
+    session.expires_at.or_else(|| jwt_expiry(&session.token))
+}
+
+/// The API answers refreshes from a revoked device with 401/403 and a
+/// `device_revoked` code
+pub fn is_revocation(status: u16, body: &str) -> bool {
+    (status == 401 || status == 403) && body.contains("device_revoked")
+}
+
+/// Exchange the device refresh token for a new session token
+pub(crate) async fn refresh_session(
+    app: &tauri::AppHandle,
+    current: &SessionData,
+) -> Result<(), String> {
+    let registration = load_device_registration(app)?
+        .ok_or_else(|| "No device registration to refresh with".to_string())?;
+
+    let response = app
+        .state::<Api>()
+        .send(
+            ApiRequest::post("/api/auth/refresh").json(&serde_json::json!({
+                "refreshToken": registration.refresh_token,
+                "userId": registration.user_id,
+            }))?,
+        )
+        .await?;
+
+    if !response.status.is_success() {
+        let status = response.status;
+        let body = response.text();
+        if is_revocation(status.as_u16(), &body) {
+            handle_device_revoked(app).await?;
+            return Err("Device has been revoked".to_string());
+        }
+        return Err(format!("Server error {}: {}", status, redact(&body)));
+    }
+
+    let refreshed: RefreshResponse = response.json("/api/auth/refresh")?;
+
+    save_session(
+        app,
+        &SessionData {
+            user_id: current.user_id.clone(),
+            session_id: refreshed
+                .session_id
+                .unwrap_or_else(|| current.session_id.clone()),
+            token: refreshed.token,
+            expires_at: refreshed.expires_in.map(|ttl| now_secs() + ttl),
+        },
+    )
+    .await?;
+
+    // Rotating refresh tokens: persist the replacement
+    if let Some(refresh_token) = refreshed.refresh_token {
+        save_device_registration(
+            app,
+            &crate::commands::auth::DeviceRegistration {
+                refresh_token,
+                ..registration
+            },
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Watch the stored session and refresh it shortly before expiry, emitting
+/// `session-expiring`, `session-refreshed`, and `session-expired`
+pub fn spawn_session_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // Token we've already announced as expired, so we only emit once
+        let mut expired_token: Option<String> = None;
+
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let Ok(Some(session)) = load_session(&app) else {
+                continue;
+            };
+            let Some(expires_at) = session_expiry(&session) else {
+                continue;
+            };
+            let remaining = expires_at - now_secs();
+            if remaining > REFRESH_LEAD_SECS {
+                continue;
+            }
+
+            let event = SessionExpiryEvent {
+                user_id: session.user_id.clone(),
+                expires_at,
+            };
+
+            if remaining > 0 {
+                events::emit_app_event(&app, AppEvent::SessionExpiring(event.clone()));
+            }
+
+            match refresh_session(&app, &session).await {
+                Ok(()) => {
+                    app_log!("[Session] Session refreshed ahead of expiry");
+                    expired_token = None;
+                    events::emit_app_event(&app, AppEvent::SessionRefreshed);
+                }
+                Err(e) => {
+                    app_log!("[Session] Refresh failed: {}", e);
+                    if remaining <= 0 && expired_token.as_deref() != Some(session.token.as_str()) {
+                        expired_token = Some(session.token.clone());
+                        events::emit_app_event(&app, AppEvent::SessionExpired(event));
+                    }
+                }
+            }
+        }
+    });
+}
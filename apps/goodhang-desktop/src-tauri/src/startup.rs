@@ -0,0 +1,110 @@
+//! Opening local data and starting the background work that depends on it.
+//! Normally this happens during setup; in portable mode the data key is
+//! sealed with a passphrase, so it waits for `unlock_portable_data` and
+//! commands that need the data are refused until then.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::errors::{AppError, ErrorCode};
+use crate::logging::app_log;
+use crate::{
+    api, browser_bridge, commands, crypto, db, deep_link, errors, image_cache, migrations, network,
+    outbox, palette, paths, realtime, session, store_integrity, user_data,
+};
+
+/// Commands the webview may call before local data is open
+const AVAILABLE_WHILE_CLOSED: &[&str] = &["get_portable_status", "unlock_portable_data"];
+
+static OPEN: AtomicBool = AtomicBool::new(false);
+// Held while opening, so two unlocks can't both run it
+static OPENING: Mutex<()> = Mutex::new(());
+
+pub fn is_open() -> bool {
+    OPEN.load(Ordering::SeqCst)
+}
+
+/// Refuse a command that needs local data while it's still locked
+pub fn check(command: &str) -> Result<(), AppError> {
+    if is_open() || AVAILABLE_WHILE_CLOSED.contains(&command) {
+        return Ok(());
+    }
+    Err(AppError::new(
+        ErrorCode::DataLocked,
+        format!("Portable data is locked: {}", command),
+    ))
+}
+
+/// Load the at-rest key (already unlocked in portable mode), open the
+/// stores and database, and start everything that reads them. Does nothing
+/// once the data is open.
+pub fn open_local_data(app: &tauri::AppHandle) -> Result<(), String> {
+    let _opening = OPENING
+        .lock()
+        .map_err(|_| "Startup lock poisoned".to_string())?;
+    if is_open() {
+        return Ok(());
+    }
+
+    // Load the at-rest key, then open the local database before any
+    // command can reach for it
+    let data_dir = paths::data_dir(app)?;
+    crypto::init_data_key(&data_dir)?;
+    // Before the store plugin first reads a store, so a corrupt one is
+    // replaced rather than loaded
+    if let Err(e) = store_integrity::verify_stores(app) {
+        app_log!("[StoreIntegrity] {}", e);
+    }
+    commands::auth::encrypt_legacy_store(app)?;
+    if let Err(e) = migrations::migrate_stores(app) {
+        app_log!("[Migrations] {}", e);
+    }
+    // The database lives in the signed-in account's namespace
+    let user_dir = user_data::init(app)?;
+    app.manage(db::Database::open(&user_dir, &crypto::data_key_hex()?)?);
+    image_cache::allow_asset_access(app);
+    // Only once the database has opened from a moved data folder is
+    // the copy it came from safe to remove
+    paths::finish_move(app);
+    errors::load_locale(app);
+    api::load_base_url(app);
+    OPEN.store(true, Ordering::SeqCst);
+
+    commands::voice_memos::spawn_memo_sync(app.clone());
+    commands::library::spawn_bookmark_sync(app.clone());
+    session::spawn_session_monitor(app.clone());
+    commands::security::spawn_idle_monitor(app.clone());
+    network::spawn_connectivity_monitor(app.clone());
+    network::spawn_compatibility_check(app.clone());
+    image_cache::spawn_prefetch(app.clone());
+    realtime::spawn_realtime(app.clone());
+    outbox::spawn_outbox(app.clone());
+    commands::trash::finish_pending_deletes(app);
+    commands::slack::spawn_presence_monitor(app.clone());
+    commands::discord::spawn_discord_presence(app.clone());
+    commands::email_signals::spawn_email_signal_scan(app.clone());
+    commands::reminders::spawn_reminder_delivery(app.clone());
+    commands::reassessment::spawn_reassessment_reminders(app.clone());
+    commands::retention::spawn_retention_cleanup(app.clone());
+    browser_bridge::spawn_bridge_server(app.clone());
+    browser_bridge::install_host_manifests();
+    commands::quick_capture::register_shortcut(app);
+    palette::refresh_if_stale(app);
+    commands::appearance::spawn_appearance_monitor(app.clone());
+    commands::sync_policy::spawn_sync_policy_monitor(app.clone());
+
+    // Handle deep links
+    #[cfg(desktop)]
+    {
+        let handle = app.clone();
+        app.deep_link()
+            .on_open_url(move |event: tauri_plugin_deep_link::OpenUrlEvent| {
+                for url in event.urls() {
+                    deep_link::handle_url(&handle, &url);
+                }
+            });
+    }
+    Ok(())
+}
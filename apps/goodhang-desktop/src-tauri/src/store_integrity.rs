@@ -0,0 +1,171 @@
+//! Startup integrity check for the key-value stores. Each save carries a
+//! checksum (see `crypto::serialize_store`); a store that fails it, or no
+//! longer decodes, is moved aside to `quarantine/` and replaced by the last
+//! snapshot that did, so one torn write can't leave the app unable to read
+//! its session until the file is deleted by hand.
+
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::commands::backup::STORES;
+use crate::crypto;
+use crate::db::now_secs;
+use crate::events::{self, AppEvent};
+use crate::logging::app_log;
+use crate::paths;
+
+pub(crate) const SNAPSHOT_DIR: &str = "snapshots";
+pub(crate) const QUARANTINE_DIR: &str = "quarantine";
+
+/// A store that was found corrupt at startup and what replaced it
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct StoreRecoveredEvent {
+    /// The store file name, e.g. `auth.json`
+    pub store: String,
+    pub reason: String,
+    /// Where the corrupt file was moved
+    #[serde(rename = "quarantinedTo")]
+    pub quarantined_to: String,
+    /// False when there was no good snapshot and the store starts empty
+    #[serde(rename = "restoredFromSnapshot")]
+    pub restored_from_snapshot: bool,
+}
+
+enum Health {
+    Intact,
+    Corrupt(String),
+    /// The checksum matches but the data key doesn't: the file is whole, so
+    /// it's left alone rather than thrown away
+    WrongKey(String),
+}
+
+// Held until the webview has loaded, since events emitted during setup
+// have no listener yet
+static RECOVERED: Mutex<Vec<StoreRecoveredEvent>> = Mutex::new(Vec::new());
+
+fn check(bytes: &[u8]) -> Health {
+    if bytes.is_empty() {
+        return Health::Corrupt("Store is empty".to_string());
+    }
+    if let Err(e) = crypto::verify_checksum(bytes) {
+        return Health::Corrupt(e);
+    }
+    match crypto::deserialize_store(bytes) {
+        Ok(_) => Health::Intact,
+        Err(e) if crypto::has_checksum(bytes) && crypto::is_encrypted(bytes) => {
+            Health::WrongKey(e.to_string())
+        }
+        Err(e) => Health::Corrupt(e.to_string()),
+    }
+}
+
+/// Write through a temporary file so a crash mid-copy can't leave a
+/// truncated snapshot behind
+fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, bytes).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o600));
+    }
+    std::fs::rename(&partial, path).map_err(|e| format!("Failed to write snapshot: {}", e))
+}
+
+/// Copy `name` to the snapshot directory if it's intact on disk. Called
+/// after saves, so the snapshot tracks the last good write.
+pub fn snapshot(app: &tauri::AppHandle, name: &str) {
+    let result = paths::data_dir(app).and_then(|dir| {
+        let Ok(bytes) = std::fs::read(dir.join(name)) else {
+            return Ok(());
+        };
+        match check(&bytes) {
+            Health::Intact => write_atomically(&dir.join(SNAPSHOT_DIR).join(name), &bytes),
+            _ => Ok(()),
+        }
+    });
+    if let Err(e) = result {
+        app_log!("[StoreIntegrity] Failed to snapshot {}: {}", name, e);
+    }
+}
+
+fn recover(dir: &Path, name: &str, reason: String) -> Result<StoreRecoveredEvent, String> {
+    let path = dir.join(name);
+    let quarantined = dir
+        .join(QUARANTINE_DIR)
+        .join(format!("{}.{}.corrupt", name, now_secs()));
+    std::fs::create_dir_all(dir.join(QUARANTINE_DIR))
+        .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+    std::fs::rename(&path, &quarantined)
+        .map_err(|e| format!("Failed to quarantine {}: {}", name, e))?;
+
+    let snapshot = std::fs::read(dir.join(SNAPSHOT_DIR).join(name))
+        .ok()
+        .filter(|bytes| matches!(check(bytes), Health::Intact));
+    let restored = match snapshot {
+        Some(bytes) => {
+            write_atomically(&path, &bytes)?;
+            true
+        }
+        None => false,
+    };
+
+    app_log!(
+        "[StoreIntegrity] {} was corrupt ({}), quarantined to {}; {}",
+        name,
+        reason,
+        quarantined.display(),
+        if restored {
+            "restored last good snapshot"
+        } else {
+            "no good snapshot, starting empty"
+        }
+    );
+    Ok(StoreRecoveredEvent {
+        store: name.to_string(),
+        reason,
+        quarantined_to: quarantined.to_string_lossy().into_owned(),
+        restored_from_snapshot: restored,
+    })
+}
+
+/// Check every store before the plugin first loads it, quarantining and
+/// replacing any that are corrupt and snapshotting the rest. Must run after
+/// the data key is loaded.
+pub fn verify_stores(app: &tauri::AppHandle) -> Result<(), String> {
+    let dir = paths::data_dir(app)?;
+    for name in STORES {
+        let Ok(bytes) = std::fs::read(dir.join(name)) else {
+            continue; // Not written yet
+        };
+        match check(&bytes) {
+            Health::Intact => snapshot(app, name),
+            Health::WrongKey(e) => {
+                app_log!("[StoreIntegrity] {} is intact but unreadable: {}", name, e);
+            }
+            Health::Corrupt(reason) => {
+                let recovered = recover(&dir, name, reason)?;
+                if let Ok(mut pending) = RECOVERED.lock() {
+                    pending.push(recovered);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Emit `store-recovered` for each recovery made at startup, once
+pub fn announce_recoveries(app: &tauri::AppHandle) {
+    let recovered = RECOVERED
+        .lock()
+        .map(|mut pending| std::mem::take(&mut *pending))
+        .unwrap_or_default();
+    for event in recovered {
+        events::emit_app_event(app, AppEvent::StoreRecovered(event));
+    }
+}
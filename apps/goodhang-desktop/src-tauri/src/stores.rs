@@ -0,0 +1,109 @@
+//! Serialized access to the key-value stores. The plugin makes each `set`
+//! atomic, but nothing stops two commands interleaving multi-key updates
+//! with each other's saves, or two saves writing the same file at once.
+//! `update` runs a change under a per-store async lock and batches saves,
+//! so writers that land together share one save covering all their changes;
+//! `save` serializes the file write for callers that can't await.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::{paths, store_integrity};
+
+pub type AppStore = Store<tauri::Wry>;
+
+/// App-wide settings; account-scoped keys in it are prefixed per user
+pub const SETTINGS_STORE: &str = "settings.json";
+
+#[derive(Default)]
+struct Generations {
+    /// Changes applied to the in-memory store
+    written: u64,
+    /// Changes known to be on disk
+    saved: u64,
+}
+
+#[derive(Default)]
+struct Slot {
+    /// Held across a change, and across the save that persists it
+    batch: tokio::sync::Mutex<Generations>,
+    /// Held only while the file is written, by async and sync callers alike
+    file: Mutex<()>,
+}
+
+static SLOTS: Mutex<Option<HashMap<String, Arc<Slot>>>> = Mutex::new(None);
+
+fn slot(name: &str) -> Arc<Slot> {
+    let mut slots = SLOTS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    slots
+        .get_or_insert_with(HashMap::new)
+        .entry(name.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Open `name` in the data directory, which is beside the executable in
+/// portable mode rather than wherever the plugin would resolve it
+pub fn open(app: &tauri::AppHandle, name: &str) -> Result<Arc<AppStore>, String> {
+    app.store(paths::data_dir(app)?.join(name))
+        .map_err(|e| format!("Failed to open store: {}", e))
+}
+
+fn write_file(
+    app: &tauri::AppHandle,
+    name: &str,
+    store: &AppStore,
+    slot: &Slot,
+) -> Result<(), String> {
+    let _file = slot
+        .file
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let _writes = paths::hold_writes()?;
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    store_integrity::snapshot(app, name);
+    Ok(())
+}
+
+/// Write `name` to disk now. For sync callers; prefer `update` where the
+/// caller can await.
+pub fn save(app: &tauri::AppHandle, name: &str) -> Result<(), String> {
+    let store = open(app, name)?;
+    write_file(app, name, &store, &slot(name))
+}
+
+/// Apply `change` to `name` with no other `update` interleaved, then make
+/// sure it's on disk. `change` should validate before it sets anything: an
+/// error leaves whatever it already set in memory, to go out with the next
+/// save.
+pub async fn update<T>(
+    app: &tauri::AppHandle,
+    name: &str,
+    change: impl FnOnce(&AppStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let slot = slot(name);
+    let store = open(app, name)?;
+    let (value, generation) = {
+        let mut generations = slot.batch.lock().await;
+        let value = change(&store)?;
+        generations.written += 1;
+        (value, generations.written)
+    };
+
+    // Let writers already queued apply their changes first, so one save
+    // covers them all and theirs finds nothing left to do
+    tokio::task::yield_now().await;
+    let mut generations = slot.batch.lock().await;
+    if generations.saved >= generation {
+        return Ok(value);
+    }
+    let target = generations.written;
+    write_file(app, name, &store, &slot)?;
+    generations.saved = target;
+    Ok(value)
+}
@@ -0,0 +1,63 @@
+//! AI output cached against a hash of its inputs: callers hash what the
+//! summary was generated from and only call the model when that changes.
+
+use sha2::{Digest, Sha256};
+
+use crate::db::{now_secs, Database};
+
+/// Stable hash of the inputs; parts are length-prefixed so moving text
+/// between parts changes the hash
+pub fn input_hash<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// The cached output for `kind`/`subject_id` if it was made from `hash`
+pub fn get(db: &Database, kind: &str, subject_id: &str, hash: &str) -> Option<String> {
+    db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT output FROM summary_cache
+             WHERE kind = ?1 AND subject_id = ?2 AND input_hash = ?3",
+            [kind, subject_id, hash],
+            |row| row.get(0),
+        )
+    })
+    .ok()
+}
+
+/// Replace the cached output for a subject
+pub fn put(
+    db: &Database,
+    kind: &str,
+    subject_id: &str,
+    hash: &str,
+    output: &str,
+) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO summary_cache (kind, subject_id, input_hash, output, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(kind, subject_id) DO UPDATE SET
+                input_hash = excluded.input_hash,
+                output = excluded.output,
+                created_at = excluded.created_at",
+            rusqlite::params![kind, subject_id, hash, output, now_secs()],
+        )
+    })?;
+    Ok(())
+}
+
+/// Drop a subject's cached output so the next request regenerates it
+pub fn invalidate(db: &Database, kind: &str, subject_id: &str) -> Result<(), String> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "DELETE FROM summary_cache WHERE kind = ?1 AND subject_id = ?2",
+            [kind, subject_id],
+        )
+    })?;
+    Ok(())
+}
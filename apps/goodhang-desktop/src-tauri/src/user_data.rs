@@ -0,0 +1,323 @@
+//! Per-account namespaces for local data. Each signed-in user gets their own
+//! database, recorded assets and image cache under `users/<namespace>/`, and
+//! their own prefix on account-scoped settings keys, so signing in as
+//! someone else on a shared machine never shows the previous account's
+//! data. Device-wide settings (locale, appearance, API URL, sync policy)
+//! stay global.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::Manager;
+
+use crate::commands::auth::{self, load_device_registration, load_session};
+use crate::commands::{
+    account, billing, discord, email_signals, location, profile, reassessment, retention,
+    signal_weights, slack, user_status,
+};
+use crate::crypto;
+use crate::db::{Database, DB_FILENAME};
+use crate::logging::app_log;
+use crate::{browser_bridge, consent, image_cache, palette, paths, stores};
+
+pub(crate) const USERS_DIR: &str = "users";
+/// Namespace for whatever is used while nobody is signed in
+const SIGNED_OUT: &str = "local";
+/// Namespaces of accounts that have signed in on this device and not been
+/// removed; anything else under `users/` is an orphan
+const KNOWN_KEY: &str = "user_namespaces";
+
+/// Settings keys that belong to an account rather than the device
+const ACCOUNT_KEYS: &[&str] = &[
+    consent::CONSENTS_KEY,
+    browser_bridge::BRIDGE_KEY,
+    slack::SLACK_KEY,
+    email_signals::EMAIL_SIGNALS_KEY,
+    signal_weights::WEIGHTS_KEY,
+    account::DELETION_KEY,
+    location::LOCATION_KEY,
+    location::LAST_LOCATION_KEY,
+    discord::DISCORD_KEY,
+    reassessment::REASSESSMENT_KEY,
+    retention::RETENTION_KEY,
+];
+
+static ACTIVE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Directory-safe namespace for a user id; `None` is the signed-out space
+fn namespace(user_id: Option<&str>) -> String {
+    match user_id {
+        Some(id) => format!(
+            "u-{}",
+            id.chars()
+                .map(|c| match c {
+                    'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+                    _ => '_',
+                })
+                .collect::<String>()
+        ),
+        None => SIGNED_OUT.to_string(),
+    }
+}
+
+fn active_user() -> Option<String> {
+    ACTIVE.read().ok().and_then(|active| active.clone())
+}
+
+fn active_namespace() -> String {
+    namespace(active_user().as_deref())
+}
+
+/// `name` scoped to the signed-in account, for account-level settings
+pub fn key(name: &str) -> String {
+    format!("{}:{}", active_namespace(), name)
+}
+
+/// The namespace a prefixed settings key belongs to, if it's prefixed
+fn key_namespace(key: &str) -> Option<&str> {
+    key.split_once(':')
+        .map(|(prefix, _)| prefix)
+        .filter(|prefix| *prefix == SIGNED_OUT || prefix.starts_with("u-"))
+}
+
+fn roots(app: &tauri::AppHandle) -> Result<[PathBuf; 2], String> {
+    Ok([
+        paths::data_dir(app)?.join(USERS_DIR),
+        paths::cache_dir(app)?.join(USERS_DIR),
+    ])
+}
+
+fn ensure(dir: PathBuf) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// The signed-in account's data directory: its database and recorded assets
+pub fn data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let [data, _] = roots(app)?;
+    ensure(data.join(active_namespace()))
+}
+
+/// The signed-in account's cache directory
+pub fn cache_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let [_, cache] = roots(app)?;
+    ensure(cache.join(active_namespace()))
+}
+
+fn known(app: &tauri::AppHandle) -> Vec<String> {
+    stores::open(app, stores::SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(KNOWN_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn set_known(app: &tauri::AppHandle, namespaces: &[String]) -> Result<(), String> {
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    store.set(KNOWN_KEY, serde_json::json!(namespaces));
+    stores::save(app, stores::SETTINGS_STORE)
+}
+
+/// Whoever the stored credentials belong to
+fn stored_user(app: &tauri::AppHandle) -> Option<String> {
+    load_session(app)
+        .ok()
+        .flatten()
+        .map(|s| s.user_id)
+        .or_else(|| {
+            load_device_registration(app)
+                .ok()
+                .flatten()
+                .map(|r| r.user_id)
+        })
+}
+
+fn move_if_present(from: &Path, to: &Path) -> Result<(), String> {
+    if !from.exists() || to.exists() {
+        return Ok(());
+    }
+    std::fs::rename(from, to).map_err(|e| format!("Failed to move {}: {}", from.display(), e))
+}
+
+/// Move data written before namespacing, when there was only ever one
+/// account, into the namespace of the account it belonged to
+fn adopt_legacy(app: &tauri::AppHandle) -> Result<(), String> {
+    let data_root = paths::data_dir(app)?;
+    let target = data_dir(app)?;
+    let legacy_db = data_root.join(DB_FILENAME);
+    if !legacy_db.exists() || target.join(DB_FILENAME).exists() {
+        return Ok(());
+    }
+
+    for suffix in ["", "-wal", "-shm"] {
+        let name = format!("{}{}", DB_FILENAME, suffix);
+        move_if_present(&data_root.join(&name), &target.join(&name))?;
+    }
+    for dir in auth::ASSET_DIRS {
+        move_if_present(&data_root.join(dir), &target.join(dir))?;
+    }
+    // Caches rebuild themselves, so the old shared ones are just dropped
+    if let Ok(cache_root) = paths::cache_dir(app) {
+        for dir in [image_cache::IMAGE_DIR, profile::PHOTO_DIR] {
+            let _ = std::fs::remove_dir_all(cache_root.join(dir));
+        }
+    }
+
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    for name in ACCOUNT_KEYS {
+        if let Some(value) = store.get(name) {
+            store.set(key(name), value);
+            let _ = store.delete(name);
+        }
+    }
+    stores::save(app, stores::SETTINGS_STORE)?;
+
+    app_log!("[UserData] Moved existing data into {}", active_namespace());
+    Ok(())
+}
+
+fn remove_namespace(app: &tauri::AppHandle, namespace: &str) -> Result<(), String> {
+    for root in roots(app)? {
+        let dir = root.join(namespace);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+        }
+    }
+    let store = stores::open(app, stores::SETTINGS_STORE)?;
+    let keys: Vec<String> = store
+        .keys()
+        .into_iter()
+        .filter(|k| key_namespace(k) == Some(namespace))
+        .collect();
+    if !keys.is_empty() {
+        for name in keys {
+            let _ = store.delete(name);
+        }
+        stores::save(app, stores::SETTINGS_STORE)?;
+    }
+    Ok(())
+}
+
+/// Remove every namespace, on disk or in settings, that isn't the signed-in
+/// account's, a known account's, or the signed-out space
+fn remove_orphans(app: &tauri::AppHandle) -> Result<(), String> {
+    let mut keep = known(app);
+    keep.push(active_namespace());
+    keep.push(SIGNED_OUT.to_string());
+
+    let mut orphans: Vec<String> = Vec::new();
+    for root in roots(app)? {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            orphans.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    if let Ok(store) = stores::open(app, stores::SETTINGS_STORE) {
+        orphans.extend(
+            store
+                .keys()
+                .iter()
+                .filter_map(|k| key_namespace(k))
+                .map(str::to_string),
+        );
+    }
+    orphans.sort();
+    orphans.dedup();
+
+    for orphan in orphans.iter().filter(|ns| !keep.contains(ns)) {
+        remove_namespace(app, orphan)?;
+        app_log!("[UserData] Removed orphaned namespace {}", orphan);
+    }
+    Ok(())
+}
+
+/// Pick the namespace for whoever is signed in, moving pre-namespace data
+/// into it and clearing out orphans. Runs before the database is opened;
+/// returns the directory to open it in.
+pub fn init(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let user = stored_user(app);
+    *ACTIVE
+        .write()
+        .map_err(|_| "User lock poisoned".to_string())? = user.clone();
+
+    if let Err(e) = adopt_legacy(app) {
+        app_log!("[UserData] Failed to move existing data: {}", e);
+    }
+    if user.is_some() {
+        let mut namespaces = known(app);
+        if !namespaces.contains(&active_namespace()) {
+            namespaces.push(active_namespace());
+            set_known(app, &namespaces)?;
+        }
+    }
+    if let Err(e) = remove_orphans(app) {
+        app_log!("[UserData] Failed to remove orphaned namespaces: {}", e);
+    }
+    data_dir(app)
+}
+
+/// Switch local data to `user_id`'s namespace (or the signed-out one),
+/// reopening the database there. A no-op when it's already active.
+pub fn activate(app: &tauri::AppHandle, user_id: Option<&str>) -> Result<(), String> {
+    if active_user().as_deref() == user_id {
+        return Ok(());
+    }
+
+    let [data, _] = roots(app)?;
+    let target = namespace(user_id);
+    app.state::<Database>()
+        .switch_to(&data.join(&target), &crypto::data_key_hex()?)?;
+    *ACTIVE
+        .write()
+        .map_err(|_| "User lock poisoned".to_string())? = user_id.map(str::to_string);
+
+    if user_id.is_some() {
+        let mut namespaces = known(app);
+        if !namespaces.contains(&target) {
+            namespaces.push(target.clone());
+            set_known(app, &namespaces)?;
+        }
+    }
+    clear_cached(app);
+    image_cache::allow_asset_access(app);
+    app_log!("[UserData] Switched to namespace {}", target);
+    Ok(())
+}
+
+/// Drop in-memory copies of the active account's data, so nothing read
+/// before an account switch or a restore is shown afterwards
+pub fn clear_cached(app: &tauri::AppHandle) {
+    user_status::clear_status_cache();
+    billing::clear_subscription_cache();
+    slack::clear_presence_cache();
+    location::clear_location_cache();
+    palette::clear(app);
+}
+
+/// Delete everything stored for `user_id` on this device, e.g. when the
+/// device is revoked. The signed-out space takes over if it was active.
+pub fn remove_user(app: &tauri::AppHandle, user_id: &str) -> Result<(), String> {
+    if active_user().as_deref() == Some(user_id) {
+        activate(app, None)?;
+    }
+    let target = namespace(Some(user_id));
+    let namespaces: Vec<String> = known(app).into_iter().filter(|ns| *ns != target).collect();
+    set_known(app, &namespaces)?;
+    remove_namespace(app, &target)?;
+    app_log!("[UserData] Removed namespace {}", target);
+    Ok(())
+}
+
+/// After a full wipe and key rotation: every other namespace's database is
+/// now unreadable, so drop them all and start the signed-out space afresh
+pub fn remove_all(app: &tauri::AppHandle) -> Result<(), String> {
+    if active_user().is_some() {
+        remove_namespace(app, SIGNED_OUT)?;
+        activate(app, None)?;
+    }
+    set_known(app, &[])?;
+    remove_orphans(app)
+}
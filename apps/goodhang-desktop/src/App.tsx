@@ -1,7 +1,7 @@
 import { Routes, Route, Navigate } from 'react-router-dom';
-import { useEffect } from 'react';
+import { useEffect, useState } from 'react';
 import { listen } from '@tauri-apps/api/event';
-import { useNavigate } from 'react-router-dom';
+import { useLocation, useNavigate } from 'react-router-dom';
 import ActivatePage from './routes/activate';
 import SignupPage from './routes/signup';
 import SigninPage from './routes/signin';
@@ -16,6 +16,9 @@ import RenubuChatPage from './routes/founder-os/renubu-chat-router';
 import VoiceTestPage from './routes/founder-os/voice-test';
 import ProductionPage from './routes/founder-os/production';
 import GoodHangAssessmentPage from './routes/goodhang/assessment';
+import QuickCapturePage from './routes/quick-capture';
+import UnlockDataPage from './routes/unlock-data';
+import { getPortableStatus, type PortableStatus } from './lib/tauri';
 import { useAuthStore } from './lib/stores/auth';
 import { useUserStatusStore, getRecommendedRoute } from './lib/stores/user';
 
@@ -24,13 +27,27 @@ import './lib/dev-utils';
 
 function App() {
   const navigate = useNavigate();
+  const location = useLocation();
   const { isAuthenticated, checkSession, token, userId, product, loading: authLoading } = useAuthStore();
   const { status, fetchStatus, loading: statusLoading } = useUserStatusStore();
+  // Portable data stays locked until its passphrase is entered
+  const [portable, setPortable] = useState<PortableStatus | null>(null);
 
-  // Check for existing session on mount
   useEffect(() => {
-    checkSession();
-  }, [checkSession]);
+    getPortableStatus()
+      .then(setPortable)
+      .catch((err) => {
+        console.error('[App] Failed to get portable status:', err);
+        setPortable({ locked: false, hasPassphrase: false });
+      });
+  }, []);
+
+  // Check for existing session once local data is open
+  useEffect(() => {
+    if (portable && !portable.locked) {
+      checkSession();
+    }
+  }, [portable, checkSession]);
 
   // Fetch user status when authenticated AND we have a token
   useEffect(() => {
@@ -83,7 +100,28 @@ function App() {
   };
 
   // Show loading while auth or status is loading
-  const isLoading = authLoading || (isAuthenticated && token && statusLoading);
+  const isLoading = !portable || authLoading || (isAuthenticated && token && statusLoading);
+
+  // The quick capture window is its own small frameless window
+  if (location.pathname === '/quick-capture') {
+    return <QuickCapturePage />;
+  }
+
+  if (portable?.locked) {
+    return (
+      <div className="min-h-screen bg-gh-dark-900">
+        <div className="titlebar">
+          <div className="titlebar-drag" />
+        </div>
+        <main className="pt-8">
+          <UnlockDataPage
+            hasPassphrase={portable.hasPassphrase}
+            onUnlocked={() => setPortable({ locked: false, hasPassphrase: true })}
+          />
+        </main>
+      </div>
+    );
+  }
 
   return (
     <div className="min-h-screen bg-gh-dark-900">
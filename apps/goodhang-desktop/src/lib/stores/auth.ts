@@ -136,7 +136,10 @@ export const useAuthStore = create<AuthState>((set) => ({
       }
     } catch (err) {
       console.error('[Auth] Failed to check session:', err);
-      set({ isAuthenticated: false, loading: false, error: String(err) });
+      // Commands reject with an AppError ({ code, message, detail })
+      const message =
+        err && typeof err === 'object' && 'message' in err ? String(err.message) : String(err);
+      set({ isAuthenticated: false, loading: false, error: message });
     }
   },
 
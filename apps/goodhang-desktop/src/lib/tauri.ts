@@ -216,3 +216,37 @@ export async function clearDeviceRegistration(): Promise<void> {
     throw err;
   }
 }
+
+// Quick capture - the small window opened by the global shortcut
+export type CaptureRoute =
+  | { route: 'journal' }
+  | { route: 'relationship_note'; relationshipId: string; name: string; body: string }
+  | { route: 'reminder'; title: string; dueAt: number };
+
+export type CaptureResult = CaptureRoute & { id: string };
+
+export async function quickCaptureClipboard(): Promise<string | null> {
+  return invoke('quick_capture_clipboard');
+}
+
+export async function previewQuickCapture(text: string): Promise<CaptureRoute> {
+  return invoke('preview_quick_capture', { text });
+}
+
+export async function submitQuickCapture(text: string): Promise<CaptureResult> {
+  return invoke('submit_quick_capture', { text });
+}
+
+// Portable mode - data beside the executable, sealed with a passphrase
+export interface PortableStatus {
+  locked: boolean;
+  hasPassphrase: boolean;
+}
+
+export async function getPortableStatus(): Promise<PortableStatus> {
+  return invoke('get_portable_status');
+}
+
+export async function unlockPortableData(passphrase: string): Promise<void> {
+  return invoke('unlock_portable_data', { passphrase });
+}
@@ -0,0 +1,94 @@
+import { useEffect, useState } from 'react';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import {
+  quickCaptureClipboard,
+  previewQuickCapture,
+  submitQuickCapture,
+  type CaptureRoute,
+} from '@/lib/tauri';
+
+function describeRoute(route: CaptureRoute): string {
+  switch (route.route) {
+    case 'relationship_note':
+      return `Note for ${route.name}`;
+    case 'reminder':
+      return `Reminder · ${new Date(route.dueAt * 1000).toLocaleString()}`;
+    default:
+      return 'Journal';
+  }
+}
+
+export default function QuickCapturePage() {
+  const [text, setText] = useState('');
+  const [route, setRoute] = useState<CaptureRoute | null>(null);
+  const [submitting, setSubmitting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  // Prefill from the clipboard when the user allowed clipboard access
+  useEffect(() => {
+    quickCaptureClipboard()
+      .then((clip) => {
+        if (clip) setText((current) => current || clip);
+      })
+      .catch(() => {});
+  }, []);
+
+  // Show where the capture will go while typing
+  useEffect(() => {
+    if (!text.trim()) {
+      setRoute(null);
+      return;
+    }
+    const timer = setTimeout(() => {
+      previewQuickCapture(text)
+        .then(setRoute)
+        .catch(() => setRoute(null));
+    }, 150);
+    return () => clearTimeout(timer);
+  }, [text]);
+
+  const hide = () => {
+    setText('');
+    setError(null);
+    getCurrentWindow().hide();
+  };
+
+  const submit = async () => {
+    if (!text.trim() || submitting) return;
+    setSubmitting(true);
+    setError(null);
+    try {
+      await submitQuickCapture(text);
+      setText('');
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  return (
+    <div className="flex h-screen flex-col justify-center gap-2 rounded-xl border border-gh-dark-700 bg-gh-dark-900 px-4">
+      <input
+        autoFocus
+        value={text}
+        onChange={(e) => setText(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === 'Enter') {
+            e.preventDefault();
+            submit();
+          } else if (e.key === 'Escape') {
+            hide();
+          }
+        }}
+        disabled={submitting}
+        placeholder="Capture a thought, @someone: a note, or remind me to…"
+        className="w-full bg-transparent text-lg text-white placeholder-gray-500 outline-none"
+      />
+      <div className="flex items-center justify-between text-xs text-gray-400">
+        <span>{error ? <span className="text-red-400">{error}</span> : route ? describeRoute(route) : ' '}</span>
+        <span>Enter to save · Esc to close</span>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,123 @@
+import { useState } from 'react';
+import { motion } from 'framer-motion';
+import { unlockPortableData } from '@/lib/tauri';
+
+interface UnlockDataPageProps {
+  // False on the first run, when the passphrase is being set
+  hasPassphrase: boolean;
+  onUnlocked: () => void;
+}
+
+export default function UnlockDataPage({ hasPassphrase, onUnlocked }: UnlockDataPageProps) {
+  const [passphrase, setPassphrase] = useState('');
+  const [confirm, setConfirm] = useState('');
+  const [loading, setLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!hasPassphrase && passphrase !== confirm) {
+      setError("The passphrases don't match");
+      return;
+    }
+
+    setLoading(true);
+    setError(null);
+    try {
+      await unlockPortableData(passphrase);
+      onUnlocked();
+    } catch (err: unknown) {
+      console.error('[UnlockData] Failed to unlock:', err);
+      const message =
+        err && typeof err === 'object' && 'message' in err
+          ? String((err as { message: unknown }).message)
+          : 'Failed to open your data. Please try again.';
+      setError(message);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div className="flex min-h-screen items-center justify-center p-8">
+      <motion.div
+        initial={{ opacity: 0, y: 20 }}
+        animate={{ opacity: 1, y: 0 }}
+        className="w-full max-w-md"
+      >
+        <div className="text-center mb-8">
+          <h1 className="text-3xl font-bold text-white mb-2">
+            {hasPassphrase ? 'Unlock Your Data' : 'Choose a Passphrase'}
+          </h1>
+          <p className="text-gray-400">
+            {hasPassphrase
+              ? 'This portable copy is locked. Enter your passphrase to open it.'
+              : 'Your data travels with this app. Choose a passphrase to protect it on whatever computer it runs on.'}
+          </p>
+        </div>
+
+        <div className="bg-gh-dark-800 rounded-2xl p-8 shadow-xl">
+          <form onSubmit={handleSubmit} className="space-y-4">
+            <div>
+              <label
+                htmlFor="passphrase"
+                className="block text-sm font-medium text-gray-300 mb-1"
+              >
+                Passphrase
+              </label>
+              <input
+                id="passphrase"
+                type="password"
+                value={passphrase}
+                onChange={(e) => setPassphrase(e.target.value)}
+                required
+                autoFocus
+                className="w-full px-4 py-3 bg-gh-dark-900 border border-gray-700 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-gh-purple-500"
+              />
+            </div>
+
+            {!hasPassphrase && (
+              <div>
+                <label
+                  htmlFor="confirm-passphrase"
+                  className="block text-sm font-medium text-gray-300 mb-1"
+                >
+                  Confirm passphrase
+                </label>
+                <input
+                  id="confirm-passphrase"
+                  type="password"
+                  value={confirm}
+                  onChange={(e) => setConfirm(e.target.value)}
+                  required
+                  className="w-full px-4 py-3 bg-gh-dark-900 border border-gray-700 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-gh-purple-500"
+                />
+                <p className="text-gray-500 text-xs mt-2">
+                  There's no way to recover it, so keep it somewhere safe.
+                </p>
+              </div>
+            )}
+
+            {error && (
+              <motion.div
+                initial={{ opacity: 0 }}
+                animate={{ opacity: 1 }}
+                className="p-3 bg-red-900/30 border border-red-700 rounded-lg text-red-300 text-sm"
+              >
+                {error}
+              </motion.div>
+            )}
+
+            <button
+              type="submit"
+              disabled={loading}
+              className="w-full py-3 px-4 bg-gh-purple-600 hover:bg-gh-purple-700 disabled:bg-gray-700 disabled:cursor-not-allowed text-white font-medium rounded-lg transition-colors mt-2"
+            >
+              {loading ? 'Opening...' : hasPassphrase ? 'Unlock' : 'Set Passphrase'}
+            </button>
+          </form>
+        </div>
+      </motion.div>
+    </div>
+  );
+}
@@ -0,0 +1,60 @@
+/**
+ * GET /api/market/search?kind=news|web&q=...&count=...
+ *
+ * Relays a Brave Search query for the desktop app's market gatherer, so the
+ * shared Brave key stays on the server. Brave's response is returned
+ * unchanged. Desktop users with their own key call Brave directly.
+ */
+
+import { NextRequest, NextResponse } from 'next/server';
+import { createClient } from '@supabase/supabase-js';
+
+const BRAVE_API_URL = 'https://api.search.brave.com/res/v1';
+const MAX_COUNT = 20;
+
+export async function GET(request: NextRequest) {
+  const token = request.headers.get('Authorization')?.replace('Bearer ', '');
+  if (!token) {
+    return NextResponse.json({ error: 'Not signed in' }, { status: 401 });
+  }
+
+  const supabase = createClient(
+    process.env.NEXT_PUBLIC_SUPABASE_URL!,
+    process.env.SUPABASE_SERVICE_ROLE_KEY!
+  );
+  const { data: { user }, error } = await supabase.auth.getUser(token);
+  if (error || !user) {
+    return NextResponse.json({ error: 'Not signed in' }, { status: 401 });
+  }
+
+  const { searchParams } = new URL(request.url);
+  const kind = searchParams.get('kind');
+  const query = searchParams.get('q')?.trim();
+  if ((kind !== 'news' && kind !== 'web') || !query) {
+    return NextResponse.json(
+      { error: 'kind must be news or web, and q is required' },
+      { status: 400 }
+    );
+  }
+  const count = Math.min(Number(searchParams.get('count')) || 10, MAX_COUNT);
+
+  const apiKey = process.env.BRAVE_API_KEY;
+  if (!apiKey) {
+    return NextResponse.json(
+      { error: 'Server configuration error: missing Brave API key' },
+      { status: 500 }
+    );
+  }
+
+  const url = new URL(`${BRAVE_API_URL}/${kind}/search`);
+  url.searchParams.set('q', query);
+  url.searchParams.set('count', String(count));
+  const response = await fetch(url, {
+    headers: { Accept: 'application/json', 'X-Subscription-Token': apiKey },
+  });
+  if (!response.ok) {
+    console.error('[market/search] Brave error', response.status);
+    return NextResponse.json({ error: 'Search failed' }, { status: 502 });
+  }
+  return NextResponse.json(await response.json());
+}